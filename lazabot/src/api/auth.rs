@@ -0,0 +1,14 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+/// Supplies the bearer token `ApiClient` sends in the `Authorization` header
+#[async_trait]
+pub trait TokenProvider: Send + Sync {
+    /// The token to send with the next request
+    async fn token(&self) -> Result<String>;
+
+    /// Called when a request came back 401; return a fresh token to retry with.
+    /// Concurrent requests may all get a 401, so implementations should avoid
+    /// refreshing more than once for the same expired token.
+    async fn refresh(&self) -> Result<String>;
+}
@@ -0,0 +1,156 @@
+use dashmap::DashMap;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Circuit breaker thresholds
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failures before the circuit opens
+    pub failure_threshold: u32,
+    /// How long the circuit stays open before allowing a trial request
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+/// State of a single host's circuit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests flow normally
+    Closed,
+    /// Requests fail fast until the cooldown expires
+    Open { until: Instant },
+    /// Cooldown expired; the next result decides whether to close or reopen
+    HalfOpen,
+}
+
+#[derive(Debug)]
+struct HostCircuit {
+    state: CircuitState,
+    consecutive_failures: u32,
+}
+
+impl Default for HostCircuit {
+    fn default() -> Self {
+        Self {
+            state: CircuitState::Closed,
+            consecutive_failures: 0,
+        }
+    }
+}
+
+/// Per-host circuit breaker
+#[derive(Debug, Default)]
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    circuits: DashMap<String, HostCircuit>,
+}
+
+impl CircuitBreaker {
+    /// Create a new circuit breaker with the given thresholds
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            circuits: DashMap::new(),
+        }
+    }
+
+    /// Check whether a request to `host` may proceed.
+    /// Returns the remaining cooldown if the circuit is open.
+    pub fn check(&self, host: &str) -> Result<(), Duration> {
+        let mut circuit = self.circuits.entry(host.to_string()).or_default();
+
+        match circuit.state {
+            CircuitState::Closed | CircuitState::HalfOpen => Ok(()),
+            CircuitState::Open { until } => {
+                let now = Instant::now();
+                if now >= until {
+                    info!("Circuit for {} is half-open, allowing trial request", host);
+                    circuit.state = CircuitState::HalfOpen;
+                    Ok(())
+                } else {
+                    Err(until - now)
+                }
+            }
+        }
+    }
+
+    /// Record a successful request, closing the circuit
+    pub fn record_success(&self, host: &str) {
+        let mut circuit = self.circuits.entry(host.to_string()).or_default();
+
+        if circuit.state != CircuitState::Closed {
+            info!("Circuit for {} closed after successful request", host);
+        }
+        circuit.state = CircuitState::Closed;
+        circuit.consecutive_failures = 0;
+    }
+
+    /// Record a failed request, opening the circuit once the threshold is reached
+    pub fn record_failure(&self, host: &str) {
+        let mut circuit = self.circuits.entry(host.to_string()).or_default();
+        circuit.consecutive_failures += 1;
+
+        let should_open = circuit.state == CircuitState::HalfOpen
+            || circuit.consecutive_failures >= self.config.failure_threshold;
+
+        if should_open {
+            warn!(
+                "Circuit for {} opened after {} consecutive failures",
+                host, circuit.consecutive_failures
+            );
+            circuit.state = CircuitState::Open {
+                until: Instant::now() + self.config.cooldown,
+            };
+        }
+    }
+
+    /// Get the current state of a host's circuit
+    pub fn state(&self, host: &str) -> CircuitState {
+        self.circuits
+            .get(host)
+            .map(|circuit| circuit.state)
+            .unwrap_or(CircuitState::Closed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_circuit_opens_and_recovers() {
+        let breaker = CircuitBreaker::new(CircuitBreakerConfig {
+            failure_threshold: 2,
+            cooldown: Duration::from_millis(20),
+        });
+
+        breaker.record_failure("example.com");
+        assert!(breaker.check("example.com").is_ok());
+        breaker.record_failure("example.com");
+        assert!(breaker.check("example.com").is_err());
+
+        // Other hosts are unaffected
+        assert!(breaker.check("other.com").is_ok());
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(breaker.check("example.com").is_ok());
+        assert_eq!(breaker.state("example.com"), CircuitState::HalfOpen);
+
+        // A failure while half-open reopens immediately
+        breaker.record_failure("example.com");
+        assert!(breaker.check("example.com").is_err());
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(breaker.check("example.com").is_ok());
+        breaker.record_success("example.com");
+        assert_eq!(breaker.state("example.com"), CircuitState::Closed);
+    }
+}
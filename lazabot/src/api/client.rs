@@ -1,17 +1,166 @@
 use anyhow::{Context, Result};
-use reqwest::cookie::Jar;
-use reqwest::{header::HeaderMap, Client, ClientBuilder, Method, Url};
+use parking_lot::Mutex;
+use reqwest::cookie::{CookieStore, Jar};
+use reqwest::header::{HeaderMap, AUTHORIZATION};
+use reqwest::{Client, ClientBuilder, Method, Url};
+use serde::de::DeserializeOwned;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::broadcast;
 use tokio::time::sleep;
 use tracing::{debug, error, info, warn};
 
+use crate::api::auth::TokenProvider;
+use crate::api::circuit_breaker::{CircuitBreaker, CircuitBreakerConfig};
+use crate::api::cookies::CookieRecorder;
+use crate::api::doh::DohResolver;
+use crate::api::har::HarRecorder;
+use crate::api::logging::{redact_body, redact_headers, DEFAULT_REDACTED_FIELDS};
+use crate::api::rate_limiter::RateLimiter;
+use crate::api::replay::ReplayClient;
+use crate::api::signing::{RequestParts, Signer};
+use crate::proxy::ProxyManager;
+
+/// API client errors
+#[derive(Error, Debug, Clone)]
+pub enum ApiError {
+    #[error("Circuit open for {host}, retry in {retry_after:?}")]
+    CircuitOpen { host: String, retry_after: Duration },
+
+    #[error("Response body exceeds the {limit} byte limit")]
+    BodyTooLarge { limit: usize },
+
+    #[error("Invalid URL {url}: {reason}")]
+    InvalidUrl { url: String, reason: String },
+
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+
+    #[error("Failed to get auth token: {0}")]
+    Auth(String),
+
+    #[error("Request timed out: {0}")]
+    Timeout(#[source] Arc<reqwest::Error>),
+
+    #[error("Connection failed: {0}")]
+    Connect(#[source] Arc<reqwest::Error>),
+
+    #[error("DNS resolution failed: {0}")]
+    Dns(#[source] Arc<reqwest::Error>),
+
+    #[error("HTTP status {0}")]
+    Status(u16),
+
+    #[error("Failed to decode response: {0}")]
+    Decode(String),
+
+    #[error("Proxy error: {0}")]
+    ProxyError(String),
+
+    #[error("Request failed: {0}")]
+    Request(#[source] Arc<reqwest::Error>),
+
+    #[error("No recorded response for {method} {url}")]
+    NoRecordedResponse { method: String, url: String },
+
+    /// The error of an identical in-flight request this one was coalesced into
+    #[error(transparent)]
+    Shared(Arc<ApiError>),
+}
+
+impl ApiError {
+    /// Classify a transport error; connect failures through a proxy are blamed on the proxy
+    fn from_reqwest(error: reqwest::Error, via_proxy: bool) -> Self {
+        if error.is_timeout() {
+            Self::Timeout(Arc::new(error))
+        } else if error.is_connect() && via_proxy {
+            Self::ProxyError(error_chain(&error))
+        } else if error.is_connect() && error_chain(&error).contains("dns error") {
+            Self::Dns(Arc::new(error))
+        } else if error.is_connect() {
+            Self::Connect(Arc::new(error))
+        } else if error.is_decode() || error.is_body() {
+            Self::Decode(error_chain(&error))
+        } else if let Some(status) = error.status() {
+            Self::Status(status.as_u16())
+        } else {
+            Self::Request(Arc::new(error))
+        }
+    }
+
+    /// The underlying error, looking through errors shared from a coalesced request
+    pub fn root(&self) -> &ApiError {
+        match self {
+            Self::Shared(error) => error.root(),
+            error => error,
+        }
+    }
+}
+
+/// `headers` with `Authorization: Bearer <token>` set
+fn with_bearer_token(headers: Option<HeaderMap>, token: &str) -> Result<HeaderMap, ApiError> {
+    let mut headers = headers.unwrap_or_default();
+    let value = format!("Bearer {}", token)
+        .parse()
+        .map_err(|_| ApiError::Auth("token is not a valid header value".to_string()))?;
+    headers.insert(AUTHORIZATION, value);
+    Ok(headers)
+}
+
+/// Error message including its sources, since reqwest keeps the detail there
+fn error_chain(error: &(dyn std::error::Error + 'static)) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    message
+}
+
+/// Protocol a proxy speaks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ProxyType {
+    #[default]
+    Http,
+    Socks5,
+}
+
+impl ProxyType {
+    /// URL scheme for proxies of this type
+    pub fn scheme(self) -> &'static str {
+        match self {
+            ProxyType::Http => "http",
+            ProxyType::Socks5 => "socks5",
+        }
+    }
+
+    /// Proxy type for a URL scheme such as `socks5` (case-insensitive)
+    pub fn from_scheme(scheme: &str) -> Option<Self> {
+        match scheme.to_ascii_lowercase().as_str() {
+            "http" | "https" => Some(ProxyType::Http),
+            "socks5" | "socks5h" => Some(ProxyType::Socks5),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ProxyInfo {
     pub host: String,
     pub port: u16,
     pub username: Option<String>,
     pub password: Option<String>,
+    /// Country or region the proxy exits from, e.g. "SG"
+    pub region: Option<String>,
+    pub proxy_type: ProxyType,
 }
 
 impl ProxyInfo {
@@ -21,26 +170,72 @@ impl ProxyInfo {
             port,
             username: None,
             password: None,
+            region: None,
+            proxy_type: ProxyType::Http,
         }
     }
 
+    pub fn with_type(mut self, proxy_type: ProxyType) -> Self {
+        self.proxy_type = proxy_type;
+        self
+    }
+
     pub fn with_auth(mut self, username: String, password: String) -> Self {
         self.username = Some(username);
         self.password = Some(password);
         self
     }
 
+    pub fn with_region(mut self, region: String) -> Self {
+        self.region = Some(region);
+        self
+    }
+
+    /// Whether the proxy is tagged with `region` (case-insensitive)
+    pub fn in_region(&self, region: &str) -> bool {
+        self.region
+            .as_deref()
+            .is_some_and(|own| own.eq_ignore_ascii_case(region))
+    }
+
     pub fn to_url(&self) -> Result<String> {
         let auth = if let (Some(username), Some(password)) = (&self.username, &self.password) {
             format!("{}:{}@", username, password)
         } else {
             String::new()
         };
-        Ok(format!("http://{}{}:{}", auth, self.host, self.port))
+        Ok(format!(
+            "{}://{}{}:{}",
+            self.proxy_type.scheme(),
+            auth,
+            self.url_host(),
+            self.port
+        ))
+    }
+
+    /// Host as written in a URL, with IPv6 addresses bracketed
+    pub fn url_host(&self) -> String {
+        if self.host.contains(':') && !self.host.starts_with('[') {
+            format!("[{}]", self.host)
+        } else {
+            self.host.clone()
+        }
     }
 }
 
-#[derive(Debug)]
+/// Largest response body buffered by default (32 MiB)
+pub const DEFAULT_MAX_BODY_SIZE: usize = 32 * 1024 * 1024;
+
+/// Maximum number of body characters included in JSON parse errors
+const BODY_SNIPPET_LEN: usize = 200;
+
+/// Response header set when the anti-bot layer demands a captcha
+const CAPTCHA_HEADER: &str = "x-captcha-required";
+
+/// Path Lazada's anti-bot layer redirects flagged clients to
+const CAPTCHA_PUNISH_MARKER: &str = "_____tmd_____/punish";
+
+#[derive(Debug, Clone)]
 pub struct ResponseBody {
     pub status: u16,
     pub headers: HeaderMap,
@@ -58,8 +253,53 @@ impl ResponseBody {
             text,
         }
     }
+
+    /// Whether the status code is 2xx
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// Whether the server answered with a captcha challenge instead of the requested content
+    pub fn is_captcha_challenge(&self) -> bool {
+        if self.headers.contains_key(CAPTCHA_HEADER) || self.text.contains(CAPTCHA_PUNISH_MARKER) {
+            return true;
+        }
+
+        serde_json::from_slice::<serde_json::Value>(&self.body)
+            .ok()
+            .and_then(|body| body.get("captcha_required")?.as_bool())
+            .unwrap_or(false)
+    }
+
+    /// Turn a non-2xx response into `ApiError::Status`
+    pub fn error_for_status(self) -> Result<Self, ApiError> {
+        if self.is_success() {
+            Ok(self)
+        } else {
+            Err(ApiError::Status(self.status))
+        }
+    }
+
+    /// Deserialize the body as JSON, including the status and a body snippet on failure
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, ApiError> {
+        serde_json::from_slice(&self.body).map_err(|e| {
+            let snippet: String = self.text.chars().take(BODY_SNIPPET_LEN).collect();
+            let ellipsis = if self.text.chars().count() > BODY_SNIPPET_LEN {
+                "..."
+            } else {
+                ""
+            };
+            ApiError::Decode(format!(
+                "Failed to parse JSON response (status {}): {}; body: {}{}",
+                self.status, e, snippet, ellipsis
+            ))
+        })
+    }
 }
 
+/// Alias used by callers that deal with parsed API responses
+pub type ApiResponse = ResponseBody;
+
 #[derive(Debug, Clone)]
 pub struct RetryConfig {
     pub max_retries: u32,
@@ -79,37 +319,271 @@ impl Default for RetryConfig {
     }
 }
 
+/// Identifies identical requests: method, URL and a hash of the body
+type RequestKey = (Method, String, u64);
+
+/// Hands the result of an in-flight request to every caller coalesced into it
+type InFlight = broadcast::Sender<Result<ResponseBody, Arc<ApiError>>>;
+
+/// Removes an in-flight entry once its leading request finishes or is cancelled
+struct InFlightGuard<'a> {
+    in_flight: &'a Mutex<HashMap<RequestKey, InFlight>>,
+    key: Option<RequestKey>,
+}
+
+impl InFlightGuard<'_> {
+    /// Remove the entry, returning the sender to broadcast the result on
+    fn finish(mut self) -> Option<InFlight> {
+        let key = self.key.take()?;
+        self.in_flight.lock().remove(&key)
+    }
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.in_flight.lock().remove(&key);
+        }
+    }
+}
+
+/// Why a request is resent through another proxy
+#[derive(Debug, Clone, Copy)]
+enum ProxySwitch {
+    /// The proxy failed or timed out
+    Failover,
+    /// The site answered with a captcha challenge
+    Captcha,
+}
+
+impl ProxySwitch {
+    /// Whether `result` calls for another proxy
+    fn applies(self, result: &Result<ResponseBody, ApiError>) -> bool {
+        match self {
+            Self::Failover => {
+                matches!(result, Err(ApiError::ProxyError(_) | ApiError::Timeout(_)))
+            }
+            Self::Captcha => matches!(result, Ok(response) if response.is_captcha_challenge()),
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            Self::Failover => "Proxy failure",
+            Self::Captcha => "Captcha challenge",
+        }
+    }
+}
+
 pub struct ApiClient {
     client: Client,
     user_agent: String,
     retry_config: RetryConfig,
+    cookie_jar: Arc<Jar>,
+    circuit_breaker: Option<Arc<CircuitBreaker>>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    logging: bool,
+    redacted_fields: Vec<String>,
+    captcha_proxy_manager: Option<Arc<ProxyManager>>,
+    max_captcha_proxy_switches: u32,
+    failover_proxy_manager: Option<Arc<ProxyManager>>,
+    max_proxy_failovers: u32,
+    dns_resolver: Option<Arc<DohResolver>>,
+    signer: Option<Arc<Signer>>,
+    har_recorder: Option<Arc<HarRecorder>>,
+    replay: Option<Arc<ReplayClient>>,
+    max_body_size: usize,
+    /// Every cookie set in `cookie_jar`, with its attributes, for `save_cookies`
+    cookie_recorder: Arc<CookieRecorder>,
+    /// Rotated round-robin across requests that don't set their own User-Agent
+    user_agents: Vec<String>,
+    next_user_agent: AtomicUsize,
+    token_provider: Option<Arc<dyn TokenProvider>>,
+    /// Requests currently being sent, when identical requests are coalesced
+    in_flight: Option<Arc<Mutex<HashMap<RequestKey, InFlight>>>>,
 }
 
 impl ApiClient {
     pub fn new(user_agent: Option<String>) -> Result<Self> {
         let cookie_store = Arc::new(Jar::default());
+        let cookie_recorder = Arc::new(CookieRecorder::new(cookie_store.clone()));
         let ua = user_agent.unwrap_or_else(|| "Lazabot/1.0".to_string());
 
-        let builder = ClientBuilder::new()
-            .cookie_provider(cookie_store)
-            .timeout(Duration::from_secs(30))
-            .connect_timeout(Duration::from_secs(10))
-            .redirect(reqwest::redirect::Policy::limited(10))
-            .user_agent(&ua);
-
-        let client = builder.build().context("Failed to create HTTP client")?;
+        let client = Self::client_builder(&ua, cookie_recorder.clone())
+            .build()
+            .context("Failed to create HTTP client")?;
         Ok(Self {
             client,
             user_agent: ua,
             retry_config: RetryConfig::default(),
+            cookie_jar: cookie_store,
+            circuit_breaker: None,
+            rate_limiter: None,
+            logging: false,
+            redacted_fields: default_redacted_fields(),
+            captcha_proxy_manager: None,
+            max_captcha_proxy_switches: 0,
+            failover_proxy_manager: None,
+            max_proxy_failovers: 0,
+            dns_resolver: None,
+            signer: None,
+            har_recorder: None,
+            replay: None,
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+            cookie_recorder,
+            user_agents: Vec::new(),
+            next_user_agent: AtomicUsize::new(0),
+            token_provider: None,
+            in_flight: None,
         })
     }
 
+    /// Shared settings for every underlying HTTP client
+    fn client_builder<C: CookieStore + 'static>(
+        user_agent: &str,
+        cookie_store: Arc<C>,
+    ) -> ClientBuilder {
+        ClientBuilder::new()
+            .cookie_provider(cookie_store)
+            .timeout(Duration::from_secs(30))
+            .connect_timeout(Duration::from_secs(10))
+            .redirect(reqwest::redirect::Policy::limited(10))
+            .gzip(true)
+            .brotli(true)
+            .deflate(true)
+            .user_agent(user_agent)
+    }
+
+    /// Resolve hostnames through DNS-over-HTTPS instead of the system resolver
+    pub fn with_doh_resolver(mut self, resolver: DohResolver) -> Result<Self> {
+        let resolver = Arc::new(resolver);
+        self.client = Self::client_builder(&self.user_agent, self.cookie_recorder.clone())
+            .dns_resolver(resolver.clone())
+            .build()
+            .context("Failed to create HTTP client with DoH resolver")?;
+        self.dns_resolver = Some(resolver);
+        Ok(self)
+    }
+
+    /// Add the headers returned by `signer` to every request, just before it is sent
+    pub fn with_signer(mut self, signer: Box<Signer>) -> Self {
+        self.signer = Some(Arc::from(signer));
+        self
+    }
+
+    /// Fail with `ApiError::BodyTooLarge` instead of buffering bodies over `bytes`
+    pub fn with_max_body_size(mut self, bytes: usize) -> Self {
+        self.max_body_size = bytes;
+        self
+    }
+
+    /// Rotate through `user_agents`, one per request. A User-Agent header passed to
+    /// `request` (e.g. from a stealth fingerprint) is kept so it matches the other
+    /// fingerprint headers.
+    pub fn with_user_agents(mut self, user_agents: Vec<String>) -> Self {
+        self.user_agents = user_agents;
+        self
+    }
+
+    /// The pooled User-Agent for the next request, if a pool is configured
+    fn next_user_agent(&self) -> Option<&str> {
+        if self.user_agents.is_empty() {
+            return None;
+        }
+        let index = self.next_user_agent.fetch_add(1, Ordering::Relaxed) % self.user_agents.len();
+        Some(&self.user_agents[index])
+    }
+
     pub fn with_retry_config(mut self, config: RetryConfig) -> Self {
         self.retry_config = config;
         self
     }
 
+    /// Fail fast for a host after `failure_threshold` consecutive failures, for `cooldown`
+    pub fn with_circuit_breaker(mut self, failure_threshold: u32, cooldown: Duration) -> Self {
+        self.circuit_breaker = Some(Arc::new(CircuitBreaker::new(CircuitBreakerConfig {
+            failure_threshold,
+            cooldown,
+        })));
+        self
+    }
+
+    /// Throttle requests to `per_host_rps` per host, allowing bursts of up to `burst`
+    pub fn with_rate_limit(mut self, per_host_rps: f64, burst: u32) -> Self {
+        self.rate_limiter = Some(Arc::new(RateLimiter::new(per_host_rps, burst)));
+        self
+    }
+
+    /// Log each request and response at debug level, with sensitive fields redacted
+    pub fn with_logging(mut self, enabled: bool) -> Self {
+        self.logging = enabled;
+        self
+    }
+
+    /// Record every request and response, redacted like the logs, to a HAR file at `path`.
+    /// The file is written by [`flush_har`](Self::flush_har) or once the client is dropped.
+    pub fn with_har_capture(mut self, path: impl Into<std::path::PathBuf>) -> Self {
+        self.har_recorder = Some(Arc::new(HarRecorder::new(path)));
+        self
+    }
+
+    /// Write the HAR capture, if enabled, with every exchange recorded so far
+    pub async fn flush_har(&self) -> Result<()> {
+        match &self.har_recorder {
+            Some(recorder) => recorder.flush().await,
+            None => Ok(()),
+        }
+    }
+
+    /// Answer every request from `replay` instead of the network
+    pub fn with_replay(mut self, replay: ReplayClient) -> Self {
+        self.replay = Some(Arc::new(replay));
+        self
+    }
+
+    /// Redact these header and JSON body fields in logs, in addition to the defaults
+    pub fn with_redacted_fields(mut self, fields: &[&str]) -> Self {
+        self.redacted_fields
+            .extend(fields.iter().map(|field| field.to_string()));
+        self
+    }
+
+    /// On a captcha challenge, mark the proxy unhealthy and retry through the next one
+    /// from `manager`, switching at most `max_switches` times
+    pub fn with_captcha_proxy_rotation(
+        mut self,
+        manager: Arc<ProxyManager>,
+        max_switches: u32,
+    ) -> Self {
+        self.captcha_proxy_manager = Some(manager);
+        self.max_captcha_proxy_switches = max_switches;
+        self
+    }
+
+    /// When the given proxy fails, mark it unhealthy and retry through the next one from
+    /// `manager`, failing over at most `max_failovers` times
+    pub fn with_proxy_failover(mut self, manager: Arc<ProxyManager>, max_failovers: u32) -> Self {
+        self.failover_proxy_manager = Some(manager);
+        self.max_proxy_failovers = max_failovers;
+        self
+    }
+
+    /// Send `Authorization: Bearer <token>` from `provider` unless the request sets its
+    /// own. On a 401 the provider is asked to refresh and the request is retried once.
+    pub fn with_token_provider(mut self, provider: Arc<dyn TokenProvider>) -> Self {
+        self.token_provider = Some(provider);
+        self
+    }
+
+    /// Collapse identical concurrent GET and HEAD requests (same method and URL) into
+    /// one and share its response. Headers and proxy are not compared, so only enable
+    /// this for traffic such as monitoring. The caller that sent the request gets its
+    /// error as is; the coalesced callers get `ApiError::Shared`.
+    pub fn with_request_dedup(mut self, enabled: bool) -> Self {
+        self.in_flight = enabled.then(Arc::default);
+        self
+    }
+
     pub async fn request(
         &self,
         method: Method,
@@ -117,28 +591,307 @@ impl ApiClient {
         headers: Option<HeaderMap>,
         body: Option<Vec<u8>>,
         proxy: Option<ProxyInfo>,
-    ) -> Result<ResponseBody> {
-        let url = Url::parse(url).context("Invalid URL")?;
+    ) -> Result<ResponseBody, ApiError> {
+        let in_flight = self
+            .in_flight
+            .as_ref()
+            .filter(|_| method == Method::GET || method == Method::HEAD);
+        let Some(in_flight) = in_flight else {
+            return self.request_once(method, url, headers, body, proxy).await;
+        };
+
+        let mut hasher = DefaultHasher::new();
+        body.hash(&mut hasher);
+        let key = (method.clone(), url.to_string(), hasher.finish());
+
+        // Join an identical request already in flight, or lead a new one
+        let receiver = {
+            let mut in_flight = in_flight.lock();
+            match in_flight.get(&key) {
+                Some(sender) => Some(sender.subscribe()),
+                None => {
+                    in_flight.insert(key.clone(), broadcast::channel(1).0);
+                    None
+                }
+            }
+        };
+        if let Some(mut receiver) = receiver {
+            return match receiver.recv().await {
+                Ok(result) => result.map_err(ApiError::Shared),
+                // The leading caller was cancelled before it finished, so send our own
+                Err(_) => self.request_once(method, url, headers, body, proxy).await,
+            };
+        }
+
+        let guard = InFlightGuard {
+            in_flight,
+            key: Some(key),
+        };
+        let result = self.request_once(method, url, headers, body, proxy).await;
+
+        // Later callers start a new request rather than joining one that has finished
+        if let Some(sender) = guard.finish() {
+            let _ = sender.send(result.clone().map_err(Arc::new));
+        }
+        result
+    }
+
+    async fn request_once(
+        &self,
+        method: Method,
+        url: &str,
+        headers: Option<HeaderMap>,
+        body: Option<Vec<u8>>,
+        proxy: Option<ProxyInfo>,
+    ) -> Result<ResponseBody, ApiError> {
+        let url = Url::parse(url).map_err(|e| ApiError::InvalidUrl {
+            url: url.to_string(),
+            reason: e.to_string(),
+        })?;
+        let host = Self::host_key(&url);
+
+        if let Some(breaker) = &self.circuit_breaker {
+            if let Err(retry_after) = breaker.check(&host) {
+                debug!("Circuit open for {}, failing fast", host);
+                return Err(ApiError::CircuitOpen { host, retry_after });
+            }
+        }
+
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.acquire(&host).await;
+        }
+
+        let has_authorization = headers
+            .as_ref()
+            .is_some_and(|headers| headers.contains_key(AUTHORIZATION));
+        let token_provider = self.token_provider.as_ref().filter(|_| !has_authorization);
+        let mut headers = match token_provider {
+            Some(provider) => {
+                let token = provider
+                    .token()
+                    .await
+                    .map_err(|e| ApiError::Auth(e.to_string()))?;
+                Some(with_bearer_token(headers, &token)?)
+            }
+            None => headers,
+        };
+
+        let mut result = self
+            .send(
+                method.clone(),
+                url.clone(),
+                headers.clone(),
+                body.clone(),
+                proxy.as_ref(),
+            )
+            .await;
+
+        let mut current = proxy;
+        let resend = |next: ProxyInfo| {
+            let (method, url, headers, body) =
+                (method.clone(), url.clone(), headers.clone(), body.clone());
+            async move { self.send(method, url, headers, body, Some(&next)).await }
+        };
+
+        if let Some(manager) = &self.failover_proxy_manager {
+            result = self
+                .switch_proxies(
+                    ProxySwitch::Failover,
+                    manager,
+                    self.max_proxy_failovers,
+                    &mut current,
+                    result,
+                    &resend,
+                )
+                .await;
+        }
+
+        if let Some(manager) = &self.captcha_proxy_manager {
+            result = self
+                .switch_proxies(
+                    ProxySwitch::Captcha,
+                    manager,
+                    self.max_captcha_proxy_switches,
+                    &mut current,
+                    result,
+                    &resend,
+                )
+                .await;
+        }
+
+        if let Some(provider) = token_provider {
+            if matches!(&result, Ok(response) if response.status == 401) {
+                info!("Got 401 from {}, refreshing auth token and retrying", host);
+                let token = provider
+                    .refresh()
+                    .await
+                    .map_err(|e| ApiError::Auth(e.to_string()))?;
+                headers = Some(with_bearer_token(headers, &token)?);
+                result = self
+                    .send(method, url, headers, body, current.as_ref())
+                    .await;
+            }
+        }
+
+        if let Some(breaker) = &self.circuit_breaker {
+            match &result {
+                Ok(response) if response.status < 500 => breaker.record_success(&host),
+                _ => breaker.record_failure(&host),
+            }
+        }
+
+        result
+    }
+
+    /// Resend through the next proxy from `manager` while `reason` applies to the result,
+    /// marking each abandoned proxy unhealthy, at most `max_switches` times
+    async fn switch_proxies<F, Fut>(
+        &self,
+        reason: ProxySwitch,
+        manager: &ProxyManager,
+        max_switches: u32,
+        current: &mut Option<ProxyInfo>,
+        mut result: Result<ResponseBody, ApiError>,
+        resend: &F,
+    ) -> Result<ResponseBody, ApiError>
+    where
+        F: Fn(ProxyInfo) -> Fut,
+        Fut: std::future::Future<Output = Result<ResponseBody, ApiError>>,
+    {
+        let mut switches = 0;
+
+        while switches < max_switches && reason.applies(&result) {
+            match (reason, current.as_ref()) {
+                (_, Some(abandoned)) => manager.set_proxy_health(abandoned, false).await,
+                // Direct requests have no proxy to blame for a failure
+                (ProxySwitch::Failover, None) => break,
+                (ProxySwitch::Captcha, None) => {}
+            }
+
+            let via = current
+                .as_ref()
+                .map_or("direct connection".to_string(), |proxy| {
+                    format!("{}:{}", proxy.host, proxy.port)
+                });
+            let Some(next) = manager.get_next_proxy().await else {
+                warn!(
+                    "{} on {} and no other proxy to retry on",
+                    reason.describe(),
+                    via
+                );
+                break;
+            };
+
+            warn!(
+                "{} on {}, retrying via proxy {}:{}",
+                reason.describe(),
+                via,
+                next.host,
+                next.port
+            );
+            *current = Some(next.clone());
+            result = resend(next).await;
+            switches += 1;
+        }
+
+        result
+    }
+
+    /// Send one request, through `proxy` if given, retrying transport failures
+    async fn send(
+        &self,
+        method: Method,
+        url: Url,
+        headers: Option<HeaderMap>,
+        body: Option<Vec<u8>>,
+        proxy: Option<&ProxyInfo>,
+    ) -> Result<ResponseBody, ApiError> {
+        if let Some(replay) = &self.replay {
+            return replay.respond(&method, &url);
+        }
+
+        let has_user_agent = headers
+            .as_ref()
+            .is_some_and(|headers| headers.contains_key(reqwest::header::USER_AGENT));
+        let headers = match self.next_user_agent().filter(|_| !has_user_agent) {
+            Some(user_agent) => {
+                let mut headers = headers.unwrap_or_default();
+                headers.insert(
+                    reqwest::header::USER_AGENT,
+                    user_agent.parse().map_err(|_| {
+                        ApiError::InvalidRequest(format!(
+                            "Invalid User-Agent in pool: {}",
+                            user_agent
+                        ))
+                    })?,
+                );
+                Some(headers)
+            }
+            None => headers,
+        };
+
+        let headers = match &self.signer {
+            Some(signer) => {
+                let mut headers = headers.unwrap_or_default();
+                let signature_headers = signer(&RequestParts {
+                    method: &method,
+                    url: &url,
+                    headers: &headers,
+                    body: body.as_deref().unwrap_or_default(),
+                });
+                headers.extend(signature_headers);
+                Some(headers)
+            }
+            None => headers,
+        };
 
         // Create client with proxy if provided
-        let client = if let Some(proxy_info) = &proxy {
-            let proxy_url = proxy_info.to_url()?;
-            let proxy = reqwest::Proxy::all(&proxy_url).context("Failed to create proxy")?;
-
-            let cookie_store = Arc::new(Jar::default());
-            let builder = ClientBuilder::new()
-                .cookie_provider(cookie_store)
-                .proxy(proxy)
-                .timeout(Duration::from_secs(30))
-                .connect_timeout(Duration::from_secs(10))
-                .redirect(reqwest::redirect::Policy::limited(10))
-                .user_agent(&self.user_agent);
-
-            builder.build().context("Failed to create proxy client")?
+        let client = if let Some(proxy_info) = proxy {
+            let proxy_error = |e: &dyn std::fmt::Display| {
+                ApiError::ProxyError(format!("{}:{}: {}", proxy_info.host, proxy_info.port, e))
+            };
+            let proxy_url = proxy_info.to_url().map_err(|e| proxy_error(&e))?;
+            let proxy = reqwest::Proxy::all(&proxy_url).map_err(|e| proxy_error(&e))?;
+
+            // A fresh jar per proxied client keeps sessions from seeing each other's cookies
+            let mut builder =
+                Self::client_builder(&self.user_agent, Arc::new(Jar::default())).proxy(proxy);
+            if let Some(resolver) = &self.dns_resolver {
+                builder = builder.dns_resolver(resolver.clone());
+            }
+
+            builder.build().map_err(|e| proxy_error(&e))?
         } else {
             self.client.clone()
         };
 
+        let log_target = self.logging.then(|| {
+            debug!(
+                "Request {} {} headers={} body={}",
+                method,
+                url,
+                headers
+                    .as_ref()
+                    .map(|h| redact_headers(h, &self.redacted_fields))
+                    .unwrap_or_else(|| "{}".to_string()),
+                body.as_deref()
+                    .map(|b| redact_body(b, &self.redacted_fields))
+                    .unwrap_or_default()
+            );
+            (method.clone(), url.clone(), std::time::Instant::now())
+        });
+
+        // Keep what was sent so the exchange can be written to the HAR file
+        let har_request = self.har_recorder.as_ref().map(|_| {
+            (
+                method.clone(),
+                url.clone(),
+                headers.clone().unwrap_or_default(),
+                body.clone().unwrap_or_default(),
+                std::time::Instant::now(),
+            )
+        });
+
         let mut request_builder = client.request(method, url);
 
         if let Some(headers) = headers {
@@ -149,20 +902,72 @@ impl ApiClient {
             request_builder = request_builder.body(body);
         }
 
-        self.execute_with_retry(request_builder).await
+        let result = self
+            .execute_with_retry(request_builder, proxy.is_some())
+            .await;
+
+        if let Some((method, url, started)) = log_target {
+            match &result {
+                Ok(response) => debug!(
+                    "Response {} {} status={} took={:?} body={}",
+                    method,
+                    url,
+                    response.status,
+                    started.elapsed(),
+                    redact_body(&response.body, &self.redacted_fields)
+                ),
+                Err(e) => debug!(
+                    "Response {} {} failed after {:?}: {}",
+                    method,
+                    url,
+                    started.elapsed(),
+                    e
+                ),
+            }
+        }
+
+        if let (Some(recorder), Some((method, url, headers, body, started))) =
+            (&self.har_recorder, har_request)
+        {
+            let request = RequestParts {
+                method: &method,
+                url: &url,
+                headers: &headers,
+                body: &body,
+            };
+            let response = match &result {
+                Ok(response) => Ok((response.status, &response.headers, &response.body[..])),
+                Err(e) => Err(e.to_string()),
+            };
+            recorder
+                .record(&request, started.elapsed(), response, &self.redacted_fields)
+                .await;
+        }
+
+        result
+    }
+
+    /// Key used for per-host state such as the circuit breaker
+    fn host_key(url: &Url) -> String {
+        let host = url.host_str().unwrap_or_default();
+        match url.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_string(),
+        }
     }
 
     async fn execute_with_retry(
         &self,
         request_builder: reqwest::RequestBuilder,
-    ) -> Result<ResponseBody> {
+        via_proxy: bool,
+    ) -> Result<ResponseBody, ApiError> {
         let mut last_error = None;
         let mut delay = self.retry_config.base_delay_ms;
 
         for attempt in 0..=self.retry_config.max_retries {
-            let request = request_builder
-                .try_clone()
-                .context("Failed to clone request")?;
+            let request = request_builder.try_clone().ok_or_else(|| {
+                ApiError::InvalidRequest("Streaming request bodies cannot be retried".to_string())
+            })?;
 
             debug!(
                 "Attempt {} of {} for request",
@@ -176,26 +981,27 @@ impl ApiClient {
                     let headers = response.headers().clone();
                     let url = response.url().clone();
 
-                    match response.bytes().await {
+                    match self.read_body(response, via_proxy).await {
                         Ok(body_bytes) => {
-                            let response_body =
-                                ResponseBody::new(status, headers, body_bytes.to_vec());
+                            let response_body = ResponseBody::new(status, headers, body_bytes);
                             info!("Request successful: {} {}", status, url);
                             return Ok(response_body);
                         }
+                        // Retrying won't make the body any smaller
+                        Err(e @ ApiError::BodyTooLarge { .. }) => return Err(e),
                         Err(e) => {
                             warn!(
                                 "Failed to read response body on attempt {}: {}",
                                 attempt + 1,
                                 e
                             );
-                            last_error = Some(e.into());
+                            last_error = Some(e);
                         }
                     }
                 }
                 Err(e) => {
                     warn!("Request failed on attempt {}: {}", attempt + 1, e);
-                    last_error = Some(e.into());
+                    last_error = Some(ApiError::from_reqwest(e, via_proxy));
                 }
             }
 
@@ -210,7 +1016,38 @@ impl ApiClient {
         }
 
         error!("All retry attempts failed");
-        Err(last_error.unwrap_or_else(|| anyhow::anyhow!("Unknown error")))
+        Err(last_error.unwrap_or_else(|| ApiError::InvalidRequest("No attempts made".to_string())))
+    }
+
+    /// Read the (decoded) body, giving up as soon as it exceeds `max_body_size`
+    async fn read_body(
+        &self,
+        mut response: reqwest::Response,
+        via_proxy: bool,
+    ) -> Result<Vec<u8>, ApiError> {
+        let limit = self.max_body_size;
+        let too_large = || ApiError::BodyTooLarge { limit };
+
+        // Compressed bodies are decoded on the fly and report no length
+        if response
+            .content_length()
+            .is_some_and(|len| len > limit as u64)
+        {
+            return Err(too_large());
+        }
+
+        let mut body = Vec::new();
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| ApiError::from_reqwest(e, via_proxy))?
+        {
+            if body.len() + chunk.len() > limit {
+                return Err(too_large());
+            }
+            body.extend_from_slice(&chunk);
+        }
+        Ok(body)
     }
 
     pub fn client(&self) -> &Client {
@@ -220,6 +1057,61 @@ impl ApiClient {
     pub fn set_retry_config(&mut self, config: RetryConfig) {
         self.retry_config = config;
     }
+
+    /// Get the cookies the client's jar would send to `url`
+    pub fn cookies_for_url(&self, url: &str) -> HashMap<String, String> {
+        let mut cookies = HashMap::new();
+        let Ok(url) = Url::parse(url) else {
+            return cookies;
+        };
+
+        if let Some(header) = self.cookie_jar.cookies(&url) {
+            if let Ok(header) = header.to_str() {
+                for pair in header.split(';') {
+                    if let Some((name, value)) = pair.trim().split_once('=') {
+                        cookies.insert(name.to_string(), value.to_string());
+                    }
+                }
+            }
+        }
+
+        cookies
+    }
+
+    /// Save every cookie this client has received or loaded, encrypted with `key`
+    /// (normally the session encryption key). Cookies keep their domain, path,
+    /// expiry and flags; expired ones are left out. Returns the number written.
+    pub async fn save_cookies(&self, path: impl AsRef<Path>, key: &[u8; 32]) -> Result<usize> {
+        let (data, count) = self.cookie_recorder.export(key)?;
+        tokio::fs::write(path.as_ref(), data)
+            .await
+            .with_context(|| format!("Failed to write cookie file {:?}", path.as_ref()))?;
+
+        debug!("Saved {} cookies to {:?}", count, path.as_ref());
+        Ok(count)
+    }
+
+    /// Add cookies written by `save_cookies` with the same `key` to this client's jar,
+    /// skipping any that have expired since. Returns the number of cookies loaded.
+    pub async fn load_cookies(&self, path: impl AsRef<Path>, key: &[u8; 32]) -> Result<usize> {
+        let data = tokio::fs::read(path.as_ref())
+            .await
+            .with_context(|| format!("Failed to read cookie file {:?}", path.as_ref()))?;
+        let count = self
+            .cookie_recorder
+            .import(&data, key)
+            .with_context(|| format!("Failed to load cookie file {:?}", path.as_ref()))?;
+
+        debug!("Loaded {} cookies from {:?}", count, path.as_ref());
+        Ok(count)
+    }
+}
+
+fn default_redacted_fields() -> Vec<String> {
+    DEFAULT_REDACTED_FIELDS
+        .iter()
+        .map(|field| field.to_string())
+        .collect()
 }
 
 impl std::fmt::Debug for ApiClient {
@@ -234,6 +1126,33 @@ impl std::fmt::Debug for ApiClient {
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn test_timeouts_trigger_proxy_failover() {
+        // Accepts connections but never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let _held = tokio::spawn(async move {
+            let mut sockets = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                sockets.push(socket);
+            }
+        });
+
+        let error = Client::builder()
+            .timeout(Duration::from_millis(50))
+            .build()
+            .unwrap()
+            .get(format!("http://{}/", addr))
+            .send()
+            .await
+            .unwrap_err();
+        let result = Err(ApiError::from_reqwest(error, true));
+
+        assert!(matches!(result, Err(ApiError::Timeout(_))));
+        assert!(ProxySwitch::Failover.applies(&result));
+        assert!(!ProxySwitch::Captcha.applies(&result));
+    }
+
     #[tokio::test]
     async fn test_client_creation() {
         let client = ApiClient::new(Some("TestAgent/1.0".to_string()));
@@ -253,26 +1172,91 @@ mod tests {
         let proxy = ProxyInfo::new("127.0.0.1".to_string(), 8080);
         let url = proxy.to_url().unwrap();
         assert_eq!(url, "http://127.0.0.1:8080");
+
+        let proxy = proxy.with_type(ProxyType::Socks5);
+        assert_eq!(proxy.to_url().unwrap(), "socks5://127.0.0.1:8080");
+    }
+
+    #[test]
+    fn test_proxy_info_ipv6() {
+        let proxy = ProxyInfo::new("::1".to_string(), 8080);
+        assert_eq!(proxy.to_url().unwrap(), "http://[::1]:8080");
+
+        let proxy = ProxyInfo::new("2001:db8::7".to_string(), 3128)
+            .with_auth("user".to_string(), "pass".to_string());
+        let url = proxy.to_url().unwrap();
+        assert_eq!(url, "http://user:pass@[2001:db8::7]:3128");
+
+        // The URL parses back to the same proxy
+        let parsed = reqwest::Url::parse(&url).unwrap();
+        assert_eq!(parsed.host_str(), Some("[2001:db8::7]"));
+        assert_eq!(parsed.port(), Some(3128));
+        assert_eq!(parsed.username(), "user");
+        assert_eq!(parsed.password(), Some("pass"));
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    struct Product {
+        id: u64,
+        name: String,
+    }
+
+    #[test]
+    fn test_response_json() {
+        let response = ResponseBody::new(
+            200,
+            HeaderMap::new(),
+            br#"{"id": 123, "name": "Test Product"}"#.to_vec(),
+        );
+
+        assert!(response.is_success());
+        let product: Product = response.json().unwrap();
+        assert_eq!(product.id, 123);
+        assert_eq!(product.name, "Test Product");
+    }
+
+    #[test]
+    fn test_response_json_error_includes_snippet() {
+        let response =
+            ResponseBody::new(502, HeaderMap::new(), b"<html>Bad Gateway</html>".to_vec());
+
+        assert!(!response.is_success());
+        let err = response.json::<Product>().unwrap_err().to_string();
+        assert!(err.contains("status 502"));
+        assert!(err.contains("<html>Bad Gateway</html>"));
     }
 }
 impl ApiClient {
     pub fn with_cookie_jar(cookie_jar: Arc<Jar>) -> Result<ApiClient> {
         let ua = "Lazabot/1.0".to_string();
+        let cookie_recorder = Arc::new(CookieRecorder::new(cookie_jar.clone()));
 
-        let builder = ClientBuilder::new()
-            .cookie_provider(cookie_jar)
-            .timeout(Duration::from_secs(30))
-            .connect_timeout(Duration::from_secs(10))
-            .redirect(reqwest::redirect::Policy::limited(10))
-            .user_agent(&ua);
-
-        let client = builder
+        let client = Self::client_builder(&ua, cookie_recorder.clone())
             .build()
             .context("Failed to create HTTP client with cookie jar")?;
         Ok(ApiClient {
             client,
             user_agent: ua,
             retry_config: RetryConfig::default(),
+            cookie_jar,
+            circuit_breaker: None,
+            rate_limiter: None,
+            logging: false,
+            redacted_fields: default_redacted_fields(),
+            captcha_proxy_manager: None,
+            max_captcha_proxy_switches: 0,
+            failover_proxy_manager: None,
+            max_proxy_failovers: 0,
+            dns_resolver: None,
+            signer: None,
+            har_recorder: None,
+            replay: None,
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+            cookie_recorder,
+            user_agents: Vec::new(),
+            next_user_agent: AtomicUsize::new(0),
+            token_provider: None,
+            in_flight: None,
         })
     }
 }
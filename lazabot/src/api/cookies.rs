@@ -0,0 +1,192 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{Context, Result};
+use cookie::time::OffsetDateTime;
+use cookie::Cookie;
+use parking_lot::Mutex;
+use reqwest::cookie::{CookieStore, Jar};
+use reqwest::header::HeaderValue;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Length of the AES-GCM nonce prefixed to exported cookies
+const NONCE_LEN: usize = 12;
+
+/// A cookie with its attributes, and the URL that set it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SavedCookie {
+    url: String,
+    /// `Set-Cookie` value, with any `Max-Age` turned into an absolute `Expires`
+    set_cookie: String,
+}
+
+impl SavedCookie {
+    fn is_expired(&self, now: OffsetDateTime) -> bool {
+        Cookie::parse(self.set_cookie.as_str())
+            .ok()
+            .and_then(|cookie| cookie.expires_datetime())
+            .is_some_and(|expires| expires <= now)
+    }
+}
+
+/// Cookie provider that forwards to a `Jar` and remembers every cookie set through it,
+/// since the jar only hands back names and values. Cookies are keyed by domain, path
+/// and name, as the jar stores them.
+pub(crate) struct CookieRecorder {
+    jar: Arc<Jar>,
+    cookies: Mutex<BTreeMap<(String, String, String), SavedCookie>>,
+}
+
+impl CookieRecorder {
+    pub(crate) fn new(jar: Arc<Jar>) -> Self {
+        Self {
+            jar,
+            cookies: Mutex::default(),
+        }
+    }
+
+    /// Remember `set_cookie` as set by `url`, or forget it if it has expired
+    fn record(&self, set_cookie: &str, url: &Url) {
+        let Ok(mut cookie) = Cookie::parse(set_cookie) else {
+            return;
+        };
+
+        let now = OffsetDateTime::now_utc();
+        if let Some(max_age) = cookie.max_age() {
+            cookie.set_max_age(None);
+            cookie.set_expires(now + max_age);
+        }
+
+        let domain = match cookie.domain() {
+            Some(domain) => format!(".{}", domain.trim_start_matches('.')),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+        let key = (
+            domain.to_ascii_lowercase(),
+            cookie.path().unwrap_or("/").to_string(),
+            cookie.name().to_string(),
+        );
+        let saved = SavedCookie {
+            url: url.to_string(),
+            set_cookie: cookie.to_string(),
+        };
+
+        let mut cookies = self.cookies.lock();
+        if saved.is_expired(now) {
+            cookies.remove(&key);
+        } else {
+            cookies.insert(key, saved);
+        }
+    }
+
+    /// Encrypt the unexpired cookies with `key`, returning the data and cookie count
+    pub(crate) fn export(&self, key: &[u8; 32]) -> Result<(Vec<u8>, usize)> {
+        let now = OffsetDateTime::now_utc();
+        let cookies: Vec<SavedCookie> = self
+            .cookies
+            .lock()
+            .values()
+            .filter(|saved| !saved.is_expired(now))
+            .cloned()
+            .collect();
+
+        let json = serde_json::to_vec(&cookies).context("Failed to serialize cookies")?;
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, json.as_slice())
+            .map_err(|e| anyhow::anyhow!("Failed to encrypt cookies: {}", e))?;
+
+        let mut data = nonce.to_vec();
+        data.extend_from_slice(&ciphertext);
+        Ok((data, cookies.len()))
+    }
+
+    /// Decrypt cookies exported with `key` and add the unexpired ones to the jar,
+    /// returning how many were added
+    pub(crate) fn import(&self, data: &[u8], key: &[u8; 32]) -> Result<usize> {
+        if data.len() < NONCE_LEN {
+            return Err(anyhow::anyhow!("Cookie data is too short"));
+        }
+        let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+        let json = cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|e| anyhow::anyhow!("Failed to decrypt cookies: {}", e))?;
+        let cookies: Vec<SavedCookie> =
+            serde_json::from_slice(&json).context("Failed to parse cookies")?;
+
+        let now = OffsetDateTime::now_utc();
+        let mut count = 0;
+        for saved in cookies.iter().filter(|saved| !saved.is_expired(now)) {
+            let url = Url::parse(&saved.url)
+                .with_context(|| format!("Invalid cookie URL: {}", saved.url))?;
+            self.jar.add_cookie_str(&saved.set_cookie, &url);
+            self.record(&saved.set_cookie, &url);
+            count += 1;
+        }
+
+        Ok(count)
+    }
+}
+
+impl CookieStore for CookieRecorder {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        let headers: Vec<&HeaderValue> = cookie_headers.collect();
+        for header in &headers {
+            if let Ok(set_cookie) = header.to_str() {
+                self.record(set_cookie, url);
+            }
+        }
+        self.jar.set_cookies(&mut headers.into_iter(), url);
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        self.jar.cookies(url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exported_cookies_keep_their_attributes() -> Result<()> {
+        let key = [7u8; 32];
+        let url = Url::parse("https://member.lazada.sg/login")?;
+        let recorder = CookieRecorder::new(Arc::new(Jar::default()));
+        recorder.record("lzd_sid=abc123; Domain=lazada.sg; Path=/; Secure", &url);
+        recorder.record("cart=xyz; Path=/cart; Max-Age=3600", &url);
+        recorder.record("old=1; Path=/; Expires=Thu, 01 Jan 2015 00:00:00 GMT", &url);
+
+        let (data, count) = recorder.export(&key)?;
+        assert_eq!(count, 2);
+
+        let restored = CookieRecorder::new(Arc::new(Jar::default()));
+        assert_eq!(restored.import(&data, &key)?, 2);
+
+        let cookies = |url: &str| {
+            restored
+                .cookies(&Url::parse(url).unwrap())
+                .map(|value| value.to_str().unwrap().to_string())
+                .unwrap_or_default()
+        };
+        // Domain and Secure: sent to any lazada.sg host, but only over https
+        assert!(cookies("https://www.lazada.sg/").contains("lzd_sid=abc123"));
+        assert!(!cookies("http://www.lazada.sg/").contains("lzd_sid"));
+        // Path, and Max-Age kept as an absolute expiry
+        assert!(cookies("https://member.lazada.sg/cart/1").contains("cart=xyz"));
+        assert!(!cookies("https://member.lazada.sg/").contains("cart"));
+        let saved = restored.cookies.lock();
+        let cart = saved
+            .values()
+            .find(|saved| saved.set_cookie.starts_with("cart="))
+            .unwrap();
+        assert!(cart.set_cookie.contains("Expires="));
+        assert!(!cart.set_cookie.contains("Max-Age"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+use anyhow::{Context, Result};
+use hyper::client::connect::dns::Name;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+use reqwest::Client;
+use serde::Deserialize;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+use tracing::debug;
+
+/// Cloudflare's public DNS-over-HTTPS endpoint
+pub const CLOUDFLARE_DOH_URL: &str = "https://cloudflare-dns.com/dns-query";
+
+/// DNS record type codes used in DoH answers
+const RECORD_A: u16 = 1;
+const RECORD_AAAA: u16 = 28;
+
+#[derive(Debug, Deserialize)]
+struct DohResponse {
+    #[serde(rename = "Status")]
+    status: u32,
+    #[serde(rename = "Answer", default)]
+    answer: Vec<DohAnswer>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DohAnswer {
+    #[serde(rename = "type")]
+    record_type: u16,
+    data: String,
+}
+
+/// Resolves hostnames with DNS-over-HTTPS using the JSON API, so lookups don't leak to
+/// the local network's resolver. The DoH endpoint itself is resolved by the system.
+#[derive(Debug, Clone)]
+pub struct DohResolver {
+    client: Client,
+    endpoint: String,
+}
+
+impl DohResolver {
+    pub fn new(endpoint: impl Into<String>) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .context("Failed to create DoH client")?;
+
+        Ok(Self {
+            client,
+            endpoint: endpoint.into(),
+        })
+    }
+
+    /// Resolver using Cloudflare's DoH endpoint
+    pub fn cloudflare() -> Result<Self> {
+        Self::new(CLOUDFLARE_DOH_URL)
+    }
+
+    /// Look up the A and AAAA records for `host`, IPv4 addresses first
+    pub async fn lookup(&self, host: &str) -> Result<Vec<IpAddr>> {
+        let (v4, v6) = tokio::join!(
+            self.query(host, RECORD_A, "A"),
+            self.query(host, RECORD_AAAA, "AAAA")
+        );
+
+        let mut addrs = v4?;
+        addrs.extend(v6?);
+        if addrs.is_empty() {
+            anyhow::bail!("DoH lookup for {} returned no addresses", host);
+        }
+
+        debug!("DoH resolved {} to {:?}", host, addrs);
+        Ok(addrs)
+    }
+
+    async fn query(&self, host: &str, record_type: u16, type_name: &str) -> Result<Vec<IpAddr>> {
+        let response: DohResponse = self
+            .client
+            .get(&self.endpoint)
+            .query(&[("name", host), ("type", type_name)])
+            .header("accept", "application/dns-json")
+            .send()
+            .await
+            .context("DoH request failed")?
+            .error_for_status()
+            .context("DoH endpoint returned an error")?
+            .json()
+            .await
+            .context("Invalid DoH response")?;
+
+        // NXDOMAIN and friends; the other record type may still resolve
+        if response.status != 0 {
+            debug!(
+                "DoH {} lookup for {} returned status {}",
+                type_name, host, response.status
+            );
+            return Ok(Vec::new());
+        }
+
+        // Answers can include CNAMEs along the way; keep only addresses
+        Ok(response
+            .answer
+            .into_iter()
+            .filter(|answer| answer.record_type == record_type)
+            .filter_map(|answer| answer.data.parse().ok())
+            .collect())
+    }
+}
+
+impl Resolve for DohResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let resolver = self.clone();
+        Box::pin(async move {
+            let addrs = resolver.lookup(name.as_str()).await?;
+            let addrs: Addrs = Box::new(addrs.into_iter().map(|ip| SocketAddr::new(ip, 0)));
+            Ok(addrs)
+        })
+    }
+}
@@ -0,0 +1,247 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+use crate::api::logging::{is_redacted, redact_body, REDACTED};
+use crate::api::signing::RequestParts;
+
+/// HAR format version written to capture files
+const HAR_VERSION: &str = "1.2";
+
+/// Headers hidden in captures whatever the configured redacted fields are
+const CREDENTIAL_HEADERS: &[&str] = &["authorization", "cookie", "set-cookie"];
+
+/// Top level of a HAR file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Har {
+    pub log: HarLog,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HarLog {
+    pub version: String,
+    pub creator: HarCreator,
+    pub entries: Vec<HarEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HarCreator {
+    pub name: String,
+    pub version: String,
+}
+
+/// One request and its response (or the error that replaced it)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarEntry {
+    pub started_date_time: DateTime<Utc>,
+    /// Total time in milliseconds
+    pub time: u64,
+    pub request: HarRequest,
+    pub response: HarResponse,
+    /// Transport error, when no response was received
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<HarHeader>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_data: Option<HarPostData>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarResponse {
+    /// 0 when the request failed before a response arrived
+    pub status: u16,
+    pub headers: Vec<HarHeader>,
+    pub content: HarContent,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HarHeader {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarPostData {
+    pub mime_type: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarContent {
+    pub size: usize,
+    pub mime_type: String,
+    pub text: String,
+}
+
+impl Har {
+    /// Read a capture file written by [`HarRecorder`]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read HAR file {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Invalid HAR file {}", path.display()))
+    }
+}
+
+/// Collects redacted request/response entries in memory and writes them to a HAR file
+/// on [`flush`](Self::flush) or when the recorder is dropped
+pub struct HarRecorder {
+    path: PathBuf,
+    har: Mutex<Har>,
+    /// Entries were recorded since the file was last written
+    unsaved: AtomicBool,
+}
+
+impl HarRecorder {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            unsaved: AtomicBool::new(false),
+            har: Mutex::new(Har {
+                log: HarLog {
+                    version: HAR_VERSION.to_string(),
+                    creator: HarCreator {
+                        name: env!("CARGO_PKG_NAME").to_string(),
+                        version: env!("CARGO_PKG_VERSION").to_string(),
+                    },
+                    entries: Vec::new(),
+                },
+            }),
+        }
+    }
+
+    /// Record one exchange that took `elapsed`; `response` is `(status, headers, body)`
+    /// or the error message. Values of `redacted_fields` are hidden.
+    pub async fn record(
+        &self,
+        request: &RequestParts<'_>,
+        elapsed: Duration,
+        response: Result<(u16, &HeaderMap, &[u8]), String>,
+        redacted_fields: &[String],
+    ) {
+        let started = Utc::now() - chrono::Duration::from_std(elapsed).unwrap_or_default();
+        let request = HarRequest {
+            method: request.method.to_string(),
+            url: request.url.to_string(),
+            headers: har_headers(request.headers, redacted_fields),
+            post_data: (!request.body.is_empty()).then(|| HarPostData {
+                mime_type: content_type(request.headers),
+                text: redact_body(request.body, redacted_fields),
+            }),
+        };
+
+        let (response, comment) = match response {
+            Ok((status, headers, body)) => (
+                HarResponse {
+                    status,
+                    headers: har_headers(headers, redacted_fields),
+                    content: HarContent {
+                        size: body.len(),
+                        mime_type: content_type(headers),
+                        text: redact_body(body, redacted_fields),
+                    },
+                },
+                None,
+            ),
+            Err(error) => (
+                HarResponse {
+                    status: 0,
+                    headers: Vec::new(),
+                    content: HarContent {
+                        size: 0,
+                        mime_type: String::new(),
+                        text: String::new(),
+                    },
+                },
+                Some(error),
+            ),
+        };
+
+        self.har.lock().await.log.entries.push(HarEntry {
+            started_date_time: started,
+            time: elapsed.as_millis() as u64,
+            request,
+            response,
+            comment,
+        });
+        self.unsaved.store(true, Ordering::Release);
+    }
+
+    /// Write every entry recorded so far to the HAR file
+    pub async fn flush(&self) -> Result<()> {
+        let har = self.har.lock().await;
+        if !self.unsaved.swap(false, Ordering::AcqRel) {
+            return Ok(());
+        }
+
+        let json = serde_json::to_string_pretty(&*har).context("Failed to serialize HAR")?;
+        tokio::fs::write(&self.path, json)
+            .await
+            .with_context(|| format!("Failed to write HAR file {}", self.path.display()))
+    }
+}
+
+impl Drop for HarRecorder {
+    fn drop(&mut self) {
+        if !*self.unsaved.get_mut() {
+            return;
+        }
+
+        let result = serde_json::to_string_pretty(self.har.get_mut())
+            .context("Failed to serialize HAR")
+            .and_then(|json| {
+                std::fs::write(&self.path, json)
+                    .with_context(|| format!("Failed to write HAR file {}", self.path.display()))
+            });
+        if let Err(e) = result {
+            tracing::warn!("Failed to save HAR capture: {:#}", e);
+        }
+    }
+}
+
+fn har_headers(headers: &HeaderMap, redacted_fields: &[String]) -> Vec<HarHeader> {
+    headers
+        .iter()
+        .map(|(name, value)| HarHeader {
+            name: name.to_string(),
+            value: if is_credential_header(name.as_str())
+                || is_redacted(name.as_str(), redacted_fields)
+            {
+                REDACTED.to_string()
+            } else {
+                value.to_str().unwrap_or("<binary>").to_string()
+            },
+        })
+        .collect()
+}
+
+fn is_credential_header(name: &str) -> bool {
+    CREDENTIAL_HEADERS
+        .iter()
+        .any(|header| header.eq_ignore_ascii_case(name))
+}
+
+fn content_type(headers: &HeaderMap) -> String {
+    headers
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string()
+}
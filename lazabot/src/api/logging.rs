@@ -0,0 +1,112 @@
+use reqwest::header::HeaderMap;
+use serde_json::Value;
+
+/// Placeholder written in place of sensitive values
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Header and body field names redacted by default (matched case-insensitively)
+pub const DEFAULT_REDACTED_FIELDS: &[&str] = &["authorization", "password", "api_key"];
+
+/// Whether `name` is one of the redacted `fields`, ignoring case
+pub(crate) fn is_redacted(name: &str, fields: &[String]) -> bool {
+    fields.iter().any(|field| field.eq_ignore_ascii_case(name))
+}
+
+/// Format headers for logging, hiding values of redacted fields
+pub fn redact_headers(headers: &HeaderMap, fields: &[String]) -> String {
+    let pairs: Vec<String> = headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if is_redacted(name.as_str(), fields) {
+                REDACTED
+            } else {
+                value.to_str().unwrap_or("<binary>")
+            };
+            format!("{}: {}", name, value)
+        })
+        .collect();
+
+    format!("{{{}}}", pairs.join(", "))
+}
+
+/// Format a body for logging, hiding values of redacted fields.
+/// JSON bodies are redacted by key at any depth, other bodies as `key=value` form pairs.
+pub fn redact_body(body: &[u8], fields: &[String]) -> String {
+    if let Ok(mut json) = serde_json::from_slice::<Value>(body) {
+        redact_json(&mut json, fields);
+        return json.to_string();
+    }
+
+    let text = String::from_utf8_lossy(body);
+    if !text.contains('=') {
+        return text.into_owned();
+    }
+
+    text.split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((key, _)) if is_redacted(key, fields) => format!("{}={}", key, REDACTED),
+            _ => pair.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn redact_json(value: &mut Value, fields: &[String]) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if is_redacted(key, fields) {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    redact_json(value, fields);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                redact_json(item, fields);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn default_fields() -> Vec<String> {
+        DEFAULT_REDACTED_FIELDS
+            .iter()
+            .map(|f| f.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_redact_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("Authorization", "Bearer secret".parse().unwrap());
+        headers.insert("Accept", "application/json".parse().unwrap());
+
+        let formatted = redact_headers(&headers, &default_fields());
+        assert!(!formatted.contains("secret"));
+        assert!(formatted.contains("authorization: [REDACTED]"));
+        assert!(formatted.contains("accept: application/json"));
+    }
+
+    #[test]
+    fn test_redact_body() {
+        let fields = default_fields();
+
+        let json = br#"{"user":{"name":"bob","Password":"hunter2"},"keys":[{"api_key":"k"}]}"#;
+        let redacted = redact_body(json, &fields);
+        assert!(!redacted.contains("hunter2"));
+        assert!(redacted.contains(r#""name":"bob""#));
+        assert!(redacted.contains(r#""api_key":"[REDACTED]""#));
+
+        let form = redact_body(b"username=bob&password=hunter2", &fields);
+        assert_eq!(form, "username=bob&password=[REDACTED]");
+
+        assert_eq!(redact_body(b"plain text", &fields), "plain text");
+    }
+}
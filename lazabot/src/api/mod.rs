@@ -1,3 +1,21 @@
+pub mod auth;
+pub mod circuit_breaker;
 pub mod client;
+pub mod cookies;
+pub mod doh;
+pub mod har;
+pub mod logging;
+pub mod rate_limiter;
+pub mod replay;
+pub mod signing;
 
-pub use client::{ApiClient, ProxyInfo, ResponseBody, RetryConfig};
+pub use auth::TokenProvider;
+pub use circuit_breaker::{CircuitBreaker, CircuitBreakerConfig, CircuitState};
+pub use client::{
+    ApiClient, ApiError, ApiResponse, ProxyInfo, ProxyType, ResponseBody, RetryConfig,
+};
+pub use doh::DohResolver;
+pub use har::{Har, HarRecorder};
+pub use rate_limiter::RateLimiter;
+pub use replay::ReplayClient;
+pub use signing::{hmac_sha256_signer, RequestParts, Signer};
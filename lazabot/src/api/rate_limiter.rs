@@ -0,0 +1,94 @@
+use dashmap::DashMap;
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+use tracing::debug;
+
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// Per-host token bucket rate limiter
+#[derive(Debug)]
+pub struct RateLimiter {
+    /// Tokens added per second
+    rate: f64,
+    /// Maximum number of tokens a bucket can hold
+    burst: f64,
+    buckets: DashMap<String, TokenBucket>,
+}
+
+impl RateLimiter {
+    /// Create a limiter allowing `per_host_rps` requests per second with bursts up to `burst`
+    pub fn new(per_host_rps: f64, burst: u32) -> Self {
+        Self {
+            rate: per_host_rps.max(f64::MIN_POSITIVE),
+            burst: f64::from(burst.max(1)),
+            buckets: DashMap::new(),
+        }
+    }
+
+    /// Requests per second allowed for each host
+    pub fn per_host_rps(&self) -> f64 {
+        self.rate
+    }
+
+    /// Wait until a token is available for `host`
+    pub async fn acquire(&self, host: &str) {
+        let wait = self.reserve(host);
+
+        if !wait.is_zero() {
+            debug!("Rate limit reached for {}, waiting {:?}", host, wait);
+            sleep(wait).await;
+        }
+    }
+
+    /// Take a token, returning how long the caller must wait before using it.
+    /// Tokens may go negative so that concurrent callers queue up fairly.
+    fn reserve(&self, host: &str) -> Duration {
+        let now = Instant::now();
+        let mut bucket = self
+            .buckets
+            .entry(host.to_string())
+            .or_insert_with(|| TokenBucket {
+                tokens: self.burst,
+                last_refill: now,
+            });
+
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.rate).min(self.burst);
+        bucket.last_refill = now;
+        bucket.tokens -= 1.0;
+
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / self.rate)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_burst_then_throttle() {
+        let limiter = RateLimiter::new(10.0, 3);
+
+        // Burst is served immediately
+        for _ in 0..3 {
+            assert!(limiter.reserve("example.com").is_zero());
+        }
+
+        // Next token is ~100ms away, the one after ~200ms
+        let wait = limiter.reserve("example.com");
+        assert!(wait > Duration::from_millis(80) && wait <= Duration::from_millis(100));
+        let wait = limiter.reserve("example.com");
+        assert!(wait > Duration::from_millis(180) && wait <= Duration::from_millis(200));
+
+        // Hosts have independent buckets
+        assert!(limiter.reserve("other.com").is_zero());
+    }
+}
@@ -0,0 +1,88 @@
+use anyhow::Result;
+use parking_lot::Mutex;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Method, Url};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::api::client::{ApiError, ResponseBody};
+use crate::api::har::{Har, HarResponse};
+
+/// Answers requests from a recorded HAR file instead of the network, for deterministic
+/// offline tests. Requests are matched on method and URL; repeated requests get the
+/// recorded responses in order, then keep getting the last one.
+pub struct ReplayClient {
+    responses: HashMap<(String, String), Vec<HarResponse>>,
+    served: Mutex<HashMap<(String, String), usize>>,
+}
+
+impl ReplayClient {
+    pub fn new(har: Har) -> Self {
+        let mut responses: HashMap<_, Vec<_>> = HashMap::new();
+        for entry in har.log.entries {
+            // Failed exchanges are recorded with status 0 and have no response to give back
+            if entry.response.status == 0 {
+                continue;
+            }
+            let key = (
+                entry.request.method.to_uppercase(),
+                normalize_url(&entry.request.url),
+            );
+            responses.entry(key).or_default().push(entry.response);
+        }
+
+        Self {
+            responses,
+            served: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Load a HAR file written by `ApiClient::with_har_capture`
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::new(Har::load(path)?))
+    }
+
+    /// The recorded response for `method` and `url`
+    pub fn respond(&self, method: &Method, url: &Url) -> Result<ResponseBody, ApiError> {
+        let key = (method.as_str().to_uppercase(), normalize_url(url.as_str()));
+        let recorded = self
+            .responses
+            .get(&key)
+            .ok_or_else(|| ApiError::NoRecordedResponse {
+                method: method.to_string(),
+                url: url.to_string(),
+            })?;
+
+        let index = {
+            let mut served = self.served.lock();
+            let count = served.entry(key).or_insert(0);
+            let index = (*count).min(recorded.len() - 1);
+            *count += 1;
+            index
+        };
+        let response = &recorded[index];
+
+        let mut headers = HeaderMap::new();
+        for header in &response.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(header.name.as_bytes()),
+                HeaderValue::from_str(&header.value),
+            ) {
+                headers.append(name, value);
+            }
+        }
+
+        Ok(ResponseBody::new(
+            response.status,
+            headers,
+            response.content.text.clone().into_bytes(),
+        ))
+    }
+}
+
+/// Parse and re-serialize so equivalent spellings of a URL match
+fn normalize_url(url: &str) -> String {
+    Url::parse(url)
+        .map(String::from)
+        .unwrap_or_else(|_| url.to_string())
+}
@@ -0,0 +1,54 @@
+use hmac::{Hmac, Mac};
+use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::{Method, Url};
+use sha2::Sha256;
+
+/// Header carrying the hex-encoded signature from `hmac_sha256_signer`
+pub const SIGNATURE_HEADER: &str = "x-signature";
+
+/// The parts of an outgoing request a signer can cover
+#[derive(Debug)]
+pub struct RequestParts<'a> {
+    pub method: &'a Method,
+    pub url: &'a Url,
+    pub headers: &'a HeaderMap,
+    pub body: &'a [u8],
+}
+
+impl RequestParts<'_> {
+    /// Path plus query string, e.g. `/orders?page=2`
+    pub fn path_and_query(&self) -> String {
+        match self.url.query() {
+            Some(query) => format!("{}?{}", self.url.path(), query),
+            None => self.url.path().to_string(),
+        }
+    }
+}
+
+/// Computes headers to add to a request just before it is sent
+pub type Signer = dyn Fn(&RequestParts) -> HeaderMap + Send + Sync;
+
+/// HMAC-SHA256 over `METHOD\npath?query\nbody`, hex-encoded
+pub fn hmac_sha256_signature(key: &[u8], parts: &RequestParts) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(parts.method.as_str().as_bytes());
+    mac.update(b"\n");
+    mac.update(parts.path_and_query().as_bytes());
+    mac.update(b"\n");
+    mac.update(parts.body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Sample signer putting `hmac_sha256_signature` in the `X-Signature` header
+pub fn hmac_sha256_signer(key: impl Into<Vec<u8>>) -> Box<Signer> {
+    let key = key.into();
+    Box::new(move |parts| {
+        let mut headers = HeaderMap::new();
+        let signature = hmac_sha256_signature(&key, parts);
+        headers.insert(
+            SIGNATURE_HEADER,
+            HeaderValue::from_str(&signature).expect("hex is a valid header value"),
+        );
+        headers
+    })
+}
@@ -1,3 +1,6 @@
 pub mod solver;
 
-pub use solver::{CaptchaSolver, CaptchaSolverTrait, CaptchaType, MockCaptchaSolver};
+pub use solver::{
+    CaptchaSolver, CaptchaSolverTrait, CaptchaType, CaptchaUsage, MockCaptchaSolver,
+    UnknownCaptchaType,
+};
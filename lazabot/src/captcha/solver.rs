@@ -1,6 +1,9 @@
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use reqwest::Client;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::{sleep, timeout};
 use tracing::{debug, info, warn};
@@ -17,12 +20,74 @@ const POLLING_INTERVAL: u64 = 5;
 /// Request timeout in seconds
 const REQUEST_TIMEOUT: u64 = 30;
 
+/// Estimated 2Captcha price per solved image captcha (USD)
+pub const IMAGE_SOLVE_COST_USD: f64 = 0.001;
+/// Estimated 2Captcha price per solved reCAPTCHA (USD)
+pub const RECAPTCHA_SOLVE_COST_USD: f64 = 0.00299;
+
 /// Types of captcha supported by 2Captcha
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CaptchaType {
     Image,
     ReCaptchaV2,
     ReCaptchaV3,
+    HCaptcha,
+}
+
+/// Captcha type name that `CaptchaType` does not recognise
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Unknown captcha type: {0}")]
+pub struct UnknownCaptchaType(pub String);
+
+impl std::str::FromStr for CaptchaType {
+    type Err = UnknownCaptchaType;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "image" => Ok(Self::Image),
+            "recaptcha_v2" => Ok(Self::ReCaptchaV2),
+            "recaptcha_v3" => Ok(Self::ReCaptchaV3),
+            "hcaptcha" => Ok(Self::HCaptcha),
+            _ => Err(UnknownCaptchaType(s.to_string())),
+        }
+    }
+}
+
+/// Captcha solving usage and estimated spend
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CaptchaUsage {
+    pub attempts: u64,
+    pub successes: u64,
+    pub estimated_cost_usd: f64,
+}
+
+/// Shared usage counter; clones of a solver count into the same totals
+#[derive(Debug, Default)]
+struct UsageCounter {
+    attempts: AtomicU64,
+    successes: AtomicU64,
+    /// Cost in millionths of a dollar so it can be kept atomically
+    cost_micro_usd: AtomicU64,
+}
+
+impl UsageCounter {
+    /// Record one solve attempt; only successful solves are billed
+    fn record<T>(&self, result: &Result<T>, cost_usd: f64) {
+        self.attempts.fetch_add(1, Ordering::Relaxed);
+        if result.is_ok() {
+            self.successes.fetch_add(1, Ordering::Relaxed);
+            self.cost_micro_usd
+                .fetch_add((cost_usd * 1_000_000.0).round() as u64, Ordering::Relaxed);
+        }
+    }
+
+    fn snapshot(&self) -> CaptchaUsage {
+        CaptchaUsage {
+            attempts: self.attempts.load(Ordering::Relaxed),
+            successes: self.successes.load(Ordering::Relaxed),
+            estimated_cost_usd: self.cost_micro_usd.load(Ordering::Relaxed) as f64 / 1_000_000.0,
+        }
+    }
 }
 
 /// Captcha solver trait for testability
@@ -30,6 +95,15 @@ pub enum CaptchaType {
 pub trait CaptchaSolverTrait {
     async fn solve_image(&self, image_bytes: &[u8]) -> Result<String>;
     async fn solve_recaptcha(&self, site_key: &str, page_url: &str) -> Result<String>;
+    async fn solve_recaptcha_v3(
+        &self,
+        site_key: &str,
+        page_url: &str,
+        action: &str,
+        min_score: f32,
+    ) -> Result<String>;
+    /// Solves attempted and estimated spend so far
+    fn usage(&self) -> CaptchaUsage;
 }
 
 /// 2Captcha solver implementation
@@ -37,6 +111,7 @@ pub trait CaptchaSolverTrait {
 pub struct CaptchaSolver {
     pub api_key: String,
     client: Client,
+    usage: Arc<UsageCounter>,
 }
 
 impl CaptchaSolver {
@@ -47,7 +122,11 @@ impl CaptchaSolver {
             .build()
             .expect("Failed to create HTTP client");
 
-        Self { api_key, client }
+        Self {
+            api_key,
+            client,
+            usage: Arc::new(UsageCounter::default()),
+        }
     }
 
     /// Create a new captcha solver from environment variable
@@ -83,14 +162,27 @@ impl CaptchaSolver {
             }
             CaptchaType::ReCaptchaV3 => {
                 params.push(("googlekey", data));
+                params.push(("version", "v3"));
+                let additional_params = additional_params.unwrap_or_default();
+                let param = |name: &str| {
+                    additional_params
+                        .iter()
+                        .find(|(k, _)| *k == name)
+                        .map(|(_, v)| *v)
+                };
+                if let Some(url) = param("pageurl") {
+                    params.push(("pageurl", url));
+                }
+                params.push(("action", param("action").unwrap_or("verify")));
+                params.push(("min_score", param("min_score").unwrap_or("0.3")));
+            }
+            CaptchaType::HCaptcha => {
+                params.push(("sitekey", data));
                 if let Some(url) = additional_params
                     .and_then(|p| p.iter().find(|(k, _)| *k == "pageurl").map(|(_, v)| *v))
                 {
                     params.push(("pageurl", url));
                 }
-                // Default action for ReCaptchaV3
-                params.push(("action", "verify"));
-                params.push(("min_score", "0.3"));
             }
         }
 
@@ -184,6 +276,7 @@ impl CaptchaSolver {
             CaptchaType::Image => "base64",
             CaptchaType::ReCaptchaV2 => "userrecaptcha",
             CaptchaType::ReCaptchaV3 => "userrecaptcha",
+            CaptchaType::HCaptcha => "hcaptcha",
         }
     }
 }
@@ -196,11 +289,15 @@ impl CaptchaSolverTrait for CaptchaSolver {
 
         let base64_image =
             base64::Engine::encode(&base64::engine::general_purpose::STANDARD, image_bytes);
-        let captcha_id = self
+        let result = match self
             .submit_captcha(CaptchaType::Image, &base64_image, None)
-            .await?;
-
-        self.poll_result(&captcha_id).await
+            .await
+        {
+            Ok(captcha_id) => self.poll_result(&captcha_id).await,
+            Err(e) => Err(e),
+        };
+        self.usage.record(&result, IMAGE_SOLVE_COST_USD);
+        result
     }
 
     /// Solve a reCAPTCHA v2
@@ -211,11 +308,49 @@ impl CaptchaSolverTrait for CaptchaSolver {
         );
 
         let additional_params = vec![("pageurl", page_url)];
-        let captcha_id = self
+        let result = match self
             .submit_captcha(CaptchaType::ReCaptchaV2, site_key, Some(additional_params))
-            .await?;
+            .await
+        {
+            Ok(captcha_id) => self.poll_result(&captcha_id).await,
+            Err(e) => Err(e),
+        };
+        self.usage.record(&result, RECAPTCHA_SOLVE_COST_USD);
+        result
+    }
+
+    /// Solve a reCAPTCHA v3 for `action`, requiring at least `min_score`
+    async fn solve_recaptcha_v3(
+        &self,
+        site_key: &str,
+        page_url: &str,
+        action: &str,
+        min_score: f32,
+    ) -> Result<String> {
+        info!(
+            "Solving reCAPTCHA v3 for site: {} at URL: {} (action={}, min_score={})",
+            site_key, page_url, action, min_score
+        );
 
-        self.poll_result(&captcha_id).await
+        let min_score = min_score.to_string();
+        let additional_params = vec![
+            ("pageurl", page_url),
+            ("action", action),
+            ("min_score", min_score.as_str()),
+        ];
+        let result = match self
+            .submit_captcha(CaptchaType::ReCaptchaV3, site_key, Some(additional_params))
+            .await
+        {
+            Ok(captcha_id) => self.poll_result(&captcha_id).await,
+            Err(e) => Err(e),
+        };
+        self.usage.record(&result, RECAPTCHA_SOLVE_COST_USD);
+        result
+    }
+
+    fn usage(&self) -> CaptchaUsage {
+        self.usage.snapshot()
     }
 }
 
@@ -224,27 +359,61 @@ impl CaptchaSolverTrait for CaptchaSolver {
 pub struct MockCaptchaSolver {
     image_result: String,
     recaptcha_result: String,
+    recaptcha_v3_result: String,
+    usage: Arc<UsageCounter>,
 }
 
 impl MockCaptchaSolver {
     pub fn new(image_result: String, recaptcha_result: String) -> Self {
         Self {
             image_result,
+            recaptcha_v3_result: recaptcha_result.clone(),
             recaptcha_result,
+            usage: Arc::new(UsageCounter::default()),
         }
     }
+
+    /// Return `result` for reCAPTCHA v3 solves instead of the v2 result
+    pub fn with_recaptcha_v3_result(mut self, result: String) -> Self {
+        self.recaptcha_v3_result = result;
+        self
+    }
 }
 
 #[async_trait]
 impl CaptchaSolverTrait for MockCaptchaSolver {
     async fn solve_image(&self, _image_bytes: &[u8]) -> Result<String> {
         debug!("Mock solving image captcha");
-        Ok(self.image_result.clone())
+        let result = Ok(self.image_result.clone());
+        self.usage.record(&result, IMAGE_SOLVE_COST_USD);
+        result
     }
 
     async fn solve_recaptcha(&self, _site_key: &str, _page_url: &str) -> Result<String> {
         debug!("Mock solving reCAPTCHA");
-        Ok(self.recaptcha_result.clone())
+        let result = Ok(self.recaptcha_result.clone());
+        self.usage.record(&result, RECAPTCHA_SOLVE_COST_USD);
+        result
+    }
+
+    async fn solve_recaptcha_v3(
+        &self,
+        _site_key: &str,
+        _page_url: &str,
+        action: &str,
+        min_score: f32,
+    ) -> Result<String> {
+        debug!(
+            "Mock solving reCAPTCHA v3 (action={}, min_score={})",
+            action, min_score
+        );
+        let result = Ok(self.recaptcha_v3_result.clone());
+        self.usage.record(&result, RECAPTCHA_SOLVE_COST_USD);
+        result
+    }
+
+    fn usage(&self) -> CaptchaUsage {
+        self.usage.snapshot()
     }
 }
 
@@ -270,6 +439,26 @@ mod tests {
         assert_eq!(result, "recaptcha123");
     }
 
+    #[tokio::test]
+    async fn test_mock_usage_accounting() {
+        let solver = MockCaptchaSolver::new("test123".to_string(), "recaptcha123".to_string());
+        assert_eq!(solver.usage(), CaptchaUsage::default());
+
+        solver
+            .solve_recaptcha("site_key", "https://example.com")
+            .await
+            .unwrap();
+        solver.solve_image(b"fake_image_data").await.unwrap();
+
+        let usage = solver.usage();
+        assert_eq!(usage.attempts, 2);
+        assert_eq!(usage.successes, 2);
+        assert!(
+            (usage.estimated_cost_usd - (RECAPTCHA_SOLVE_COST_USD + IMAGE_SOLVE_COST_USD)).abs()
+                < 1e-9
+        );
+    }
+
     #[test]
     fn test_captcha_solver_creation() {
         let solver = CaptchaSolver::new("test_api_key".to_string());
@@ -288,5 +477,24 @@ mod tests {
             solver.get_method(&CaptchaType::ReCaptchaV3),
             "userrecaptcha"
         );
+        assert_eq!(solver.get_method(&CaptchaType::HCaptcha), "hcaptcha");
+    }
+
+    #[test]
+    fn test_captcha_type_from_str() {
+        assert_eq!("image".parse::<CaptchaType>(), Ok(CaptchaType::Image));
+        assert_eq!(
+            "recaptcha_v2".parse::<CaptchaType>(),
+            Ok(CaptchaType::ReCaptchaV2)
+        );
+        assert_eq!(
+            "recaptcha_v3".parse::<CaptchaType>(),
+            Ok(CaptchaType::ReCaptchaV3)
+        );
+        assert_eq!("hCaptcha".parse::<CaptchaType>(), Ok(CaptchaType::HCaptcha));
+        assert_eq!(
+            "funcaptcha".parse::<CaptchaType>(),
+            Err(UnknownCaptchaType("funcaptcha".to_string()))
+        );
     }
 }
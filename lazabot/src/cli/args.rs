@@ -1,10 +1,17 @@
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(name = "lazabot")]
 #[command(about = "A CLI bot for Lazada automation")]
 #[command(version)]
 pub struct Cli {
+    /// Path to a .env file to load before running (defaults to ./.env if present)
+    #[arg(long, global = true)]
+    pub env_file: Option<String>,
+    /// Directory holding host configs (overrides LAZABOT_CONFIG_DIR, defaults to ./config)
+    #[arg(long, global = true)]
+    pub config_dir: Option<PathBuf>,
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -22,6 +29,15 @@ pub enum Commands {
         /// Enable verbose logging
         #[arg(short, long)]
         verbose: bool,
+        /// Append monitor events to this file as JSON lines
+        #[arg(short, long)]
+        output: Option<String>,
+        /// Serve /metrics and /status on this local port while monitoring
+        #[arg(long)]
+        metrics_port: Option<u16>,
+        /// Proxy file reported on /status (defaults to config/proxies.txt if present)
+        #[arg(long)]
+        proxies: Option<String>,
     },
     /// Buy products automatically
     Buy {
@@ -34,6 +50,12 @@ pub enum Commands {
         /// Dry run mode (don't actually buy)
         #[arg(long)]
         dry_run: bool,
+        /// Account the order is placed with
+        #[arg(short, long, default_value = "default")]
+        account: String,
+        /// Database path
+        #[arg(long, default_value = "./data/lazabot.db")]
+        database: String,
     },
     /// Manage proxy settings
     Proxy {
@@ -43,6 +65,9 @@ pub enum Commands {
         /// Add new proxy
         #[arg(short = 'a', long)]
         add: Option<String>,
+        /// Remove proxy by host:port
+        #[arg(short = 'r', long)]
+        remove: Option<String>,
         /// List all proxies
         #[arg(short = 'l', long)]
         list: bool,
@@ -61,6 +86,15 @@ pub enum Commands {
         /// Show current session status
         #[arg(short, long)]
         status: bool,
+        /// Vault path
+        #[arg(long, default_value = "./data/credentials.vault")]
+        vault_path: String,
+        /// Account ID to log in with (defaults to the first account)
+        #[arg(long)]
+        account_id: Option<String>,
+        /// Session ID to log out (defaults to the most recent session)
+        #[arg(long)]
+        session_id: Option<String>,
     },
     /// Manage configuration
     Config {
@@ -76,6 +110,12 @@ pub enum Commands {
         /// Reset to default configuration
         #[arg(long)]
         reset: bool,
+        /// Print the merged configuration (base + host + environment)
+        #[arg(long)]
+        effective: bool,
+        /// Show values the host and environment layers change from the base file
+        #[arg(long)]
+        diff: bool,
     },
     /// Validate environment and configuration
     Validate {
@@ -91,6 +131,12 @@ pub enum Commands {
         /// Exit with error code if validation fails
         #[arg(long)]
         strict: bool,
+        /// Check that proxies stored in the vault are reachable
+        #[arg(long)]
+        check_proxies: bool,
+        /// Output format (text, json)
+        #[arg(long, default_value = "text")]
+        format: String,
     },
     /// Generate secure keys and configuration
     Generate {
@@ -106,6 +152,18 @@ pub enum Commands {
         /// Output format (hex, base64)
         #[arg(long, default_value = "hex")]
         format: String,
+        /// Number of keys to generate of each kind
+        #[arg(long, default_value_t = 1)]
+        count: usize,
+        /// Print the keys of each kind as a JSON array
+        #[arg(long)]
+        json: bool,
+        /// Derive a master key with Argon2id from a passphrase read from stdin
+        #[arg(long, conflicts_with_all = ["master_key", "session_secret", "all", "count"])]
+        from_passphrase: bool,
+        /// Hex-encoded salt for --from-passphrase (random if omitted)
+        #[arg(long, requires = "from_passphrase")]
+        salt: Option<String>,
     },
     /// Manage credentials securely
     Credentials {
@@ -125,4 +183,34 @@ pub enum Commands {
         #[arg(long)]
         account_id: Option<String>,
     },
+    /// Dump or replay recorded monitor events
+    Events {
+        /// Product ID to show events for
+        #[arg(short, long)]
+        product: String,
+        /// Only events at or after this RFC 3339 timestamp
+        #[arg(long)]
+        since: Option<String>,
+        /// Database path
+        #[arg(long, default_value = "./data/lazabot.db")]
+        database: String,
+        /// Re-emit the events through a monitor event channel instead of printing records
+        #[arg(long)]
+        replay: bool,
+    },
+    /// Show recorded checkout orders
+    Orders {
+        /// List orders, newest first
+        #[arg(short, long)]
+        list: bool,
+        /// Only orders placed with this account
+        #[arg(short, long)]
+        account: Option<String>,
+        /// Only orders with this status (success, failed, dry_run)
+        #[arg(short, long)]
+        status: Option<String>,
+        /// Database path
+        #[arg(long, default_value = "./data/lazabot.db")]
+        database: String,
+    },
 }
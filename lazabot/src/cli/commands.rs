@@ -1,40 +1,140 @@
 use anyhow::Result;
+use crate::api::ApiClient;
 use crate::cli::args::Commands;
-use crate::config::loader::load_config;
-use crate::config::validation::EnvValidator;
+use crate::config::loader::{load_config, set_config_value_in_file};
+use crate::config::validation::{check_proxy_reachability, EnvValidator, ValidationReport};
 use crate::config::credentials::CredentialManager;
-use crate::proxy::ProxyManager;
+use crate::config::ConfigManager;
+use crate::core::monitor::ProductAvailabilityEvent;
+use crate::core::{Credentials, EventLog, MonitorEngine, SessionManager, SessionPool};
+use crate::proxy::{ProxyHealth, ProxyManager};
+use crate::storage::{AvailabilityEventRecord, Database, NewOrder, OrderRecord};
+use crate::tasks::{ShutdownCoordinator, TaskManager};
+use crate::utils::{MetricsCollector, MetricsServer};
+use chrono::{DateTime, Utc};
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::mpsc;
 
 /// Handle monitor command
 pub async fn handle_monitor(
     products: Option<String>,
     interval: u64,
     verbose: bool,
+    output: Option<String>,
+    metrics_port: Option<u16>,
+    proxies: Option<String>,
+    shutdown: &ShutdownCoordinator,
 ) -> Result<()> {
     println!("Monitor command executed");
     println!("Products file: {:?}", products);
     println!("Interval: {} seconds", interval);
     println!("Verbose: {}", verbose);
+
+    let metrics = MetricsCollector::new();
+    let mut engine = MonitorEngine::new().with_metrics(metrics.clone());
+    let event_log = match output {
+        Some(path) => {
+            println!("Writing events to: {}", path);
+            Some(Arc::new(EventLog::open(path).await?))
+        }
+        None => None,
+    };
+    if let Some(log) = &event_log {
+        engine = engine.with_event_log(log.clone());
+    }
+
+    let engine = Arc::new(engine);
+    shutdown.register_monitor_engine(engine.clone());
+    engine.start().await?;
+
+    // Proxies, pooled sessions and tasks owned by this run
+    let proxy_manager = Arc::new(match proxies {
+        Some(path) => ProxyManager::from_file(&path).await?,
+        None if std::path::Path::new("config/proxies.txt").exists() => {
+            ProxyManager::from_file("config/proxies.txt").await?
+        }
+        None => ProxyManager::new(Vec::new()),
+    });
+    let api_client = Arc::new(ApiClient::new(None)?);
+    let session_pool = Arc::new(SessionPool::new(Arc::new(SessionManager::new(api_client).await?)));
+    let task_manager = Arc::new(TaskManager::new(1));
+    shutdown.register_task_manager(task_manager.clone());
+
+    if let Some(port) = metrics_port {
+        let server = metrics_server(metrics, port, proxy_manager, session_pool, task_manager);
+        println!("📈 Serving metrics on http://127.0.0.1:{}/status", port);
+        tokio::spawn(async move {
+            if let Err(e) = server.start().await {
+                tracing::error!("Metrics server stopped: {}", e);
+            }
+        });
+    }
+
+    println!("👀 Monitoring... press Ctrl-C to stop");
+    shutdown.token().cancelled().await;
+    if let Some(log) = &event_log {
+        log.flush().await;
+    }
+    println!("🛑 Monitoring stopped");
     Ok(())
 }
 
+/// Build the metrics server for a monitor run, reporting on the proxies, pooled sessions
+/// and tasks the run owns
+fn metrics_server(
+    metrics: MetricsCollector,
+    port: u16,
+    proxy_manager: Arc<ProxyManager>,
+    session_pool: Arc<SessionPool>,
+    task_manager: Arc<TaskManager>,
+) -> MetricsServer {
+    MetricsServer::new(metrics, format!("127.0.0.1:{}", port))
+        .with_proxy_manager(proxy_manager)
+        .with_session_pool(session_pool)
+        .with_task_manager(task_manager)
+}
+
 /// Handle buy command
 pub async fn handle_buy(
     product: Option<String>,
     quantity: u32,
     dry_run: bool,
+    account: String,
+    database: String,
 ) -> Result<()> {
     println!("Buy command executed");
     println!("Product: {:?}", product);
     println!("Quantity: {}", quantity);
     println!("Dry run: {}", dry_run);
+
+    if let (Some(product), true) = (product, dry_run) {
+        let db = Database::new(&database)?;
+        let order = dry_run_order(&product, quantity, &account);
+        db.upsert_order(&order)?;
+        println!("📝 Recorded dry-run order {} in {}", order.order_id, database);
+    }
     Ok(())
 }
 
+/// Order row recording a dry-run buy, which places nothing and has no price
+fn dry_run_order(product: &str, quantity: u32, account: &str) -> NewOrder {
+    NewOrder {
+        order_id: format!("dry-run-{}", uuid::Uuid::new_v4()),
+        product_id: product.to_string(),
+        account_id: account.to_string(),
+        status: "dry_run".to_string(),
+        price: 0.0,
+        quantity: quantity as i32,
+        metadata: Some(serde_json::json!({ "dry_run": true }).to_string()),
+    }
+}
+
 /// Handle proxy command
 pub async fn handle_proxy(
     test: bool,
     add: Option<String>,
+    remove: Option<String>,
     list: bool,
     proxies: Option<String>,
 ) -> Result<()> {
@@ -69,12 +169,24 @@ pub async fn handle_proxy(
             println!("  {}: {}:{}", i + 1, proxy.host, proxy.port);
         }
     } else if let Some(proxy_str) = add {
-        println!("Adding proxy: {}", proxy_str);
-        // TODO: Implement adding proxy to file
-        println!("Proxy addition not yet implemented");
+        let proxy_file = proxies.unwrap_or_else(|| "config/proxies.txt".to_string());
+        println!("Adding proxy to: {}", proxy_file);
+
+        let total = ProxyManager::add_to_file(&proxy_file, &proxy_str).await?;
+        println!("✅ Added proxy: {}", proxy_str);
+        println!("Total proxies: {}", total);
+    } else if let Some(proxy_str) = remove {
+        let proxy_file = proxies.unwrap_or_else(|| "config/proxies.txt".to_string());
+        println!("Removing proxy from: {}", proxy_file);
+
+        if ProxyManager::remove_from_file(&proxy_file, &proxy_str).await? {
+            println!("✅ Removed proxy: {}", proxy_str);
+        } else {
+            println!("⚠️  No proxy matching {} found", proxy_str);
+        }
     } else {
         println!("Proxy command executed");
-        println!("Use --test to test proxies, --list to list them, or --add to add new ones");
+        println!("Use --test to test proxies, --list to list them, --add to add new ones, or --remove to remove one");
         println!("Use --proxies to specify a custom proxy file path");
     }
 
@@ -82,21 +194,145 @@ pub async fn handle_proxy(
 }
 
 /// Handle session command
-pub async fn handle_session(login: bool, logout: bool, status: bool) -> Result<()> {
-    println!("Session command executed");
-    println!("Login: {}", login);
-    println!("Logout: {}", logout);
-    println!("Status: {}", status);
+pub async fn handle_session(
+    login: bool,
+    logout: bool,
+    status: bool,
+    vault_path: String,
+    account_id: Option<String>,
+    session_id: Option<String>,
+) -> Result<()> {
+    if !login && !logout && !status {
+        println!("🔑 Session management");
+        println!("\nAvailable commands:");
+        println!("  --login          Log in with vault credentials and save the session");
+        println!("  --status         List saved sessions");
+        println!("  --logout         Delete the most recent (or --session-id) session");
+        return Ok(());
+    }
+
+    let api_client = Arc::new(ApiClient::new(None)?);
+    let manager = SessionManager::new(api_client).await?;
+
+    if login {
+        let mut credentials = CredentialManager::new(&vault_path).map_err(|e| {
+            anyhow::anyhow!("Failed to open credential vault {}: {}", vault_path, e)
+        })?;
+        credentials
+            .load_from_env()
+            .map_err(|e| anyhow::anyhow!("Failed to load credentials: {}", e))?;
+
+        let account = match &account_id {
+            Some(id) => credentials
+                .get_account(id)
+                .map_err(|e| anyhow::anyhow!("{}", e))?,
+            None => {
+                let mut ids = credentials.get_account_ids();
+                ids.sort();
+                let id = ids.first().ok_or_else(|| {
+                    anyhow::anyhow!("No accounts found in vault; set LAZABOT_USERNAME and LAZABOT_PASSWORD")
+                })?;
+                credentials
+                    .get_account(id)
+                    .map_err(|e| anyhow::anyhow!("{}", e))?
+            }
+        };
+
+        println!("🔐 Logging in as {} ({})...", account.username, account.account_id);
+        let mut login_credentials =
+            Credentials::new(account.username.clone(), account.password.clone());
+        if let Some(email) = &account.email {
+            login_credentials = login_credentials.with_email(email.clone());
+        }
+
+        let session = manager.login(login_credentials).await?;
+        manager.persist_session(&session).await?;
+        println!("✅ Logged in, session saved: {}", session.id);
+    }
+
+    if status {
+        let sessions = manager.list_session_details().await?;
+        if sessions.is_empty() {
+            println!("No saved sessions");
+        } else {
+            println!("📋 Sessions ({}):", sessions.len());
+            let now = chrono::Utc::now();
+            for session in &sessions {
+                let validity = if session.is_valid { "✓ valid" } else { "✗ invalid" };
+                println!(
+                    "  {} {} - {} (age: {})",
+                    validity,
+                    session.id,
+                    session.credentials.username,
+                    format_age(now - session.created_at)
+                );
+            }
+        }
+    }
+
+    if logout {
+        let target = match session_id {
+            Some(id) => Some(id),
+            None => manager.most_recent_session().await?.map(|session| session.id),
+        };
+
+        match target {
+            Some(id) => {
+                if !manager.list_sessions().await?.contains(&id) {
+                    anyhow::bail!("Session not found: {}", id);
+                }
+                manager.delete_session(&id).await?;
+                println!("✅ Logged out, session deleted: {}", id);
+            }
+            None => println!("No saved sessions to log out"),
+        }
+    }
+
     Ok(())
 }
 
+/// Format a duration as a short human-readable age
+fn format_age(age: chrono::Duration) -> String {
+    if age.num_days() > 0 {
+        format!("{}d {}h", age.num_days(), age.num_hours() % 24)
+    } else if age.num_hours() > 0 {
+        format!("{}h {}m", age.num_hours(), age.num_minutes() % 60)
+    } else {
+        format!("{}m", age.num_minutes().max(0))
+    }
+}
+
 /// Handle config command
 pub async fn handle_config(
     file: Option<String>,
     show: bool,
     set: Option<String>,
     reset: bool,
+    effective: bool,
+    diff: bool,
+    config_dir: Option<&Path>,
 ) -> Result<()> {
+    if effective || diff {
+        let file_path = file.unwrap_or_else(|| "config/config.toml".to_string());
+        let mut manager = ConfigManager::new();
+        manager.load_layers(&file_path, config_dir)?;
+
+        if effective {
+            let merged = manager.get_merged_config()?;
+            print!("{}", toml::to_string_pretty(&merged)?);
+        }
+        if diff {
+            let differences = manager.effective_diff()?;
+            if differences.is_empty() {
+                println!("Effective configuration matches {}", file_path);
+            }
+            for difference in differences {
+                println!("{}", difference);
+            }
+        }
+        return Ok(());
+    }
+
     if reset {
         println!("Resetting to default configuration...");
         let default_config = crate::config::create_default_config();
@@ -107,16 +343,33 @@ pub async fn handle_config(
         return Ok(());
     }
 
+    if let Some(assignment) = set {
+        let file_path = file.unwrap_or_else(|| "config/config.toml".to_string());
+        set_config_value_in_file(&file_path, &assignment)?;
+        println!("✅ Updated {}: {}", file_path, assignment);
+        return Ok(());
+    }
+
     if let Some(file_path) = file {
         println!("Loading configuration from: {}", file_path);
         match load_config(&file_path) {
             Ok(config) => {
                 println!("Configuration loaded successfully!");
+                if let Err(problems) = config.validate() {
+                    println!("⚠️  Configuration has {} problem(s):", problems.len());
+                    for problem in &problems {
+                        println!("  - {}", problem);
+                    }
+                }
                 println!("Bot name: {}", config.bot.name);
                 println!("Default delay: {}ms", config.bot.default_delay);
                 println!("Max retries: {}", config.bot.max_retries);
                 println!("Debug mode: {}", config.bot.debug);
-                println!("Number of accounts: {}", config.accounts.len());
+                println!(
+                    "Number of accounts: {} ({} active)",
+                    config.accounts.len(),
+                    config.active_accounts().len()
+                );
                 println!("Number of proxies: {}", config.proxies.len());
                 println!("Captcha service: {}", config.captcha.service);
                 println!(
@@ -146,11 +399,7 @@ pub async fn handle_config(
         println!("Use --file to specify a configuration file");
         println!("Use --show to display the loaded configuration");
         println!("Use --reset to reset to default configuration");
-    }
-
-    if let Some(set_value) = set {
-        println!("Set configuration value: {}", set_value);
-        // TODO: Implement configuration value setting
+        println!("Use --set section.key=value to update a value in the configuration file");
     }
 
     Ok(())
@@ -162,9 +411,24 @@ pub async fn handle_validate(
     credentials: bool,
     vault_path: String,
     strict: bool,
+    check_proxies: bool,
+    format: String,
 ) -> Result<()> {
+    if format.eq_ignore_ascii_case("json") {
+        let report = build_validation_report(credentials, &vault_path, strict, check_proxies).await;
+        println!("{}", report.to_json()?);
+        if report.has_errors() {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     println!("🔍 Validating environment and configuration...\n");
 
+    // Environment and proxy results are collected here and printed together at the end
+    let mut report = ValidationReport::new();
+    let mut unreachable_proxies = 0;
+
     if credentials {
         // Validate credentials only
         println!("Validating credentials...");
@@ -182,6 +446,10 @@ pub async fn handle_validate(
                     println!("  Created: {}", vault_info.created_at);
                     println!("  Last updated: {}", vault_info.last_updated);
                 }
+
+                if check_proxies {
+                    unreachable_proxies = validate_vault_proxies(&manager, strict, &mut report).await?;
+                }
             }
             Err(e) => {
                 eprintln!("❌ Credentials validation failed: {}", e);
@@ -196,18 +464,9 @@ pub async fn handle_validate(
         let validator = EnvValidator::new();
         
         match validator.validate_all() {
-            Ok(report) => {
+            Ok(env_report) => {
                 println!("✅ Environment validation successful");
-                
-                if verbose {
-                    report.print_report();
-                } else {
-                    println!("  Total variables checked: {}", 
-                        report.successes.len() + report.errors.len() + report.infos.len());
-                    println!("  Successful: {}", report.successes.len());
-                    println!("  Errors: {}", report.errors.len());
-                    println!("  Info: {}", report.infos.len());
-                }
+                report = env_report;
 
                 // Also validate credentials
                 println!("\n🔐 Validating credentials...");
@@ -215,6 +474,11 @@ pub async fn handle_validate(
                     Ok(mut manager) => {
                         manager.load_from_env().map_err(|e| anyhow::anyhow!("Credential validation failed: {}", e))?;
                         println!("✅ Credentials validation successful");
+
+                        if check_proxies {
+                            unreachable_proxies =
+                                validate_vault_proxies(&manager, strict, &mut report).await?;
+                        }
                     }
                     Err(e) => {
                         eprintln!("❌ Credentials validation failed: {}", e);
@@ -235,31 +499,154 @@ pub async fn handle_validate(
         }
     }
 
+    if !credentials || check_proxies {
+        if verbose {
+            report.print_report();
+        } else {
+            println!();
+            println!("  Total variables checked: {}", 
+                report.successes.len() + report.errors.len() + report.warnings.len() + report.infos.len());
+            println!("  Successful: {}", report.successes.len());
+            println!("  Errors: {}", report.errors.len());
+            println!("  Warnings: {}", report.warnings.len());
+            println!("  Info: {}", report.infos.len());
+        }
+    }
+
+    if strict && unreachable_proxies > 0 {
+        return Err(anyhow::anyhow!(
+            "Proxy validation failed: {} proxies unreachable",
+            unreachable_proxies
+        ));
+    }
+
     println!("\n🎉 All validations passed!");
     Ok(())
 }
 
+/// Run the same checks as the text output, collecting every result in one report
+async fn build_validation_report(
+    credentials: bool,
+    vault_path: &str,
+    strict: bool,
+    check_proxies: bool,
+) -> ValidationReport {
+    let mut report = if credentials {
+        ValidationReport::new()
+    } else {
+        EnvValidator::new().report()
+    };
+
+    let manager = CredentialManager::new(vault_path).and_then(|mut manager| {
+        manager.load_from_env()?;
+        Ok(manager)
+    });
+
+    match manager {
+        Ok(manager) => {
+            report.add_success("CREDENTIAL_VAULT", "Credential vault", Some(vault_path));
+
+            if check_proxies {
+                let proxies: Vec<_> = manager
+                    .get_vault_info()
+                    .proxies
+                    .iter()
+                    .map(|(id, proxy)| (id.clone(), proxy.to_proxy_info()))
+                    .collect();
+
+                match ProxyHealth::new() {
+                    Ok(checker) => {
+                        check_proxy_reachability(&checker, &proxies, strict, &mut report).await;
+                    }
+                    Err(e) => report.add_error("PROXIES", "Proxy reachability", &e.to_string()),
+                }
+            }
+        }
+        Err(e) => report.add_error("CREDENTIAL_VAULT", "Credential vault", &e.to_string()),
+    }
+
+    report
+}
+
+/// Check reachability of the proxies stored in the credential vault, returning how
+/// many were unreachable
+async fn validate_vault_proxies(
+    manager: &CredentialManager,
+    strict: bool,
+    report: &mut ValidationReport,
+) -> Result<usize> {
+    println!("\n🌐 Checking proxy reachability...");
+
+    let proxies: Vec<_> = manager
+        .get_vault_info()
+        .proxies
+        .iter()
+        .map(|(id, proxy)| (id.clone(), proxy.to_proxy_info()))
+        .collect();
+
+    if proxies.is_empty() {
+        println!("  No proxies configured");
+        return Ok(0);
+    }
+
+    let checker = ProxyHealth::new()?;
+    let errors_before = report.errors.len();
+    let warnings_before = report.warnings.len();
+    let reachable = check_proxy_reachability(&checker, &proxies, strict, report).await;
+
+    println!("  Reachable: {}/{}", reachable, proxies.len());
+    for item in report.errors[errors_before..]
+        .iter()
+        .chain(report.warnings[warnings_before..].iter())
+    {
+        println!("  {} {}", item.status, item.variable);
+    }
+
+    Ok(proxies.len() - reachable)
+}
+
 /// Handle generate command
 pub async fn handle_generate(
     master_key: bool,
     session_secret: bool,
     all: bool,
     format: String,
+    count: usize,
+    json: bool,
 ) -> Result<()> {
+    if count == 0 {
+        return Err(anyhow::anyhow!("--count must be at least 1"));
+    }
+
+    if json {
+        if all || master_key {
+            println!("{}", serde_json::to_string(&generate_keys(count, &format, generate_master_key)?)?);
+        }
+        if all || session_secret {
+            println!("{}", serde_json::to_string(&generate_keys(count, &format, generate_session_secret)?)?);
+        }
+        if !all && !master_key && !session_secret {
+            return Err(anyhow::anyhow!("No keys specified. Use --master-key, --session-secret, or --all"));
+        }
+        return Ok(());
+    }
+
     println!("🔑 Generating secure keys...\n");
 
     if all || master_key {
-        println!("Master Encryption Key:");
-        let key = generate_master_key(&format)?;
-        println!("  {}", key);
+        println!("Master Encryption Key{}:", if count > 1 { "s" } else { "" });
+        for key in generate_keys(count, &format, generate_master_key)? {
+            println!("  {}", key);
+        }
         println!("  Set this as LAZABOT_MASTER_KEY environment variable");
         println!();
     }
 
     if all || session_secret {
-        println!("Session Secret:");
-        let secret = generate_session_secret(&format)?;
-        println!("  {}", secret);
+        println!("Session Secret{}:", if count > 1 { "s" } else { "" });
+        for secret in generate_keys(count, &format, generate_session_secret)? {
+            println!("  {}", secret);
+        }
         println!("  Set this as LAZABOT_SESSION_SECRET environment variable");
         println!();
     }
@@ -271,11 +658,53 @@ pub async fn handle_generate(
         println!("  --session-secret Generate session secret");
         println!("  --all            Generate all keys");
         println!("  --format         Output format (hex, base64)");
+        println!("  --count          Number of keys of each kind");
+        println!("  --json           Print keys as a JSON array");
     }
 
     Ok(())
 }
 
+/// Handle `generate --from-passphrase`
+pub async fn handle_derive_key(salt: Option<String>, format: String, json: bool) -> Result<()> {
+    let passphrase = tokio::task::spawn_blocking(|| {
+        use std::io::IsTerminal;
+
+        let stdin = std::io::stdin();
+        let prompt = stdin.is_terminal();
+        read_passphrase(stdin.lock(), prompt)
+    })
+    .await??;
+    let salt = match salt {
+        Some(salt) => hex::decode(&salt).map_err(|e| anyhow::anyhow!("Invalid salt (expected hex): {}", e))?,
+        None => {
+            use rand::RngCore;
+
+            let mut salt = vec![0u8; DERIVED_SALT_LEN];
+            rand::thread_rng().fill_bytes(&mut salt);
+            salt
+        }
+    };
+    let key = derive_key_from_passphrase(&passphrase, &salt, &format)?;
+
+    if json {
+        println!("{}", serde_json::json!({ "salt": hex::encode(&salt), "key": key }));
+        return Ok(());
+    }
+
+    println!("🔑 Deriving master key from passphrase (Argon2id)...\n");
+    println!("Salt:");
+    println!("  {}", hex::encode(&salt));
+    println!("  Keep this salt to re-derive the same key with --salt");
+    println!();
+    println!("Master Encryption Key:");
+    println!("  {}", key);
+    println!("  Set this as LAZABOT_MASTER_KEY environment variable");
+    println!();
+
+    Ok(())
+}
+
 /// Handle credentials command
 pub async fn handle_credentials(
     list: bool,
@@ -357,17 +786,7 @@ fn generate_master_key(format: &str) -> Result<String> {
     let mut key_bytes = [0u8; 32];
     rand::thread_rng().fill_bytes(&mut key_bytes);
     
-    match format.to_lowercase().as_str() {
-        "hex" => Ok(hex::encode(key_bytes)),
-        "base64" => {
-            use base64::{engine::general_purpose, Engine as _};
-            Ok(general_purpose::STANDARD.encode(key_bytes))
-        }
-        _ => {
-            eprintln!("Invalid format: {}. Using hex format.", format);
-            Ok(hex::encode(key_bytes))
-        }
-    }
+    Ok(encode_key(&key_bytes, format))
 }
 
 /// Generate a session secret
@@ -377,61 +796,261 @@ fn generate_session_secret(format: &str) -> Result<String> {
     let mut secret_bytes = [0u8; 32];
     rand::thread_rng().fill_bytes(&mut secret_bytes);
     
+    Ok(encode_key(&secret_bytes, format))
+}
+
+/// Encode key bytes as hex or base64
+fn encode_key(bytes: &[u8], format: &str) -> String {
     match format.to_lowercase().as_str() {
-        "hex" => Ok(hex::encode(secret_bytes)),
+        "hex" => hex::encode(bytes),
         "base64" => {
             use base64::{engine::general_purpose, Engine as _};
-            Ok(general_purpose::STANDARD.encode(secret_bytes))
+            general_purpose::STANDARD.encode(bytes)
         }
         _ => {
             eprintln!("Invalid format: {}. Using hex format.", format);
-            Ok(hex::encode(secret_bytes))
+            hex::encode(bytes)
+        }
+    }
+}
+
+/// Salt length used when `--salt` is not given
+const DERIVED_SALT_LEN: usize = 16;
+
+/// Read a passphrase from the first line of `input`, prompting on stderr first if
+/// `prompt` is set. Keeps the passphrase out of shell history and process lists.
+fn read_passphrase(mut input: impl std::io::BufRead, prompt: bool) -> Result<String> {
+    use std::io::Write;
+
+    if prompt {
+        eprint!("Passphrase: ");
+        std::io::stderr().flush()?;
+    }
+
+    let mut line = String::new();
+    input
+        .read_line(&mut line)
+        .map_err(|e| anyhow::anyhow!("Failed to read passphrase: {}", e))?;
+    let passphrase = line.trim_end_matches(['\r', '\n']);
+    if passphrase.is_empty() {
+        return Err(anyhow::anyhow!("Passphrase must not be empty"));
+    }
+
+    Ok(passphrase.to_string())
+}
+
+/// Derive a 32-byte key from a passphrase and salt with Argon2id, encoded as `format`
+fn derive_key_from_passphrase(passphrase: &str, salt: &[u8], format: &str) -> Result<String> {
+    let mut key_bytes = [0u8; 32];
+    argon2::Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key_bytes)
+        .map_err(|e| anyhow::anyhow!("Key derivation failed: {}", e))?;
+
+    Ok(encode_key(&key_bytes, format))
+}
+
+/// Generate `count` keys, each from independent CSPRNG output
+fn generate_keys(count: usize, format: &str, generate: fn(&str) -> Result<String>) -> Result<Vec<String>> {
+    (0..count).map(|_| generate(format)).collect()
+}
+
+/// Handle events command
+pub async fn handle_events(
+    product: String,
+    since: Option<String>,
+    database: String,
+    replay: bool,
+) -> Result<()> {
+    let since = parse_since(since.as_deref())?;
+    let db = Database::new(&database)?;
+    let records = db.get_events_for_product(&product, since)?;
+
+    if replay {
+        let mut receiver = replay_events(records);
+        while let Some(event) = receiver.recv().await {
+            println!("{}", serde_json::to_string(&event)?);
+        }
+    } else {
+        for line in event_lines(&records)? {
+            println!("{}", line);
         }
     }
+
+    Ok(())
+}
+
+/// Handle orders command
+pub async fn handle_orders(
+    list: bool,
+    account: Option<String>,
+    status: Option<String>,
+    database: String,
+) -> Result<()> {
+    if !list {
+        println!("Use --list to show recorded orders");
+        return Ok(());
+    }
+
+    let lines = list_order_lines(&database, account.as_deref(), status.as_deref())?;
+    if lines.is_empty() {
+        println!("No orders found");
+    }
+    for line in lines {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+/// Lines printed by `orders --list`
+fn list_order_lines(
+    database: &str,
+    account: Option<&str>,
+    status: Option<&str>,
+) -> Result<Vec<String>> {
+    let db = Database::new(database)?;
+    Ok(order_lines(&db.list_orders(account, status)?))
+}
+
+/// One summary line per order
+fn order_lines(orders: &[OrderRecord]) -> Vec<String> {
+    orders
+        .iter()
+        .map(|order| {
+            format!(
+                "{}  {}  account={} product={} qty={} price={:.2} status={}",
+                order.created_at.to_rfc3339(),
+                order.order_id,
+                order.account_id,
+                order.product_id,
+                order.quantity,
+                order.price,
+                order.status
+            )
+        })
+        .collect()
+}
+
+/// Parse an RFC 3339 `--since`, defaulting to the start of recorded history
+fn parse_since(since: Option<&str>) -> Result<DateTime<Utc>> {
+    match since {
+        Some(since) => Ok(DateTime::parse_from_rfc3339(since)
+            .map_err(|e| anyhow::anyhow!("Invalid --since timestamp {}: {}", since, e))?
+            .with_timezone(&Utc)),
+        None => Ok(DateTime::UNIX_EPOCH),
+    }
+}
+
+/// One JSON object per event
+fn event_lines(records: &[AvailabilityEventRecord]) -> Result<Vec<String>> {
+    records
+        .iter()
+        .map(|record| Ok(serde_json::to_string(record)?))
+        .collect()
+}
+
+/// Re-emit recorded events, in order, as the monitor would have sent them
+pub fn replay_events(
+    records: Vec<AvailabilityEventRecord>,
+) -> mpsc::UnboundedReceiver<ProductAvailabilityEvent> {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    for record in records {
+        // The URL isn't recorded; receivers only get an ID to go on
+        let event = ProductAvailabilityEvent {
+            kind: record.kind.parse().unwrap_or_default(),
+            product_id: record.product_id,
+            product_url: String::new(),
+            timestamp: record.timestamp,
+            price: record.price,
+            stock: record.stock,
+            is_available: record.available,
+        };
+        // The receiver is still held here, so sending can't fail
+        let _ = sender.send(event);
+    }
+    receiver
 }
 
 /// Main command dispatcher
-pub async fn execute_command(command: Commands) -> Result<()> {
+/// Run `command`; `config_dir` overrides where host configs are read from
+pub async fn execute_command(
+    command: Commands,
+    config_dir: Option<&Path>,
+    shutdown: &ShutdownCoordinator,
+) -> Result<()> {
     match command {
         Commands::Monitor {
             products,
             interval,
             verbose,
-        } => handle_monitor(products, interval, verbose).await,
+            output,
+            metrics_port,
+            proxies,
+        } => {
+            handle_monitor(
+                products,
+                interval,
+                verbose,
+                output,
+                metrics_port,
+                proxies,
+                shutdown,
+            )
+            .await
+        }
         Commands::Buy {
             product,
             quantity,
             dry_run,
-        } => handle_buy(product, quantity, dry_run).await,
+            account,
+            database,
+        } => handle_buy(product, quantity, dry_run, account, database).await,
         Commands::Proxy {
             test,
             add,
+            remove,
             list,
             proxies,
-        } => handle_proxy(test, add, list, proxies).await,
+        } => handle_proxy(test, add, remove, list, proxies).await,
         Commands::Session {
             login,
             logout,
             status,
-        } => handle_session(login, logout, status).await,
+            vault_path,
+            account_id,
+            session_id,
+        } => handle_session(login, logout, status, vault_path, account_id, session_id).await,
         Commands::Config {
             file,
             show,
             set,
             reset,
-        } => handle_config(file, show, set, reset).await,
+            effective,
+            diff,
+        } => handle_config(file, show, set, reset, effective, diff, config_dir).await,
         Commands::Validate {
             verbose,
             credentials,
             vault_path,
             strict,
-        } => handle_validate(verbose, credentials, vault_path, strict).await,
+            check_proxies,
+            format,
+        } => handle_validate(verbose, credentials, vault_path, strict, check_proxies, format).await,
         Commands::Generate {
             master_key,
             session_secret,
             all,
             format,
-        } => handle_generate(master_key, session_secret, all, format).await,
+            count,
+            json,
+            from_passphrase,
+            salt,
+        } => {
+            if from_passphrase {
+                handle_derive_key(salt, format, json).await
+            } else {
+                handle_generate(master_key, session_secret, all, format, count, json).await
+            }
+        }
         Commands::Credentials {
             list,
             add,
@@ -439,5 +1058,142 @@ pub async fn execute_command(command: Commands) -> Result<()> {
             vault_path,
             account_id,
         } => handle_credentials(list, add, remove, vault_path, account_id).await,
+        Commands::Events {
+            product,
+            since,
+            database,
+            replay,
+        } => handle_events(product, since, database, replay).await,
+        Commands::Orders {
+            list,
+            account,
+            status,
+            database,
+        } => handle_orders(list, account, status, database).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::MonitorEventKind;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_generate_keys_count() {
+        let keys = generate_keys(5, "hex", generate_master_key).unwrap();
+        assert_eq!(keys.len(), 5);
+        assert!(keys.iter().all(|k| k.len() == 64 && k.chars().all(|c| c.is_ascii_hexdigit())));
+        assert_eq!(keys.iter().collect::<HashSet<_>>().len(), 5);
+
+        let secrets = generate_keys(5, "base64", generate_session_secret).unwrap();
+        assert!(secrets.iter().all(|s| s.len() == 44));
+        assert_eq!(secrets.iter().collect::<HashSet<_>>().len(), 5);
+    }
+
+    #[test]
+    fn test_derive_key_from_passphrase() {
+        let key = derive_key_from_passphrase("correct horse battery staple", b"salt-one-16bytes", "hex").unwrap();
+        let again = derive_key_from_passphrase("correct horse battery staple", b"salt-one-16bytes", "hex").unwrap();
+        let other_salt = derive_key_from_passphrase("correct horse battery staple", b"salt-two-16bytes", "hex").unwrap();
+
+        assert_eq!(key.len(), 64);
+        assert_eq!(key, again);
+        assert_ne!(key, other_salt);
+    }
+
+    #[test]
+    fn test_read_passphrase() {
+        let passphrase = read_passphrase("correct horse battery staple\r\nrest".as_bytes(), false).unwrap();
+        assert_eq!(passphrase, "correct horse battery staple");
+        assert!(read_passphrase("\n".as_bytes(), false).is_err());
+        assert!(read_passphrase("".as_bytes(), false).is_err());
+    }
+
+    #[test]
+    fn test_from_passphrase_conflicts_with_key_options() {
+        use crate::cli::args::Cli;
+        use clap::Parser;
+
+        let parse = |args: &[&str]| Cli::try_parse_from([&["lazabot", "generate"], args].concat());
+
+        assert!(parse(&["--from-passphrase"]).is_ok());
+        assert!(parse(&["--from-passphrase", "--salt", "00ff"]).is_ok());
+        assert!(parse(&["--from-passphrase", "--count", "2"]).is_err());
+        assert!(parse(&["--from-passphrase", "--master-key"]).is_err());
+        // The passphrase itself is never taken from the command line
+        assert!(parse(&["--from-passphrase", "hunter2"]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_events_query_and_replay() {
+        let db = Database::in_memory().unwrap();
+        let since = parse_since(Some("2024-03-01T00:00:00Z")).unwrap();
+        let at = |rfc3339: &str| parse_since(Some(rfc3339)).unwrap();
+
+        db.insert_availability_event("PROD-1", Some(10.0), Some(0), false, at("2024-02-28T12:00:00Z"))
+            .unwrap();
+        db.insert_availability_event("PROD-1", Some(9.5), Some(3), true, at("2024-03-02T08:30:00Z"))
+            .unwrap();
+        db.insert_availability_event("PROD-1", Some(9.5), Some(0), false, at("2024-03-03T08:30:00+08:00"))
+            .unwrap();
+        db.insert_availability_event("PROD-2", Some(1.0), Some(1), true, at("2024-03-02T00:00:00Z"))
+            .unwrap();
+        db.insert_monitor_event("price_dropped", "PROD-1", Some(5.0), Some(0), false, at("2024-03-04T00:00:00Z"))
+            .unwrap();
+
+        let records = db.get_events_for_product("PROD-1", since).unwrap();
+        let lines = event_lines(&records).unwrap();
+        assert_eq!(lines.len(), 3);
+        let first: serde_json::Value = serde_json::from_str(&lines[0]).unwrap();
+        assert_eq!(first["kind"], "availability_changed");
+        assert_eq!(first["product_id"], "PROD-1");
+        assert_eq!(first["price"], 9.5);
+        assert_eq!(first["available"], true);
+        assert_eq!(first["timestamp"], "2024-03-02T08:30:00Z");
+
+        let mut receiver = replay_events(records);
+        let replayed: Vec<(MonitorEventKind, bool)> =
+            std::iter::from_fn(|| receiver.try_recv().ok())
+                .map(|event| (event.kind, event.is_available))
+                .collect();
+        assert_eq!(
+            replayed,
+            vec![
+                (MonitorEventKind::AvailabilityChanged, true),
+                (MonitorEventKind::AvailabilityChanged, false),
+                (MonitorEventKind::PriceDropped, false),
+            ]
+        );
+
+        assert!(parse_since(Some("yesterday")).is_err());
+        assert_eq!(parse_since(None).unwrap(), DateTime::UNIX_EPOCH);
+    }
+
+    #[tokio::test]
+    async fn test_orders_are_listed() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let database = temp_dir.path().join("lazabot.db").to_string_lossy().to_string();
+
+        handle_buy(Some("PROD-1".to_string()), 2, true, "acct-1".to_string(), database.clone())
+            .await
+            .unwrap();
+        // Only dry runs are recorded from the CLI
+        handle_buy(Some("PROD-2".to_string()), 1, false, "acct-2".to_string(), database.clone())
+            .await
+            .unwrap();
+
+        let lines = list_order_lines(&database, None, None).unwrap();
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].contains("dry-run-"));
+        assert!(lines[0].contains("account=acct-1 product=PROD-1 qty=2"));
+        assert!(lines[0].contains("status=dry_run"));
+
+        assert_eq!(list_order_lines(&database, Some("acct-1"), Some("dry_run")).unwrap().len(), 1);
+        assert!(list_order_lines(&database, Some("acct-2"), None).unwrap().is_empty());
+        assert!(list_order_lines(&database, None, Some("success")).unwrap().is_empty());
+        handle_orders(true, Some("acct-1".to_string()), None, database)
+            .await
+            .unwrap();
     }
 }
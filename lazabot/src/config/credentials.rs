@@ -56,6 +56,18 @@ pub struct ProxyCredentials {
     pub proxy_type: String, // http, socks5, etc.
 }
 
+impl ProxyCredentials {
+    /// Convert to the proxy representation used by the HTTP client
+    pub fn to_proxy_info(&self) -> crate::api::ProxyInfo {
+        let proxy_type = crate::api::ProxyType::from_scheme(&self.proxy_type).unwrap_or_default();
+        let proxy = crate::api::ProxyInfo::new(self.host.clone(), self.port).with_type(proxy_type);
+        match (&self.username, &self.password) {
+            (Some(username), Some(password)) => proxy.with_auth(username.clone(), password.clone()),
+            _ => proxy,
+        }
+    }
+}
+
 /// Master encryption key
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MasterKey {
@@ -146,7 +158,11 @@ pub struct CredentialManager {
 impl CredentialManager {
     /// Create a new credential manager
     pub fn new(vault_path: &str) -> CredentialResult<Self> {
-        let encryption_manager = EncryptionManager::new()?;
+        Self::with_encryption_manager(vault_path, EncryptionManager::new()?)
+    }
+
+    /// Create a credential manager that encrypts the vault with `encryption_manager`
+    pub fn with_encryption_manager(vault_path: &str, encryption_manager: EncryptionManager) -> CredentialResult<Self> {
         let vault = Self::load_vault(vault_path, &encryption_manager)?;
         
         Ok(Self {
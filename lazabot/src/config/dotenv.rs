@@ -0,0 +1,171 @@
+use anyhow::{Context, Result};
+use std::env;
+use std::fs;
+use std::path::Path;
+use tracing::debug;
+
+/// Load variables from a `.env` file into the process environment.
+///
+/// Variables that are already set are left untouched, so values exported in
+/// the shell always win over the file. Returns the number of variables set.
+pub fn load_dotenv<P: AsRef<Path>>(path: P) -> Result<usize> {
+    let path = path.as_ref();
+
+    if !path.exists() {
+        anyhow::bail!("Environment file not found: {}", path.display());
+    }
+
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read environment file: {}", path.display()))?;
+
+    let entries = parse_dotenv(&content)
+        .with_context(|| format!("Failed to parse environment file: {}", path.display()))?;
+
+    let mut loaded = 0;
+    for (key, value) in entries {
+        if env::var_os(&key).is_some() {
+            debug!("Skipping {} from env file: already set", key);
+            continue;
+        }
+        env::set_var(&key, value);
+        loaded += 1;
+    }
+
+    debug!("Loaded {} variables from {}", loaded, path.display());
+    Ok(loaded)
+}
+
+/// Parse the contents of a `.env` file into key/value pairs.
+///
+/// Supports blank lines, `#` comments, an optional `export` prefix, single-
+/// and double-quoted values, and trailing comments after unquoted values.
+pub fn parse_dotenv(content: &str) -> Result<Vec<(String, String)>> {
+    let mut entries = Vec::new();
+
+    for (line_num, line) in content.lines().enumerate() {
+        let line = line.trim();
+
+        // Skip empty lines and comments
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let line = line.strip_prefix("export ").unwrap_or(line);
+
+        let (key, raw_value) = line
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("Missing '=' on line {}", line_num + 1))?;
+
+        let key = key.trim();
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            anyhow::bail!("Invalid variable name on line {}: {}", line_num + 1, key);
+        }
+
+        let value = parse_value(raw_value.trim())
+            .with_context(|| format!("Invalid value on line {}", line_num + 1))?;
+
+        entries.push((key.to_string(), value));
+    }
+
+    Ok(entries)
+}
+
+/// Parse a single value, handling quoting and inline comments
+fn parse_value(raw: &str) -> Result<String> {
+    if let Some(rest) = raw.strip_prefix('"') {
+        let mut value = String::new();
+        let mut chars = rest.chars();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => return Ok(value),
+                '\\' => match chars.next() {
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some(other) => value.push(other),
+                    None => break,
+                },
+                _ => value.push(c),
+            }
+        }
+
+        anyhow::bail!("Unterminated double-quoted value");
+    }
+
+    if let Some(rest) = raw.strip_prefix('\'') {
+        let end = rest
+            .find('\'')
+            .ok_or_else(|| anyhow::anyhow!("Unterminated single-quoted value"))?;
+        return Ok(rest[..end].to_string());
+    }
+
+    // Unquoted values end at an inline comment
+    let value = match raw.find(" #") {
+        Some(idx) => &raw[..idx],
+        None => raw,
+    };
+
+    Ok(value.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    const SAMPLE_ENV: &str = r#"
+# Lazabot environment
+LAZABOT_LOG_LEVEL=debug
+LAZABOT_BOT_NAME="my lazada bot"
+# LAZABOT_USERNAME=commented@example.com
+export LAZABOT_DATA_DIR='./data dir'
+LAZABOT_LOG_DIR=./logs # trailing comment
+"#;
+
+    #[test]
+    fn test_parse_dotenv() {
+        let entries = parse_dotenv(SAMPLE_ENV).unwrap();
+
+        assert_eq!(
+            entries,
+            vec![
+                ("LAZABOT_LOG_LEVEL".to_string(), "debug".to_string()),
+                ("LAZABOT_BOT_NAME".to_string(), "my lazada bot".to_string()),
+                ("LAZABOT_DATA_DIR".to_string(), "./data dir".to_string()),
+                ("LAZABOT_LOG_DIR".to_string(), "./logs".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_dotenv_invalid_lines() {
+        assert!(parse_dotenv("NO_EQUALS_SIGN").is_err());
+        assert!(parse_dotenv("BAD KEY=value").is_err());
+        assert!(parse_dotenv("UNTERMINATED=\"value").is_err());
+    }
+
+    #[test]
+    fn test_load_dotenv_does_not_overwrite() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "LAZABOT_DOTENV_TEST_NEW=\"from file\"").unwrap();
+        writeln!(file, "LAZABOT_DOTENV_TEST_EXISTING=from_file").unwrap();
+
+        env::set_var("LAZABOT_DOTENV_TEST_EXISTING", "from_shell");
+        env::remove_var("LAZABOT_DOTENV_TEST_NEW");
+
+        let loaded = load_dotenv(file.path()).unwrap();
+
+        assert_eq!(loaded, 1);
+        assert_eq!(env::var("LAZABOT_DOTENV_TEST_NEW").unwrap(), "from file");
+        assert_eq!(
+            env::var("LAZABOT_DOTENV_TEST_EXISTING").unwrap(),
+            "from_shell"
+        );
+    }
+
+    #[test]
+    fn test_load_dotenv_missing_file() {
+        assert!(load_dotenv("/nonexistent/path/.env").is_err());
+    }
+}
@@ -39,8 +39,13 @@ impl EncryptionManager {
             )
         })?;
 
+        Self::from_hex_key(&master_key)
+    }
+
+    /// Create an encryption manager from a hex-encoded 32-byte master key
+    pub fn from_hex_key(master_key: &str) -> EncryptionResult<Self> {
         // Decode the hex-encoded master key
-        let key_bytes = hex::decode(&master_key)
+        let key_bytes = hex::decode(master_key)
             .map_err(|e| EncryptionError::InvalidKeyFormat(format!("Invalid hex format: {}", e)))?;
 
         if key_bytes.len() != 32 {
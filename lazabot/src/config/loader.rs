@@ -3,8 +3,47 @@ use anyhow::{Context, Result};
 use std::fs;
 use std::path::Path;
 
-/// Load configuration from a TOML file
+/// Supported configuration file formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl ConfigFormat {
+    /// Detect the format from a file extension, defaulting to TOML for extensionless files
+    pub fn from_path(path: &str) -> Result<Self> {
+        match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+            None => Ok(Self::Toml),
+            Some(ext) => match ext.to_lowercase().as_str() {
+                "toml" => Ok(Self::Toml),
+                "yaml" | "yml" => Ok(Self::Yaml),
+                "json" => Ok(Self::Json),
+                other => anyhow::bail!(
+                    "Unsupported configuration file extension '.{}' for {} (expected .toml, .yaml, .yml or .json)",
+                    other,
+                    path
+                ),
+            },
+        }
+    }
+}
+
+/// Load configuration from a file, choosing the format by extension
 pub fn load_config(path: &str) -> Result<Config> {
+    load_config_with(path, &env_lookup)
+}
+
+/// `load_config`, resolving `${VAR}` references with `lookup`
+fn load_config_with(path: &str, lookup: &EnvLookup) -> Result<Config> {
+    interpolate_config(read_config(path)?, lookup)
+        .with_context(|| format!("Failed to interpolate configuration from: {}", path))
+}
+
+/// Parse a configuration file as written, leaving `${VAR}` references in place
+fn read_config(path: &str) -> Result<Config> {
+    let format = ConfigFormat::from_path(path)?;
     let config_path = Path::new(path);
 
     if !config_path.exists() {
@@ -14,8 +53,14 @@ pub fn load_config(path: &str) -> Result<Config> {
     let content = fs::read_to_string(config_path)
         .with_context(|| format!("Failed to read configuration file: {}", path))?;
 
-    let config: Config = toml::from_str(&content)
-        .with_context(|| format!("Failed to parse TOML configuration from: {}", path))?;
+    let config = match format {
+        ConfigFormat::Toml => toml::from_str(&content)
+            .with_context(|| format!("Failed to parse TOML configuration from: {}", path))?,
+        ConfigFormat::Yaml => serde_yaml::from_str(&content)
+            .with_context(|| format!("Failed to parse YAML configuration from: {}", path))?,
+        ConfigFormat::Json => serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse JSON configuration from: {}", path))?,
+    };
 
     Ok(config)
 }
@@ -34,7 +79,77 @@ pub fn load_config_yaml(path: &str) -> Result<Config> {
     let config: Config = serde_yaml::from_str(&content)
         .with_context(|| format!("Failed to parse YAML configuration from: {}", path))?;
 
-    Ok(config)
+    interpolate_config(config, &env_lookup)
+        .with_context(|| format!("Failed to interpolate configuration from: {}", path))
+}
+
+/// Looks up the value of a variable referenced as `${VAR}`
+type EnvLookup = dyn Fn(&str) -> Option<String>;
+
+fn env_lookup(name: &str) -> Option<String> {
+    std::env::var(name).ok()
+}
+
+/// Resolve `${VAR}` references in every string value of a parsed configuration.
+/// Only values are rewritten, so comments and keys are left alone and substituted
+/// text can't change the structure of the file.
+fn interpolate_config(config: Config, lookup: &EnvLookup) -> Result<Config> {
+    fn interpolate_value(value: &mut serde_json::Value, lookup: &EnvLookup) -> Result<()> {
+        match value {
+            serde_json::Value::String(text) => *text = interpolate_with(text, lookup)?,
+            serde_json::Value::Array(items) => {
+                for item in items {
+                    interpolate_value(item, lookup)?;
+                }
+            }
+            serde_json::Value::Object(fields) => {
+                for (key, field) in fields {
+                    interpolate_value(field, lookup).with_context(|| format!("In {}", key))?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    let mut value = serde_json::to_value(&config).context("Failed to serialize configuration")?;
+    interpolate_value(&mut value, lookup)?;
+    serde_json::from_value(value).context("Failed to rebuild configuration")
+}
+
+/// Replace `${VAR}` references in a configuration value with the values from
+/// `lookup`; `$$` is a literal `$`
+fn interpolate_with(content: &str, lookup: &EnvLookup) -> Result<String> {
+    let mut output = String::with_capacity(content.len());
+    let mut rest = content;
+
+    while let Some(index) = rest.find('$') {
+        output.push_str(&rest[..index]);
+        rest = &rest[index..];
+
+        if let Some(after) = rest.strip_prefix("$$") {
+            output.push('$');
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("${") {
+            let end = after
+                .find('}')
+                .ok_or_else(|| anyhow::anyhow!("Unterminated variable reference: ${{{}", after))?;
+            let name = &after[..end];
+            if name.is_empty() {
+                anyhow::bail!("Empty variable reference: ${{}}");
+            }
+            let value = lookup(name)
+                .ok_or_else(|| anyhow::anyhow!("Environment variable {} is not set", name))?;
+            output.push_str(&value);
+            rest = &after[end + 1..];
+        } else {
+            output.push('$');
+            rest = &rest[1..];
+        }
+    }
+
+    output.push_str(rest);
+    Ok(output)
 }
 
 /// Save configuration to a TOML file
@@ -59,6 +174,83 @@ pub fn save_config_yaml(config: &Config, path: &str) -> Result<()> {
     Ok(())
 }
 
+/// Apply a `dotted.path=value` assignment to a configuration.
+///
+/// The new value is parsed according to the type of the existing field, so
+/// unknown paths and type mismatches are rejected.
+pub fn set_config_value(config: &Config, assignment: &str) -> Result<Config> {
+    let (path, raw_value) = assignment.split_once('=').ok_or_else(|| {
+        anyhow::anyhow!("Invalid assignment '{}': expected path=value", assignment)
+    })?;
+    let path = path.trim();
+    let raw_value = raw_value.trim();
+
+    let mut root = serde_json::to_value(config).context("Failed to serialize configuration")?;
+
+    let mut current = &mut root;
+    for segment in path.split('.') {
+        current = match current {
+            serde_json::Value::Object(map) => map.get_mut(segment),
+            serde_json::Value::Array(items) => segment
+                .parse::<usize>()
+                .ok()
+                .and_then(move |index| items.get_mut(index)),
+            _ => None,
+        }
+        .ok_or_else(|| anyhow::anyhow!("Unknown configuration path: {}", path))?;
+    }
+
+    let new_value = match current {
+        serde_json::Value::Bool(_) => raw_value
+            .parse::<bool>()
+            .map(serde_json::Value::Bool)
+            .map_err(|_| {
+                anyhow::anyhow!(
+                    "{} expects a boolean (true/false), got '{}'",
+                    path,
+                    raw_value
+                )
+            })?,
+        serde_json::Value::Number(_) => raw_value
+            .parse::<u64>()
+            .map(serde_json::Value::from)
+            .map_err(|_| {
+                anyhow::anyhow!(
+                    "{} expects a non-negative integer, got '{}'",
+                    path,
+                    raw_value
+                )
+            })?,
+        serde_json::Value::String(_) | serde_json::Value::Null => {
+            serde_json::Value::String(raw_value.to_string())
+        }
+        _ => anyhow::bail!("{} is a section and cannot be set directly", path),
+    };
+    *current = new_value;
+
+    serde_json::from_value(root).map_err(|e| anyhow::anyhow!("Invalid value for {}: {}", path, e))
+}
+
+/// Apply a `dotted.path=value` assignment to a configuration file, keeping its format.
+/// `${VAR}` references are left unresolved, in the file and in the returned config.
+pub fn set_config_value_in_file(path: &str, assignment: &str) -> Result<Config> {
+    let config = read_config(path)?;
+    let updated = set_config_value(&config, assignment)?;
+
+    match ConfigFormat::from_path(path)? {
+        ConfigFormat::Toml => save_config(&updated, path)?,
+        ConfigFormat::Yaml => save_config_yaml(&updated, path)?,
+        ConfigFormat::Json => {
+            let content = serde_json::to_string_pretty(&updated)
+                .context("Failed to serialize configuration to JSON")?;
+            fs::write(path, content)
+                .with_context(|| format!("Failed to write configuration to: {}", path))?;
+        }
+    }
+
+    Ok(updated)
+}
+
 /// Create a default configuration and save it to a file
 pub fn create_default_config_file(path: &str) -> Result<()> {
     let default_config = crate::config::create_default_config();
@@ -69,7 +261,7 @@ pub fn create_default_config_file(path: &str) -> Result<()> {
 /// Create a default configuration with sample data
 pub fn create_sample_config() -> Config {
     use crate::config::*;
-    
+
     Config {
         bot: BotConfig {
             name: "lazabot".to_string(),
@@ -153,3 +345,161 @@ pub fn create_sample_config() -> Config {
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_set_config_value_in_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.toml");
+        let path = path.to_str().unwrap();
+        save_config(&create_sample_config(), path).unwrap();
+
+        set_config_value_in_file(path, "bot.default_delay=2000").unwrap();
+        set_config_value_in_file(path, "stealth.user_agent_rotation=true").unwrap();
+
+        let config = load_config(path).unwrap();
+        assert_eq!(config.bot.default_delay, 2000);
+        assert!(config.stealth.user_agent_rotation);
+        // Everything else is preserved
+        assert_eq!(config.bot.max_retries, 3);
+        assert_eq!(config.accounts.len(), 2);
+    }
+
+    #[test]
+    fn test_set_config_value_in_file_keeps_env_references() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.toml");
+        let path = path.to_str().unwrap();
+        let mut config = create_sample_config();
+        config.captcha.api_key = "${LAZABOT_TEST_UNSET_VAR}".to_string();
+        save_config(&config, path).unwrap();
+
+        // The referenced variable is unset, which must not stop the edit
+        let updated = set_config_value_in_file(path, "bot.default_delay=2000").unwrap();
+        assert_eq!(updated.captcha.api_key, "${LAZABOT_TEST_UNSET_VAR}");
+
+        let content = fs::read_to_string(path).unwrap();
+        assert!(content.contains("${LAZABOT_TEST_UNSET_VAR}"));
+        assert!(content.contains("default_delay = 2000"));
+    }
+
+    #[test]
+    fn test_set_config_value_rejects_invalid() {
+        let config = create_sample_config();
+
+        assert!(set_config_value(&config, "bot.unknown_field=1").is_err());
+        assert!(set_config_value(&config, "bot.default_delay=fast").is_err());
+        assert!(set_config_value(&config, "bot.debug=yes").is_err());
+        assert!(set_config_value(&config, "bot=1").is_err());
+        assert!(set_config_value(&config, "bot.debug").is_err());
+        // Out of range for u16
+        assert!(set_config_value(&config, "monitoring.metrics_port=70000").is_err());
+
+        let updated = set_config_value(&config, "accounts.1.status=active").unwrap();
+        assert_eq!(updated.accounts[1].status, "active");
+    }
+
+    #[test]
+    fn test_load_config_detects_format() {
+        let temp_dir = TempDir::new().unwrap();
+        let expected = create_sample_config();
+
+        let toml_path = temp_dir.path().join("config.toml");
+        let yaml_path = temp_dir.path().join("config.yaml");
+        let yml_path = temp_dir.path().join("config.yml");
+        let json_path = temp_dir.path().join("config.json");
+        let bare_path = temp_dir.path().join("config");
+
+        fs::write(&toml_path, toml::to_string_pretty(&expected).unwrap()).unwrap();
+        fs::write(&yaml_path, serde_yaml::to_string(&expected).unwrap()).unwrap();
+        fs::write(&yml_path, serde_yaml::to_string(&expected).unwrap()).unwrap();
+        fs::write(&json_path, serde_json::to_string_pretty(&expected).unwrap()).unwrap();
+        fs::write(&bare_path, toml::to_string_pretty(&expected).unwrap()).unwrap();
+
+        for path in [&toml_path, &yaml_path, &yml_path, &json_path, &bare_path] {
+            let config = load_config(path.to_str().unwrap()).unwrap();
+            assert_eq!(config, expected, "mismatch loading {}", path.display());
+        }
+    }
+
+    #[test]
+    fn test_load_config_unknown_extension() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.ini");
+        fs::write(&path, "[bot]").unwrap();
+
+        let err = load_config(path.to_str().unwrap()).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Unsupported configuration file extension"));
+    }
+
+    fn test_env(name: &str) -> Option<String> {
+        match name {
+            "LAZABOT_TEST_INTERPOLATED_KEY" => Some("secret-key".to_string()),
+            "LAZABOT_TEST_QUOTED" => Some("a\"b\nc = 1".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_load_config_interpolates_env() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.toml");
+        let mut config = create_sample_config();
+        config.captcha.api_key = "${LAZABOT_TEST_INTERPOLATED_KEY}".to_string();
+        config.bot.name = "bot-$$HOME-${LAZABOT_TEST_INTERPOLATED_KEY}".to_string();
+        config.accounts[0].password = "${LAZABOT_TEST_QUOTED}".to_string();
+        save_config(&config, path.to_str().unwrap()).unwrap();
+
+        let loaded = load_config_with(path.to_str().unwrap(), &test_env).unwrap();
+        assert_eq!(loaded.captcha.api_key, "secret-key");
+        assert_eq!(loaded.bot.name, "bot-$HOME-secret-key");
+        // Substituted values are taken verbatim, not parsed as part of the file
+        assert_eq!(loaded.accounts[0].password, "a\"b\nc = 1");
+    }
+
+    #[test]
+    fn test_load_config_unset_env_var() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.toml");
+        let mut config = create_sample_config();
+        config.captcha.api_key = "${LAZABOT_TEST_UNSET_VAR}".to_string();
+        save_config(&config, path.to_str().unwrap()).unwrap();
+
+        let err = load_config_with(path.to_str().unwrap(), &test_env).unwrap_err();
+        assert!(format!("{:#}", err).contains("LAZABOT_TEST_UNSET_VAR is not set"));
+    }
+
+    #[test]
+    fn test_load_config_ignores_references_in_comments() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.toml");
+        let content = toml::to_string_pretty(&create_sample_config()).unwrap();
+        fs::write(
+            &path,
+            format!(
+                "# Set ${{LAZABOT_TEST_UNSET_VAR}} first, costs $$5\n{}",
+                content
+            ),
+        )
+        .unwrap();
+
+        let loaded = load_config_with(path.to_str().unwrap(), &test_env).unwrap();
+        assert_eq!(loaded, create_sample_config());
+    }
+
+    #[test]
+    fn test_interpolate_env_escapes() {
+        let interpolate = |value: &str| interpolate_with(value, &test_env);
+        assert_eq!(interpolate("cost: $$5").unwrap(), "cost: $5");
+        assert_eq!(interpolate("$${NOT_A_VAR}").unwrap(), "${NOT_A_VAR}");
+        assert_eq!(interpolate("a lone $ sign").unwrap(), "a lone $ sign");
+        assert!(interpolate("${UNTERMINATED").is_err());
+        assert!(interpolate("${}").is_err());
+    }
+}
@@ -1,13 +1,19 @@
 pub mod loader;
 pub mod encryption;
 pub mod credentials;
+pub mod dotenv;
 pub mod host_config;
 pub mod validation;
+pub mod watcher;
+
+pub use dotenv::load_dotenv;
+pub use watcher::ConfigWatcher;
 
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 
 /// Main configuration structure for the Lazada bot
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Config {
     /// Bot configuration settings
     pub bot: BotConfig,
@@ -23,8 +29,70 @@ pub struct Config {
     pub monitoring: MonitoringConfig,
 }
 
+/// Log levels accepted in `monitoring.log_level`
+const VALID_LOG_LEVELS: &[&str] = &["trace", "debug", "info", "warn", "error"];
+
+/// Upper bound for `bot.max_retries`
+const MAX_RETRIES_LIMIT: u32 = 100;
+
+impl Config {
+    /// Validate the loaded configuration, collecting every problem found
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+
+        if self.bot.name.trim().is_empty() {
+            errors.push("bot.name must not be empty".to_string());
+        }
+        if self.bot.default_delay == 0 {
+            errors.push("bot.default_delay must be greater than 0".to_string());
+        }
+        if self.bot.max_retries > MAX_RETRIES_LIMIT {
+            errors.push(format!(
+                "bot.max_retries must be at most {} (got {})",
+                MAX_RETRIES_LIMIT, self.bot.max_retries
+            ));
+        }
+
+        let mut seen_ids = std::collections::HashSet::new();
+        for account in &self.accounts {
+            if !seen_ids.insert(account.id.as_str()) {
+                errors.push(format!("Duplicate account id: {}", account.id));
+            }
+        }
+
+        if let Err(problems) = self.captcha.validate() {
+            errors.extend(problems);
+        }
+
+        if !VALID_LOG_LEVELS.contains(&self.monitoring.log_level.to_lowercase().as_str()) {
+            errors.push(format!(
+                "monitoring.log_level '{}' is invalid (expected one of: {})",
+                self.monitoring.log_level,
+                VALID_LOG_LEVELS.join(", ")
+            ));
+        }
+        if self.monitoring.metrics_port == 0 {
+            errors.push("monitoring.metrics_port must not be 0".to_string());
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Get accounts whose status is active
+    pub fn active_accounts(&self) -> Vec<&AccountConfig> {
+        self.accounts
+            .iter()
+            .filter(|account| account.account_status() == AccountStatus::Active)
+            .collect()
+    }
+}
+
 /// Bot-specific configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BotConfig {
     /// Bot name/identifier
     pub name: String,
@@ -39,7 +107,7 @@ pub struct BotConfig {
 }
 
 /// Account configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AccountConfig {
     /// Account identifier
     pub id: String,
@@ -55,8 +123,38 @@ pub struct AccountConfig {
     pub settings: AccountSettings,
 }
 
+/// Typed view of `AccountConfig::status`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountStatus {
+    Active,
+    Inactive,
+    Banned,
+    Unknown,
+}
+
+impl std::str::FromStr for AccountStatus {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.trim().to_lowercase().as_str() {
+            "active" => Self::Active,
+            "inactive" => Self::Inactive,
+            "banned" => Self::Banned,
+            _ => Self::Unknown,
+        })
+    }
+}
+
+impl AccountConfig {
+    /// Parse the account status string
+    pub fn account_status(&self) -> AccountStatus {
+        // FromStr for AccountStatus is infallible
+        self.status.parse().unwrap_or(AccountStatus::Unknown)
+    }
+}
+
 /// Account-specific settings
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AccountSettings {
     /// Preferred payment method
     pub payment_method: String,
@@ -67,7 +165,7 @@ pub struct AccountSettings {
 }
 
 /// Proxy configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ProxyConfig {
     /// Proxy identifier
     pub id: String,
@@ -88,7 +186,7 @@ pub struct ProxyConfig {
 }
 
 /// Captcha solving configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CaptchaConfig {
     /// Captcha service provider
     pub service: String,
@@ -106,8 +204,51 @@ pub struct CaptchaConfig {
     pub max_attempts: u32,
 }
 
+impl CaptchaConfig {
+    /// Check the captcha settings are consistent, collecting every problem found
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+
+        match reqwest::Url::parse(&self.endpoint) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+            Ok(url) => errors.push(format!(
+                "captcha.endpoint '{}' must use http or https (got {})",
+                self.endpoint,
+                url.scheme()
+            )),
+            Err(e) => errors.push(format!(
+                "captcha.endpoint '{}' is not a valid URL: {}",
+                self.endpoint, e
+            )),
+        }
+
+        if self.timeout < self.polling_interval {
+            errors.push(format!(
+                "captcha.timeout ({}s) must not be less than captcha.polling_interval ({}s)",
+                self.timeout, self.polling_interval
+            ));
+        }
+
+        if self.max_attempts == 0 {
+            errors.push("captcha.max_attempts must be at least 1".to_string());
+        } else if self.polling_interval * u64::from(self.max_attempts) < self.timeout {
+            // Polling would give up before the timeout is reached
+            errors.push(format!(
+                "captcha.polling_interval ({}s) x captcha.max_attempts ({}) must cover captcha.timeout ({}s)",
+                self.polling_interval, self.max_attempts, self.timeout
+            ));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
 /// Stealth and anti-detection configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct StealthConfig {
     /// Enable random delays
     pub random_delays: bool,
@@ -122,7 +263,7 @@ pub struct StealthConfig {
 }
 
 /// Monitoring and logging configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MonitoringConfig {
     /// Enable logging
     pub enable_logging: bool,
@@ -180,6 +321,49 @@ pub fn create_default_config() -> Config {
     }
 }
 
+/// Environment variable overriding the host configuration directory
+pub const CONFIG_DIR_ENV: &str = "LAZABOT_CONFIG_DIR";
+
+/// Host configuration directory used when no override is given
+pub const DEFAULT_CONFIG_DIR: &str = "config";
+
+/// Resolve the host configuration directory: explicit override, then
+/// `LAZABOT_CONFIG_DIR`, then `config`
+pub fn resolve_config_dir(override_dir: Option<&Path>) -> PathBuf {
+    override_dir
+        .map(Path::to_path_buf)
+        .or_else(|| {
+            std::env::var_os(CONFIG_DIR_ENV)
+                .filter(|dir| !dir.is_empty())
+                .map(PathBuf::from)
+        })
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_CONFIG_DIR))
+}
+
+/// Errors returned by [`ConfigManager`]
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error(transparent)]
+    Validation(#[from] validation::ValidationError),
+    #[error(transparent)]
+    Credential(#[from] credentials::CredentialError),
+    #[error(transparent)]
+    Encryption(#[from] encryption::EncryptionError),
+    #[error("Configuration validation failed")]
+    Invalid,
+    #[error("Main configuration not loaded")]
+    NotLoaded,
+    #[error("Invalid value for {var}: {value}")]
+    InvalidOverride { var: String, value: String },
+    #[error("Merged configuration is invalid: {0}")]
+    InvalidMerge(#[from] serde_json::Error),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+/// Result type for configuration manager operations
+pub type ConfigResult<T> = Result<T, ConfigError>;
+
 /// Configuration manager that handles loading, validation, and merging
 pub struct ConfigManager {
     main_config: Option<Config>,
@@ -198,29 +382,52 @@ impl ConfigManager {
     }
 
     /// Load configuration from files and environment
-    pub fn load(&mut self, config_path: &str, vault_path: &str) -> anyhow::Result<()> {
+    ///
+    /// Host configs are read from `config_dir`, falling back to
+    /// [`resolve_config_dir`] when `None`.
+    pub fn load(&mut self, config_path: &str, vault_path: &str, config_dir: Option<&Path>) -> ConfigResult<()> {
+        self.load_with(config_path, config_dir, || {
+            crate::config::credentials::CredentialManager::new(vault_path)
+        })
+    }
+
+    /// [`load`](Self::load) with the credential manager built by `credentials`
+    fn load_with(
+        &mut self,
+        config_path: &str,
+        config_dir: Option<&Path>,
+        credentials: impl FnOnce() -> credentials::CredentialResult<credentials::CredentialManager>,
+    ) -> ConfigResult<()> {
+        self.load_layers(config_path, config_dir)?;
+
+        // Initialize credential manager
+        self.credential_manager = Some(credentials()?);
+
+        Ok(())
+    }
+
+    /// Load the base and host configuration layers without touching credentials
+    pub fn load_layers(&mut self, config_path: &str, config_dir: Option<&Path>) -> ConfigResult<()> {
         // Load main configuration
         self.main_config = Some(loader::load_config(config_path)?);
 
         // Detect and load host-specific configuration
-        let mut host_manager = crate::config::host_config::HostConfigManager::new("config");
+        let config_dir = resolve_config_dir(config_dir);
+        let host_manager = crate::config::host_config::HostConfigManager::new(&config_dir.to_string_lossy());
         self.host_config = Some(host_manager.detect_and_load()?);
 
-        // Initialize credential manager
-        self.credential_manager = Some(crate::config::credentials::CredentialManager::new(vault_path)?);
-
         Ok(())
     }
 
     /// Validate all configuration components
-    pub fn validate(&self) -> anyhow::Result<()> {
+    pub fn validate(&self) -> ConfigResult<()> {
         // Validate environment variables
         let validator = crate::config::validation::EnvValidator::new();
         let report = validator.validate_all()?;
         
         if report.has_errors() {
             report.print_report();
-            anyhow::bail!("Configuration validation failed");
+            return Err(ConfigError::Invalid);
         }
 
         // Validate credentials if credential manager is available
@@ -233,6 +440,16 @@ impl ConfigManager {
         Ok(())
     }
 
+    /// Reload the config file at `path` whenever it changes, calling `on_change` with each
+    /// new config that parses and validates. Invalid edits are logged and the previous
+    /// config is kept. Watching stops when the returned [`ConfigWatcher`] is dropped.
+    pub fn watch(
+        path: impl AsRef<std::path::Path>,
+        on_change: impl Fn(Config) + Send + 'static,
+    ) -> ConfigResult<ConfigWatcher> {
+        ConfigWatcher::start(path, on_change)
+    }
+
     /// Get the main configuration
     pub fn get_main_config(&self) -> Option<&Config> {
         self.main_config.as_ref()
@@ -248,25 +465,149 @@ impl ConfigManager {
         self.credential_manager.as_ref()
     }
 
-    /// Get merged configuration (main + host overrides)
-    pub fn get_merged_config(&self) -> anyhow::Result<Config> {
-        let config = self.main_config.clone()
-            .ok_or_else(|| anyhow::anyhow!("Main configuration not loaded"))?;
+    /// Get merged configuration
+    ///
+    /// Layers are applied lowest to highest precedence:
+    /// 1. the base config file
+    /// 2. host overrides, limited to the [`HOST_OVERRIDE_SECTIONS`] sections
+    ///    (accounts and proxies always come from the base config)
+    /// 3. `LAZABOT_*` environment variables listed in [`ENV_OVERRIDES`];
+    ///    empty variables are treated as unset
+    pub fn get_merged_config(&self) -> ConfigResult<Config> {
+        self.merged_config_with(|var| std::env::var(var).ok())
+    }
+
+    /// [`get_merged_config`](Self::get_merged_config) with environment variables read from `env`
+    fn merged_config_with(&self, env: impl Fn(&str) -> Option<String>) -> ConfigResult<Config> {
+        let config = self.main_config.as_ref().ok_or(ConfigError::NotLoaded)?;
+        let mut merged = serde_json::to_value(config)?;
+
+        if let Some(host_config) = &self.host_config {
+            for section in HOST_OVERRIDE_SECTIONS {
+                if let Some(overrides) = host_config.overrides.get(*section) {
+                    merge_json(&mut merged[*section], overrides);
+                }
+            }
+        }
 
-        // Apply host-specific overrides if available
-        if let Some(_host_config) = &self.host_config {
-            // Apply overrides to the configuration
-            // This is a simplified implementation
-            // In a real implementation, you would use a more sophisticated merging strategy
+        for (var, path) in ENV_OVERRIDES {
+            let value = match env(var) {
+                Some(value) if !value.is_empty() => value,
+                _ => continue,
+            };
+            let target = merged
+                .pointer_mut(path)
+                .ok_or_else(|| anyhow::anyhow!("Unknown config path {} for {}", path, var))?;
+            *target = parse_env_value(target, &value).ok_or_else(|| ConfigError::InvalidOverride {
+                var: var.to_string(),
+                value: value.clone(),
+            })?;
         }
 
-        Ok(config)
+        Ok(serde_json::from_value(merged)?)
+    }
+
+    /// Values in the merged configuration that differ from the base config file
+    pub fn effective_diff(&self) -> ConfigResult<Vec<ConfigDifference>> {
+        let base = self.main_config.as_ref().ok_or(ConfigError::NotLoaded)?;
+        Ok(diff_configs(base, &self.get_merged_config()?))
+    }
+}
+
+/// A config value that differs between two configurations
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigDifference {
+    /// Dotted path of the value, e.g. `bot.default_delay`
+    pub path: String,
+    pub base: serde_json::Value,
+    pub effective: serde_json::Value,
+}
+
+impl std::fmt::Display for ConfigDifference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} -> {}", self.path, self.base, self.effective)
+    }
+}
+
+/// List the values that differ between `base` and `effective`, sorted by path
+pub fn diff_configs(base: &Config, effective: &Config) -> Vec<ConfigDifference> {
+    let base = serde_json::to_value(base).unwrap_or_default();
+    let effective = serde_json::to_value(effective).unwrap_or_default();
+    let mut differences = Vec::new();
+    collect_differences(String::new(), &base, &effective, &mut differences);
+    differences
+}
+
+fn collect_differences(
+    path: String,
+    base: &serde_json::Value,
+    effective: &serde_json::Value,
+    differences: &mut Vec<ConfigDifference>,
+) {
+    match (base, effective) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(effective)) => {
+            let mut keys: Vec<&String> = base.keys().chain(effective.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let child = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                collect_differences(
+                    child,
+                    base.get(key).unwrap_or(&serde_json::Value::Null),
+                    effective.get(key).unwrap_or(&serde_json::Value::Null),
+                    differences,
+                );
+            }
+        }
+        (base, effective) if base != effective => differences.push(ConfigDifference {
+            path,
+            base: base.clone(),
+            effective: effective.clone(),
+        }),
+        _ => {}
+    }
+}
+
+/// Config sections a host config may override
+pub const HOST_OVERRIDE_SECTIONS: &[&str] = &["bot", "captcha", "stealth", "monitoring"];
+
+/// Environment variables that override config keys, as (variable, JSON pointer)
+pub const ENV_OVERRIDES: &[(&str, &str)] = &[
+    ("LAZABOT_DEBUG", "/bot/debug"),
+    ("LAZABOT_DEFAULT_DELAY", "/bot/default_delay"),
+    ("LAZABOT_MAX_RETRIES", "/bot/max_retries"),
+    ("LAZABOT_USER_AGENT", "/bot/user_agent"),
+    ("LAZABOT_CAPTCHA_API_KEY", "/captcha/api_key"),
+    ("LAZABOT_CAPTCHA_ENDPOINT", "/captcha/endpoint"),
+    ("LAZABOT_LOG_LEVEL", "/monitoring/log_level"),
+    ("LAZABOT_METRICS_PORT", "/monitoring/metrics_port"),
+];
+
+/// Recursively merge `overlay` into `base`; non-object values are replaced
+fn merge_json(base: &mut serde_json::Value, overlay: &serde_json::Value) {
+    match (base, overlay) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                merge_json(base.entry(key.clone()).or_insert(serde_json::Value::Null), value);
+            }
+        }
+        (base, overlay) => *base = overlay.clone(),
+    }
+}
+
+/// Parse an env var value using the type of the value it replaces
+fn parse_env_value(current: &serde_json::Value, raw: &str) -> Option<serde_json::Value> {
+    match current {
+        serde_json::Value::Bool(_) => raw.trim().to_lowercase().parse::<bool>().ok().map(Into::into),
+        serde_json::Value::Number(_) => raw.trim().parse::<u64>().ok().map(Into::into),
+        _ => Some(raw.into()),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
 
     #[test]
     fn test_default_config_creation() {
@@ -297,4 +638,260 @@ mod tests {
         assert_eq!(deserialized.bot.name, config.bot.name);
         assert_eq!(deserialized.bot.default_delay, config.bot.default_delay);
     }
+
+    #[test]
+    fn test_config_validate_valid() {
+        let config = loader::create_sample_config();
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_config_validate_individual_violations() {
+        let mut config = create_default_config();
+        config.bot.name = "  ".to_string();
+        assert_eq!(config.validate().unwrap_err().len(), 1);
+
+        let mut config = create_default_config();
+        config.bot.default_delay = 0;
+        assert!(config.validate().unwrap_err()[0].contains("default_delay"));
+
+        let mut config = create_default_config();
+        config.monitoring.log_level = "verbose".to_string();
+        assert!(config.validate().unwrap_err()[0].contains("log_level"));
+
+        let mut config = create_default_config();
+        config.captcha.timeout = 2;
+        config.captcha.polling_interval = 5;
+        assert!(config.validate().unwrap_err()[0].contains("captcha.timeout"));
+
+        let mut config = loader::create_sample_config();
+        config.accounts[1].id = config.accounts[0].id.clone();
+        assert!(config.validate().unwrap_err()[0].contains("Duplicate account id"));
+    }
+
+    #[test]
+    fn test_captcha_config_validate() {
+        assert!(create_default_config().captcha.validate().is_ok());
+
+        let mut captcha = create_default_config().captcha;
+        captcha.endpoint = "not a url".to_string();
+        assert!(captcha.validate().unwrap_err()[0].contains("not a valid URL"));
+
+        let mut captcha = create_default_config().captcha;
+        captcha.endpoint = "ftp://2captcha.com/api".to_string();
+        assert!(captcha.validate().unwrap_err()[0].contains("http or https"));
+
+        let mut captcha = create_default_config().captcha;
+        captcha.timeout = 2;
+        captcha.polling_interval = 5;
+        assert!(captcha.validate().unwrap_err()[0].contains("captcha.timeout"));
+
+        let mut captcha = create_default_config().captcha;
+        captcha.max_attempts = 0;
+        assert_eq!(
+            captcha.validate().unwrap_err(),
+            vec!["captcha.max_attempts must be at least 1".to_string()]
+        );
+
+        let mut captcha = create_default_config().captcha;
+        captcha.polling_interval = 5;
+        captcha.max_attempts = 3;
+        captcha.timeout = 120;
+        assert!(captcha.validate().unwrap_err()[0].contains("must cover captcha.timeout"));
+
+        // All problems are reported and surface through Config::validate
+        let mut config = create_default_config();
+        config.captcha.endpoint = String::new();
+        config.captcha.max_attempts = 0;
+        assert_eq!(config.validate().unwrap_err().len(), 2);
+    }
+
+    #[test]
+    fn test_config_validate_collects_all_errors() {
+        let mut config = create_default_config();
+        config.bot.name = String::new();
+        config.bot.default_delay = 0;
+        config.bot.max_retries = 1000;
+        config.monitoring.metrics_port = 0;
+
+        let errors = config.validate().unwrap_err();
+        assert_eq!(errors.len(), 4);
+    }
+
+    #[test]
+    fn test_active_accounts() {
+        let mut config = loader::create_sample_config();
+        let mut banned = config.accounts[0].clone();
+        banned.id = "account_3".to_string();
+        banned.status = "banned".to_string();
+        let mut unknown = config.accounts[0].clone();
+        unknown.id = "account_4".to_string();
+        unknown.status = "suspended".to_string();
+        config.accounts.push(banned);
+        config.accounts.push(unknown);
+
+        let active = config.active_accounts();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].id, "account_1");
+
+        assert_eq!(config.accounts[1].account_status(), AccountStatus::Inactive);
+        assert_eq!(config.accounts[2].account_status(), AccountStatus::Banned);
+        assert_eq!(config.accounts[3].account_status(), AccountStatus::Unknown);
+        assert_eq!("ACTIVE".parse::<AccountStatus>(), Ok(AccountStatus::Active));
+    }
+
+    #[test]
+    fn test_config_manager_custom_config_dir() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let config_dir = temp_dir.path().join("hosts-root");
+        let config_path = temp_dir.path().join("config.toml");
+        std::fs::write(&config_path, toml::to_string(&create_default_config()).unwrap()).unwrap();
+
+        let mut manager = ConfigManager::new();
+        manager
+            .load_layers(config_path.to_str().unwrap(), Some(&config_dir))
+            .unwrap();
+
+        let host_id = manager.get_host_config().unwrap().host_id.clone();
+        let host_file = config_dir.join("hosts").join(format!("{}.toml", host_id));
+        assert!(host_file.exists());
+
+        // A second load reads the file created in the custom directory
+        let created_at = manager.get_host_config().unwrap().created_at.clone();
+        manager
+            .load_layers(config_path.to_str().unwrap(), Some(&config_dir))
+            .unwrap();
+        assert_eq!(manager.get_host_config().unwrap().created_at, created_at);
+    }
+
+    #[test]
+    fn test_resolve_config_dir() {
+        assert_eq!(
+            resolve_config_dir(Some(Path::new("/etc/lazabot"))),
+            PathBuf::from("/etc/lazabot")
+        );
+    }
+
+    #[test]
+    fn test_merged_config_precedence() {
+        let mut base = create_default_config();
+        base.bot.default_delay = 1000;
+        base.bot.user_agent = "base-agent".to_string();
+        base.bot.max_retries = 3;
+
+        let host = host_config::HostConfig {
+            host_id: "test".to_string(),
+            environment: "testing".to_string(),
+            overrides: serde_json::json!({
+                "bot": { "default_delay": 2000, "user_agent": "host-agent" },
+                "accounts": [{ "id": "ignored" }]
+            }),
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            last_updated: "2024-01-01T00:00:00Z".to_string(),
+        };
+
+        let mut manager = ConfigManager::new();
+        manager.main_config = Some(base.clone());
+
+        let mut env = HashMap::new();
+        let merged = |manager: &ConfigManager, env: &HashMap<&str, &str>| {
+            manager.merged_config_with(|var| env.get(var).map(|value| value.to_string()))
+        };
+
+        // Base only
+        assert_eq!(merged(&manager, &env).unwrap().bot.default_delay, 1000);
+
+        // Host overrides base; accounts are not overridable
+        manager.host_config = Some(host);
+        let config = merged(&manager, &env).unwrap();
+        assert_eq!(config.bot.default_delay, 2000);
+        assert_eq!(config.bot.user_agent, "host-agent");
+        assert_eq!(config.bot.max_retries, 3);
+        assert!(config.accounts.is_empty());
+
+        // Env overrides host
+        env.insert("LAZABOT_DEFAULT_DELAY", "3000");
+        // Empty env var is treated as unset
+        env.insert("LAZABOT_USER_AGENT", "");
+        let config = merged(&manager, &env).unwrap();
+        assert_eq!(config.bot.default_delay, 3000);
+        assert_eq!(config.bot.user_agent, "host-agent");
+
+        // Env overrides base when the host does not touch the key
+        env.insert("LAZABOT_USER_AGENT", "env-agent");
+        manager.host_config = None;
+        let config = merged(&manager, &env).unwrap();
+        assert_eq!(config.bot.default_delay, 3000);
+        assert_eq!(config.bot.user_agent, "env-agent");
+
+        // Unparseable env values are rejected
+        env.insert("LAZABOT_DEFAULT_DELAY", "soon");
+        assert!(matches!(
+            merged(&manager, &env),
+            Err(ConfigError::InvalidOverride { .. })
+        ));
+    }
+
+    #[test]
+    fn test_config_manager_credential_error() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        std::fs::write(&config_path, toml::to_string(&create_default_config()).unwrap()).unwrap();
+        let vault_path = temp_dir.path().join("credentials.vault");
+        std::fs::write(&vault_path, "not an encrypted vault").unwrap();
+
+        let mut manager = ConfigManager::new();
+        let err = manager
+            .load_with(config_path.to_str().unwrap(), Some(temp_dir.path()), || {
+                let key = "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
+                credentials::CredentialManager::with_encryption_manager(
+                    vault_path.to_str().unwrap(),
+                    encryption::EncryptionManager::from_hex_key(key)?,
+                )
+            })
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::Credential(credentials::CredentialError::EncryptionError(_))
+        ));
+
+        // Still usable from anyhow call sites
+        let err: anyhow::Error = err.into();
+        assert!(err.downcast_ref::<ConfigError>().is_some());
+    }
+
+    #[test]
+    fn test_effective_diff_shows_overrides() {
+        let mut base = create_default_config();
+        base.bot.default_delay = 1000;
+        let mut manager = ConfigManager::new();
+        manager.main_config = Some(base.clone());
+        manager.host_config = Some(host_config::HostConfig {
+            host_id: "test".to_string(),
+            environment: "testing".to_string(),
+            overrides: serde_json::json!({ "stealth": { "proxy_rotation": false } }),
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            last_updated: "2024-01-01T00:00:00Z".to_string(),
+        });
+        let env = HashMap::from([("LAZABOT_DEFAULT_DELAY", "2500")]);
+        let merged = manager
+            .merged_config_with(|var| env.get(var).map(|value| value.to_string()))
+            .unwrap();
+
+        let effective = toml::to_string_pretty(&merged).unwrap();
+        assert!(effective.contains("proxy_rotation = false"));
+        assert!(effective.contains("default_delay = 2500"));
+
+        let diff: Vec<String> = diff_configs(&base, &merged)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            diff,
+            vec![
+                "bot.default_delay: 1000 -> 2500",
+                "stealth.proxy_rotation: true -> false"
+            ]
+        );
+    }
 }
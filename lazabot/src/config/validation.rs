@@ -1,8 +1,11 @@
 use anyhow::Result;
+use serde::Serialize;
 use std::env;
 use thiserror::Error;
 
+use crate::api::ProxyInfo;
 use crate::config::credentials::CredentialManager;
+use crate::proxy::ProxyHealth;
 
 /// Validation errors
 #[derive(Error, Debug)]
@@ -32,6 +35,8 @@ pub struct RequiredVar {
     pub name: String,
     pub description: String,
     pub validation_fn: Option<fn(&str) -> ValidationResult<()>>,
+    /// Mask the value in reports
+    pub sensitive: bool,
 }
 
 /// Optional environment variable definition
@@ -41,6 +46,8 @@ pub struct OptionalVar {
     pub description: String,
     pub default_value: Option<String>,
     pub validation_fn: Option<fn(&str) -> ValidationResult<()>>,
+    /// Mask the value in reports
+    pub sensitive: bool,
 }
 
 impl EnvValidator {
@@ -52,13 +59,13 @@ impl EnvValidator {
         };
 
         // Add required variables
-        validator.add_required_var(
+        validator.add_sensitive_required_var(
             "LAZABOT_MASTER_KEY",
             "Master encryption key (32 bytes, hex-encoded)",
             Some(validate_master_key),
         );
 
-        validator.add_required_var(
+        validator.add_sensitive_required_var(
             "LAZABOT_CAPTCHA_API_KEY",
             "2Captcha API key for solving captchas",
             Some(validate_api_key),
@@ -89,6 +96,13 @@ impl EnvValidator {
             Some(validate_directory_path),
         );
 
+        validator.add_optional_var(
+            "LAZABOT_CONFIG_DIR",
+            "Host configuration directory",
+            Some("./config".to_string()),
+            Some(validate_directory_path),
+        );
+
         validator.add_optional_var(
             "LAZABOT_VAULT_PATH",
             "Path to encrypted credential vault",
@@ -96,13 +110,27 @@ impl EnvValidator {
             None,
         );
 
-        validator.add_optional_var(
+        validator.add_sensitive_optional_var(
             "LAZABOT_DATABASE_URL",
             "Database connection URL",
             Some("sqlite://./data/lazabot.db".to_string()),
             None,
         );
 
+        validator.add_sensitive_optional_var(
+            "LAZABOT_TELEGRAM_BOT_TOKEN",
+            "Telegram bot token for notifications",
+            None,
+            None,
+        );
+
+        validator.add_optional_var(
+            "LAZABOT_TELEGRAM_CHAT_ID",
+            "Telegram chat id for notifications",
+            None,
+            None,
+        );
+
         // Add proxy variables (optional)
         validator.add_proxy_variables();
 
@@ -120,9 +148,23 @@ impl EnvValidator {
             name: name.to_string(),
             description: description.to_string(),
             validation_fn,
+            sensitive: false,
         });
     }
 
+    /// Add a required environment variable whose value is masked in reports
+    pub fn add_sensitive_required_var(
+        &mut self,
+        name: &str,
+        description: &str,
+        validation_fn: Option<fn(&str) -> ValidationResult<()>>,
+    ) {
+        self.add_required_var(name, description, validation_fn);
+        if let Some(var) = self.required_vars.last_mut() {
+            var.sensitive = true;
+        }
+    }
+
     /// Add an optional environment variable
     pub fn add_optional_var(
         &mut self,
@@ -136,9 +178,24 @@ impl EnvValidator {
             description: description.to_string(),
             default_value,
             validation_fn,
+            sensitive: false,
         });
     }
 
+    /// Add an optional environment variable whose value is masked in reports
+    pub fn add_sensitive_optional_var(
+        &mut self,
+        name: &str,
+        description: &str,
+        default_value: Option<String>,
+        validation_fn: Option<fn(&str) -> ValidationResult<()>>,
+    ) {
+        self.add_optional_var(name, description, default_value, validation_fn);
+        if let Some(var) = self.optional_vars.last_mut() {
+            var.sensitive = true;
+        }
+    }
+
     /// Add account-related environment variables
     fn add_account_variables(&mut self) {
         // Single account variables
@@ -148,7 +205,7 @@ impl EnvValidator {
             Some(validate_email),
         );
 
-        self.add_required_var(
+        self.add_sensitive_required_var(
             "LAZABOT_PASSWORD",
             "Lazada account password",
             Some(validate_password),
@@ -170,7 +227,7 @@ impl EnvValidator {
                 Some(validate_email),
             );
 
-            self.add_optional_var(
+            self.add_sensitive_optional_var(
                 &format!("LAZABOT_ACCOUNT_{}_PASSWORD", i),
                 &format!("Lazada account {} password", i),
                 None,
@@ -210,7 +267,7 @@ impl EnvValidator {
             None,
         );
 
-        self.add_optional_var(
+        self.add_sensitive_optional_var(
             "LAZABOT_PROXY_PASSWORD",
             "Proxy server password",
             None,
@@ -247,7 +304,7 @@ impl EnvValidator {
                 None,
             );
 
-            self.add_optional_var(
+            self.add_sensitive_optional_var(
                 &format!("LAZABOT_PROXY_{}_PASSWORD", i),
                 &format!("Proxy server {} password", i),
                 None,
@@ -265,8 +322,20 @@ impl EnvValidator {
 
     /// Validate all environment variables
     pub fn validate_all(&self) -> ValidationResult<ValidationReport> {
+        let report = self.report();
+
+        if report.has_errors() {
+            Err(ValidationError::ConfigValidationFailed(
+                "Environment validation failed. See report for details.".to_string()
+            ))
+        } else {
+            Ok(report)
+        }
+    }
+
+    /// Build the validation report, including errors
+    pub fn report(&self) -> ValidationReport {
         let mut report = ValidationReport::new();
-        let mut has_errors = false;
 
         // Validate required variables
         for var in &self.required_vars {
@@ -275,20 +344,18 @@ impl EnvValidator {
                     if let Some(validator) = var.validation_fn {
                         match validator(&value) {
                             Ok(()) => {
-                                report.add_success(&var.name, &var.description, Some(&value));
+                                report.add_success(&var.name, &var.description, Some(display_value(var.sensitive, &value)));
                             }
                             Err(e) => {
                                 report.add_error(&var.name, &var.description, &e.to_string());
-                                has_errors = true;
                             }
                         }
                     } else {
-                        report.add_success(&var.name, &var.description, Some(&value));
+                        report.add_success(&var.name, &var.description, Some(display_value(var.sensitive, &value)));
                     }
                 }
                 Err(_) => {
                     report.add_error(&var.name, &var.description, "Variable not set");
-                    has_errors = true;
                 }
             }
         }
@@ -300,15 +367,14 @@ impl EnvValidator {
                     if let Some(validator) = var.validation_fn {
                         match validator(&value) {
                             Ok(()) => {
-                                report.add_success(&var.name, &var.description, Some(&value));
+                                report.add_success(&var.name, &var.description, Some(display_value(var.sensitive, &value)));
                             }
                             Err(e) => {
                                 report.add_error(&var.name, &var.description, &e.to_string());
-                                has_errors = true;
                             }
                         }
                     } else {
-                        report.add_success(&var.name, &var.description, Some(&value));
+                        report.add_success(&var.name, &var.description, Some(display_value(var.sensitive, &value)));
                     }
                 }
                 Err(_) => {
@@ -332,16 +398,9 @@ impl EnvValidator {
                 "At least one Lazada account must be configured",
                 "No valid account configuration found. Set LAZABOT_USERNAME/LAZABOT_PASSWORD or LAZABOT_ACCOUNT_1_USERNAME/LAZABOT_ACCOUNT_1_PASSWORD"
             );
-            has_errors = true;
         }
 
-        if has_errors {
-            Err(ValidationError::ConfigValidationFailed(
-                "Environment validation failed. See report for details.".to_string()
-            ))
-        } else {
-            Ok(report)
-        }
+        report
     }
 
     /// Validate credentials using the credential manager
@@ -357,15 +416,27 @@ impl EnvValidator {
     }
 }
 
+/// Placeholder shown instead of sensitive values
+pub const MASKED_VALUE: &str = "****";
+
+fn display_value(sensitive: bool, value: &str) -> &str {
+    if sensitive {
+        MASKED_VALUE
+    } else {
+        value
+    }
+}
+
 /// Validation report containing results of environment validation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ValidationReport {
     pub successes: Vec<ValidationItem>,
     pub errors: Vec<ValidationItem>,
+    pub warnings: Vec<ValidationItem>,
     pub infos: Vec<ValidationItem>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ValidationItem {
     pub variable: String,
     pub description: String,
@@ -378,6 +449,7 @@ impl ValidationReport {
         Self {
             successes: Vec::new(),
             errors: Vec::new(),
+            warnings: Vec::new(),
             infos: Vec::new(),
         }
     }
@@ -400,6 +472,15 @@ impl ValidationReport {
         });
     }
 
+    pub fn add_warning(&mut self, variable: &str, description: &str, warning: &str) {
+        self.warnings.push(ValidationItem {
+            variable: variable.to_string(),
+            description: description.to_string(),
+            status: format!("⚠ Warning: {}", warning),
+            value: None,
+        });
+    }
+
     pub fn add_info(&mut self, variable: &str, description: &str, status: &str) {
         self.infos.push(ValidationItem {
             variable: variable.to_string(),
@@ -410,48 +491,125 @@ impl ValidationReport {
     }
 
     pub fn print_report(&self) {
-        println!("\n=== Environment Validation Report ===\n");
+        print!("{}", self.format_report());
+    }
+
+    /// Render the report as printed by `print_report`
+    pub fn format_report(&self) -> String {
+        self.to_string()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+
+    pub fn has_warnings(&self) -> bool {
+        !self.warnings.is_empty()
+    }
+
+    /// Serialize the report as pretty JSON with an overall `valid` flag
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&serde_json::json!({
+            "valid": !self.has_errors(),
+            "successes": self.successes,
+            "errors": self.errors,
+            "warnings": self.warnings,
+            "infos": self.infos,
+        }))
+    }
+}
+
+impl std::fmt::Display for ValidationReport {
+    fn fmt(&self, out: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(out, "\n=== Environment Validation Report ===\n")?;
 
         if !self.successes.is_empty() {
-            println!("✓ SUCCESSFUL VALIDATIONS:");
+            writeln!(out, "✓ SUCCESSFUL VALIDATIONS:")?;
             for item in &self.successes {
                 let value_display = item.value.as_ref()
                     .map(|v| format!(" = {}", v))
                     .unwrap_or_default();
-                println!("  {} {}: {}{}", 
-                    item.status, item.variable, item.description, value_display);
+                writeln!(out, "  {} {}: {}{}", 
+                    item.status, item.variable, item.description, value_display)?;
             }
-            println!();
+            writeln!(out)?;
         }
 
         if !self.errors.is_empty() {
-            println!("✗ ERRORS:");
+            writeln!(out, "✗ ERRORS:")?;
             for item in &self.errors {
-                println!("  {} {}: {}", 
-                    item.status, item.variable, item.description);
+                writeln!(out, "  {} {}: {}", 
+                    item.status, item.variable, item.description)?;
+            }
+            writeln!(out)?;
+        }
+
+        if !self.warnings.is_empty() {
+            writeln!(out, "⚠ WARNINGS:")?;
+            for item in &self.warnings {
+                writeln!(out, "  {} {}: {}", 
+                    item.status, item.variable, item.description)?;
             }
-            println!();
+            writeln!(out)?;
         }
 
         if !self.infos.is_empty() {
-            println!("ℹ INFO:");
+            writeln!(out, "ℹ INFO:")?;
             for item in &self.infos {
-                println!("  {} {}: {}", 
-                    item.status, item.variable, item.description);
+                writeln!(out, "  {} {}: {}", 
+                    item.status, item.variable, item.description)?;
             }
-            println!();
+            writeln!(out)?;
         }
 
-        let total = self.successes.len() + self.errors.len() + self.infos.len();
-        println!("Total variables checked: {}", total);
-        println!("Successful: {}", self.successes.len());
-        println!("Errors: {}", self.errors.len());
-        println!("Info: {}", self.infos.len());
+        let total = self.successes.len() + self.errors.len() + self.warnings.len() + self.infos.len();
+        writeln!(out, "Total variables checked: {}", total)?;
+        writeln!(out, "Successful: {}", self.successes.len())?;
+        writeln!(out, "Errors: {}", self.errors.len())?;
+        writeln!(out, "Warnings: {}", self.warnings.len())?;
+        writeln!(out, "Info: {}", self.infos.len())?;
+        Ok(())
     }
+}
 
-    pub fn has_errors(&self) -> bool {
-        !self.errors.is_empty()
+/// Check that each configured proxy is reachable, recording the outcome in the report.
+///
+/// All proxies are checked concurrently. An unreachable proxy is recorded as an
+/// error when `strict` is set and as a warning otherwise. Returns the number of
+/// reachable proxies.
+pub async fn check_proxy_reachability(
+    checker: &ProxyHealth,
+    proxies: &[(String, ProxyInfo)],
+    strict: bool,
+    report: &mut ValidationReport,
+) -> usize {
+    let checks = proxies
+        .iter()
+        .map(|(_, proxy)| checker.check_proxy_health(proxy));
+    let results = futures::future::join_all(checks).await;
+
+    let mut reachable = 0;
+    for ((id, proxy), is_healthy) in proxies.iter().zip(results) {
+        let variable = format!("PROXY_{}", id.to_uppercase());
+        let address = format!("{}:{}", proxy.host, proxy.port);
+
+        if is_healthy {
+            reachable += 1;
+            report.add_success(&variable, "Proxy reachability", Some(&address));
+        } else if strict {
+            report.add_error(&variable, "Proxy reachability", &format!("{} is unreachable", address));
+        } else {
+            report.add_warning(&variable, "Proxy reachability", &format!("{} is unreachable", address));
+        }
     }
+
+    report.add_info(
+        "PROXY_REACHABILITY",
+        "Configured proxies reachable",
+        &format!("{}/{} reachable", reachable, proxies.len()),
+    );
+
+    reachable
 }
 
 // Validation functions
@@ -645,11 +803,57 @@ mod tests {
         let mut report = ValidationReport::new();
         report.add_success("TEST_VAR", "Test variable", Some("test_value"));
         report.add_error("ERROR_VAR", "Error variable", "Test error");
+        report.add_warning("WARN_VAR", "Warning variable", "Test warning");
         report.add_info("INFO_VAR", "Info variable", "Not set");
 
         assert_eq!(report.successes.len(), 1);
         assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.warnings.len(), 1);
         assert_eq!(report.infos.len(), 1);
         assert!(report.has_errors());
+        assert!(report.has_warnings());
+    }
+
+    #[test]
+    fn test_validation_report_json() {
+        let mut report = ValidationReport::new();
+        report.add_success("TEST_VAR", "Test variable", Some("test_value"));
+        report.add_error("ERROR_VAR", "Error variable", "Test error");
+        report.add_error("OTHER_VAR", "Other variable", "Variable not set");
+        report.add_info("INFO_VAR", "Info variable", "Not set");
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert_eq!(json["valid"], false);
+        assert_eq!(json["errors"].as_array().unwrap().len(), report.errors.len());
+        assert_eq!(json["successes"].as_array().unwrap().len(), 1);
+        assert_eq!(json["errors"][0]["variable"], "ERROR_VAR");
+        assert_eq!(json["errors"][1]["status"], "✗ Error: Variable not set");
+    }
+
+    #[test]
+    fn test_sensitive_value_masked() {
+        env::set_var("LAZABOT_TEST_SECRET_VALUE", "hunter2-secret");
+        let mut validator = EnvValidator {
+            required_vars: Vec::new(),
+            optional_vars: Vec::new(),
+        };
+        validator.add_sensitive_required_var("LAZABOT_TEST_SECRET_VALUE", "Test secret", None);
+
+        let output = validator.report().format_report();
+        env::remove_var("LAZABOT_TEST_SECRET_VALUE");
+
+        assert!(output.contains("LAZABOT_TEST_SECRET_VALUE: Test secret = ****"));
+        assert!(!output.contains("hunter2-secret"));
+    }
+
+    #[test]
+    fn test_database_url_is_sensitive() {
+        let validator = EnvValidator::new();
+        let var = validator
+            .optional_vars
+            .iter()
+            .find(|var| var.name == "LAZABOT_DATABASE_URL")
+            .unwrap();
+        assert!(var.sensitive);
     }
 }
@@ -0,0 +1,131 @@
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use parking_lot::Mutex;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{error, info};
+
+use crate::config::{loader, Config, ConfigError, ConfigResult};
+
+/// Reloads a config file whenever it changes on disk. Watching stops when this is dropped.
+pub struct ConfigWatcher {
+    _watcher: RecommendedWatcher,
+    current: Arc<Mutex<Config>>,
+}
+
+impl ConfigWatcher {
+    /// Load `path` and start watching it, calling `on_change` with every new valid config
+    pub fn start(
+        path: impl AsRef<Path>,
+        on_change: impl Fn(Config) + Send + 'static,
+    ) -> ConfigResult<Self> {
+        let path = path.as_ref().to_path_buf();
+        let current = Arc::new(Mutex::new(load_valid(&path)?));
+
+        // Watch the directory so editors that save by renaming a new file into place
+        // are still picked up
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        let file_name = path.file_name().map(|name| name.to_os_string());
+
+        let state = Arc::clone(&current);
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+            let event = match res {
+                Ok(event) => event,
+                Err(e) => {
+                    error!("Config watcher error: {}", e);
+                    return;
+                }
+            };
+            if event.kind.is_access()
+                || !event
+                    .paths
+                    .iter()
+                    .any(|changed| changed.file_name() == file_name.as_deref())
+            {
+                return;
+            }
+
+            match load_valid(&path) {
+                Ok(config) => {
+                    let mut current = state.lock();
+                    // One save usually produces several events; only report real changes
+                    if *current == config {
+                        return;
+                    }
+                    *current = config.clone();
+                    drop(current);
+                    info!("Reloaded configuration from {}", path.display());
+                    on_change(config);
+                }
+                Err(e) => error!(
+                    "Ignoring invalid configuration in {}, keeping the previous one: {}",
+                    path.display(),
+                    e
+                ),
+            }
+        })
+        .map_err(|e| ConfigError::Other(e.into()))?;
+
+        watcher
+            .watch(&dir, RecursiveMode::NonRecursive)
+            .map_err(|e| ConfigError::Other(e.into()))?;
+
+        Ok(Self {
+            _watcher: watcher,
+            current,
+        })
+    }
+
+    /// The last config that loaded and validated successfully
+    pub fn current(&self) -> Config {
+        self.current.lock().clone()
+    }
+}
+
+fn load_valid(path: &Path) -> ConfigResult<Config> {
+    let config = loader::load_config(&path.to_string_lossy())?;
+    config
+        .validate()
+        .map_err(|errors| anyhow::anyhow!("{}", errors.join("; ")))?;
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{create_default_config, ConfigManager};
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_watch_reloads_on_change() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        let mut config = create_default_config();
+        std::fs::write(&config_path, toml::to_string(&config).unwrap()).unwrap();
+
+        let (tx, rx) = mpsc::channel();
+        let watcher = ConfigManager::watch(&config_path, move |config| {
+            let _ = tx.send(config);
+        })
+        .unwrap();
+
+        config.bot.default_delay = 2500;
+        std::fs::write(&config_path, toml::to_string(&config).unwrap()).unwrap();
+        let reloaded = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(reloaded.bot.default_delay, 2500);
+
+        // A broken edit keeps the previous config
+        std::fs::write(&config_path, "bot = [").unwrap();
+        assert!(rx.recv_timeout(Duration::from_millis(500)).is_err());
+        assert_eq!(watcher.current().bot.default_delay, 2500);
+
+        // So does one that parses but fails validation
+        config.bot.default_delay = 0;
+        std::fs::write(&config_path, toml::to_string(&config).unwrap()).unwrap();
+        assert!(rx.recv_timeout(Duration::from_millis(500)).is_err());
+        assert_eq!(watcher.current().bot.default_delay, 2500);
+    }
+}
@@ -1,15 +1,23 @@
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
+use futures::stream::{FuturesUnordered, StreamExt};
+use rand::Rng;
 use reqwest::Method;
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::sleep;
 use tracing::{debug, error, info, warn};
 
-use crate::api::ApiClient;
-use crate::captcha::CaptchaSolverTrait;
-use crate::config::AccountSettings;
-use crate::core::Session;
+use crate::api::{ApiClient, ApiError};
+use crate::captcha::{CaptchaSolverTrait, CaptchaType, UnknownCaptchaType};
+use crate::config::credentials::CredentialVault;
+use crate::config::{AccountSettings, AccountStatus, Config};
+use crate::core::monitor::ProductInfo;
+use crate::core::{Credentials, PerformanceMonitor, Session, SessionPool};
+use crate::notifications::{NotificationEvent, Notifier};
+use crate::storage::{Database, NewOrder};
+use crate::utils::MetricsCollector;
 
 /// Product information for checkout
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,6 +59,78 @@ pub struct Account {
     pub settings: AccountSettings,
 }
 
+/// Product, account and session for one checkout, resolved and checked up front
+#[derive(Debug, Clone)]
+pub struct CheckoutContext {
+    pub product: Product,
+    pub account: Account,
+    pub session: Session,
+}
+
+impl CheckoutContext {
+    /// Resolve `product_id` among the monitored `products`, `account_id` from `config`
+    /// with its credentials from `vault`, and a valid session for it from `sessions`
+    pub async fn build(
+        product_id: &str,
+        account_id: &str,
+        config: &Config,
+        products: &[ProductInfo],
+        vault: &CredentialVault,
+        sessions: &SessionPool,
+    ) -> Result<Self> {
+        let product_info = products
+            .iter()
+            .find(|product| product.id == product_id)
+            .ok_or_else(|| anyhow!("Product {} is not among the monitored products", product_id))?;
+
+        let account_config = config
+            .accounts
+            .iter()
+            .find(|account| account.id == account_id)
+            .ok_or_else(|| anyhow!("Account {} is not in the configuration", account_id))?;
+        if account_config.account_status() != AccountStatus::Active {
+            bail!(
+                "Account {} is not active (status: {})",
+                account_id,
+                account_config.status
+            );
+        }
+
+        let credentials = vault
+            .get_account(account_id)
+            .map_err(|_| anyhow!("No credentials for account {} in the vault", account_id))?;
+        let mut login =
+            Credentials::new(credentials.username.clone(), credentials.password.clone());
+        if let Some(email) = &credentials.email {
+            login = login.with_email(email.clone());
+        }
+
+        let session = sessions
+            .get(account_id, &login)
+            .await
+            .with_context(|| format!("No valid session for account {}", account_id))?;
+
+        let mut product = Product::new(
+            product_info.id.clone(),
+            product_info.name.clone(),
+            product_info.url.clone(),
+        );
+        if let Some(price) = product_info.target_price {
+            product = product.with_price(price);
+        }
+
+        Ok(Self {
+            product,
+            account: Account {
+                id: account_config.id.clone(),
+                username: credentials.username.clone(),
+                settings: account_config.settings.clone(),
+            },
+            session,
+        })
+    }
+}
+
 /// Checkout errors
 #[derive(Debug, thiserror::Error)]
 pub enum CheckoutError {
@@ -94,14 +174,103 @@ pub enum CheckoutError {
     Other(String),
 }
 
+/// Kind of a checkout failure, for callers deciding whether to retry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckoutErrorKind {
+    AddToCartFailed,
+    CheckoutUrlFailed,
+    ShippingFailed,
+    PaymentFailed,
+    CaptchaDetectionFailed,
+    CaptchaSolvingFailed,
+    OrderSubmissionFailed,
+    NetworkError,
+    Timeout,
+    InvalidResponse,
+    SessionExpired,
+    ProductUnavailable,
+    Other,
+}
+
+impl CheckoutErrorKind {
+    /// Whether a later attempt may succeed without any change on our side
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::NetworkError | Self::Timeout)
+    }
+}
+
+impl CheckoutError {
+    pub fn kind(&self) -> CheckoutErrorKind {
+        match self {
+            Self::AddToCartFailed(_) => CheckoutErrorKind::AddToCartFailed,
+            Self::CheckoutUrlFailed(_) => CheckoutErrorKind::CheckoutUrlFailed,
+            Self::ShippingFailed(_) => CheckoutErrorKind::ShippingFailed,
+            Self::PaymentFailed(_) => CheckoutErrorKind::PaymentFailed,
+            Self::CaptchaDetectionFailed(_) => CheckoutErrorKind::CaptchaDetectionFailed,
+            Self::CaptchaSolvingFailed(_) => CheckoutErrorKind::CaptchaSolvingFailed,
+            Self::OrderSubmissionFailed(_) => CheckoutErrorKind::OrderSubmissionFailed,
+            Self::NetworkError(_) => CheckoutErrorKind::NetworkError,
+            Self::Timeout(_) => CheckoutErrorKind::Timeout,
+            Self::InvalidResponse(_) => CheckoutErrorKind::InvalidResponse,
+            Self::SessionExpired => CheckoutErrorKind::SessionExpired,
+            Self::ProductUnavailable => CheckoutErrorKind::ProductUnavailable,
+            Self::Other(_) => CheckoutErrorKind::Other,
+        }
+    }
+}
+
+/// Classify a step error: typed checkout and transport errors anywhere in the
+/// chain win, otherwise the failure is attributed to the step itself
+fn classify_error(error: &anyhow::Error, step: CheckoutErrorKind) -> CheckoutErrorKind {
+    for cause in error.chain() {
+        if let Some(checkout_error) = cause.downcast_ref::<CheckoutError>() {
+            return checkout_error.kind();
+        }
+        if let Some(reqwest_error) = cause.downcast_ref::<reqwest::Error>() {
+            return if reqwest_error.is_timeout() {
+                CheckoutErrorKind::Timeout
+            } else {
+                CheckoutErrorKind::NetworkError
+            };
+        }
+        if let Some(api_error) = cause.downcast_ref::<ApiError>() {
+            return match api_error {
+                ApiError::Timeout(_) => CheckoutErrorKind::Timeout,
+                ApiError::Connect(_)
+                | ApiError::Dns(_)
+                | ApiError::ProxyError(_)
+                | ApiError::Request(_) => CheckoutErrorKind::NetworkError,
+                ApiError::Decode(_) => CheckoutErrorKind::InvalidResponse,
+                // Retrying can't fix these, so blame the step
+                ApiError::CircuitOpen { .. }
+                | ApiError::BodyTooLarge { .. }
+                | ApiError::InvalidUrl { .. }
+                | ApiError::InvalidRequest(_)
+                | ApiError::Auth(_)
+                | ApiError::Status(_)
+                | ApiError::NoRecordedResponse { .. }
+                | ApiError::Shared(_) => step,
+            };
+        }
+    }
+    step
+}
+
 /// Result of a checkout attempt
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CheckoutResult {
     pub success: bool,
     pub order_id: Option<String>,
     pub error: Option<String>,
+    /// Kind of failure, set alongside `error`
+    #[serde(default)]
+    pub error_kind: Option<CheckoutErrorKind>,
     pub timestamp: chrono::DateTime<chrono::Utc>,
     pub duration_ms: u64,
+    /// Idempotency key sent with order submission, if submission was attempted
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
 }
 
 impl CheckoutResult {
@@ -110,8 +279,10 @@ impl CheckoutResult {
             success: true,
             order_id: Some(order_id),
             error: None,
+            error_kind: None,
             timestamp: chrono::Utc::now(),
             duration_ms,
+            idempotency_key: None,
         }
     }
 
@@ -120,8 +291,49 @@ impl CheckoutResult {
             success: false,
             order_id: None,
             error: Some(error),
+            error_kind: None,
             timestamp: chrono::Utc::now(),
             duration_ms,
+            idempotency_key: None,
+        }
+    }
+
+    pub fn with_idempotency_key(mut self, key: String) -> Self {
+        self.idempotency_key = Some(key);
+        self
+    }
+
+    pub fn with_error_kind(mut self, kind: CheckoutErrorKind) -> Self {
+        self.error_kind = Some(kind);
+        self
+    }
+
+    /// Order status recorded for this result
+    pub fn order_status(&self) -> &'static str {
+        if self.success {
+            "success"
+        } else {
+            "failed"
+        }
+    }
+
+    /// Order row for this result. Failed checkouts have no order id, so they are keyed
+    /// by their idempotency key or a generated id; the whole result goes in `metadata`.
+    pub fn to_order(&self, product: &Product, account_id: &str) -> NewOrder {
+        let order_id = self
+            .order_id
+            .clone()
+            .or_else(|| self.idempotency_key.clone())
+            .unwrap_or_else(|| format!("failed-{}", uuid::Uuid::new_v4()));
+
+        NewOrder {
+            order_id,
+            product_id: product.id.clone(),
+            account_id: account_id.to_string(),
+            status: self.order_status().to_string(),
+            price: product.price.unwrap_or_default(),
+            quantity: product.quantity as i32,
+            metadata: serde_json::to_string(self).ok(),
         }
     }
 }
@@ -137,6 +349,86 @@ pub struct CheckoutConfig {
     pub max_delay_ms: u64,
     pub backoff_multiplier: f64,
     pub captcha_timeout_secs: u64,
+    /// Retries shared by all steps of one checkout; per-step limits still cap each step
+    pub total_retries: u32,
+    /// Base URL that cart and checkout endpoints are built from
+    pub base_url: String,
+    /// Latency added to each step's measured duration, for timing tests
+    pub simulated_latency: Option<SimulatedLatency>,
+}
+
+/// Steps of a checkout, in the order they run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CheckoutStep {
+    AddToCart,
+    CheckoutUrl,
+    Shipping,
+    Payment,
+    Captcha,
+    SubmitOrder,
+}
+
+/// Artificial per-step latency in milliseconds. It is added to
+/// `CheckoutResult::duration_ms` without sleeping, so timing tests stay fast and
+/// deterministic.
+#[derive(Debug, Clone, Default)]
+pub struct SimulatedLatency {
+    pub add_to_cart_ms: u64,
+    pub checkout_url_ms: u64,
+    pub shipping_ms: u64,
+    pub payment_ms: u64,
+    pub captcha_ms: u64,
+    pub submit_order_ms: u64,
+}
+
+impl SimulatedLatency {
+    fn for_step(&self, step: CheckoutStep) -> u64 {
+        match step {
+            CheckoutStep::AddToCart => self.add_to_cart_ms,
+            CheckoutStep::CheckoutUrl => self.checkout_url_ms,
+            CheckoutStep::Shipping => self.shipping_ms,
+            CheckoutStep::Payment => self.payment_ms,
+            CheckoutStep::Captcha => self.captcha_ms,
+            CheckoutStep::SubmitOrder => self.submit_order_ms,
+        }
+    }
+}
+
+/// Measures how long a checkout takes, including any simulated step latency
+struct CheckoutClock {
+    start: std::time::Instant,
+    simulated_ms: u64,
+}
+
+impl CheckoutClock {
+    fn start() -> Self {
+        Self {
+            start: std::time::Instant::now(),
+            simulated_ms: 0,
+        }
+    }
+
+    fn elapsed_ms(&self) -> u64 {
+        self.start.elapsed().as_millis() as u64 + self.simulated_ms
+    }
+
+    /// Record the simulated latency configured for `step`
+    fn step(&mut self, config: &CheckoutConfig, step: CheckoutStep) {
+        if let Some(latency) = &config.simulated_latency {
+            self.simulated_ms += latency.for_step(step);
+        }
+    }
+}
+
+/// Next backoff delay after `current`, capped at `max_delay_ms`
+fn next_delay(current: u64, config: &CheckoutConfig) -> u64 {
+    ((current as f64 * config.backoff_multiplier) as u64).min(config.max_delay_ms)
+}
+
+/// Randomize `delay` to somewhere in `[delay / 2, delay]` so concurrent retries spread out
+fn jittered(delay: u64) -> u64 {
+    let half = delay / 2;
+    half + rand::thread_rng().gen_range(0..=delay - half)
 }
 
 impl Default for CheckoutConfig {
@@ -150,10 +442,25 @@ impl Default for CheckoutConfig {
             max_delay_ms: 10000,
             backoff_multiplier: 2.0,
             captcha_timeout_secs: 120,
+            total_retries: 5,
+            base_url: DEFAULT_BASE_URL.to_string(),
+            simulated_latency: None,
         }
     }
 }
 
+/// Default Lazada API base URL
+pub const DEFAULT_BASE_URL: &str = "https://api.lazada.com";
+
+/// Header carrying the idempotency key on order submission
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// reCAPTCHA v3 action used when the captcha check does not name one
+const DEFAULT_RECAPTCHA_V3_ACTION: &str = "verify";
+
+/// reCAPTCHA v3 minimum score used when the captcha check does not give one
+const DEFAULT_RECAPTCHA_V3_MIN_SCORE: f32 = 0.3;
+
 /// Response from add-to-cart API
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct AddToCartResponse {
@@ -176,6 +483,10 @@ struct CaptchaDetectionResponse {
     captcha_type: Option<String>,
     site_key: Option<String>,
     page_url: Option<String>,
+    /// reCAPTCHA v3 action name
+    action: Option<String>,
+    /// reCAPTCHA v3 minimum score
+    min_score: Option<f32>,
 }
 
 /// Response from order submission
@@ -186,11 +497,35 @@ struct OrderSubmissionResponse {
     error: Option<String>,
 }
 
+/// Retry budget shared by every step of a single checkout
+#[derive(Debug)]
+struct RetryBudget {
+    remaining: AtomicU32,
+}
+
+impl RetryBudget {
+    fn new(total: u32) -> Self {
+        Self {
+            remaining: AtomicU32::new(total),
+        }
+    }
+
+    /// Take one retry from the budget, returning false once it is exhausted
+    fn try_take(&self) -> bool {
+        self.remaining
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+            .is_ok()
+    }
+}
+
 /// Checkout engine for instant checkout functionality
 pub struct CheckoutEngine {
     api_client: Arc<ApiClient>,
     captcha_solver: Arc<dyn CaptchaSolverTrait + Send + Sync>,
     config: CheckoutConfig,
+    notifier: Option<Arc<dyn Notifier>>,
+    metrics: Option<MetricsCollector>,
+    order_store: Option<Arc<Database>>,
 }
 
 impl CheckoutEngine {
@@ -203,6 +538,9 @@ impl CheckoutEngine {
             api_client,
             captcha_solver,
             config: CheckoutConfig::default(),
+            notifier: None,
+            metrics: None,
+            order_store: None,
         }
     }
 
@@ -216,9 +554,46 @@ impl CheckoutEngine {
             api_client,
             captcha_solver,
             config,
+            notifier: None,
+            metrics: None,
+            order_store: None,
+        }
+    }
+
+    /// Send checkout results to a notifier
+    pub fn with_notifier(mut self, notifier: Arc<dyn Notifier>) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    /// Record every checkout result in the `orders` table of `db`
+    pub fn with_order_store(mut self, db: Arc<Database>) -> Self {
+        self.order_store = Some(db);
+        self
+    }
+
+    /// Report captcha solver usage to a metrics collector after each solve
+    pub fn with_metrics(mut self, metrics: MetricsCollector) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Push the solver's usage totals to metrics, if configured
+    fn record_captcha_usage(&self) {
+        if let Some(metrics) = &self.metrics {
+            metrics.set_captcha_usage(self.captcha_solver.usage());
         }
     }
 
+    /// Build an API endpoint URL from the configured base URL
+    fn endpoint(&self, path: &str) -> String {
+        format!(
+            "{}/{}",
+            self.config.base_url.trim_end_matches('/'),
+            path.trim_start_matches('/')
+        )
+    }
+
     /// Perform instant checkout
     pub async fn instant_checkout(
         &self,
@@ -226,7 +601,65 @@ impl CheckoutEngine {
         account: &Account,
         session: &Session,
     ) -> Result<CheckoutResult> {
-        let start_time = std::time::Instant::now();
+        let result = self.run_checkout(product, account, session).await?;
+        self.persist_result(product, account, &result);
+        self.notify_result(product, account, &result).await;
+        Ok(result)
+    }
+
+    /// Save a finished checkout to the order store, if configured. Failures are logged.
+    fn persist_result(&self, product: &Product, account: &Account, result: &CheckoutResult) {
+        let Some(db) = &self.order_store else {
+            return;
+        };
+
+        if let Err(e) = db.upsert_order(&result.to_order(product, &account.id)) {
+            error!("Failed to persist checkout result: {}", e);
+        }
+    }
+
+    /// Send a notification for a finished checkout. Failures are logged, not returned.
+    async fn notify_result(&self, product: &Product, account: &Account, result: &CheckoutResult) {
+        let Some(notifier) = &self.notifier else {
+            return;
+        };
+
+        let event = match &result.order_id {
+            Some(order_id) if result.success => NotificationEvent::CheckoutSucceeded {
+                order_id: order_id.clone(),
+                product_id: product.id.clone(),
+                product_name: product.name.clone(),
+                account_id: account.id.clone(),
+                timestamp: result.timestamp,
+            },
+            _ => NotificationEvent::CheckoutFailed {
+                error: result
+                    .error
+                    .clone()
+                    .unwrap_or_else(|| "Unknown error".to_string()),
+                product_id: product.id.clone(),
+                product_name: product.name.clone(),
+                account_id: account.id.clone(),
+                timestamp: result.timestamp,
+            },
+        };
+
+        if let Err(e) = notifier.notify(event).await {
+            warn!("Failed to send checkout notification: {}", e);
+        }
+    }
+
+    /// Run the checkout steps for a single account
+    async fn run_checkout(
+        &self,
+        product: &Product,
+        account: &Account,
+        session: &Session,
+    ) -> Result<CheckoutResult> {
+        let mut clock = CheckoutClock::start();
+        let mut perf = PerformanceMonitor::new("checkout");
+        let budget = RetryBudget::new(self.config.total_retries);
+        perf.enter("checkout");
         info!(
             "Starting instant checkout for product: {} ({})",
             product.name, product.id
@@ -236,96 +669,200 @@ impl CheckoutEngine {
         if !session.is_valid {
             error!("Session is not valid");
             return Ok(CheckoutResult::failure(
-                "Session expired".to_string(),
-                start_time.elapsed().as_millis() as u64,
-            ));
+                CheckoutError::SessionExpired.to_string(),
+                clock.elapsed_ms(),
+            )
+            .with_error_kind(CheckoutErrorKind::SessionExpired));
         }
 
         // Step 1: Add to cart with retries
-        let cart_id = match self.add_to_cart_with_retry(product, session).await {
+        perf.enter("add_to_cart");
+        clock.step(&self.config, CheckoutStep::AddToCart);
+        let cart_id = match self.add_to_cart_with_retry(product, session, &budget).await {
             Ok(id) => id,
             Err(e) => {
                 error!("Failed to add product to cart: {}", e);
                 return Ok(CheckoutResult::failure(
-                    format!("Add to cart failed: {}", e),
-                    start_time.elapsed().as_millis() as u64,
-                ));
+                    format!("Add to cart failed: {:#}", e),
+                    clock.elapsed_ms(),
+                )
+                .with_error_kind(classify_error(&e, CheckoutErrorKind::AddToCartFailed)));
             }
         };
 
+        perf.exit();
+
         // Step 2: Get checkout URL
-        let checkout_url = match self.get_checkout_url_with_retry(&cart_id, session).await {
+        perf.enter("get_checkout_url");
+        clock.step(&self.config, CheckoutStep::CheckoutUrl);
+        let checkout_url = match self
+            .get_checkout_url_with_retry(&cart_id, session, &budget)
+            .await
+        {
             Ok(url) => url,
             Err(e) => {
                 error!("Failed to get checkout URL: {}", e);
                 return Ok(CheckoutResult::failure(
-                    format!("Get checkout URL failed: {}", e),
-                    start_time.elapsed().as_millis() as u64,
-                ));
+                    format!("Get checkout URL failed: {:#}", e),
+                    clock.elapsed_ms(),
+                )
+                .with_error_kind(classify_error(&e, CheckoutErrorKind::CheckoutUrlFailed)));
             }
         };
 
+        perf.exit();
+
         // Step 3: Fill shipping information
+        perf.enter("shipping");
+        clock.step(&self.config, CheckoutStep::Shipping);
         if let Err(e) = self
             .fill_shipping_info(&checkout_url, &account.settings, session)
             .await
         {
             error!("Failed to fill shipping info: {}", e);
             return Ok(CheckoutResult::failure(
-                format!("Shipping info failed: {}", e),
-                start_time.elapsed().as_millis() as u64,
-            ));
+                format!("Shipping info failed: {:#}", e),
+                clock.elapsed_ms(),
+            )
+            .with_error_kind(classify_error(&e, CheckoutErrorKind::ShippingFailed)));
         }
 
+        perf.exit();
+
         // Step 4: Select payment method
+        perf.enter("payment");
+        clock.step(&self.config, CheckoutStep::Payment);
         if let Err(e) = self
             .select_payment_method(&checkout_url, &account.settings, session)
             .await
         {
             error!("Failed to select payment method: {}", e);
             return Ok(CheckoutResult::failure(
-                format!("Payment selection failed: {}", e),
-                start_time.elapsed().as_millis() as u64,
-            ));
+                format!("Payment selection failed: {:#}", e),
+                clock.elapsed_ms(),
+            )
+            .with_error_kind(classify_error(&e, CheckoutErrorKind::PaymentFailed)));
         }
 
+        perf.exit();
+
         // Step 5: Detect and solve captcha if present
+        perf.enter("captcha");
+        clock.step(&self.config, CheckoutStep::Captcha);
         let captcha_token = match self.detect_and_solve_captcha(&checkout_url, session).await {
             Ok(token) => token,
             Err(e) => {
                 error!("Failed to handle captcha: {}", e);
                 return Ok(CheckoutResult::failure(
-                    format!("Captcha handling failed: {}", e),
-                    start_time.elapsed().as_millis() as u64,
-                ));
+                    format!("Captcha handling failed: {:#}", e),
+                    clock.elapsed_ms(),
+                )
+                .with_error_kind(classify_error(&e, CheckoutErrorKind::CaptchaSolvingFailed)));
             }
         };
 
-        // Step 6: Submit order with retries
+        perf.exit();
+
+        // Step 6: Submit order with retries, reusing one idempotency key so the
+        // server can dedupe retried submissions
+        perf.enter("submit_order");
+        clock.step(&self.config, CheckoutStep::SubmitOrder);
+        let idempotency_key = uuid::Uuid::new_v4().to_string();
         let order_id = match self
-            .submit_order_with_retry(&checkout_url, captcha_token.as_deref(), session)
+            .submit_order_with_retry(
+                &checkout_url,
+                captcha_token.as_deref(),
+                session,
+                &idempotency_key,
+                &budget,
+            )
             .await
         {
             Ok(id) => id,
             Err(e) => {
                 error!("Failed to submit order: {}", e);
                 return Ok(CheckoutResult::failure(
-                    format!("Order submission failed: {}", e),
-                    start_time.elapsed().as_millis() as u64,
-                ));
+                    format!("Order submission failed: {:#}", e),
+                    clock.elapsed_ms(),
+                )
+                .with_error_kind(classify_error(&e, CheckoutErrorKind::OrderSubmissionFailed))
+                .with_idempotency_key(idempotency_key));
             }
         };
 
-        let duration_ms = start_time.elapsed().as_millis() as u64;
+        perf.exit();
+        perf.exit();
+        debug!("Checkout step timings:\n{}", perf.report_tree());
+
+        let duration_ms = clock.elapsed_ms();
         info!(
             "Checkout completed successfully! Order ID: {} (took {}ms)",
             order_id, duration_ms
         );
-        Ok(CheckoutResult::success(order_id, duration_ms))
+        Ok(CheckoutResult::success(order_id, duration_ms).with_idempotency_key(idempotency_key))
+    }
+
+    /// Run checkout for the same product on every account concurrently.
+    ///
+    /// Returns the first successful result, dropping (and thereby cancelling)
+    /// the remaining attempts, or the last failure if every attempt fails.
+    /// Accounts and sessions are paired by position.
+    pub async fn race_checkout(
+        &self,
+        product: &Product,
+        accounts: &[Account],
+        sessions: &[Session],
+    ) -> CheckoutResult {
+        if accounts.len() != sessions.len() {
+            warn!(
+                "race_checkout got {} accounts but {} sessions, extra entries are ignored",
+                accounts.len(),
+                sessions.len()
+            );
+        }
+
+        info!(
+            "Racing checkout for product {} across {} accounts",
+            product.id,
+            accounts.len().min(sessions.len())
+        );
+
+        let attempts = accounts
+            .iter()
+            .zip(sessions)
+            .map(|(account, session)| async move {
+                let start_time = std::time::Instant::now();
+                let result = match self.instant_checkout(product, account, session).await {
+                    Ok(result) => result,
+                    Err(e) => CheckoutResult::failure(
+                        e.to_string(),
+                        start_time.elapsed().as_millis() as u64,
+                    )
+                    .with_error_kind(classify_error(&e, CheckoutErrorKind::Other)),
+                };
+
+                if result.success {
+                    info!("Account {} won the checkout race", account.id);
+                } else {
+                    debug!(
+                        "Account {} checkout failed: {}",
+                        account.id,
+                        result.error.as_deref().unwrap_or("unknown error")
+                    );
+                }
+                result
+            });
+
+        race_first_success(attempts).await
     }
 
     /// Add product to cart with retry logic
-    async fn add_to_cart_with_retry(&self, product: &Product, session: &Session) -> Result<String> {
+    async fn add_to_cart_with_retry(
+        &self,
+        product: &Product,
+        session: &Session,
+        budget: &RetryBudget,
+    ) -> Result<String> {
         let mut delay = self.config.base_delay_ms;
 
         for attempt in 0..self.config.add_to_cart_retries {
@@ -343,14 +880,19 @@ impl CheckoutEngine {
                 Err(e) => {
                     warn!("Add to cart attempt {} failed: {}", attempt + 1, e);
 
-                    if attempt < self.config.add_to_cart_retries - 1 {
-                        debug!("Waiting {}ms before retry", delay);
-                        sleep(Duration::from_millis(delay)).await;
-                        delay = std::cmp::min(
-                            (delay as f64 * self.config.backoff_multiplier) as u64,
-                            self.config.max_delay_ms,
-                        );
+                    if attempt + 1 >= self.config.add_to_cart_retries {
+                        return Err(e.context(format!(
+                            "Failed to add to cart after {} retries",
+                            self.config.add_to_cart_retries
+                        )));
+                    }
+                    if !budget.try_take() {
+                        return Err(e.context("Retry budget exhausted during add to cart"));
                     }
+                    let wait = jittered(delay);
+                    debug!("Waiting {}ms before retry", wait);
+                    sleep(Duration::from_millis(wait)).await;
+                    delay = next_delay(delay, &self.config);
                 }
             }
         }
@@ -365,7 +907,7 @@ impl CheckoutEngine {
     async fn add_to_cart(&self, product: &Product, session: &Session) -> Result<String> {
         debug!("Adding product {} to cart", product.id);
 
-        let url = format!("https://api.lazada.com/cart/add");
+        let url = self.endpoint("cart/add");
         let body = serde_json::json!({
             "product_id": product.id,
             "quantity": product.quantity,
@@ -384,6 +926,9 @@ impl CheckoutEngine {
             .await
             .context("Failed to send add-to-cart request")?;
 
+        if response.status == 404 {
+            return Err(CheckoutError::ProductUnavailable.into());
+        }
         if response.status != 200 {
             return Err(anyhow!(
                 "Add to cart failed with status {}",
@@ -391,7 +936,8 @@ impl CheckoutEngine {
             ));
         }
 
-        let cart_response: AddToCartResponse = serde_json::from_slice(&response.body)
+        let cart_response: AddToCartResponse = response
+            .json()
             .context("Failed to parse add-to-cart response")?;
 
         if !cart_response.success {
@@ -413,6 +959,7 @@ impl CheckoutEngine {
         &self,
         cart_id: &str,
         session: &Session,
+        budget: &RetryBudget,
     ) -> Result<String> {
         let mut delay = self.config.base_delay_ms;
 
@@ -431,14 +978,19 @@ impl CheckoutEngine {
                 Err(e) => {
                     warn!("Get checkout URL attempt {} failed: {}", attempt + 1, e);
 
-                    if attempt < self.config.checkout_url_retries - 1 {
-                        debug!("Waiting {}ms before retry", delay);
-                        sleep(Duration::from_millis(delay)).await;
-                        delay = std::cmp::min(
-                            (delay as f64 * self.config.backoff_multiplier) as u64,
-                            self.config.max_delay_ms,
-                        );
+                    if attempt + 1 >= self.config.checkout_url_retries {
+                        return Err(e.context(format!(
+                            "Failed to get checkout URL after {} retries",
+                            self.config.checkout_url_retries
+                        )));
+                    }
+                    if !budget.try_take() {
+                        return Err(e.context("Retry budget exhausted during get checkout URL"));
                     }
+                    let wait = jittered(delay);
+                    debug!("Waiting {}ms before retry", wait);
+                    sleep(Duration::from_millis(wait)).await;
+                    delay = next_delay(delay, &self.config);
                 }
             }
         }
@@ -453,7 +1005,7 @@ impl CheckoutEngine {
     async fn get_checkout_url(&self, cart_id: &str, _session: &Session) -> Result<String> {
         debug!("Getting checkout URL for cart {}", cart_id);
 
-        let url = format!("https://api.lazada.com/cart/{}/checkout", cart_id);
+        let url = self.endpoint(&format!("cart/{}/checkout", cart_id));
 
         let response = self
             .api_client
@@ -468,7 +1020,8 @@ impl CheckoutEngine {
             ));
         }
 
-        let checkout_response: CheckoutUrlResponse = serde_json::from_slice(&response.body)
+        let checkout_response: CheckoutUrlResponse = response
+            .json()
             .context("Failed to parse checkout URL response")?;
 
         checkout_response
@@ -575,7 +1128,8 @@ impl CheckoutEngine {
             ));
         }
 
-        let captcha_detection: CaptchaDetectionResponse = serde_json::from_slice(&response.body)
+        let captcha_detection: CaptchaDetectionResponse = response
+            .json()
             .context("Failed to parse captcha detection response")?;
 
         if !captcha_detection.has_captcha {
@@ -585,9 +1139,20 @@ impl CheckoutEngine {
 
         info!("Captcha detected, solving...");
 
+        let captcha_type: CaptchaType = captcha_detection
+            .captcha_type
+            .as_deref()
+            .ok_or_else(|| {
+                CheckoutError::CaptchaDetectionFailed("captcha type not provided".to_string())
+            })?
+            .parse()
+            .map_err(|e: UnknownCaptchaType| {
+                CheckoutError::CaptchaDetectionFailed(format!("unknown captcha type '{}'", e.0))
+            })?;
+
         // Solve captcha based on type
-        let captcha_token = match captcha_detection.captcha_type.as_deref() {
-            Some("recaptcha_v2") => {
+        let captcha_token = match captcha_type {
+            CaptchaType::ReCaptchaV2 => {
                 let site_key = captcha_detection
                     .site_key
                     .ok_or_else(|| anyhow!("Site key not provided for reCAPTCHA"))?;
@@ -595,19 +1160,46 @@ impl CheckoutEngine {
                     .page_url
                     .unwrap_or_else(|| checkout_url.to_string());
 
-                self.captcha_solver
+                let token = self
+                    .captcha_solver
                     .solve_recaptcha(&site_key, &page_url)
-                    .await
-                    .context("Failed to solve reCAPTCHA")?
+                    .await;
+                self.record_captcha_usage();
+                token.context("Failed to solve reCAPTCHA")?
             }
-            Some("image") => {
+            CaptchaType::Image => {
                 // For image captcha, we'd need to fetch the image first
                 // This is a simplified version
                 warn!("Image captcha detected but not fully implemented");
                 return Err(anyhow!("Image captcha handling not fully implemented"));
             }
-            _ => {
-                return Err(anyhow!("Unknown captcha type"));
+            CaptchaType::ReCaptchaV3 => {
+                let site_key = captcha_detection
+                    .site_key
+                    .ok_or_else(|| anyhow!("Site key not provided for reCAPTCHA"))?;
+                let page_url = captcha_detection
+                    .page_url
+                    .unwrap_or_else(|| checkout_url.to_string());
+                let action = captcha_detection
+                    .action
+                    .unwrap_or_else(|| DEFAULT_RECAPTCHA_V3_ACTION.to_string());
+                let min_score = captcha_detection
+                    .min_score
+                    .unwrap_or(DEFAULT_RECAPTCHA_V3_MIN_SCORE);
+
+                let token = self
+                    .captcha_solver
+                    .solve_recaptcha_v3(&site_key, &page_url, &action, min_score)
+                    .await;
+                self.record_captcha_usage();
+                token.context("Failed to solve reCAPTCHA v3")?
+            }
+            CaptchaType::HCaptcha => {
+                return Err(CheckoutError::CaptchaSolvingFailed(format!(
+                    "{:?} solving is not supported",
+                    captcha_type
+                ))
+                .into());
             }
         };
 
@@ -621,6 +1213,8 @@ impl CheckoutEngine {
         checkout_url: &str,
         captcha_token: Option<&str>,
         session: &Session,
+        idempotency_key: &str,
+        budget: &RetryBudget,
     ) -> Result<String> {
         let mut delay = self.config.base_delay_ms;
 
@@ -632,7 +1226,7 @@ impl CheckoutEngine {
             );
 
             match self
-                .submit_order(checkout_url, captcha_token, session)
+                .submit_order(checkout_url, captcha_token, session, idempotency_key)
                 .await
             {
                 Ok(order_id) => {
@@ -642,14 +1236,19 @@ impl CheckoutEngine {
                 Err(e) => {
                     warn!("Submit order attempt {} failed: {}", attempt + 1, e);
 
-                    if attempt < self.config.submission_retries - 1 {
-                        debug!("Waiting {}ms before retry", delay);
-                        sleep(Duration::from_millis(delay)).await;
-                        delay = std::cmp::min(
-                            (delay as f64 * self.config.backoff_multiplier) as u64,
-                            self.config.max_delay_ms,
-                        );
+                    if attempt + 1 >= self.config.submission_retries {
+                        return Err(e.context(format!(
+                            "Failed to submit order after {} retries",
+                            self.config.submission_retries
+                        )));
                     }
+                    if !budget.try_take() {
+                        return Err(e.context("Retry budget exhausted during submit order"));
+                    }
+                    let wait = jittered(delay);
+                    debug!("Waiting {}ms before retry", wait);
+                    sleep(Duration::from_millis(wait)).await;
+                    delay = next_delay(delay, &self.config);
                 }
             }
         }
@@ -666,8 +1265,9 @@ impl CheckoutEngine {
         checkout_url: &str,
         captcha_token: Option<&str>,
         session: &Session,
+        idempotency_key: &str,
     ) -> Result<String> {
-        debug!("Submitting order");
+        debug!("Submitting order (idempotency key {})", idempotency_key);
 
         let url = format!("{}/submit", checkout_url);
         let mut body_data = serde_json::json!({
@@ -678,12 +1278,18 @@ impl CheckoutEngine {
             body_data["captcha_token"] = serde_json::json!(token);
         }
 
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(
+            IDEMPOTENCY_KEY_HEADER,
+            idempotency_key.parse().context("Invalid idempotency key")?,
+        );
+
         let response = self
             .api_client
             .request(
                 Method::POST,
                 &url,
-                None,
+                Some(headers),
                 Some(body_data.to_string().into_bytes()),
                 None,
             )
@@ -697,9 +1303,9 @@ impl CheckoutEngine {
             ));
         }
 
-        let submission_response: OrderSubmissionResponse =
-            serde_json::from_slice(&response.body)
-                .context("Failed to parse order submission response")?;
+        let submission_response: OrderSubmissionResponse = response
+            .json()
+            .context("Failed to parse order submission response")?;
 
         if !submission_response.success {
             return Err(anyhow!(
@@ -716,12 +1322,70 @@ impl CheckoutEngine {
     }
 }
 
+/// Await checkout attempts concurrently and return the first success.
+/// Pending attempts are dropped once one succeeds.
+async fn race_first_success<I, F>(attempts: I) -> CheckoutResult
+where
+    I: IntoIterator<Item = F>,
+    F: std::future::Future<Output = CheckoutResult>,
+{
+    let start_time = std::time::Instant::now();
+    let mut pending: FuturesUnordered<F> = attempts.into_iter().collect();
+
+    if pending.is_empty() {
+        return CheckoutResult::failure("No accounts available for checkout".to_string(), 0);
+    }
+
+    let mut last_failure = None;
+    while let Some(result) = pending.next().await {
+        if result.success {
+            return result;
+        }
+        last_failure = Some(result);
+    }
+
+    last_failure.unwrap_or_else(|| {
+        CheckoutResult::failure(
+            "All checkout attempts failed".to_string(),
+            start_time.elapsed().as_millis() as u64,
+        )
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::captcha::MockCaptchaSolver;
     use crate::config::AccountSettings;
 
+    #[test]
+    fn test_classify_api_errors() {
+        let classify = |error: ApiError| {
+            classify_error(
+                &anyhow::Error::new(error).context("step failed"),
+                CheckoutErrorKind::PaymentFailed,
+            )
+        };
+
+        assert_eq!(
+            classify(ApiError::ProxyError("refused".to_string())),
+            CheckoutErrorKind::NetworkError
+        );
+        assert_eq!(
+            classify(ApiError::Decode("bad json".to_string())),
+            CheckoutErrorKind::InvalidResponse
+        );
+        for error in [
+            ApiError::Status(500),
+            ApiError::BodyTooLarge { limit: 1024 },
+            ApiError::InvalidRequest("no body".to_string()),
+        ] {
+            let kind = classify(error);
+            assert_eq!(kind, CheckoutErrorKind::PaymentFailed);
+            assert!(!kind.is_retryable());
+        }
+    }
+
     #[tokio::test]
     async fn test_checkout_result_success() {
         let result = CheckoutResult::success("ORDER123".to_string(), 5000);
@@ -763,5 +1427,154 @@ mod tests {
         assert_eq!(config.base_delay_ms, 1000);
         assert_eq!(config.max_delay_ms, 10000);
         assert_eq!(config.backoff_multiplier, 2.0);
+        assert_eq!(config.base_url, DEFAULT_BASE_URL);
+    }
+
+    #[test]
+    fn test_backoff_jitter_respects_cap() {
+        let config = CheckoutConfig::default();
+        let mut delay = config.base_delay_ms;
+        for _ in 0..10 {
+            delay = next_delay(delay, &config);
+        }
+        assert_eq!(delay, config.max_delay_ms);
+
+        for _ in 0..1000 {
+            let wait = jittered(next_delay(delay, &config));
+            assert!((config.max_delay_ms / 2..=config.max_delay_ms).contains(&wait));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_race_first_success() {
+        async fn attempt(delay_ms: u64, order_id: Option<&str>) -> CheckoutResult {
+            sleep(Duration::from_millis(delay_ms)).await;
+            match order_id {
+                Some(id) => CheckoutResult::success(id.to_string(), delay_ms),
+                None => CheckoutResult::failure("Out of stock".to_string(), delay_ms),
+            }
+        }
+
+        let start = std::time::Instant::now();
+        let result = race_first_success(vec![
+            attempt(10, None),
+            attempt(30, Some("ORDER_B")),
+            attempt(5000, Some("ORDER_C")),
+        ])
+        .await;
+
+        // The slow attempt is cancelled rather than awaited
+        assert!(result.success);
+        assert_eq!(result.order_id, Some("ORDER_B".to_string()));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_race_checkout_all_fail() {
+        let api_client = Arc::new(ApiClient::new(None).unwrap());
+        let captcha_solver = Arc::new(MockCaptchaSolver::new(
+            "mock_image_solution".to_string(),
+            "mock_recaptcha_solution".to_string(),
+        ));
+        let engine = CheckoutEngine::new(api_client, captcha_solver);
+
+        let product = Product::new(
+            "PROD123".to_string(),
+            "Test Product".to_string(),
+            "https://lazada.com/prod123".to_string(),
+        );
+        let accounts: Vec<Account> = (1..=3)
+            .map(|i| Account {
+                id: format!("ACC{}", i),
+                username: format!("user{}@example.com", i),
+                settings: AccountSettings {
+                    payment_method: "credit_card".to_string(),
+                    shipping_address: "123 Test St".to_string(),
+                    notifications: false,
+                },
+            })
+            .collect();
+        // Invalid sessions fail before any network call
+        let sessions: Vec<Session> = accounts
+            .iter()
+            .map(|account| {
+                let mut session = Session::new(
+                    format!("SESSION_{}", account.id),
+                    crate::core::Credentials::new(account.username.clone(), "pass".to_string()),
+                );
+                session.is_valid = false;
+                session
+            })
+            .collect();
+
+        let result = engine.race_checkout(&product, &accounts, &sessions).await;
+        assert!(!result.success);
+        assert_eq!(result.error, Some("Session expired".to_string()));
+
+        let result = engine.race_checkout(&product, &[], &[]).await;
+        assert!(!result.success);
+    }
+
+    #[tokio::test]
+    async fn test_submit_retries_reuse_idempotency_key() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/checkout/CART1/submit"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(2)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/checkout/CART1/submit"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "success": true,
+                "order_id": "ORDER1"
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let api_client = Arc::new(ApiClient::new(None).unwrap().with_retry_config(
+            crate::api::RetryConfig {
+                max_retries: 0,
+                ..Default::default()
+            },
+        ));
+        let captcha_solver = Arc::new(MockCaptchaSolver::new(
+            "mock_image_solution".to_string(),
+            "mock_recaptcha_solution".to_string(),
+        ));
+        let config = CheckoutConfig {
+            base_delay_ms: 1,
+            ..Default::default()
+        };
+        let engine = CheckoutEngine::with_config(api_client, captcha_solver, config);
+        let session = Session::new(
+            "SESSION1".to_string(),
+            crate::core::Credentials::new("user".to_string(), "pass".to_string()),
+        );
+
+        let order_id = engine
+            .submit_order_with_retry(
+                &format!("{}/checkout/CART1", mock_server.uri()),
+                None,
+                &session,
+                "key-123",
+                &RetryBudget::new(2),
+            )
+            .await
+            .unwrap();
+        assert_eq!(order_id, "ORDER1");
+
+        let requests = mock_server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 3);
+        for request in &requests {
+            assert_eq!(
+                request.headers.get(&IDEMPOTENCY_KEY_HEADER.into()).unwrap(),
+                "key-123"
+            );
+        }
     }
 }
@@ -0,0 +1,217 @@
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::fs::{File, OpenOptions};
+use tokio::io::{AsyncWriteExt, BufWriter};
+use tokio::sync::{mpsc, oneshot};
+use tracing::error;
+
+use crate::core::monitor::ProductAvailabilityEvent;
+
+/// How long buffered events may sit before being flushed to disk
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Appends monitor events to a file as newline-delimited JSON. Writes happen on a
+/// background task that flushes every `FLUSH_INTERVAL`; if the file is rotated away or
+/// a write fails, it is reopened at the same path.
+pub struct EventLog {
+    commands: mpsc::UnboundedSender<Command>,
+}
+
+enum Command {
+    Append(Vec<u8>),
+    Flush(oneshot::Sender<()>),
+}
+
+impl EventLog {
+    /// Open `path` for appending, creating it if needed
+    pub async fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        Self::open_with_flush_interval(path, FLUSH_INTERVAL).await
+    }
+
+    /// Open `path` for appending, flushing buffered events every `interval`
+    pub async fn open_with_flush_interval(
+        path: impl Into<PathBuf>,
+        interval: Duration,
+    ) -> Result<Self> {
+        let path = path.into();
+        let writer = open_append(&path).await?;
+        let (commands, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(
+            EventWriter {
+                path,
+                writer: Some(writer),
+            }
+            .run(receiver, interval),
+        );
+        Ok(Self { commands })
+    }
+
+    /// Queue `event` to be written as one JSON line
+    pub fn append(&self, event: &ProductAvailabilityEvent) {
+        let mut line = match serde_json::to_vec(event) {
+            Ok(line) => line,
+            Err(e) => {
+                error!("Failed to serialize monitor event: {}", e);
+                return;
+            }
+        };
+        line.push(b'\n');
+
+        if self.commands.send(Command::Append(line)).is_err() {
+            error!("Event log writer has stopped; dropping monitor event");
+        }
+    }
+
+    /// Write out every event appended so far
+    pub async fn flush(&self) {
+        let (done, flushed) = oneshot::channel();
+        if self.commands.send(Command::Flush(done)).is_ok() {
+            let _ = flushed.await;
+        }
+    }
+}
+
+/// Owns the file on the background task; flushes once more when the log is dropped
+struct EventWriter {
+    path: PathBuf,
+    writer: Option<BufWriter<File>>,
+}
+
+impl EventWriter {
+    async fn run(mut self, mut commands: mpsc::UnboundedReceiver<Command>, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                command = commands.recv() => match command {
+                    Some(Command::Append(line)) => self.append(&line).await,
+                    Some(Command::Flush(done)) => {
+                        self.flush().await;
+                        let _ = done.send(());
+                    }
+                    None => break,
+                },
+                _ = ticker.tick() => self.flush().await,
+            }
+        }
+
+        self.flush().await;
+    }
+
+    async fn append(&mut self, line: &[u8]) {
+        if let Err(e) = self.write(line).await {
+            // Retry once on a fresh handle in case the file was rotated or removed
+            self.writer = None;
+            if let Err(retry) = self.write(line).await {
+                error!(
+                    "Failed to write monitor event to {}: {} ({})",
+                    self.path.display(),
+                    retry,
+                    e
+                );
+            }
+        }
+    }
+
+    async fn write(&mut self, line: &[u8]) -> Result<()> {
+        let writer = match &mut self.writer {
+            Some(writer) => writer,
+            None => self.writer.insert(open_append(&self.path).await?),
+        };
+        writer
+            .write_all(line)
+            .await
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+
+    async fn flush(&mut self) {
+        let Some(writer) = self.writer.as_mut() else {
+            return;
+        };
+        if let Err(e) = writer.flush().await {
+            error!("Failed to flush {}: {}", self.path.display(), e);
+            self.writer = None;
+            return;
+        }
+        // Writes to a renamed file still succeed, so check the path to notice rotation
+        if !tokio::fs::try_exists(&self.path).await.unwrap_or(false) {
+            self.writer = None;
+        }
+    }
+}
+
+async fn open_append(path: &Path) -> Result<BufWriter<File>> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await
+        .with_context(|| format!("Failed to open event log {}", path.display()))?;
+    Ok(BufWriter::new(file))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::MonitorEventKind;
+
+    fn event(product_id: &str) -> ProductAvailabilityEvent {
+        ProductAvailabilityEvent {
+            kind: MonitorEventKind::AvailabilityChanged,
+            product_id: product_id.to_string(),
+            product_url: format!("https://www.lazada.test/products/{}", product_id),
+            timestamp: chrono::Utc::now(),
+            price: Some(9.5),
+            stock: Some(3),
+            is_available: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_event_log_reopens_after_rotation() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("events.ndjson");
+        let rotated = temp_dir.path().join("events.ndjson.1");
+
+        let log = EventLog::open(&path).await.unwrap();
+        log.append(&event("before"));
+        log.flush().await;
+        std::fs::rename(&path, &rotated).unwrap();
+
+        // The flush after rotation notices the file is gone and the next write recreates it
+        log.flush().await;
+        log.append(&event("after"));
+        log.flush().await;
+
+        let old = std::fs::read_to_string(&rotated).unwrap();
+        let new = std::fs::read_to_string(&path).unwrap();
+        assert!(old.contains("\"before\"") && !old.contains("\"after\""));
+        assert!(new.contains("\"after\"") && !new.contains("\"before\""));
+    }
+
+    #[tokio::test]
+    async fn test_event_log_flushes_on_a_timer() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("events.ndjson");
+
+        let log = EventLog::open_with_flush_interval(&path, Duration::from_millis(10))
+            .await
+            .unwrap();
+        log.append(&event("quiet"));
+
+        // Nothing else is written or flushed, so only the timer can get the event out
+        let written = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let content = tokio::fs::read_to_string(&path).await.unwrap();
+                if content.contains("\"quiet\"") {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await;
+        assert!(written.is_ok());
+    }
+}
@@ -1,15 +1,26 @@
+pub mod event_log;
 pub mod monitor;
+pub mod monitor_pool;
 pub mod performance;
 
-pub use monitor::{MonitorEngine, MonitorTask};
-pub use performance::PerformanceMonitor;
+pub use event_log::EventLog;
+pub use monitor::{
+    BatchMonitorTask, MonitorEngine, MonitorEventKind, MonitorTask, ProductSnapshot,
+};
+pub use monitor_pool::{MonitorPool, PoolStats};
+pub use performance::{PerfSummary, PerformanceMonitor, SpanNode};
 
 pub mod session;
 
-pub use session::{Credentials, Session, SessionManager};
+pub use session::{Credentials, SerializationFormat, Session, SessionManager};
+
+pub mod session_pool;
+
+pub use session_pool::SessionPool;
 
 pub mod checkout;
 
 pub use checkout::{
-    Account, CheckoutConfig, CheckoutEngine, CheckoutError, CheckoutResult, Product,
+    Account, CheckoutConfig, CheckoutContext, CheckoutEngine, CheckoutError, CheckoutErrorKind,
+    CheckoutResult, Product, SimulatedLatency,
 };
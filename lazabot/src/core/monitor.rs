@@ -1,5 +1,10 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE, ETAG, IF_NONE_MATCH};
 use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::mpsc;
@@ -7,13 +12,53 @@ use tokio::task::JoinHandle;
 use tokio::time::{interval, sleep};
 use tracing::{debug, error, info, warn};
 
-use crate::api::ApiClient;
+use crate::api::{ApiClient, ProxyInfo, RateLimiter};
+use crate::config::MonitoringConfig;
+use crate::core::event_log::EventLog;
+use crate::core::monitor_pool::{MonitorPool, PoolStats};
 use crate::core::PerformanceMonitor;
 use crate::proxy::ProxyManager;
+use crate::storage::Database;
+use crate::utils::MetricsCollector;
 
-/// Event emitted when a product becomes available
+/// What a monitor event reports
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MonitorEventKind {
+    /// The product became available or unavailable
+    #[default]
+    AvailabilityChanged,
+    /// The price fell well below its moving average
+    PriceDropped,
+}
+
+impl MonitorEventKind {
+    /// Name used when the kind is serialized or stored
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::AvailabilityChanged => "availability_changed",
+            Self::PriceDropped => "price_dropped",
+        }
+    }
+}
+
+impl std::str::FromStr for MonitorEventKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "availability_changed" => Ok(Self::AvailabilityChanged),
+            "price_dropped" => Ok(Self::PriceDropped),
+            other => Err(anyhow!("Unknown monitor event kind: {}", other)),
+        }
+    }
+}
+
+/// Event emitted when a product's availability changes or its price drops
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProductAvailabilityEvent {
+    #[serde(default)]
+    pub kind: MonitorEventKind,
     pub product_id: String,
     pub product_url: String,
     pub timestamp: chrono::DateTime<chrono::Utc>,
@@ -22,6 +67,75 @@ pub struct ProductAvailabilityEvent {
     pub is_available: bool,
 }
 
+/// Point-in-time view of a product's availability
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProductSnapshot {
+    pub price: Option<f64>,
+    pub stock: Option<u32>,
+    pub is_available: bool,
+    /// Exponential moving average of the prices seen so far, this one included
+    #[serde(default)]
+    pub ema_price: Option<f64>,
+}
+
+/// Default weight of the newest price in the moving average
+const DEFAULT_PRICE_SMOOTHING: f64 = 0.3;
+
+/// Exponential moving average of observed prices, flagging drops well below it
+#[derive(Debug, Clone)]
+struct PriceTracker {
+    alpha: f64,
+    /// Fraction below the average a price must fall to count as a drop
+    drop_factor: Option<f64>,
+    ema: Option<f64>,
+    below_threshold: bool,
+    drop_pending: bool,
+}
+
+impl PriceTracker {
+    fn new() -> Self {
+        Self {
+            alpha: DEFAULT_PRICE_SMOOTHING,
+            drop_factor: None,
+            ema: None,
+            below_threshold: false,
+            drop_pending: false,
+        }
+    }
+
+    /// Fold `price` into the average, flagging a drop when it first falls below the
+    /// threshold; it must recover before another drop is flagged
+    fn observe(&mut self, price: f64) {
+        // Compare against the average before this price pulls it down
+        let below_threshold = match (self.ema, self.drop_factor) {
+            (Some(ema), Some(factor)) => price < ema * (1.0 - factor),
+            _ => false,
+        };
+        if below_threshold && !self.below_threshold {
+            self.drop_pending = true;
+        }
+        self.below_threshold = below_threshold;
+
+        self.ema = Some(match self.ema {
+            Some(ema) => self.alpha * price + (1.0 - self.alpha) * ema,
+            None => price,
+        });
+    }
+
+    /// Whether a drop was flagged since the last call
+    fn take_drop(&mut self) -> bool {
+        std::mem::take(&mut self.drop_pending)
+    }
+}
+
+/// Last successful response, used for conditional requests
+#[derive(Debug, Clone)]
+struct LastResponse {
+    etag: Option<String>,
+    body_hash: u64,
+    snapshot: ProductSnapshot,
+}
+
 /// Product information for monitoring
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProductInfo {
@@ -32,23 +146,84 @@ pub struct ProductInfo {
     pub min_stock: Option<u32>,
 }
 
-/// Configuration for a monitor task
+/// Polling configuration shared by single-product and batch monitors
 #[derive(Debug, Clone)]
 pub struct MonitorConfig {
-    pub product: ProductInfo,
     pub interval_ms: u64,
     pub timeout_ms: u64,
     pub max_retries: u32,
 }
 
+impl MonitorConfig {
+    fn new(interval_ms: u64) -> Self {
+        Self {
+            interval_ms,
+            timeout_ms: 30000, // 30 seconds default timeout
+            max_retries: 3,
+        }
+    }
+}
+
+/// Where a monitor delivers its events; the engine attaches the outputs it was built with
+#[derive(Clone)]
+struct EventSink {
+    sender: mpsc::UnboundedSender<ProductAvailabilityEvent>,
+    /// Emitted events are also persisted here when set
+    store: Option<Arc<Database>>,
+    /// Emitted events are also appended here as JSON lines when set
+    log: Option<Arc<EventLog>>,
+    /// Told about every emitted event when set
+    metrics: Option<MetricsCollector>,
+}
+
+impl EventSink {
+    /// A sink whose receiver is already dropped, replaced when the monitor joins an engine
+    fn detached() -> Self {
+        let (sender, _) = mpsc::unbounded_channel();
+        Self {
+            sender,
+            store: None,
+            log: None,
+            metrics: None,
+        }
+    }
+
+    fn emit(&self, event: ProductAvailabilityEvent) {
+        if let Some(store) = &self.store {
+            persist_event(store, &event);
+        }
+        if let Some(metrics) = &self.metrics {
+            metrics.record_monitor_event(event.timestamp);
+        }
+        if let Some(log) = &self.log {
+            log.append(&event);
+        }
+        if let Err(e) = self.sender.send(event) {
+            error!("Failed to send availability event: {}", e);
+        }
+    }
+}
+
 /// Monitor task that polls a product endpoint and emits events when availability changes
 pub struct MonitorTask {
+    product: ProductInfo,
     config: MonitorConfig,
     api_client: Arc<ApiClient>,
     proxy_manager: Arc<ProxyManager>,
-    event_sender: mpsc::UnboundedSender<ProductAvailabilityEvent>,
+    events: EventSink,
     performance_monitor: PerformanceMonitor,
     is_running: Arc<tokio::sync::RwLock<bool>>,
+    last_response: Arc<Mutex<Option<LastResponse>>>,
+    /// Keep using one proxy until it becomes unhealthy or fails
+    sticky_proxy: bool,
+    current_proxy: Arc<Mutex<Option<ProxyInfo>>>,
+    price_tracker: Arc<Mutex<PriceTracker>>,
+    /// Extra headers sent with every poll
+    headers: HeaderMap,
+    /// Poll with a POST of this JSON body instead of a GET
+    post_body: Option<Vec<u8>>,
+    /// Shared across the engine's monitors to space out polls to the same host
+    rate_limiter: Option<Arc<RateLimiter>>,
 }
 
 impl MonitorTask {
@@ -69,36 +244,36 @@ impl MonitorTask {
             min_stock: None,
         };
 
-        let config = MonitorConfig {
-            product,
-            interval_ms,
-            timeout_ms: 30000, // 30 seconds default timeout
-            max_retries: 3,
-        };
-
-        let (event_sender, _) = mpsc::unbounded_channel();
         let performance_monitor = PerformanceMonitor::new(&format!("monitor_{}", product_id));
         let is_running = Arc::new(tokio::sync::RwLock::new(false));
 
         Self {
-            config,
+            product,
+            config: MonitorConfig::new(interval_ms),
             api_client,
             proxy_manager,
-            event_sender,
+            events: EventSink::detached(),
             performance_monitor,
             is_running,
+            last_response: Arc::new(Mutex::new(None)),
+            sticky_proxy: false,
+            current_proxy: Arc::new(Mutex::new(None)),
+            price_tracker: Arc::new(Mutex::new(PriceTracker::new())),
+            headers: HeaderMap::new(),
+            post_body: None,
+            rate_limiter: None,
         }
     }
 
     /// Set target price for the product
     pub fn with_target_price(mut self, price: f64) -> Self {
-        self.config.product.target_price = Some(price);
+        self.product.target_price = Some(price);
         self
     }
 
     /// Set minimum stock requirement
     pub fn with_min_stock(mut self, stock: u32) -> Self {
-        self.config.product.min_stock = Some(stock);
+        self.product.min_stock = Some(stock);
         self
     }
 
@@ -114,6 +289,44 @@ impl MonitorTask {
         self
     }
 
+    /// Send `headers` (e.g. Referer, X-Requested-With) with every poll. They take
+    /// precedence over headers the monitor or client would otherwise set.
+    pub fn with_headers(mut self, headers: HeaderMap) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// Poll by POSTing `body` as JSON instead of a GET, for availability APIs that take
+    /// the product ids (possibly a batch of SKUs) in the request body
+    pub fn with_post_body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.post_body = Some(body.into());
+        self
+    }
+
+    /// Stick to one proxy across polls, rotating only when it becomes unhealthy or fails
+    pub fn with_sticky_proxy(mut self, sticky: bool) -> Self {
+        self.sticky_proxy = sticky;
+        self
+    }
+
+    /// Weight of the newest price in the moving average, between 0 and 1
+    pub fn with_price_smoothing(self, alpha: f64) -> Self {
+        self.price_tracker.lock().alpha = alpha.clamp(f64::EPSILON, 1.0);
+        self
+    }
+
+    /// Emit a `PriceDropped` event when the price falls more than `factor` (e.g. 0.1 for
+    /// 10%) below its moving average
+    pub fn with_price_drop_factor(self, factor: f64) -> Self {
+        self.price_tracker.lock().drop_factor = Some(factor);
+        self
+    }
+
+    /// Proxy currently assigned in sticky mode
+    pub fn current_proxy(&self) -> Option<ProxyInfo> {
+        self.current_proxy.lock().clone()
+    }
+
     /// Get the event receiver for this monitor
     pub fn get_event_receiver(&self) -> mpsc::UnboundedReceiver<ProductAvailabilityEvent> {
         let (_, receiver) = mpsc::unbounded_channel();
@@ -128,7 +341,7 @@ impl MonitorTask {
 
         info!(
             "Starting monitor for product: {} ({})",
-            self.config.product.name, self.config.product.id
+            self.product.name, self.product.id
         );
 
         let mut interval_timer = interval(Duration::from_millis(self.config.interval_ms));
@@ -139,48 +352,90 @@ impl MonitorTask {
             {
                 let is_running = self.is_running.read().await;
                 if !*is_running {
-                    info!("Monitor for product {} stopped", self.config.product.id);
+                    info!("Monitor for product {} stopped", self.product.id);
                     break;
                 }
             }
 
             interval_timer.tick().await;
+            self.poll(&mut last_availability).await;
+        }
 
-            // Perform the check
-            match self.check_product_availability().await {
-                Ok(current_availability) => {
-                    // Check if availability has changed
-                    if last_availability != Some(current_availability) {
-                        let event = ProductAvailabilityEvent {
-                            product_id: self.config.product.id.clone(),
-                            product_url: self.config.product.url.clone(),
-                            timestamp: chrono::Utc::now(),
-                            price: None, // TODO: Extract from response
-                            stock: None, // TODO: Extract from response
-                            is_available: current_availability,
-                        };
-
-                        if let Err(e) = self.event_sender.send(event) {
-                            error!("Failed to send availability event: {}", e);
-                        }
-
-                        last_availability = Some(current_availability);
-                    }
-                }
-                Err(e) => {
-                    warn!(
-                        "Failed to check product availability for {}: {}",
-                        self.config.product.id, e
+        Ok(())
+    }
+
+    /// Check once and emit an event if availability differs from `last_availability`
+    pub(crate) async fn poll(&self, last_availability: &mut Option<bool>) {
+        match self.check_for_change().await {
+            Ok(None) => {
+                debug!("Product {} unchanged since last check", self.product.id);
+            }
+            Ok(Some(snapshot)) => {
+                let current_availability = snapshot.is_available;
+
+                if self.price_tracker.lock().take_drop() {
+                    info!(
+                        "Price of {} dropped to {:?} (average {:?})",
+                        self.product.id, snapshot.price, snapshot.ema_price
                     );
+                    self.emit(self.event_for(MonitorEventKind::PriceDropped, &snapshot));
+                }
+
+                // Check if availability has changed
+                if *last_availability != Some(current_availability) {
+                    self.emit(self.event_for(MonitorEventKind::AvailabilityChanged, &snapshot));
+                    *last_availability = Some(current_availability);
                 }
             }
+            Err(e) => {
+                warn!(
+                    "Failed to check product availability for {}: {}",
+                    self.product.id, e
+                );
+            }
         }
+    }
 
-        Ok(())
+    fn event_for(
+        &self,
+        kind: MonitorEventKind,
+        snapshot: &ProductSnapshot,
+    ) -> ProductAvailabilityEvent {
+        ProductAvailabilityEvent {
+            kind,
+            product_id: self.product.id.clone(),
+            product_url: self.product.url.clone(),
+            timestamp: chrono::Utc::now(),
+            price: snapshot.price,
+            stock: snapshot.stock,
+            is_available: snapshot.is_available,
+        }
+    }
+
+    fn emit(&self, event: ProductAvailabilityEvent) {
+        self.events.emit(event);
+    }
+
+    /// Time between checks
+    pub fn interval(&self) -> Duration {
+        Duration::from_millis(self.config.interval_ms)
     }
 
-    /// Check if the product is currently available
-    async fn check_product_availability(&self) -> Result<bool> {
+    /// Check the product once, with retries, and return what was found
+    pub async fn check_product_availability(&self) -> Result<ProductSnapshot> {
+        match self.check_for_change().await? {
+            Some(snapshot) => Ok(snapshot),
+            None => self
+                .last_response
+                .lock()
+                .as_ref()
+                .map(|last| last.snapshot.clone())
+                .ok_or_else(|| anyhow!("Product reported unchanged before any response")),
+        }
+    }
+
+    /// Check the product, returning `None` if it is unchanged since the last check
+    async fn check_for_change(&self) -> Result<Option<ProductSnapshot>> {
         let mut monitor = self.performance_monitor.clone();
         monitor.start();
 
@@ -191,62 +446,156 @@ impl MonitorTask {
     }
 
     /// Check product availability with retry logic
-    async fn check_with_retry(&self) -> Result<bool> {
-        let mut last_error = None;
-
-        for attempt in 0..=self.config.max_retries {
-            match self.single_check().await {
-                Ok(availability) => {
-                    debug!(
-                        "Product {} check successful (attempt {}): available={}",
-                        self.config.product.id,
-                        attempt + 1,
-                        availability
-                    );
-                    return Ok(availability);
-                }
-                Err(e) => {
-                    warn!(
-                        "Product {} check failed (attempt {}): {}",
-                        self.config.product.id,
-                        attempt + 1,
-                        e
-                    );
-                    last_error = Some(e);
-                }
-            }
-
-            if attempt < self.config.max_retries {
-                let delay = Duration::from_millis(1000 * (attempt + 1) as u64);
-                debug!("Retrying in {:?}", delay);
-                sleep(delay).await;
-            }
-        }
-
-        Err(last_error.unwrap_or_else(|| anyhow::anyhow!("All retry attempts failed")))
+    async fn check_with_retry(&self) -> Result<Option<ProductSnapshot>> {
+        let label = format!("Product {}", self.product.id);
+        retry_check(&label, self.config.max_retries, || self.single_check()).await
     }
 
     /// Perform a single availability check
-    async fn single_check(&self) -> Result<bool> {
+    async fn single_check(&self) -> Result<Option<ProductSnapshot>> {
         // Get a proxy for this request
-        let proxy = self.proxy_manager.get_next_proxy().await;
+        let proxy = self.select_proxy().await;
+
+        // GETs send the last seen ETag so the server can answer 304 Not Modified
+        let etag = self
+            .last_response
+            .lock()
+            .as_ref()
+            .and_then(|last| last.etag.clone());
+        let mut headers = self.headers.clone();
+        let method = match &self.post_body {
+            Some(_) => {
+                headers
+                    .entry(CONTENT_TYPE)
+                    .or_insert(HeaderValue::from_static("application/json"));
+                reqwest::Method::POST
+            }
+            None => {
+                if let Some(Ok(value)) = etag.as_deref().map(HeaderValue::from_str) {
+                    headers.entry(IF_NONE_MATCH).or_insert(value);
+                }
+                reqwest::Method::GET
+            }
+        };
+        let headers = (!headers.is_empty()).then_some(headers);
+
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.acquire(&host_key(&self.product.url)).await;
+        }
 
         // Make the request
-        let response = self
+        let result = self
             .api_client
             .request(
-                reqwest::Method::GET,
-                &self.config.product.url,
-                None,
-                None,
+                method,
+                &self.product.url,
+                headers,
+                self.post_body.clone(),
                 proxy,
             )
-            .await?;
+            .await;
+        let response = match result {
+            Ok(response) => response,
+            Err(e) => {
+                // Mark a failing sticky proxy unhealthy so the next attempt rotates
+                if self.sticky_proxy {
+                    let failed = self.current_proxy.lock().take();
+                    if let Some(failed) = failed {
+                        self.proxy_manager.set_proxy_health(&failed, false).await;
+                    }
+                }
+                return Err(e.into());
+            }
+        };
+
+        if response.status == 304 {
+            return Ok(None);
+        }
+
+        if response.status != 200 {
+            return self.parse_snapshot_from_response(&response).map(Some);
+        }
+
+        let mut hasher = DefaultHasher::new();
+        response.body.hash(&mut hasher);
+        let body_hash = hasher.finish();
+        let etag = response
+            .headers
+            .get(ETAG)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+
+        let mut last_response = self.last_response.lock();
+        if let Some(last) = last_response.as_mut() {
+            if last.body_hash == body_hash {
+                last.etag = etag;
+                return Ok(None);
+            }
+        }
 
         // Check if the response indicates availability
-        let is_available = self.parse_availability_from_response(&response)?;
+        let mut snapshot = self.parse_snapshot_from_response(&response)?;
+        {
+            let mut tracker = self.price_tracker.lock();
+            if let Some(price) = snapshot.price {
+                tracker.observe(price);
+            }
+            snapshot.ema_price = tracker.ema;
+        }
+        *last_response = Some(LastResponse {
+            etag,
+            body_hash,
+            snapshot: snapshot.clone(),
+        });
+
+        Ok(Some(snapshot))
+    }
+
+    /// Pick the proxy for the next request, reusing the sticky proxy while it stays healthy
+    async fn select_proxy(&self) -> Option<ProxyInfo> {
+        if !self.sticky_proxy {
+            return self.proxy_manager.get_next_proxy().await;
+        }
+
+        let current = self.current_proxy.lock().clone();
+        if let Some(proxy) = current {
+            if self.proxy_manager.is_proxy_healthy(&proxy).await
+                && !self.proxy_manager.is_drained(&proxy)
+            {
+                return Some(proxy);
+            }
+            info!(
+                "Sticky proxy {}:{} for product {} is unhealthy or drained, rotating",
+                proxy.host, proxy.port, self.product.id
+            );
+        }
 
-        Ok(is_available)
+        let next = self.proxy_manager.get_next_proxy().await;
+        *self.current_proxy.lock() = next.clone();
+        next
+    }
+
+    /// Parse a snapshot from the HTTP response.
+    /// JSON bodies are read for `price`, `stock` and `available`; other bodies
+    /// fall back to the text heuristic with no price or stock.
+    fn parse_snapshot_from_response(
+        &self,
+        response: &crate::api::ResponseBody,
+    ) -> Result<ProductSnapshot> {
+        if response.status == 200 {
+            if let Ok(serde_json::Value::Object(json)) =
+                serde_json::from_slice::<serde_json::Value>(&response.body)
+            {
+                return Ok(snapshot_from_json(&json));
+            }
+        }
+
+        Ok(ProductSnapshot {
+            price: None,
+            stock: None,
+            is_available: self.parse_availability_from_response(response)?,
+            ema_price: None,
+        })
     }
 
     /// Parse availability information from the HTTP response
@@ -286,7 +635,238 @@ impl MonitorTask {
     pub async fn stop(&self) {
         let mut is_running = self.is_running.write().await;
         *is_running = false;
-        info!("Stopping monitor for product: {}", self.config.product.id);
+        info!("Stopping monitor for product: {}", self.product.id);
+    }
+}
+
+/// Run `check` until it succeeds, at most `max_retries` extra times, backing off
+/// linearly between attempts
+async fn retry_check<T, F, Fut>(label: &str, max_retries: u32, mut check: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T>>,
+{
+    let mut last_error = None;
+
+    for attempt in 0..=max_retries {
+        match check().await {
+            Ok(result) => {
+                debug!("{} check successful (attempt {})", label, attempt + 1);
+                return Ok(result);
+            }
+            Err(e) => {
+                warn!("{} check failed (attempt {}): {}", label, attempt + 1, e);
+                last_error = Some(e);
+            }
+        }
+
+        if attempt < max_retries {
+            let delay = Duration::from_millis(1000 * (attempt + 1) as u64);
+            debug!("Retrying in {:?}", delay);
+            sleep(delay).await;
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| anyhow::anyhow!("All retry attempts failed")))
+}
+
+/// Read `price`, `stock` and `available` from a JSON product object. Without an
+/// `available` flag, a product is available when its stock is above 0; with neither,
+/// availability is unknown and the product is treated as unavailable.
+fn snapshot_from_json(json: &serde_json::Map<String, serde_json::Value>) -> ProductSnapshot {
+    let price = json.get("price").and_then(|v| v.as_f64());
+    let stock = json
+        .get("stock")
+        .and_then(|v| v.as_u64())
+        .map(|stock| stock.min(u32::MAX as u64) as u32);
+    let is_available = json
+        .get("available")
+        .and_then(|v| v.as_bool())
+        .unwrap_or_else(|| stock.is_some_and(|stock| stock > 0));
+
+    ProductSnapshot {
+        price,
+        stock,
+        is_available,
+        ema_price: None,
+    }
+}
+
+/// Host (and port) of `url`, the unit polls are rate limited by
+fn host_key(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(url) => match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => url.to_string(),
+        },
+        Err(_) => url.to_string(),
+    }
+}
+
+fn persist_event(store: &Database, event: &ProductAvailabilityEvent) {
+    if let Err(e) = store.insert_monitor_event(
+        event.kind.as_str(),
+        &event.product_id,
+        event.price,
+        event.stock,
+        event.is_available,
+        event.timestamp,
+    ) {
+        error!("Failed to persist availability event: {}", e);
+    }
+}
+
+/// Monitors many products with one request per poll. The product ids are POSTed as
+/// `{"ids": [...]}` and the response is fanned out into one event per product.
+pub struct BatchMonitorTask {
+    url: String,
+    products: Vec<ProductInfo>,
+    config: MonitorConfig,
+    api_client: Arc<ApiClient>,
+    proxy_manager: Arc<ProxyManager>,
+    events: EventSink,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    is_running: Arc<tokio::sync::RwLock<bool>>,
+}
+
+impl BatchMonitorTask {
+    /// Create a batch monitor polling `url` for `products`
+    pub fn new(
+        url: String,
+        products: Vec<ProductInfo>,
+        api_client: Arc<ApiClient>,
+        proxy_manager: Arc<ProxyManager>,
+        interval_ms: u64,
+    ) -> Self {
+        Self {
+            url,
+            products,
+            config: MonitorConfig::new(interval_ms),
+            api_client,
+            proxy_manager,
+            events: EventSink::detached(),
+            rate_limiter: None,
+            is_running: Arc::new(tokio::sync::RwLock::new(false)),
+        }
+    }
+
+    /// Set maximum retries
+    pub fn with_max_retries(mut self, retries: u32) -> Self {
+        self.config.max_retries = retries;
+        self
+    }
+
+    /// Time between checks
+    pub fn interval(&self) -> Duration {
+        Duration::from_millis(self.config.interval_ms)
+    }
+
+    /// Start the batch monitor
+    pub async fn run(&self) -> Result<()> {
+        *self.is_running.write().await = true;
+        info!(
+            "Starting batch monitor for {} products at {}",
+            self.products.len(),
+            self.url
+        );
+
+        let mut interval_timer = interval(self.interval());
+        while *self.is_running.read().await {
+            interval_timer.tick().await;
+            self.poll().await;
+        }
+
+        info!("Batch monitor for {} stopped", self.url);
+        Ok(())
+    }
+
+    /// Stop the batch monitor
+    pub async fn stop(&self) {
+        *self.is_running.write().await = false;
+    }
+
+    /// Check every product once and emit one event per product in the response
+    pub(crate) async fn poll(&self) {
+        let label = format!("Batch {}", self.url);
+        let result = retry_check(&label, self.config.max_retries, || self.check_products()).await;
+        let snapshots = match result {
+            Ok(snapshots) => snapshots,
+            Err(e) => {
+                warn!("Failed to check batch availability at {}: {}", self.url, e);
+                return;
+            }
+        };
+
+        for product in &self.products {
+            let Some(snapshot) = snapshots.get(&product.id) else {
+                debug!(
+                    "Batch response at {} has no entry for {}",
+                    self.url, product.id
+                );
+                continue;
+            };
+
+            let event = ProductAvailabilityEvent {
+                kind: MonitorEventKind::AvailabilityChanged,
+                product_id: product.id.clone(),
+                product_url: product.url.clone(),
+                timestamp: chrono::Utc::now(),
+                price: snapshot.price,
+                stock: snapshot.stock,
+                is_available: snapshot.is_available,
+            };
+            self.events.emit(event);
+        }
+    }
+
+    /// Request availability for all products, keyed by product id
+    pub async fn check_products(&self) -> Result<HashMap<String, ProductSnapshot>> {
+        let ids: Vec<&str> = self.products.iter().map(|p| p.id.as_str()).collect();
+        let body = serde_json::to_vec(&serde_json::json!({ "ids": ids }))?;
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        let proxy = self.proxy_manager.get_next_proxy().await;
+
+        if let Some(limiter) = &self.rate_limiter {
+            limiter.acquire(&host_key(&self.url)).await;
+        }
+
+        let response = self
+            .api_client
+            .request(
+                reqwest::Method::POST,
+                &self.url,
+                Some(headers),
+                Some(body),
+                proxy,
+            )
+            .await?
+            .error_for_status()?;
+
+        // Either a bare array of products or one wrapped in `items`/`products`
+        let json: serde_json::Value = response.json()?;
+        let items = match &json {
+            serde_json::Value::Array(items) => items,
+            serde_json::Value::Object(map) => map
+                .get("items")
+                .or_else(|| map.get("products"))
+                .and_then(|items| items.as_array())
+                .ok_or_else(|| anyhow!("Batch response has no items array"))?,
+            _ => return Err(anyhow!("Batch response is not a JSON array or object")),
+        };
+
+        Ok(items
+            .iter()
+            .filter_map(|item| {
+                let item = item.as_object()?;
+                let id = match item.get("id")? {
+                    serde_json::Value::String(id) => id.clone(),
+                    other => other.to_string(),
+                };
+                Some((id, snapshot_from_json(item)))
+            })
+            .collect())
     }
 }
 
@@ -295,6 +875,12 @@ pub struct MonitorEngine {
     tasks: Vec<JoinHandle<Result<()>>>,
     event_receivers: Vec<mpsc::UnboundedReceiver<ProductAvailabilityEvent>>,
     is_running: Arc<tokio::sync::RwLock<bool>>,
+    /// Shared worker pool; when set, monitors are polled by the pool instead of one task each
+    pool: Option<Arc<MonitorPool>>,
+    event_store: Option<Arc<Database>>,
+    event_log: Option<Arc<EventLog>>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    metrics: Option<MetricsCollector>,
 }
 
 impl MonitorEngine {
@@ -304,6 +890,69 @@ impl MonitorEngine {
             tasks: Vec::new(),
             event_receivers: Vec::new(),
             is_running: Arc::new(tokio::sync::RwLock::new(false)),
+            pool: None,
+            event_store: None,
+            event_log: None,
+            rate_limiter: None,
+            metrics: None,
+        }
+    }
+
+    /// Create an engine that polls through `max_concurrent_monitors` workers
+    pub fn from_config(config: &MonitoringConfig) -> Self {
+        Self::new().with_worker_pool(config.max_concurrent_monitors as usize)
+    }
+
+    /// Poll monitors with a fixed pool of `workers` instead of one task per monitor
+    pub fn with_worker_pool(mut self, workers: usize) -> Self {
+        self.pool = Some(Arc::new(MonitorPool::new(workers)));
+        self
+    }
+
+    /// Persist every availability event from monitors added afterwards to `db`
+    pub fn with_event_store(mut self, db: Arc<Database>) -> Self {
+        self.event_store = Some(db);
+        self
+    }
+
+    /// Append every event from monitors added afterwards to `log`
+    pub fn with_event_log(mut self, log: Arc<EventLog>) -> Self {
+        self.event_log = Some(log);
+        self
+    }
+
+    /// Record when monitors added afterwards emit events, for the metrics `/status` report
+    pub fn with_metrics(mut self, metrics: MetricsCollector) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Limit polls from monitors added afterwards to `per_host_rps` per host, shared
+    /// across all of them, allowing bursts of up to `burst`
+    pub fn with_host_rate_limit(mut self, per_host_rps: f64, burst: u32) -> Self {
+        self.rate_limiter = Some(Arc::new(RateLimiter::new(per_host_rps, burst)));
+        self
+    }
+
+    /// Polls per second allowed for each host, if rate limited
+    pub fn host_rps(&self) -> Option<f64> {
+        self.rate_limiter
+            .as_ref()
+            .map(|limiter| limiter.per_host_rps())
+    }
+
+    /// Worker pool activity, if running in pooled mode
+    pub fn pool_stats(&self) -> Option<PoolStats> {
+        self.pool.as_ref().map(|pool| pool.stats())
+    }
+
+    /// Sink delivering to `sender` and to the engine's configured outputs
+    fn event_sink(&self, sender: mpsc::UnboundedSender<ProductAvailabilityEvent>) -> EventSink {
+        EventSink {
+            sender,
+            store: self.event_store.clone(),
+            log: self.event_log.clone(),
+            metrics: self.metrics.clone(),
         }
     }
 
@@ -316,10 +965,16 @@ impl MonitorEngine {
 
         // Create a new monitor task with the provided sender
         let task = MonitorTask {
-            event_sender: sender,
+            events: self.event_sink(sender),
+            rate_limiter: self.rate_limiter.clone(),
             ..monitor
         };
 
+        if let Some(pool) = &self.pool {
+            pool.add(task);
+            return receiver;
+        }
+
         let _is_running = self.is_running.clone();
         let task_handle = tokio::spawn(async move { task.run().await });
 
@@ -327,20 +982,50 @@ impl MonitorEngine {
         receiver
     }
 
+    /// Add a batch monitor; it runs on its own task even in pooled mode
+    pub fn add_batch_monitor(
+        &mut self,
+        monitor: BatchMonitorTask,
+    ) -> mpsc::UnboundedReceiver<ProductAvailabilityEvent> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let task = BatchMonitorTask {
+            events: self.event_sink(sender),
+            rate_limiter: self.rate_limiter.clone(),
+            ..monitor
+        };
+
+        self.tasks
+            .push(tokio::spawn(async move { task.run().await }));
+        receiver
+    }
+
     /// Start all monitor tasks
     pub async fn start(&self) -> Result<()> {
         let mut is_running = self.is_running.write().await;
         *is_running = true;
         info!("Starting monitor engine with {} tasks", self.tasks.len());
+
+        if let Some(pool) = &self.pool {
+            pool.start();
+        }
         Ok(())
     }
 
+    /// Whether the engine has been started and not stopped
+    pub async fn is_running(&self) -> bool {
+        *self.is_running.read().await
+    }
+
     /// Stop all monitor tasks
     pub async fn stop(&self) -> Result<()> {
         let mut is_running = self.is_running.write().await;
         *is_running = false;
         info!("Stopping monitor engine");
 
+        if let Some(pool) = &self.pool {
+            pool.stop();
+        }
+
         // Wait for all tasks to complete
         for task in &self.tasks {
             if !task.is_finished() {
@@ -348,6 +1033,10 @@ impl MonitorEngine {
             }
         }
 
+        if let Some(log) = &self.event_log {
+            log.flush().await;
+        }
+
         Ok(())
     }
 }
@@ -377,10 +1066,46 @@ mod tests {
             1000,
         );
 
-        assert_eq!(monitor.config.product.id, "test-product");
+        assert_eq!(monitor.product.id, "test-product");
         assert_eq!(monitor.config.interval_ms, 1000);
     }
 
+    #[test]
+    fn test_snapshot_without_stock_signal_is_unavailable() {
+        let snapshot = |value: serde_json::Value| match value {
+            serde_json::Value::Object(json) => snapshot_from_json(&json),
+            _ => unreachable!(),
+        };
+
+        assert!(!snapshot(serde_json::json!({ "status": "sold out" })).is_available);
+        assert!(!snapshot(serde_json::json!({ "price": 9.9 })).is_available);
+        assert!(snapshot(serde_json::json!({ "stock": 2 })).is_available);
+        assert!(snapshot(serde_json::json!({ "available": true })).is_available);
+    }
+
+    #[test]
+    fn test_price_tracker_ignores_noise() {
+        let mut tracker = PriceTracker::new();
+        tracker.drop_factor = Some(0.1);
+
+        let prices = [
+            100.0, 103.0, 98.0, 101.0, 96.0, 102.0, // noise around 100
+            75.0, 74.0, 76.0, 75.0, 73.0, 75.0, // one genuine drop, then settling
+        ];
+        let drops: Vec<usize> = prices
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &price)| {
+                tracker.observe(price);
+                tracker.take_drop().then_some(i)
+            })
+            .collect();
+
+        assert_eq!(drops, vec![6]);
+        let ema = tracker.ema.unwrap();
+        assert!(ema > 75.0 && ema < 100.0, "ema {}", ema);
+    }
+
     #[tokio::test]
     async fn test_monitor_engine_creation() {
         let engine = MonitorEngine::new();
@@ -0,0 +1,160 @@
+use parking_lot::Mutex;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+use tracing::{debug, info};
+
+use crate::core::MonitorTask;
+
+/// Longest a worker sleeps before re-checking the schedule
+const MAX_IDLE_WAIT: Duration = Duration::from_millis(100);
+
+/// Counters describing pool activity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PoolStats {
+    /// Number of workers in the pool
+    pub workers: usize,
+    /// Total product checks performed
+    pub polls: u64,
+    /// Highest number of checks that were in flight at once
+    pub peak_in_flight: usize,
+}
+
+struct PooledMonitor {
+    task: MonitorTask,
+    last_availability: Mutex<Option<bool>>,
+}
+
+/// Fixed set of workers polling many monitors, soonest-due first
+pub struct MonitorPool {
+    workers: usize,
+    monitors: Mutex<Vec<Arc<PooledMonitor>>>,
+    /// Min-heap of (next check time, monitor index)
+    schedule: Mutex<BinaryHeap<Reverse<(Instant, usize)>>>,
+    polls: AtomicU64,
+    in_flight: AtomicUsize,
+    peak_in_flight: AtomicUsize,
+    handles: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl MonitorPool {
+    /// Create a pool with `workers` workers (at least one)
+    pub fn new(workers: usize) -> Self {
+        Self {
+            workers: workers.max(1),
+            monitors: Mutex::new(Vec::new()),
+            schedule: Mutex::new(BinaryHeap::new()),
+            polls: AtomicU64::new(0),
+            in_flight: AtomicUsize::new(0),
+            peak_in_flight: AtomicUsize::new(0),
+            handles: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Add a monitor, due for its first check immediately
+    pub fn add(&self, task: MonitorTask) {
+        let mut monitors = self.monitors.lock();
+        monitors.push(Arc::new(PooledMonitor {
+            task,
+            last_availability: Mutex::new(None),
+        }));
+        self.schedule
+            .lock()
+            .push(Reverse((Instant::now(), monitors.len() - 1)));
+    }
+
+    /// Number of monitors registered
+    pub fn len(&self) -> usize {
+        self.monitors.lock().len()
+    }
+
+    /// Whether no monitors are registered
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Spawn the workers
+    pub fn start(self: &Arc<Self>) {
+        let mut handles = self.handles.lock();
+        if !handles.is_empty() {
+            return;
+        }
+
+        info!(
+            "Starting monitor pool with {} workers for {} products",
+            self.workers,
+            self.len()
+        );
+        for worker_id in 0..self.workers {
+            let pool = Arc::clone(self);
+            handles.push(tokio::spawn(async move { pool.work(worker_id).await }));
+        }
+    }
+
+    /// Abort all workers
+    pub fn stop(&self) {
+        for handle in self.handles.lock().drain(..) {
+            handle.abort();
+        }
+    }
+
+    /// Current activity counters
+    pub fn stats(&self) -> PoolStats {
+        PoolStats {
+            workers: self.workers,
+            polls: self.polls.load(Ordering::SeqCst),
+            peak_in_flight: self.peak_in_flight.load(Ordering::SeqCst),
+        }
+    }
+
+    async fn work(&self, worker_id: usize) {
+        debug!("Monitor pool worker {} started", worker_id);
+
+        loop {
+            let wait = match self.next_due() {
+                Ok(index) => {
+                    self.poll(index).await;
+                    continue;
+                }
+                Err(wait) => wait,
+            };
+            sleep(wait).await;
+        }
+    }
+
+    /// Pop the next due monitor, or return how long to wait for one
+    fn next_due(&self) -> Result<usize, Duration> {
+        let mut schedule = self.schedule.lock();
+        let now = Instant::now();
+
+        match schedule.peek() {
+            Some(Reverse((due, _))) if *due <= now => {
+                let Reverse((_, index)) = schedule.pop().expect("peeked entry");
+                Ok(index)
+            }
+            Some(Reverse((due, _))) => Err((*due - now).min(MAX_IDLE_WAIT)),
+            None => Err(MAX_IDLE_WAIT),
+        }
+    }
+
+    async fn poll(&self, index: usize) {
+        let monitor = Arc::clone(&self.monitors.lock()[index]);
+
+        let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+
+        let mut last_availability = *monitor.last_availability.lock();
+        monitor.task.poll(&mut last_availability).await;
+        *monitor.last_availability.lock() = last_availability;
+
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        self.polls.fetch_add(1, Ordering::SeqCst);
+
+        let next_check = Instant::now() + monitor.task.interval();
+        self.schedule.lock().push(Reverse((next_check, index)));
+    }
+}
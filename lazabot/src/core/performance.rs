@@ -6,6 +6,38 @@ use tracing::{debug, info, warn};
 pub struct PerformanceMonitor {
     start_time: Option<Instant>,
     operation_name: String,
+    samples: Vec<Duration>,
+    /// Completed top-level spans
+    spans: Vec<SpanNode>,
+    /// Spans entered but not yet exited, innermost last
+    open_spans: Vec<OpenSpan>,
+}
+
+/// A completed named span and its nested child spans
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpanNode {
+    pub name: String,
+    pub duration: Duration,
+    pub children: Vec<SpanNode>,
+}
+
+#[derive(Debug, Clone)]
+struct OpenSpan {
+    name: String,
+    start: Instant,
+    children: Vec<SpanNode>,
+}
+
+/// Aggregate statistics over recorded durations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PerfSummary {
+    pub count: usize,
+    pub min: Duration,
+    pub max: Duration,
+    pub mean: Duration,
+    pub p50: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
 }
 
 impl PerformanceMonitor {
@@ -14,6 +46,9 @@ impl PerformanceMonitor {
         Self {
             start_time: None,
             operation_name: operation_name.to_string(),
+            samples: Vec::new(),
+            spans: Vec::new(),
+            open_spans: Vec::new(),
         }
     }
 
@@ -57,6 +92,98 @@ impl PerformanceMonitor {
         self.start_time = None;
         debug!("Reset timer for operation: {}", self.operation_name);
     }
+
+    /// Record a duration sample for aggregate statistics
+    pub fn record(&mut self, duration: Duration) {
+        self.samples.push(duration);
+    }
+
+    /// Summarize recorded samples. Percentiles use the nearest-rank method.
+    pub fn summary(&self) -> PerfSummary {
+        if self.samples.is_empty() {
+            return PerfSummary::default();
+        }
+
+        let mut sorted = self.samples.clone();
+        sorted.sort_unstable();
+
+        let count = sorted.len();
+        let total: Duration = sorted.iter().sum();
+        let percentile = |p: f64| {
+            let rank = ((p / 100.0) * count as f64).ceil() as usize;
+            sorted[rank.clamp(1, count) - 1]
+        };
+
+        PerfSummary {
+            count,
+            min: sorted[0],
+            max: sorted[count - 1],
+            mean: total / count as u32,
+            p50: percentile(50.0),
+            p95: percentile(95.0),
+            p99: percentile(99.0),
+        }
+    }
+
+    /// Enter a named span nested inside the currently open span, if any
+    pub fn enter(&mut self, name: &str) {
+        self.open_spans.push(OpenSpan {
+            name: name.to_string(),
+            start: Instant::now(),
+            children: Vec::new(),
+        });
+    }
+
+    /// Exit the innermost open span, returning its duration
+    pub fn exit(&mut self) -> Option<Duration> {
+        let Some(span) = self.open_spans.pop() else {
+            warn!(
+                "Attempted to exit a span for '{}' but none was entered",
+                self.operation_name
+            );
+            return None;
+        };
+
+        let node = SpanNode {
+            name: span.name,
+            duration: span.start.elapsed(),
+            children: span.children,
+        };
+        let duration = node.duration;
+
+        match self.open_spans.last_mut() {
+            Some(parent) => parent.children.push(node),
+            None => self.spans.push(node),
+        }
+
+        Some(duration)
+    }
+
+    /// Completed top-level spans
+    pub fn span_tree(&self) -> &[SpanNode] {
+        &self.spans
+    }
+
+    /// Indented breakdown of completed spans, one line per span
+    pub fn report_tree(&self) -> String {
+        fn write_node(node: &SpanNode, depth: usize, out: &mut String) {
+            out.push_str(&format!(
+                "{}{} {:?}\n",
+                "  ".repeat(depth),
+                node.name,
+                node.duration
+            ));
+            for child in &node.children {
+                write_node(child, depth + 1, out);
+            }
+        }
+
+        let mut out = String::new();
+        for node in &self.spans {
+            write_node(node, 0, &mut out);
+        }
+        out
+    }
 }
 
 /// Macro for easy performance monitoring
@@ -101,6 +228,63 @@ mod tests {
         monitor.end();
     }
 
+    #[test]
+    fn test_summary_percentiles() {
+        let mut monitor = PerformanceMonitor::new("test_summary");
+        assert_eq!(monitor.summary(), PerfSummary::default());
+
+        // Record 100ms down to 1ms so ordering is exercised
+        for ms in (1..=100).rev() {
+            monitor.record(Duration::from_millis(ms));
+        }
+
+        let summary = monitor.summary();
+        assert_eq!(summary.count, 100);
+        assert_eq!(summary.min, Duration::from_millis(1));
+        assert_eq!(summary.max, Duration::from_millis(100));
+        assert_eq!(summary.mean, Duration::from_micros(50_500));
+        assert_eq!(summary.p50, Duration::from_millis(50));
+        assert_eq!(summary.p95, Duration::from_millis(95));
+        assert_eq!(summary.p99, Duration::from_millis(99));
+    }
+
+    #[test]
+    fn test_nested_spans() {
+        let mut monitor = PerformanceMonitor::new("test_spans");
+
+        monitor.enter("checkout");
+        monitor.enter("add_to_cart");
+        thread::sleep(Duration::from_millis(5));
+        monitor.exit();
+        monitor.enter("payment");
+        monitor.enter("captcha");
+        thread::sleep(Duration::from_millis(5));
+        monitor.exit();
+        monitor.exit();
+        monitor.exit();
+        assert!(monitor.exit().is_none());
+
+        let tree = monitor.span_tree();
+        assert_eq!(tree.len(), 1);
+        let root = &tree[0];
+        assert_eq!(root.name, "checkout");
+        let names: Vec<&str> = root.children.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["add_to_cart", "payment"]);
+        assert_eq!(root.children[1].children[0].name, "captcha");
+
+        let child_total: Duration = root.children.iter().map(|c| c.duration).sum();
+        assert!(child_total <= root.duration);
+        assert!(root.children[1].children[0].duration <= root.children[1].duration);
+
+        let report = monitor.report_tree();
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("checkout "));
+        assert!(lines[1].starts_with("  add_to_cart "));
+        assert!(lines[2].starts_with("  payment "));
+        assert!(lines[3].starts_with("    captcha "));
+    }
+
     #[test]
     fn test_reset() {
         let mut monitor = PerformanceMonitor::new("test_reset");
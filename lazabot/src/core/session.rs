@@ -1,4 +1,5 @@
 use anyhow::{Context, Result};
+use futures::stream::{self, StreamExt};
 use reqwest::cookie::Jar;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -7,7 +8,7 @@ use std::sync::Arc;
 use tokio::fs;
 use tracing::{debug, error, info, warn};
 
-use crate::api::ApiClient;
+use crate::api::{ApiClient, ProxyInfo};
 
 /// Session credentials for authentication
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,6 +43,14 @@ pub struct Session {
     pub last_used: chrono::DateTime<chrono::Utc>,
     pub is_valid: bool,
     pub metadata: HashMap<String, serde_json::Value>,
+    /// Proxy (`host:port`) the session logged in through, if any
+    #[serde(default)]
+    pub proxy: Option<String>,
+    /// Domain-scoped cookie values, by domain and then cookie name, so cookies sharing
+    /// a name on different domains are kept apart. A leading `.` means the cookie also
+    /// applies to subdomains; cookies only in `cookies` use the validation URL.
+    #[serde(default)]
+    pub domain_cookies: HashMap<String, HashMap<String, String>>,
 }
 
 impl Session {
@@ -55,6 +64,8 @@ impl Session {
             last_used: now,
             is_valid: true,
             metadata: HashMap::new(),
+            proxy: None,
+            domain_cookies: HashMap::new(),
         }
     }
 
@@ -67,16 +78,161 @@ impl Session {
         self.update_last_used();
     }
 
+    /// Add a cookie scoped to `domain`; a leading `.` covers its subdomains too
+    pub fn add_cookie_for_domain(&mut self, name: String, value: String, domain: String) {
+        self.domain_cookies
+            .entry(domain)
+            .or_default()
+            .insert(name.clone(), value.clone());
+        self.add_cookie(name, value);
+    }
+
     pub fn add_metadata(&mut self, key: String, value: serde_json::Value) {
         self.metadata.insert(key, value);
     }
+
+    /// Whether using the session through `proxy` differs from the proxy it logged in with
+    pub fn is_proxy_mismatch(&self, proxy: Option<&ProxyInfo>) -> bool {
+        self.proxy != proxy.map(proxy_id)
+    }
+}
+
+/// Identifies a proxy by address, leaving out credentials
+fn proxy_id(proxy: &ProxyInfo) -> String {
+    format!("{}:{}", proxy.host, proxy.port)
+}
+
+/// Encoding used for session data before encryption
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SerializationFormat {
+    #[default]
+    Json,
+    Bincode,
+}
+
+impl SerializationFormat {
+    /// Leading byte identifying the format of a persisted session
+    fn tag(self) -> u8 {
+        match self {
+            Self::Json => 1,
+            Self::Bincode => 2,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Result<Self> {
+        match tag {
+            1 => Ok(Self::Json),
+            2 => Ok(Self::Bincode),
+            other => Err(anyhow::anyhow!("Unknown session format tag: {}", other)),
+        }
+    }
+
+    /// Serialize a session, prefixed with this format's tag
+    fn encode(self, session: &Session) -> Result<Vec<u8>> {
+        let mut data = vec![self.tag()];
+        match self {
+            Self::Json => serde_json::to_writer(&mut data, session)?,
+            Self::Bincode => bincode::serialize_into(&mut data, &BinarySession::from(session))?,
+        }
+        Ok(data)
+    }
+
+    /// Deserialize a tagged session; untagged data is read as legacy JSON
+    fn decode(data: &[u8]) -> Result<Session> {
+        match data.split_first() {
+            Some((b'{', _)) => Ok(serde_json::from_slice(data)?),
+            Some((&tag, payload)) => match Self::from_tag(tag)? {
+                Self::Json => Ok(serde_json::from_slice(payload)?),
+                Self::Bincode => bincode::deserialize::<BinarySession>(payload)?.try_into(),
+            },
+            None => Err(anyhow::anyhow!("Session data is empty")),
+        }
+    }
+}
+
+/// Leading byte of a persisted session whose encoded data is zstd-compressed
+const ZSTD_TAG: u8 = 0x10;
+
+/// zstd level used for session data (0 selects zstd's default)
+const ZSTD_LEVEL: i32 = 0;
+
+/// Bincode form of `Session`; bincode cannot decode `serde_json::Value`, so metadata
+/// values are kept as JSON strings
+#[derive(Serialize, Deserialize)]
+struct BinarySession {
+    id: String,
+    credentials: Credentials,
+    cookies: HashMap<String, String>,
+    created_at: chrono::DateTime<chrono::Utc>,
+    last_used: chrono::DateTime<chrono::Utc>,
+    is_valid: bool,
+    metadata: HashMap<String, String>,
+    proxy: Option<String>,
+    domain_cookies: HashMap<String, HashMap<String, String>>,
+}
+
+impl From<&Session> for BinarySession {
+    fn from(session: &Session) -> Self {
+        Self {
+            id: session.id.clone(),
+            credentials: session.credentials.clone(),
+            cookies: session.cookies.clone(),
+            created_at: session.created_at,
+            last_used: session.last_used,
+            is_valid: session.is_valid,
+            metadata: session
+                .metadata
+                .iter()
+                .map(|(key, value)| (key.clone(), value.to_string()))
+                .collect(),
+            proxy: session.proxy.clone(),
+            domain_cookies: session.domain_cookies.clone(),
+        }
+    }
 }
 
+impl TryFrom<BinarySession> for Session {
+    type Error = anyhow::Error;
+
+    fn try_from(session: BinarySession) -> Result<Self> {
+        let metadata = session
+            .metadata
+            .into_iter()
+            .map(|(key, value)| Ok((key, serde_json::from_str(&value)?)))
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            id: session.id,
+            credentials: session.credentials,
+            cookies: session.cookies,
+            created_at: session.created_at,
+            last_used: session.last_used,
+            is_valid: session.is_valid,
+            metadata,
+            proxy: session.proxy,
+            domain_cookies: session.domain_cookies,
+        })
+    }
+}
+
+/// Sessions validated at once by `SessionManager::cleanup_expired_sessions`
+const CLEANUP_CONCURRENCY: usize = 8;
+
+/// Login endpoint used when none is configured (httpbin for testing)
+const DEFAULT_LOGIN_URL: &str = "https://httpbin.org/cookies/set";
+
+/// Endpoint pinged to check a session when none is configured (httpbin for testing)
+const DEFAULT_VALIDATION_URL: &str = "https://httpbin.org/headers";
+
 /// Session manager for handling authentication and cookie persistence
 pub struct SessionManager {
     sessions_dir: PathBuf,
     encryption_key: [u8; 32],
     api_client: Arc<ApiClient>,
+    login_url: String,
+    validation_url: String,
+    format: SerializationFormat,
+    compress: bool,
 }
 
 impl SessionManager {
@@ -107,9 +263,37 @@ impl SessionManager {
             sessions_dir,
             encryption_key,
             api_client,
+            login_url: DEFAULT_LOGIN_URL.to_string(),
+            validation_url: DEFAULT_VALIDATION_URL.to_string(),
+            format: SerializationFormat::default(),
+            compress: false,
         })
     }
 
+    /// Use a custom login endpoint
+    pub fn with_login_url(mut self, login_url: impl Into<String>) -> Self {
+        self.login_url = login_url.into();
+        self
+    }
+
+    /// Use a custom endpoint for session validation
+    pub fn with_validation_url(mut self, validation_url: impl Into<String>) -> Self {
+        self.validation_url = validation_url.into();
+        self
+    }
+
+    /// Encode newly persisted sessions with `format`; existing files restore in any format
+    pub fn with_serialization_format(mut self, format: SerializationFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Compress newly persisted sessions with zstd; uncompressed files still restore
+    pub fn with_compression(mut self, enabled: bool) -> Self {
+        self.compress = enabled;
+        self
+    }
+
     /// Get the default sessions directory
     fn default_sessions_dir() -> Result<PathBuf> {
         let home = dirs::home_dir()
@@ -147,20 +331,38 @@ impl SessionManager {
 
     /// Login with credentials and create a new session
     pub async fn login(&self, credentials: Credentials) -> Result<Session> {
+        self.login_via(credentials, None).await
+    }
+
+    /// Login through `proxy`, binding the new session to it
+    pub async fn login_with_proxy(
+        &self,
+        credentials: Credentials,
+        proxy: ProxyInfo,
+    ) -> Result<Session> {
+        self.login_via(credentials, Some(proxy)).await
+    }
+
+    async fn login_via(
+        &self,
+        credentials: Credentials,
+        proxy: Option<ProxyInfo>,
+    ) -> Result<Session> {
         info!("Attempting login for user: {}", credentials.username);
 
         // Create a new session
         let session_id = Self::generate_session_id();
         let mut session = Session::new(session_id, credentials.clone());
+        session.proxy = proxy.as_ref().map(proxy_id);
 
         // Perform login request (using httpbin for testing)
-        let login_result = self.perform_login(&credentials).await;
+        let login_result = self.perform_login(&credentials, proxy).await;
 
         match login_result {
             Ok(cookies) => {
-                // Store cookies in session
-                for (name, value) in cookies {
-                    session.add_cookie(name, value);
+                // Store cookies in session, scoped to the domain that set them
+                for (name, value, domain) in cookies {
+                    session.add_cookie_for_domain(name, value, domain);
                 }
 
                 // Add login metadata
@@ -183,13 +385,54 @@ impl SessionManager {
         }
     }
 
-    /// Perform the actual login request
-    async fn perform_login(&self, credentials: &Credentials) -> Result<HashMap<String, String>> {
-        // For testing purposes, we'll use httpbin.org to simulate login
-        // In production, this would be the actual Lazada login endpoint
-        let login_url = "https://httpbin.org/cookies/set";
+    /// Build a session for `account_id` from cookies exported from a browser, as
+    /// `(name, value, domain)`, without logging in. The session is validated and only
+    /// persisted if it is still accepted.
+    pub async fn import_from_cookies(
+        &self,
+        account_id: &str,
+        cookies: Vec<(String, String, String)>,
+    ) -> Result<Session> {
+        if cookies.is_empty() {
+            return Err(anyhow::anyhow!("No cookies to import for {}", account_id));
+        }
+
+        let credentials = Credentials::new(account_id.to_string(), String::new());
+        let mut session = Session::new(Self::generate_session_id(), credentials);
+        for (name, value, domain) in cookies {
+            session.add_cookie_for_domain(name, value, domain);
+        }
+        session.add_metadata(
+            "account_id".to_string(),
+            serde_json::Value::String(account_id.to_string()),
+        );
+        session.add_metadata("imported".to_string(), serde_json::Value::Bool(true));
+
+        if !self.validate_session(&mut session).await? {
+            return Err(anyhow::anyhow!(
+                "Imported cookies for {} were not accepted",
+                account_id
+            ));
+        }
+
+        self.persist_session(&session).await?;
+        info!(
+            "Imported session {} for {} with {} cookies",
+            session.id,
+            account_id,
+            session.cookies.len()
+        );
+        Ok(session)
+    }
+
+    /// Perform the actual login request, returning the cookies set as `(name, value, domain)`
+    async fn perform_login(
+        &self,
+        credentials: &Credentials,
+        proxy: Option<ProxyInfo>,
+    ) -> Result<Vec<(String, String, String)>> {
+        let login_url = self.login_url.as_str();
 
-        // Create a request to set some test cookies
         let mut headers = reqwest::header::HeaderMap::new();
         headers.insert("Content-Type", "application/json".parse()?);
 
@@ -206,25 +449,30 @@ impl SessionManager {
                 login_url,
                 Some(headers),
                 Some(login_data.to_string().as_bytes().to_vec()),
-                None, // No proxy for login
+                proxy,
             )
             .await?;
 
-        if response.status >= 200 && response.status < 300 {
-            // Parse cookies from response headers
-            let mut cookies = HashMap::new();
-
-            // Simulate setting some test cookies
-            cookies.insert("session_id".to_string(), uuid::Uuid::new_v4().to_string());
-            cookies.insert("user_id".to_string(), credentials.username.clone());
-            cookies.insert("login_time".to_string(), chrono::Utc::now().to_rfc3339());
-            cookies.insert(
-                "auth_token".to_string(),
-                format!(
-                    "token_{}",
-                    uuid::Uuid::new_v4().to_string()[..16].to_string()
-                ),
-            );
+        if response.is_success() {
+            let login_host = reqwest::Url::parse(login_url)
+                .ok()
+                .and_then(|url| url.host_str().map(str::to_string))
+                .unwrap_or_default();
+            let mut cookies = parse_scoped_set_cookies(&response.headers, &login_host);
+
+            // Fall back to the client's cookie jar, e.g. when cookies were set during a redirect
+            if cookies.is_empty() {
+                cookies = self
+                    .api_client
+                    .cookies_for_url(login_url)
+                    .into_iter()
+                    .map(|(name, value)| (name, value, login_host.clone()))
+                    .collect();
+            }
+
+            if cookies.is_empty() {
+                warn!("Login response did not set any cookies");
+            }
 
             debug!("Login response received with {} cookies", cookies.len());
             Ok(cookies)
@@ -243,8 +491,20 @@ impl SessionManager {
         info!("Persisting session {} to {:?}", session.id, session_file);
 
         // Serialize session data
-        let session_data =
-            serde_json::to_vec(session).context("Failed to serialize session data")?;
+        let session_data = self
+            .format
+            .encode(session)
+            .context("Failed to serialize session data")?;
+        let session_data = if self.compress {
+            let mut compressed = vec![ZSTD_TAG];
+            compressed.extend(
+                zstd::encode_all(session_data.as_slice(), ZSTD_LEVEL)
+                    .context("Failed to compress session data")?,
+            );
+            compressed
+        } else {
+            session_data
+        };
 
         // Encrypt the session data
         let encrypted_data = self
@@ -283,9 +543,16 @@ impl SessionManager {
             .decrypt_data(&encrypted_data)
             .context("Failed to decrypt session data")?;
 
+        let session_data = match session_data.split_first() {
+            Some((&ZSTD_TAG, compressed)) => {
+                zstd::decode_all(compressed).context("Failed to decompress session data")?
+            }
+            _ => session_data,
+        };
+
         // Deserialize session
-        let session: Session =
-            serde_json::from_slice(&session_data).context("Failed to deserialize session data")?;
+        let session = SerializationFormat::decode(&session_data)
+            .context("Failed to deserialize session data")?;
 
         debug!("Session {} restored successfully", session_id);
         Ok(session)
@@ -293,21 +560,30 @@ impl SessionManager {
 
     /// Validate session by pinging a lightweight endpoint
     pub async fn validate_session(&self, session: &mut Session) -> Result<bool> {
+        self.validate_session_with_proxy(session, None).await
+    }
+
+    /// Validate session through `proxy`, warning if it differs from the login proxy
+    pub async fn validate_session_with_proxy(
+        &self,
+        session: &mut Session,
+        proxy: Option<&ProxyInfo>,
+    ) -> Result<bool> {
         info!("Validating session: {}", session.id);
 
+        if session.is_proxy_mismatch(proxy) {
+            warn!(
+                "Session {} logged in via {} but is used via {}; it may be flagged",
+                session.id,
+                session.proxy.as_deref().unwrap_or("no proxy"),
+                proxy.map(proxy_id).as_deref().unwrap_or("no proxy")
+            );
+        }
+
         // Update last used timestamp
         session.update_last_used();
 
-        // Create a cookie jar from session cookies
-        let cookie_jar = self.create_cookie_jar_from_session(session);
-
-        // Create a temporary API client with the session cookies
-        let temp_client = ApiClient::with_cookie_jar(cookie_jar)?;
-
-        // Ping a lightweight endpoint to validate the session
-        let validation_result = self.ping_validation_endpoint(&temp_client).await;
-
-        match validation_result {
+        match self.ping_session(session, proxy).await {
             Ok(is_valid) => {
                 session.is_valid = is_valid;
                 if is_valid {
@@ -325,17 +601,34 @@ impl SessionManager {
         }
     }
 
-    /// Ping a lightweight endpoint to check session validity
-    async fn ping_validation_endpoint(&self, client: &ApiClient) -> Result<bool> {
-        // Use httpbin.org for testing - in production this would be a lightweight auth endpoint
-        let validation_url = "https://httpbin.org/headers";
+    /// Ping the validation endpoint with the session's cookies. Errors mean the
+    /// endpoint could not be reached, not that the session was rejected.
+    async fn ping_session(&self, session: &Session, proxy: Option<&ProxyInfo>) -> Result<bool> {
+        // Create a temporary API client with the session cookies
+        let cookie_jar = self.create_cookie_jar_from_session(session);
+        let temp_client = ApiClient::with_cookie_jar(cookie_jar)?;
+
+        self.ping_validation_endpoint(&temp_client, proxy).await
+    }
 
+    /// Ping a lightweight endpoint to check session validity
+    async fn ping_validation_endpoint(
+        &self,
+        client: &ApiClient,
+        proxy: Option<&ProxyInfo>,
+    ) -> Result<bool> {
         let response = client
-            .request(reqwest::Method::GET, validation_url, None, None, None)
+            .request(
+                reqwest::Method::GET,
+                &self.validation_url,
+                None,
+                None,
+                proxy.cloned(),
+            )
             .await?;
 
         // Consider session valid if we get a successful response
-        let is_valid = response.status >= 200 && response.status < 300;
+        let is_valid = response.is_success();
 
         if is_valid {
             debug!("Validation endpoint responded successfully");
@@ -346,18 +639,39 @@ impl SessionManager {
         Ok(is_valid)
     }
 
-    /// Create a cookie jar from session cookies
+    /// Create a cookie jar from session cookies, each scoped to its own domain
     fn create_cookie_jar_from_session(&self, session: &Session) -> Arc<Jar> {
         let jar = Arc::new(Jar::default());
+        let validation_url = reqwest::Url::parse(&self.validation_url).ok();
+
+        for (domain, cookies) in &session.domain_cookies {
+            let host = domain.trim_start_matches('.');
+            let Ok(url) = reqwest::Url::parse(&format!("https://{}/", host)) else {
+                warn!("Skipping cookies with invalid domain {}", domain);
+                continue;
+            };
+            for (name, value) in cookies {
+                let cookie_str = if domain.starts_with('.') {
+                    format!("{}={}; Domain={}; Path=/", name, value, host)
+                } else {
+                    format!("{}={}; Path=/", name, value)
+                };
+                jar.add_cookie_str(&cookie_str, &url);
+            }
+        }
 
-        // Add cookies to the jar
-        for (name, value) in &session.cookies {
-            // Create a simple cookie string
-            let cookie_str = format!("{}={}", name, value);
-            jar.add_cookie_str(
-                &cookie_str,
-                &reqwest::Url::parse("https://httpbin.org").unwrap(),
-            );
+        // Cookies from before domains were recorded go to the validation host
+        let unscoped = session.cookies.iter().filter(|(name, _)| {
+            !session
+                .domain_cookies
+                .values()
+                .any(|cookies| cookies.contains_key(*name))
+        });
+        for (name, value) in unscoped {
+            match &validation_url {
+                Some(url) => jar.add_cookie_str(&format!("{}={}", name, value), url),
+                None => warn!("Invalid validation URL: {}", self.validation_url),
+            }
         }
 
         jar
@@ -384,6 +698,46 @@ impl SessionManager {
         Ok(sessions)
     }
 
+    /// Restore all sessions that can be decrypted, newest first
+    pub async fn list_session_details(&self) -> Result<Vec<Session>> {
+        let mut sessions = Vec::new();
+
+        for session_id in self.list_sessions().await? {
+            match self.restore_session(&session_id).await {
+                Ok(session) => sessions.push(session),
+                Err(e) => warn!("Skipping unreadable session {}: {}", session_id, e),
+            }
+        }
+
+        sessions.sort_by_key(|session| std::cmp::Reverse(session.created_at));
+        Ok(sessions)
+    }
+
+    /// Ids of stored sessions whose metadata has `key` set to `value`, sorted.
+    /// Sessions that can't be restored are skipped.
+    pub async fn find_sessions_by_metadata(
+        &self,
+        key: &str,
+        value: &serde_json::Value,
+    ) -> Result<Vec<String>> {
+        let mut matches = Vec::new();
+
+        for session_id in self.list_sessions().await? {
+            match self.restore_session(&session_id).await {
+                Ok(session) if session.metadata.get(key) == Some(value) => matches.push(session_id),
+                Ok(_) => {}
+                Err(e) => warn!("Skipping unreadable session {}: {}", session_id, e),
+            }
+        }
+
+        Ok(matches)
+    }
+
+    /// Get the most recently created session, if any
+    pub async fn most_recent_session(&self) -> Result<Option<Session>> {
+        Ok(self.list_session_details().await?.into_iter().next())
+    }
+
     /// Delete a session
     pub async fn delete_session(&self, session_id: &str) -> Result<()> {
         let session_file = self.sessions_dir.join(format!("{}.bin", session_id));
@@ -400,31 +754,77 @@ impl SessionManager {
         Ok(())
     }
 
-    /// Clean up expired sessions
+    /// Restore every stored session and run `check` on it, up to `concurrency` at a
+    /// time. Sessions that can't be restored are reported as `None` rather than
+    /// stopping the batch.
+    async fn check_all_sessions<T, F, Fut>(
+        &self,
+        concurrency: usize,
+        check: F,
+    ) -> Result<Vec<(String, Option<T>)>>
+    where
+        F: Fn(Session) -> Fut,
+        Fut: std::future::Future<Output = T>,
+    {
+        let session_ids = self.list_sessions().await?;
+        let check = &check;
+
+        Ok(stream::iter(session_ids)
+            .map(|session_id| async move {
+                let outcome = match self.restore_session(&session_id).await {
+                    Ok(session) => Some(check(session).await),
+                    Err(e) => {
+                        warn!("Session {} is corrupted: {:#}", session_id, e);
+                        None
+                    }
+                };
+                (session_id, outcome)
+            })
+            .buffered(concurrency.max(1))
+            .collect()
+            .await)
+    }
+
+    /// Restore and validate every stored session, up to `concurrency` at a time.
+    /// Returns each session id with whether it is valid; sessions that can't be
+    /// restored are reported as invalid rather than stopping the batch.
+    pub async fn validate_all_sessions(&self, concurrency: usize) -> Vec<(String, bool)> {
+        let results = self
+            .check_all_sessions(concurrency, |mut session| async move {
+                self.validate_session(&mut session).await.unwrap_or(false)
+            })
+            .await;
+
+        match results {
+            Ok(results) => results
+                .into_iter()
+                .map(|(session_id, is_valid)| (session_id, is_valid.unwrap_or(false)))
+                .collect(),
+            Err(e) => {
+                error!("Failed to list sessions for validation: {}", e);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Clean up expired and corrupted sessions, restoring up to `CLEANUP_CONCURRENCY`
+    /// at a time
     pub async fn cleanup_expired_sessions(&self, max_age_days: i64) -> Result<usize> {
         let mut cleaned_count = 0;
         let cutoff_time = chrono::Utc::now() - chrono::Duration::days(max_age_days);
 
-        let sessions = self.list_sessions().await?;
+        let results = self
+            .check_all_sessions(CLEANUP_CONCURRENCY, |session| async move {
+                session.last_used < cutoff_time
+            })
+            .await?;
 
-        for session_id in sessions {
-            match self.restore_session(&session_id).await {
-                Ok(session) => {
-                    if session.last_used < cutoff_time {
-                        self.delete_session(&session_id).await?;
-                        cleaned_count += 1;
-                        info!("Cleaned up expired session: {}", session_id);
-                    }
-                }
-                Err(e) => {
-                    warn!(
-                        "Failed to restore session {} for cleanup: {}",
-                        session_id, e
-                    );
-                    // Delete corrupted session files
-                    self.delete_session(&session_id).await?;
-                    cleaned_count += 1;
-                }
+        for (session_id, expired) in results {
+            // Corrupted session files are removed too
+            if expired.unwrap_or(true) {
+                self.delete_session(&session_id).await?;
+                cleaned_count += 1;
+                info!("Cleaned up expired session: {}", session_id);
             }
         }
 
@@ -478,6 +878,36 @@ impl SessionManager {
     }
 }
 
+/// Cookies from `Set-Cookie` headers as `(name, value, domain)`, where the domain is
+/// `.example.com` for a cookie with a `Domain` attribute, otherwise `host` (the host
+/// that set it)
+pub fn parse_scoped_set_cookies(
+    headers: &reqwest::header::HeaderMap,
+    host: &str,
+) -> Vec<(String, String, String)> {
+    headers
+        .get_all(reqwest::header::SET_COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .filter_map(|value| {
+            let mut parts = value.split(';');
+            let (name, value) = parts.next()?.split_once('=')?;
+            let name = name.trim();
+            if name.is_empty() {
+                return None;
+            }
+            let domain = parts
+                .filter_map(|attribute| attribute.split_once('='))
+                .find(|(key, _)| key.trim().eq_ignore_ascii_case("domain"))
+                .map(|(_, domain)| domain.trim().trim_start_matches('.'))
+                .filter(|domain| !domain.is_empty())
+                .map(|domain| format!(".{}", domain))
+                .unwrap_or_else(|| host.to_string());
+            Some((name.to_string(), value.trim().to_string(), domain))
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -559,4 +989,236 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_session_management_operations() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let api_client = Arc::new(ApiClient::new(Some("Lazabot-Test/1.0".to_string()))?);
+        let manager = SessionManager::with_sessions_dir(
+            api_client,
+            temp_dir.path().to_path_buf(),
+            SessionManager::default_encryption_key(),
+        )
+        .await?;
+
+        assert!(manager.most_recent_session().await?.is_none());
+
+        let credentials = Credentials::new("testuser".to_string(), "testpass".to_string());
+        let mut older = Session::new("session_older".to_string(), credentials.clone());
+        older.created_at = chrono::Utc::now() - chrono::Duration::hours(2);
+        let newer = Session::new("session_newer".to_string(), credentials);
+
+        manager.persist_session(&older).await?;
+        manager.persist_session(&newer).await?;
+
+        // Sessions are listed newest first
+        let sessions = manager.list_session_details().await?;
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].id, "session_newer");
+        assert_eq!(sessions[1].id, "session_older");
+
+        // Logging out removes the most recent session
+        let latest = manager.most_recent_session().await?.unwrap();
+        manager.delete_session(&latest.id).await?;
+
+        let remaining = manager.list_sessions().await?;
+        assert_eq!(remaining, vec!["session_older".to_string()]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_session_round_trip_in_each_format() -> Result<()> {
+        for format in [SerializationFormat::Json, SerializationFormat::Bincode] {
+            let temp_dir = tempfile::TempDir::new()?;
+            let api_client = Arc::new(ApiClient::new(None)?);
+            let manager = SessionManager::with_sessions_dir(
+                api_client,
+                temp_dir.path().to_path_buf(),
+                SessionManager::default_encryption_key(),
+            )
+            .await?
+            .with_serialization_format(format);
+
+            let credentials = Credentials::new("testuser".to_string(), "testpass".to_string())
+                .with_email("test@example.com".to_string());
+            let mut session = Session::new("session_format".to_string(), credentials);
+            session.add_cookie("lzd_sid".to_string(), "abc123".to_string());
+            session.add_metadata("logged_in".to_string(), serde_json::json!(true));
+            session.add_metadata(
+                "profile".to_string(),
+                serde_json::json!({ "tier": "gold", "orders": [1, 2, 3] }),
+            );
+
+            manager.persist_session(&session).await?;
+            let restored = manager.restore_session(&session.id).await?;
+
+            assert_eq!(restored.id, session.id, "{:?}", format);
+            assert_eq!(restored.credentials.email, session.credentials.email);
+            assert_eq!(restored.cookies, session.cookies);
+            assert_eq!(restored.created_at, session.created_at);
+            assert_eq!(restored.metadata, session.metadata);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_session_compression() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let plain = SessionManager::with_sessions_dir(
+            Arc::new(ApiClient::new(None)?),
+            temp_dir.path().join("plain"),
+            SessionManager::default_encryption_key(),
+        )
+        .await?;
+        let compressed = SessionManager::with_sessions_dir(
+            Arc::new(ApiClient::new(None)?),
+            temp_dir.path().join("zstd"),
+            SessionManager::default_encryption_key(),
+        )
+        .await?
+        .with_compression(true);
+
+        let mut session = Session::new(
+            "session_large".to_string(),
+            Credentials::new("testuser".to_string(), "testpass".to_string()),
+        );
+        for i in 0..200 {
+            session.add_cookie(
+                format!("cookie_{}", i),
+                format!("value_{}_{}", i, "x".repeat(40)),
+            );
+            session.add_metadata(format!("key_{}", i), serde_json::json!({ "index": i }));
+        }
+
+        plain.persist_session(&session).await?;
+        compressed.persist_session(&session).await?;
+
+        let file_len = |manager: &SessionManager| {
+            std::fs::metadata(manager.sessions_dir.join("session_large.bin")).map(|m| m.len())
+        };
+        assert!(file_len(&compressed)? < file_len(&plain)?);
+
+        let restored = compressed.restore_session(&session.id).await?;
+        assert_eq!(restored.cookies, session.cookies);
+        assert_eq!(restored.metadata, session.metadata);
+        assert_eq!(restored.last_used, session.last_used);
+
+        // Files written without compression still load when it is enabled
+        std::fs::copy(
+            plain.sessions_dir.join("session_large.bin"),
+            compressed.sessions_dir.join("session_plain.bin"),
+        )?;
+        let restored = compressed.restore_session("session_plain").await?;
+        assert_eq!(restored.cookies, session.cookies);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_untagged_json_session_decodes() -> Result<()> {
+        let session = Session::new(
+            "session_legacy".to_string(),
+            Credentials::new("testuser".to_string(), "testpass".to_string()),
+        );
+        let legacy = serde_json::to_vec(&session)?;
+
+        let restored = SerializationFormat::decode(&legacy)?;
+        assert_eq!(restored.id, "session_legacy");
+        assert!(SerializationFormat::decode(&[9, 0]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_scoped_set_cookies() {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.append(
+            reqwest::header::SET_COOKIE,
+            "lzd_sid=abc123; Path=/; Domain=lazada.sg".parse().unwrap(),
+        );
+        headers.append(
+            reqwest::header::SET_COOKIE,
+            "_tb_token_=xyz; Path=/".parse().unwrap(),
+        );
+        headers.append(reqwest::header::SET_COOKIE, "malformed".parse().unwrap());
+        headers.append(
+            reqwest::header::SET_COOKIE,
+            "lzd_sid=other; Domain=pay.example.com".parse().unwrap(),
+        );
+
+        let cookies = parse_scoped_set_cookies(&headers, "member.lazada.sg");
+        assert_eq!(
+            cookies,
+            vec![
+                (
+                    "lzd_sid".to_string(),
+                    "abc123".to_string(),
+                    ".lazada.sg".to_string()
+                ),
+                (
+                    "_tb_token_".to_string(),
+                    "xyz".to_string(),
+                    "member.lazada.sg".to_string()
+                ),
+                (
+                    "lzd_sid".to_string(),
+                    "other".to_string(),
+                    ".pay.example.com".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cookie_jar_scopes_cookies_to_their_domains() -> Result<()> {
+        use reqwest::cookie::CookieStore;
+
+        let temp_dir = tempfile::TempDir::new()?;
+        let api_client = Arc::new(ApiClient::new(None)?);
+        let manager =
+            SessionManager::with_sessions_dir(api_client, temp_dir.path().to_path_buf(), [7u8; 32])
+                .await?;
+
+        let credentials = Credentials::new("testuser".to_string(), "testpass".to_string());
+        let mut session = Session::new("multi_domain".to_string(), credentials);
+        session.add_cookie_for_domain(
+            "lzd_sid".to_string(),
+            "abc123".to_string(),
+            ".lazada.sg".to_string(),
+        );
+        session.add_cookie_for_domain(
+            "pay_token".to_string(),
+            "xyz".to_string(),
+            "pay.example.com".to_string(),
+        );
+        session.add_cookie_for_domain(
+            "lzd_sid".to_string(),
+            "pay_sid".to_string(),
+            "pay.example.com".to_string(),
+        );
+
+        let jar = manager.create_cookie_jar_from_session(&session);
+        let cookies = |url: &str| {
+            jar.cookies(&reqwest::Url::parse(url).unwrap())
+                .map(|value| value.to_str().unwrap().to_string())
+                .unwrap_or_default()
+        };
+
+        let lazada = cookies("https://www.lazada.sg/cart");
+        assert!(lazada.contains("lzd_sid=abc123"));
+        assert!(!lazada.contains("pay_token"));
+
+        // A cookie name reused on another domain keeps its own value there
+        let pay = cookies("https://pay.example.com/checkout");
+        assert!(pay.contains("pay_token=xyz"));
+        assert!(pay.contains("lzd_sid=pay_sid"));
+        assert!(!pay.contains("abc123"));
+
+        // Host-only cookies don't leak to sibling hosts
+        assert!(!cookies("https://www.example.com/").contains("pay_token"));
+
+        Ok(())
+    }
 }
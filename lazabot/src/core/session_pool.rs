@@ -0,0 +1,98 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{debug, info};
+
+use crate::core::session::{Credentials, Session, SessionManager};
+
+/// Sessions older than this are discarded instead of revalidated
+const DEFAULT_MAX_SESSION_AGE_HOURS: i64 = 24;
+
+/// Pooled session for one account, locked while it is validated or replaced
+type SessionSlot = Arc<Mutex<Option<Session>>>;
+
+/// Validated sessions shared between flows, one per account
+pub struct SessionPool {
+    manager: Arc<SessionManager>,
+    slots: Mutex<HashMap<String, SessionSlot>>,
+    max_age: chrono::Duration,
+}
+
+impl SessionPool {
+    pub fn new(manager: Arc<SessionManager>) -> Self {
+        Self {
+            manager,
+            slots: Mutex::new(HashMap::new()),
+            max_age: chrono::Duration::hours(DEFAULT_MAX_SESSION_AGE_HOURS),
+        }
+    }
+
+    /// Discard pooled sessions created longer ago than `max_age`
+    pub fn with_max_age(mut self, max_age: chrono::Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// Slot for `account_id`, created empty on first use. The map lock is only held here.
+    async fn slot(&self, account_id: &str) -> SessionSlot {
+        self.slots
+            .lock()
+            .await
+            .entry(account_id.to_string())
+            .or_default()
+            .clone()
+    }
+
+    /// Get a valid session for `account_id`, logging in with `credentials` only when the
+    /// pooled session is missing, expired or no longer valid
+    pub async fn get(&self, account_id: &str, credentials: &Credentials) -> Result<Session> {
+        // Held across validation and login so concurrent callers for the same account
+        // share one login, while other accounts proceed independently
+        let slot = self.slot(account_id).await;
+        let mut pooled = slot.lock().await;
+
+        if let Some(mut session) = pooled.take() {
+            if chrono::Utc::now() - session.created_at > self.max_age {
+                debug!("Pooled session {} for {} expired", session.id, account_id);
+            } else if self.manager.validate_session(&mut session).await? {
+                debug!("Reusing session {} for {}", session.id, account_id);
+                *pooled = Some(session.clone());
+                return Ok(session);
+            } else {
+                debug!(
+                    "Pooled session {} for {} is invalid",
+                    session.id, account_id
+                );
+            }
+        }
+
+        info!("Logging in to create pooled session for {}", account_id);
+        let session = self.manager.login(credentials.clone()).await?;
+        *pooled = Some(session.clone());
+        Ok(session)
+    }
+
+    /// Drop the pooled session for `account_id`, e.g. after the site rejects it
+    pub async fn invalidate(&self, account_id: &str) -> Option<Session> {
+        let slot = self.slots.lock().await.get(account_id).cloned()?;
+        let session = slot.lock().await.take();
+        session
+    }
+
+    /// Number of accounts with a pooled session
+    pub async fn len(&self) -> usize {
+        let slots: Vec<SessionSlot> = self.slots.lock().await.values().cloned().collect();
+        let mut count = 0;
+        for slot in slots {
+            if slot.lock().await.is_some() {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.len().await == 0
+    }
+}
@@ -4,8 +4,11 @@ pub mod cli;
 pub mod config;
 pub mod core;
 pub mod integrations;
+pub mod notifications;
 pub mod proxy;
 pub mod stealth;
 pub mod storage;
 pub mod tasks;
+#[cfg(feature = "testing")]
+pub mod testing;
 pub mod utils;
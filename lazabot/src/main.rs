@@ -7,18 +7,55 @@ mod captcha;
 mod cli;
 mod config;
 mod core;
+mod notifications;
 mod proxy;
+mod storage;
 mod tasks;
+mod utils;
 
 use cli::{execute_command, Cli};
+use tasks::ShutdownCoordinator;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    tracing_subscriber::fmt().with_max_level(Level::INFO).init();
+    // Logs go to stderr so machine-readable output on stdout stays clean
+    tracing_subscriber::fmt()
+        .with_max_level(Level::INFO)
+        .with_writer(std::io::stderr)
+        .init();
     info!("Starting Lazabot CLI...");
 
     let cli = Cli::parse();
-    execute_command(cli.command).await?;
+
+    // Load environment file before anything reads LAZABOT_* variables
+    match &cli.env_file {
+        Some(path) => {
+            let loaded = config::load_dotenv(path)?;
+            info!("Loaded {} variables from {}", loaded, path);
+        }
+        None if std::path::Path::new(".env").exists() => {
+            let loaded = config::load_dotenv(".env")?;
+            info!("Loaded {} variables from .env", loaded);
+        }
+        None => {}
+    }
+
+    // Install the signal handler once; handlers observe it via the coordinator
+    let shutdown = ShutdownCoordinator::new();
+    let mut listener = tokio::spawn(shutdown.clone().listen());
+
+    tokio::select! {
+        result = execute_command(cli.command, cli.config_dir.as_deref(), &shutdown) => {
+            // Stop the signal listener and wait for it before exiting
+            shutdown.token().cancel();
+            listener.await?;
+            result?
+        }
+        _ = &mut listener => {
+            info!("Lazabot CLI stopped by signal");
+            return Ok(());
+        }
+    }
 
     info!("Lazabot CLI completed successfully!");
     Ok(())
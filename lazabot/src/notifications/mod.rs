@@ -0,0 +1,7 @@
+pub mod notifier;
+pub mod telegram;
+pub mod webhook;
+
+pub use notifier::{NotificationEvent, Notifier};
+pub use telegram::TelegramNotifier;
+pub use webhook::WebhookNotifier;
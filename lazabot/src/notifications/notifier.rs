@@ -0,0 +1,94 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+/// Events that can be sent to a notifier
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum NotificationEvent {
+    /// A monitored product came back in stock
+    ProductAvailable {
+        product_id: String,
+        product_name: String,
+        price: Option<f64>,
+        url: String,
+        timestamp: chrono::DateTime<chrono::Utc>,
+    },
+    /// An order was placed successfully
+    CheckoutSucceeded {
+        order_id: String,
+        product_id: String,
+        product_name: String,
+        account_id: String,
+        timestamp: chrono::DateTime<chrono::Utc>,
+    },
+    /// Checkout failed after all retries
+    CheckoutFailed {
+        error: String,
+        product_id: String,
+        product_name: String,
+        account_id: String,
+        timestamp: chrono::DateTime<chrono::Utc>,
+    },
+}
+
+impl NotificationEvent {
+    /// Human-readable one-line summary of the event
+    pub fn message(&self) -> String {
+        match self {
+            Self::ProductAvailable {
+                product_name,
+                price,
+                url,
+                ..
+            } => match price {
+                Some(price) => format!("🔔 {} is available at {:.2}: {}", product_name, price, url),
+                None => format!("🔔 {} is available: {}", product_name, url),
+            },
+            Self::CheckoutSucceeded {
+                order_id,
+                product_name,
+                account_id,
+                ..
+            } => format!(
+                "✅ Order {} placed for {} (account {})",
+                order_id, product_name, account_id
+            ),
+            Self::CheckoutFailed {
+                error,
+                product_name,
+                account_id,
+                ..
+            } => format!(
+                "❌ Checkout failed for {} (account {}): {}",
+                product_name, account_id, error
+            ),
+        }
+    }
+}
+
+/// Notification sink for bot events
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    async fn notify(&self, event: NotificationEvent) -> Result<()>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_message() {
+        let event = NotificationEvent::CheckoutSucceeded {
+            order_id: "ORDER123".to_string(),
+            product_id: "PROD123".to_string(),
+            product_name: "Test Product".to_string(),
+            account_id: "ACC123".to_string(),
+            timestamp: chrono::Utc::now(),
+        };
+
+        let message = event.message();
+        assert!(message.contains("ORDER123"));
+        assert!(message.contains("Test Product"));
+    }
+}
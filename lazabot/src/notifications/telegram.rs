@@ -0,0 +1,143 @@
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use std::env;
+use std::time::Duration;
+use tokio::time::sleep;
+use tracing::{debug, warn};
+
+use crate::notifications::{NotificationEvent, Notifier};
+
+/// Default Telegram Bot API base URL
+pub const DEFAULT_API_BASE: &str = "https://api.telegram.org";
+
+/// Request timeout in seconds
+const REQUEST_TIMEOUT: u64 = 10;
+
+/// Maximum number of retries after being rate limited
+const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+
+/// Error body returned by the Bot API
+#[derive(Debug, Deserialize)]
+struct TelegramError {
+    description: Option<String>,
+    parameters: Option<TelegramErrorParameters>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TelegramErrorParameters {
+    retry_after: Option<u64>,
+}
+
+/// Notifier that sends messages to a chat via the Telegram Bot API
+#[derive(Clone)]
+pub struct TelegramNotifier {
+    bot_token: String,
+    chat_id: String,
+    api_base: String,
+    client: Client,
+}
+
+impl std::fmt::Debug for TelegramNotifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TelegramNotifier")
+            .field("bot_token", &"<redacted>")
+            .field("chat_id", &self.chat_id)
+            .field("api_base", &self.api_base)
+            .finish()
+    }
+}
+
+impl TelegramNotifier {
+    /// Create a new Telegram notifier
+    pub fn new(bot_token: String, chat_id: String) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT))
+            .build()
+            .context("Failed to create HTTP client")?;
+
+        Ok(Self {
+            bot_token,
+            chat_id,
+            api_base: DEFAULT_API_BASE.to_string(),
+            client,
+        })
+    }
+
+    /// Create a notifier from `LAZABOT_TELEGRAM_BOT_TOKEN` and `LAZABOT_TELEGRAM_CHAT_ID`
+    pub fn from_env() -> Result<Self> {
+        let bot_token = env::var("LAZABOT_TELEGRAM_BOT_TOKEN")
+            .context("LAZABOT_TELEGRAM_BOT_TOKEN environment variable not set")?;
+        let chat_id = env::var("LAZABOT_TELEGRAM_CHAT_ID")
+            .context("LAZABOT_TELEGRAM_CHAT_ID environment variable not set")?;
+
+        Self::new(bot_token, chat_id)
+    }
+
+    /// Override the Bot API base URL
+    pub fn with_api_base(mut self, api_base: impl Into<String>) -> Self {
+        self.api_base = api_base.into();
+        self
+    }
+
+    /// Send a text message to the configured chat
+    pub async fn send_message(&self, text: &str) -> Result<()> {
+        let url = format!(
+            "{}/bot{}/sendMessage",
+            self.api_base.trim_end_matches('/'),
+            self.bot_token
+        );
+        let payload = serde_json::json!({
+            "chat_id": self.chat_id,
+            "text": text,
+        });
+
+        let mut retries = 0;
+        loop {
+            let response = self
+                .client
+                .post(&url)
+                .json(&payload)
+                .send()
+                .await
+                .context("Failed to send Telegram message")?;
+
+            let status = response.status();
+            if status.is_success() {
+                debug!("Telegram message sent to chat {}", self.chat_id);
+                return Ok(());
+            }
+
+            let error: Option<TelegramError> = response.json().await.ok();
+
+            if status == StatusCode::TOO_MANY_REQUESTS && retries < MAX_RATE_LIMIT_RETRIES {
+                retries += 1;
+                // Prefer the server's hint, otherwise back off exponentially
+                let retry_after = error
+                    .as_ref()
+                    .and_then(|e| e.parameters.as_ref())
+                    .and_then(|p| p.retry_after)
+                    .unwrap_or(1 << retries);
+                warn!(
+                    "Telegram rate limit hit, retrying in {}s (attempt {}/{})",
+                    retry_after, retries, MAX_RATE_LIMIT_RETRIES
+                );
+                sleep(Duration::from_secs(retry_after)).await;
+                continue;
+            }
+
+            let description = error
+                .and_then(|e| e.description)
+                .unwrap_or_else(|| "no description".to_string());
+            return Err(anyhow!("Telegram API returned {}: {}", status, description));
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for TelegramNotifier {
+    async fn notify(&self, event: NotificationEvent) -> Result<()> {
+        self.send_message(&event.message()).await
+    }
+}
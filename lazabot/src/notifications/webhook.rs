@@ -0,0 +1,57 @@
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use std::time::Duration;
+use tracing::debug;
+
+use crate::notifications::{NotificationEvent, Notifier};
+
+/// Request timeout in seconds
+const REQUEST_TIMEOUT: u64 = 10;
+
+/// Notifier that POSTs events as JSON to a webhook URL
+#[derive(Debug, Clone)]
+pub struct WebhookNotifier {
+    url: String,
+    client: Client,
+}
+
+impl WebhookNotifier {
+    /// Create a new webhook notifier
+    pub fn new(url: String) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT))
+            .build()
+            .context("Failed to create HTTP client")?;
+
+        Ok(Self { url, client })
+    }
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    async fn notify(&self, event: NotificationEvent) -> Result<()> {
+        let payload = serde_json::json!({
+            "text": event.message(),
+            "event": event,
+        });
+
+        let response = self
+            .client
+            .post(&self.url)
+            .json(&payload)
+            .send()
+            .await
+            .context("Failed to send webhook notification")?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "Webhook notification failed with status {}",
+                response.status()
+            ));
+        }
+
+        debug!("Webhook notification sent to {}", self.url);
+        Ok(())
+    }
+}
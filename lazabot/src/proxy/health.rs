@@ -1,10 +1,38 @@
 use super::manager::ProxyManager;
-use crate::api::{ApiClient, ProxyInfo};
-use anyhow::Result;
+use crate::api::{ApiClient, ProxyInfo, ProxyType};
+use anyhow::{bail, Context, Result};
 use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
 use tokio::time::timeout;
 use tracing::{debug, info, warn};
 
+const SOCKS5_VERSION: u8 = 0x05;
+const SOCKS5_NO_AUTH: u8 = 0x00;
+const SOCKS5_USER_PASS: u8 = 0x02;
+const SOCKS5_CONNECT: u8 = 0x01;
+const SOCKS5_DOMAIN: u8 = 0x03;
+/// Version of the username/password sub-negotiation (RFC 1929)
+const SOCKS5_AUTH_VERSION: u8 = 0x01;
+
+/// What a health check exercised, which depends on the proxy's type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthCheckKind {
+    /// GET of the test URL through an HTTP proxy
+    HttpGet,
+    /// SOCKS5 handshake and CONNECT to the test URL's host
+    Socks5Connect,
+}
+
+/// Outcome of checking one proxy
+#[derive(Debug, Clone)]
+pub struct ProxyCheck {
+    pub kind: HealthCheckKind,
+    pub healthy: bool,
+    /// Why the check failed, when unhealthy
+    pub error: Option<String>,
+}
+
 /// Proxy health checker that tests proxies against httpbin.org/ip
 #[derive(Debug)]
 pub struct ProxyHealth {
@@ -41,51 +69,144 @@ impl ProxyHealth {
 
     /// Check the health of a single proxy
     pub async fn check_proxy_health(&self, proxy: &ProxyInfo) -> bool {
-        debug!("Checking health of proxy {}:{}", proxy.host, proxy.port);
+        self.check_proxy(proxy).await.healthy
+    }
+
+    /// Check a single proxy in the way its type calls for: an HTTP proxy must fetch the
+    /// test URL, a SOCKS5 proxy must complete a handshake and CONNECT to the test URL's host
+    pub async fn check_proxy(&self, proxy: &ProxyInfo) -> ProxyCheck {
+        debug!(
+            "Checking health of {:?} proxy {}:{}",
+            proxy.proxy_type, proxy.host, proxy.port
+        );
+
+        let (kind, result) = match proxy.proxy_type {
+            ProxyType::Http => (
+                HealthCheckKind::HttpGet,
+                timeout(self.timeout_duration, self.http_get(proxy)).await,
+            ),
+            ProxyType::Socks5 => (
+                HealthCheckKind::Socks5Connect,
+                timeout(self.timeout_duration, self.socks5_connect(proxy)).await,
+            ),
+        };
+
+        let error = match result {
+            Ok(Ok(())) => {
+                debug!("Proxy {}:{} is healthy", proxy.host, proxy.port);
+                None
+            }
+            Ok(Err(e)) => Some(format!("{:#}", e)),
+            Err(_) => Some(format!("timed out after {:?}", self.timeout_duration)),
+        };
+        if let Some(error) = &error {
+            warn!(
+                "Proxy {}:{} health check failed: {}",
+                proxy.host, proxy.port, error
+            );
+        }
+
+        ProxyCheck {
+            kind,
+            healthy: error.is_none(),
+            error,
+        }
+    }
 
-        let result = timeout(
-            self.timeout_duration,
-            self.client.request(
+    /// Fetch the test URL through an HTTP proxy, expecting a 200
+    async fn http_get(&self, proxy: &ProxyInfo) -> Result<()> {
+        let response = self
+            .client
+            .request(
                 reqwest::Method::GET,
                 &self.test_url,
                 None,
                 None,
                 Some(proxy.clone()),
-            ),
-        )
-        .await;
-
-        match result {
-            Ok(Ok(response)) => {
-                if response.status == 200 {
-                    debug!(
-                        "Proxy {}:{} is healthy (status: {})",
-                        proxy.host, proxy.port, response.status
-                    );
-                    true
-                } else {
-                    warn!(
-                        "Proxy {}:{} returned non-200 status: {}",
-                        proxy.host, proxy.port, response.status
-                    );
-                    false
-                }
-            }
-            Ok(Err(e)) => {
-                warn!(
-                    "Proxy {}:{} health check failed: {}",
-                    proxy.host, proxy.port, e
-                );
-                false
-            }
-            Err(_) => {
-                warn!(
-                    "Proxy {}:{} health check timed out after {:?}",
-                    proxy.host, proxy.port, self.timeout_duration
-                );
-                false
+            )
+            .await?;
+
+        if response.status != 200 {
+            bail!("returned non-200 status: {}", response.status);
+        }
+        Ok(())
+    }
+
+    /// Negotiate with a SOCKS5 proxy and ask it to CONNECT to the test URL's host
+    async fn socks5_connect(&self, proxy: &ProxyInfo) -> Result<()> {
+        let target = reqwest::Url::parse(&self.test_url).context("Invalid test URL")?;
+        let host = target.host_str().context("Test URL has no host")?;
+        let port = target
+            .port_or_known_default()
+            .context("Test URL has no port")?;
+
+        let mut stream = TcpStream::connect((proxy.host.as_str(), proxy.port))
+            .await
+            .context("Failed to connect to proxy")?;
+
+        let credentials = proxy.username.as_deref().zip(proxy.password.as_deref());
+        let method = if credentials.is_some() {
+            SOCKS5_USER_PASS
+        } else {
+            SOCKS5_NO_AUTH
+        };
+        stream.write_all(&[SOCKS5_VERSION, 1, method]).await?;
+
+        let mut reply = [0u8; 2];
+        stream
+            .read_exact(&mut reply)
+            .await
+            .context("No SOCKS5 greeting reply")?;
+        if reply[0] != SOCKS5_VERSION {
+            bail!("not a SOCKS5 proxy (version {})", reply[0]);
+        }
+        if reply[1] != method {
+            bail!("proxy rejected authentication method {}", method);
+        }
+
+        if let Some((username, password)) = credentials {
+            let mut auth = vec![SOCKS5_AUTH_VERSION];
+            auth.push(length_byte(username, "username")?);
+            auth.extend_from_slice(username.as_bytes());
+            auth.push(length_byte(password, "password")?);
+            auth.extend_from_slice(password.as_bytes());
+            stream.write_all(&auth).await?;
+
+            stream
+                .read_exact(&mut reply)
+                .await
+                .context("No SOCKS5 authentication reply")?;
+            if reply[1] != 0 {
+                bail!("SOCKS5 authentication failed");
             }
         }
+
+        let mut request = vec![
+            SOCKS5_VERSION,
+            SOCKS5_CONNECT,
+            0,
+            SOCKS5_DOMAIN,
+            length_byte(host, "host")?,
+        ];
+        request.extend_from_slice(host.as_bytes());
+        request.extend_from_slice(&port.to_be_bytes());
+        stream.write_all(&request).await?;
+
+        // Only the reply code matters; the bound address that follows is ignored
+        let mut header = [0u8; 4];
+        stream
+            .read_exact(&mut header)
+            .await
+            .context("No SOCKS5 CONNECT reply")?;
+        if header[1] != 0 {
+            bail!(
+                "proxy refused CONNECT to {}:{} (reply code {})",
+                host,
+                port,
+                header[1]
+            );
+        }
+        Ok(())
     }
 
     /// Check health of all proxies in the manager
@@ -96,7 +217,7 @@ impl ProxyHealth {
         let mut healthy_count = 0;
         let mut unhealthy_count = 0;
 
-        for proxy in proxies {
+        for proxy in proxies.iter() {
             let is_healthy = self.check_proxy_health(proxy).await;
 
             if is_healthy {
@@ -153,7 +274,7 @@ impl ProxyHealth {
         let mut unhealthy_proxies = Vec::new();
 
         // Find currently unhealthy proxies
-        for proxy in all_proxies {
+        for proxy in all_proxies.iter() {
             if !manager.is_proxy_healthy(proxy).await {
                 unhealthy_proxies.push(proxy.clone());
             }
@@ -206,7 +327,7 @@ impl ProxyHealth {
 
         let start_time = std::time::Instant::now();
 
-        for proxy in proxies {
+        for proxy in proxies.iter() {
             let is_healthy = self.check_proxy_health(proxy).await;
 
             if is_healthy {
@@ -250,6 +371,11 @@ impl ProxyHealth {
     }
 }
 
+/// Length prefix for a SOCKS5 field, which must fit in one byte
+fn length_byte(value: &str, field: &str) -> Result<u8> {
+    u8::try_from(value.len()).with_context(|| format!("SOCKS5 {} is too long", field))
+}
+
 /// Health check report with detailed results
 #[derive(Debug, Clone)]
 pub struct HealthReport {
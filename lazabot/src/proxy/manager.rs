@@ -1,22 +1,220 @@
-use crate::api::ProxyInfo;
+use super::health::ProxyHealth;
+use crate::api::{ApiClient, ProxyInfo, ProxyType};
 use anyhow::{Context, Result};
-use std::collections::HashMap;
+use futures::stream::{self, StreamExt};
+use parking_lot::Mutex;
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Deserializer};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
 use tracing::{debug, info, warn};
 
+/// Summary of re-probing every proxy
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HealthRefresh {
+    pub checked: usize,
+    pub healthy: usize,
+    /// Proxies that were unhealthy and now pass
+    pub recovered: usize,
+    /// Proxies that were healthy and now fail
+    pub failed: usize,
+}
+
+impl HealthRefresh {
+    /// Number of proxies whose health changed
+    pub fn flipped(&self) -> usize {
+        self.recovered + self.failed
+    }
+}
+
+/// When to disable a proxy based on its recent request outcomes
+#[derive(Debug, Clone, Copy)]
+pub struct FailurePolicy {
+    /// Number of most recent uses considered
+    pub window: usize,
+    /// Failure rate above which a proxy is disabled, once the window is full
+    pub max_failure_rate: f64,
+    /// How long a disabled proxy sits out before it is used again
+    pub cooldown: Duration,
+}
+
+impl Default for FailurePolicy {
+    fn default() -> Self {
+        Self {
+            window: 20,
+            max_failure_rate: 0.5,
+            cooldown: Duration::from_secs(300),
+        }
+    }
+}
+
+/// Recent outcomes of requests made through a proxy
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ProxyStats {
+    pub successes: usize,
+    pub failures: usize,
+    /// Failure rate over the tracked window, 0.0 when unused
+    pub failure_rate: f64,
+    /// Remaining cooldown if the proxy was disabled for failing too often
+    pub disabled_for: Option<Duration>,
+}
+
+#[derive(Debug, Default)]
+struct ProxyUsage {
+    /// `true` for success, newest last
+    outcomes: VecDeque<bool>,
+    disabled_until: Option<Instant>,
+}
+
+/// One proxy as exported by a provider, in CSV or JSON. Field names vary between
+/// providers, so the common spellings are accepted.
+#[derive(Debug, Deserialize)]
+struct ProviderProxy {
+    #[serde(alias = "ip", alias = "address", alias = "proxy_address")]
+    host: String,
+    #[serde(alias = "proxy_port", deserialize_with = "port_from_number_or_string")]
+    port: u16,
+    #[serde(default, alias = "user", alias = "login")]
+    username: Option<String>,
+    #[serde(default, alias = "pass")]
+    password: Option<String>,
+    #[serde(default, rename = "type", alias = "protocol", alias = "proxy_type")]
+    proxy_type: Option<String>,
+}
+
+impl ProviderProxy {
+    fn into_proxy_info(self) -> Result<ProxyInfo> {
+        let host = self.host.trim();
+        if host.is_empty() {
+            anyhow::bail!("empty host");
+        }
+        let proxy_type = match self.proxy_type.as_deref().map(str::trim) {
+            None | Some("") => ProxyType::Http,
+            Some(name) => ProxyType::from_scheme(name)
+                .with_context(|| format!("unsupported proxy type '{}'", name))?,
+        };
+
+        let proxy = ProxyInfo::new(host.to_string(), self.port).with_type(proxy_type);
+        let non_empty = |value: Option<String>| value.filter(|v| !v.is_empty());
+        Ok(match (non_empty(self.username), non_empty(self.password)) {
+            (Some(username), Some(password)) => proxy.with_auth(username, password),
+            _ => proxy,
+        })
+    }
+}
+
+/// Split `host:port[:...]` into its fields. An IPv6 host must be bracketed, as in
+/// `[::1]:8080`, and is returned without the brackets.
+fn split_proxy_fields(address: &str) -> Result<Vec<&str>> {
+    let Some(bracketed) = address.strip_prefix('[') else {
+        return Ok(address.split(':').collect());
+    };
+
+    let (host, rest) = bracketed
+        .split_once(']')
+        .context("missing closing ']' after IPv6 host")?;
+    if host.parse::<std::net::Ipv6Addr>().is_err() {
+        anyhow::bail!("'{}' is not an IPv6 address", host);
+    }
+    let rest = rest
+        .strip_prefix(':')
+        .context("expected ':' and a port after IPv6 host")?;
+
+    let mut fields = vec![host];
+    fields.extend(rest.split(':'));
+    Ok(fields)
+}
+
+/// Providers export ports as either numbers or strings
+fn port_from_number_or_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u16, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Port {
+        Number(u16),
+        Text(String),
+    }
+
+    match Port::deserialize(deserializer)? {
+        Port::Number(port) => Ok(port),
+        Port::Text(text) => text.trim().parse().map_err(serde::de::Error::custom),
+    }
+}
+
+/// Provider URL serving the current proxy list, in the line or JSON format
+#[derive(Debug, Clone)]
+pub struct ProxySubscription {
+    url: String,
+    headers: HeaderMap,
+}
+
+impl ProxySubscription {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            headers: HeaderMap::new(),
+        }
+    }
+
+    /// Send these headers with every fetch, e.g. the provider's API key
+    pub fn with_headers(mut self, headers: HeaderMap) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// Download and parse the proxy list
+    async fn fetch(&self) -> Result<Vec<ProxyInfo>> {
+        let client = ApiClient::new(None)?;
+        let response = client
+            .request(
+                reqwest::Method::GET,
+                &self.url,
+                Some(self.headers.clone()),
+                None,
+                None,
+            )
+            .await
+            .with_context(|| format!("Failed to fetch proxy subscription {}", self.url))?;
+        if !response.is_success() {
+            anyhow::bail!(
+                "Proxy subscription {} returned status {}",
+                self.url,
+                response.status
+            );
+        }
+
+        let content = String::from_utf8_lossy(&response.body);
+        let proxies = if content.trim_start().starts_with('[') {
+            ProxyManager::parse_json(&content)?
+        } else {
+            ProxyManager::parse_proxies(&content)?
+        };
+        if proxies.is_empty() {
+            anyhow::bail!("No valid proxies found in subscription {}", self.url);
+        }
+        Ok(proxies)
+    }
+}
+
 /// Thread-safe proxy manager with round-robin selection and health tracking
 #[derive(Debug)]
 pub struct ProxyManager {
-    /// List of available proxies
-    proxies: Vec<ProxyInfo>,
+    /// List of available proxies, replaced wholesale when a subscription refreshes
+    proxies: parking_lot::RwLock<Arc<Vec<ProxyInfo>>>,
     /// Current index for round-robin selection
     current_index: AtomicUsize,
     /// Health status of each proxy (proxy_id -> is_healthy)
     health_status: Arc<RwLock<HashMap<String, bool>>>,
-    /// Total number of proxies
-    total_proxies: usize,
+    failure_policy: FailurePolicy,
+    /// Rolling request outcomes per proxy_id
+    usage: Mutex<HashMap<String, ProxyUsage>>,
+    /// Proxies (by proxy_id) kept in the list but no longer handed out
+    drained: Mutex<HashSet<String>>,
+    /// Where the proxy list came from, if it can be re-fetched
+    subscription: Option<ProxySubscription>,
 }
 
 impl ProxyManager {
@@ -47,16 +245,118 @@ impl ProxyManager {
         info!("Loaded {} proxies from {}", total_proxies, file_path);
 
         Ok(Self {
-            proxies,
+            proxies: parking_lot::RwLock::new(Arc::new(proxies)),
             current_index: AtomicUsize::new(0),
             health_status,
-            total_proxies,
+            failure_policy: FailurePolicy::default(),
+            usage: Mutex::new(HashMap::new()),
+            drained: Mutex::new(HashSet::new()),
+            subscription: None,
         })
     }
 
+    /// Create a ProxyManager from the proxy list served at `url`
+    pub async fn from_subscription_url(url: &str) -> Result<Self> {
+        Self::from_subscription(ProxySubscription::new(url)).await
+    }
+
+    /// Create a ProxyManager from a provider subscription; see `refresh_from_subscription`
+    pub async fn from_subscription(subscription: ProxySubscription) -> Result<Self> {
+        let proxies = subscription.fetch().await?;
+        info!(
+            "Loaded {} proxies from subscription {}",
+            proxies.len(),
+            subscription.url
+        );
+        Ok(Self {
+            subscription: Some(subscription),
+            ..Self::new(proxies)
+        })
+    }
+
+    /// Re-fetch the subscription and replace the proxy list. Proxies that are still
+    /// listed keep their health; new ones start healthy. Returns the new proxy count.
+    pub async fn refresh_from_subscription(&self) -> Result<usize> {
+        let subscription = self
+            .subscription
+            .as_ref()
+            .context("Proxy manager was not created from a subscription")?;
+        let proxies = subscription.fetch().await?;
+        let count = proxies.len();
+        self.replace_proxies(proxies).await;
+        info!(
+            "Refreshed {} proxies from subscription {}",
+            count, subscription.url
+        );
+        Ok(count)
+    }
+
+    /// Refresh from the subscription every `every` until the manager is dropped. A failed
+    /// refresh is logged and the current proxies are kept.
+    pub fn spawn_subscription_refresh(self: &Arc<Self>, every: Duration) -> JoinHandle<()> {
+        let manager: Weak<Self> = Arc::downgrade(self);
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(every).await;
+                let Some(manager) = manager.upgrade() else {
+                    return;
+                };
+                if let Err(e) = manager.refresh_from_subscription().await {
+                    warn!(
+                        "Keeping current proxies, subscription refresh failed: {:#}",
+                        e
+                    );
+                }
+            }
+        })
+    }
+
+    async fn replace_proxies(&self, proxies: Vec<ProxyInfo>) {
+        let ids: Vec<String> = proxies
+            .iter()
+            .map(|proxy| format!("{}:{}", proxy.host, proxy.port))
+            .collect();
+
+        // Swap the list while holding the health lock so no one sees one without the other
+        let mut status = self.health_status.write().await;
+        let previous = std::mem::take(&mut *status);
+        for id in &ids {
+            let is_healthy = previous.get(id).copied().unwrap_or(true);
+            status.insert(id.clone(), is_healthy);
+        }
+        *self.proxies.write() = Arc::new(proxies);
+        self.usage.lock().retain(|id, _| ids.contains(id));
+        self.drained.lock().retain(|id| ids.contains(id));
+    }
+
+    /// Create a ProxyManager from a provider's CSV export. The header row names the
+    /// columns (host, port, username, password, type); malformed rows are skipped.
+    pub async fn from_csv(file_path: &str) -> Result<Self> {
+        let content = tokio::fs::read_to_string(file_path)
+            .await
+            .context("Failed to read proxy CSV file")?;
+        Self::from_imported(Self::parse_csv(&content)?, file_path)
+    }
+
+    /// Create a ProxyManager from a provider's JSON array of proxy objects. Malformed
+    /// entries are skipped.
+    pub async fn from_json(file_path: &str) -> Result<Self> {
+        let content = tokio::fs::read_to_string(file_path)
+            .await
+            .context("Failed to read proxy JSON file")?;
+        Self::from_imported(Self::parse_json(&content)?, file_path)
+    }
+
+    fn from_imported(proxies: Vec<ProxyInfo>, file_path: &str) -> Result<Self> {
+        if proxies.is_empty() {
+            return Err(anyhow::anyhow!("No valid proxies found in {}", file_path));
+        }
+        info!("Imported {} proxies from {}", proxies.len(), file_path);
+        Ok(Self::new(proxies))
+    }
+
     /// Create a new ProxyManager with a list of proxies
     pub fn new(proxies: Vec<ProxyInfo>) -> Self {
-        let total_proxies = proxies.len();
         let health_status = Arc::new(RwLock::new(HashMap::new()));
 
         // Initialize all proxies as healthy
@@ -69,31 +369,126 @@ impl ProxyManager {
         }
 
         Self {
-            proxies,
+            proxies: parking_lot::RwLock::new(Arc::new(proxies)),
             current_index: AtomicUsize::new(0),
             health_status,
-            total_proxies,
+            failure_policy: FailurePolicy::default(),
+            usage: Mutex::new(HashMap::new()),
+            drained: Mutex::new(HashSet::new()),
+            subscription: None,
+        }
+    }
+
+    /// Disable proxies whose recent failure rate exceeds `policy`
+    pub fn with_failure_policy(mut self, policy: FailurePolicy) -> Self {
+        self.failure_policy = policy;
+        self
+    }
+
+    /// Record the outcome of a request made through `proxy`. Disables the proxy when its
+    /// failure rate over the policy window is too high.
+    pub async fn record_result(&self, proxy: &ProxyInfo, success: bool) {
+        let proxy_id = format!("{}:{}", proxy.host, proxy.port);
+        let policy = self.failure_policy;
+
+        let disable = {
+            let mut usage = self.usage.lock();
+            let entry = usage.entry(proxy_id.clone()).or_default();
+            entry.outcomes.push_back(success);
+            while entry.outcomes.len() > policy.window {
+                entry.outcomes.pop_front();
+            }
+
+            let failures = entry.outcomes.iter().filter(|&&ok| !ok).count();
+            let failure_rate = failures as f64 / entry.outcomes.len() as f64;
+            if entry.outcomes.len() >= policy.window && failure_rate > policy.max_failure_rate {
+                entry.outcomes.clear();
+                entry.disabled_until = Some(Instant::now() + policy.cooldown);
+                true
+            } else {
+                false
+            }
+        };
+
+        if disable {
+            warn!(
+                "Proxy {} failed too often, disabling for {:?}",
+                proxy_id, policy.cooldown
+            );
+            self.health_status.write().await.insert(proxy_id, false);
+        }
+    }
+
+    /// Recent success and failure counts for `proxy`
+    pub fn proxy_stats(&self, proxy: &ProxyInfo) -> ProxyStats {
+        let proxy_id = format!("{}:{}", proxy.host, proxy.port);
+        let usage = self.usage.lock();
+        let Some(entry) = usage.get(&proxy_id) else {
+            return ProxyStats::default();
+        };
+
+        let failures = entry.outcomes.iter().filter(|&&ok| !ok).count();
+        ProxyStats {
+            successes: entry.outcomes.len() - failures,
+            failures,
+            failure_rate: if entry.outcomes.is_empty() {
+                0.0
+            } else {
+                failures as f64 / entry.outcomes.len() as f64
+            },
+            disabled_for: entry
+                .disabled_until
+                .map(|until| until.saturating_duration_since(Instant::now()))
+                .filter(|remaining| !remaining.is_zero()),
+        }
+    }
+
+    /// Re-enable proxies whose failure cooldown has passed
+    async fn recover_cooled_down(&self) {
+        let recovered: Vec<String> = {
+            let now = Instant::now();
+            let mut usage = self.usage.lock();
+            usage
+                .iter_mut()
+                .filter(|(_, entry)| entry.disabled_until.is_some_and(|until| until <= now))
+                .map(|(proxy_id, entry)| {
+                    entry.disabled_until = None;
+                    proxy_id.clone()
+                })
+                .collect()
+        };
+
+        if recovered.is_empty() {
+            return;
+        }
+
+        let mut status = self.health_status.write().await;
+        for proxy_id in recovered {
+            info!("Proxy {} cooldown elapsed, re-enabling", proxy_id);
+            status.insert(proxy_id, true);
         }
     }
 
     /// Get the next available proxy using round-robin selection
     /// Only returns healthy proxies
     pub async fn get_next_proxy(&self) -> Option<ProxyInfo> {
-        if self.total_proxies == 0 {
+        let proxies = self.get_all_proxies();
+        if proxies.is_empty() {
             return None;
         }
 
+        self.recover_cooled_down().await;
+
         let mut attempts = 0;
-        let max_attempts = self.total_proxies;
+        let max_attempts = proxies.len();
 
         while attempts < max_attempts {
-            let current_idx =
-                self.current_index.fetch_add(1, Ordering::Relaxed) % self.total_proxies;
-            let proxy = &self.proxies[current_idx];
+            let current_idx = self.current_index.fetch_add(1, Ordering::Relaxed) % proxies.len();
+            let proxy = &proxies[current_idx];
             let proxy_id = format!("{}:{}", proxy.host, proxy.port);
 
             // Check if this proxy is healthy
-            {
+            if !self.drained.lock().contains(&proxy_id) {
                 let status = self.health_status.read().await;
                 if status.get(&proxy_id).copied().unwrap_or(false) {
                     debug!("Selected proxy: {}:{}", proxy.host, proxy.port);
@@ -108,9 +503,70 @@ impl ProxyManager {
         None
     }
 
+    /// Get the next healthy proxy tagged with `region`, round-robin. Falls back to any
+    /// healthy proxy when none in the region is available.
+    pub async fn get_next_proxy_in_region(&self, region: &str) -> Option<ProxyInfo> {
+        let proxies = self.get_all_proxies();
+        if proxies.is_empty() {
+            return None;
+        }
+
+        self.recover_cooled_down().await;
+
+        {
+            let status = self.health_status.read().await;
+            for _ in 0..proxies.len() {
+                let current_idx =
+                    self.current_index.fetch_add(1, Ordering::Relaxed) % proxies.len();
+                let proxy = &proxies[current_idx];
+                let proxy_id = format!("{}:{}", proxy.host, proxy.port);
+
+                if proxy.in_region(region)
+                    && status.get(&proxy_id).copied().unwrap_or(false)
+                    && !self.drained.lock().contains(&proxy_id)
+                {
+                    debug!(
+                        "Selected proxy {}:{} in region {}",
+                        proxy.host, proxy.port, region
+                    );
+                    return Some(proxy.clone());
+                }
+            }
+        }
+
+        warn!(
+            "No healthy proxy in region {}, falling back to any region",
+            region
+        );
+        self.get_next_proxy().await
+    }
+
     /// Get a specific proxy by index
-    pub fn get_proxy_by_index(&self, index: usize) -> Option<&ProxyInfo> {
-        self.proxies.get(index)
+    pub fn get_proxy_by_index(&self, index: usize) -> Option<ProxyInfo> {
+        self.proxies.read().get(index).cloned()
+    }
+
+    /// Stop handing out `proxy` while leaving it in the list, so requests already
+    /// using it can finish
+    pub fn drain(&self, proxy: &ProxyInfo) {
+        let proxy_id = format!("{}:{}", proxy.host, proxy.port);
+        if self.drained.lock().insert(proxy_id) {
+            info!("Draining proxy {}:{}", proxy.host, proxy.port);
+        }
+    }
+
+    /// Hand out a drained proxy again
+    pub fn undrain(&self, proxy: &ProxyInfo) {
+        let proxy_id = format!("{}:{}", proxy.host, proxy.port);
+        if self.drained.lock().remove(&proxy_id) {
+            info!("Proxy {}:{} is no longer drained", proxy.host, proxy.port);
+        }
+    }
+
+    /// Whether `proxy` is drained
+    pub fn is_drained(&self, proxy: &ProxyInfo) -> bool {
+        let proxy_id = format!("{}:{}", proxy.host, proxy.port);
+        self.drained.lock().contains(&proxy_id)
     }
 
     /// Mark a proxy as healthy or unhealthy
@@ -128,6 +584,7 @@ impl ProxyManager {
 
     /// Get health status of a specific proxy
     pub async fn is_proxy_healthy(&self, proxy: &ProxyInfo) -> bool {
+        self.recover_cooled_down().await;
         let proxy_id = format!("{}:{}", proxy.host, proxy.port);
         let status = self.health_status.read().await;
         status.get(&proxy_id).copied().unwrap_or(false)
@@ -136,7 +593,7 @@ impl ProxyManager {
     /// Get all healthy proxies
     pub async fn get_healthy_proxies(&self) -> Vec<ProxyInfo> {
         let status = self.health_status.read().await;
-        self.proxies
+        self.get_all_proxies()
             .iter()
             .filter(|proxy| {
                 let proxy_id = format!("{}:{}", proxy.host, proxy.port);
@@ -146,14 +603,15 @@ impl ProxyManager {
             .collect()
     }
 
-    /// Get all proxies (regardless of health status)
-    pub fn get_all_proxies(&self) -> &[ProxyInfo] {
-        &self.proxies
+    /// Get all proxies (regardless of health status). The list is a snapshot that a
+    /// subscription refresh does not change.
+    pub fn get_all_proxies(&self) -> Arc<Vec<ProxyInfo>> {
+        self.proxies.read().clone()
     }
 
     /// Get total number of proxies
     pub fn total_proxies(&self) -> usize {
-        self.total_proxies
+        self.proxies.read().len()
     }
 
     /// Get number of healthy proxies
@@ -162,16 +620,254 @@ impl ProxyManager {
         status.values().filter(|&&is_healthy| is_healthy).count()
     }
 
+    /// Probe every proxy, at most `concurrency` at a time, and update the health map
+    pub async fn refresh_health(&self, concurrency: usize) -> Result<HealthRefresh> {
+        let checker = ProxyHealth::new()?;
+        Ok(self.refresh_health_with(&checker, concurrency).await)
+    }
+
+    /// Like `refresh_health`, probing with the given checker
+    pub async fn refresh_health_with(
+        &self,
+        checker: &ProxyHealth,
+        concurrency: usize,
+    ) -> HealthRefresh {
+        let proxies = self.get_all_proxies();
+        info!(
+            "Refreshing health of {} proxies ({} at a time)",
+            proxies.len(),
+            concurrency
+        );
+
+        let results: Vec<(&ProxyInfo, bool)> = stream::iter(proxies.iter())
+            .map(|proxy| async move { (proxy, checker.check_proxy_health(proxy).await) })
+            .buffer_unordered(concurrency.max(1))
+            .collect()
+            .await;
+
+        let mut summary = HealthRefresh {
+            checked: results.len(),
+            ..HealthRefresh::default()
+        };
+        let mut status = self.health_status.write().await;
+        for (proxy, is_healthy) in results {
+            let proxy_id = format!("{}:{}", proxy.host, proxy.port);
+            let was_healthy = status.insert(proxy_id, is_healthy).unwrap_or(false);
+
+            if is_healthy {
+                summary.healthy += 1;
+            }
+            match (was_healthy, is_healthy) {
+                (false, true) => summary.recovered += 1,
+                (true, false) => summary.failed += 1,
+                _ => {}
+            }
+        }
+
+        info!(
+            "Health refresh completed: {}/{} healthy, {} recovered, {} failed",
+            summary.healthy, summary.checked, summary.recovered, summary.failed
+        );
+        summary
+    }
+
     /// Reset all proxies to healthy status
     pub async fn reset_all_health(&self) {
         let mut status = self.health_status.write().await;
-        for proxy in &self.proxies {
+        for proxy in self.get_all_proxies().iter() {
             let proxy_id = format!("{}:{}", proxy.host, proxy.port);
             status.insert(proxy_id, true);
         }
         info!("Reset all proxies to healthy status");
     }
 
+    /// Append a `host:port[:user:pass]` entry to a proxy file, creating it if absent.
+    /// Returns the total number of proxies in the file afterwards.
+    pub async fn add_to_file(file_path: &str, entry: &str) -> Result<usize> {
+        let entry = entry.trim();
+        let proxy = Self::parse_proxy_entry(entry)?;
+
+        let content = match tokio::fs::read_to_string(file_path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e).context("Failed to read proxy file"),
+        };
+
+        let mut proxies = Self::parse_proxies(&content)?;
+        if proxies
+            .iter()
+            .any(|p| p.host == proxy.host && p.port == proxy.port)
+        {
+            anyhow::bail!(
+                "Proxy {}:{} already exists in {}",
+                proxy.host,
+                proxy.port,
+                file_path
+            );
+        }
+
+        if let Some(parent) = std::path::Path::new(file_path).parent() {
+            if !parent.as_os_str().is_empty() {
+                tokio::fs::create_dir_all(parent)
+                    .await
+                    .context("Failed to create proxy file directory")?;
+            }
+        }
+
+        let mut new_content = content;
+        if !new_content.is_empty() && !new_content.ends_with('\n') {
+            new_content.push('\n');
+        }
+        new_content.push_str(entry);
+        new_content.push('\n');
+
+        tokio::fs::write(file_path, new_content)
+            .await
+            .context("Failed to write proxy file")?;
+
+        info!("Added proxy {}:{} to {}", proxy.host, proxy.port, file_path);
+        proxies.push(proxy);
+        Ok(proxies.len())
+    }
+
+    /// Remove the entry matching `host:port` from a proxy file, ignoring any auth suffix.
+    /// Returns whether an entry was removed.
+    pub async fn remove_from_file(file_path: &str, target: &str) -> Result<bool> {
+        let parts = split_proxy_fields(target.trim())?;
+        if parts.len() < 2 || parts[0].is_empty() {
+            anyhow::bail!("Invalid proxy '{}': expected host:port", target);
+        }
+        let host = parts[0];
+        let port = parts[1]
+            .parse::<u16>()
+            .context(format!("Invalid port number in '{}'", target))?;
+
+        let content = tokio::fs::read_to_string(file_path)
+            .await
+            .context("Failed to read proxy file")?;
+
+        let mut removed = false;
+        let mut new_content = String::with_capacity(content.len());
+        for line in content.lines() {
+            let trimmed = line.trim();
+            let is_match = !trimmed.starts_with('#')
+                && split_proxy_fields(trimmed).is_ok_and(|fields| {
+                    fields.first() == Some(&host)
+                        && fields.get(1).and_then(|p| p.parse::<u16>().ok()) == Some(port)
+                });
+
+            if is_match {
+                removed = true;
+                continue;
+            }
+            new_content.push_str(line);
+            new_content.push('\n');
+        }
+
+        if removed {
+            tokio::fs::write(file_path, new_content)
+                .await
+                .context("Failed to write proxy file")?;
+            info!("Removed proxy {}:{} from {}", host, port, file_path);
+        }
+
+        Ok(removed)
+    }
+
+    /// Parse and validate a single `[scheme://]host:port[:user:pass]` entry; the scheme
+    /// (`http` or `socks5`) defaults to HTTP
+    pub fn parse_proxy_entry(entry: &str) -> Result<ProxyInfo> {
+        let (proxy_type, address) = match entry.trim().split_once("://") {
+            Some((scheme, address)) => (
+                ProxyType::from_scheme(scheme).with_context(|| {
+                    format!("Unsupported proxy scheme '{}' in '{}'", scheme, entry)
+                })?,
+                address,
+            ),
+            None => (ProxyType::Http, entry.trim()),
+        };
+        let parts = split_proxy_fields(address)
+            .with_context(|| format!("Invalid proxy format '{}'", entry))?;
+
+        if ![2, 4, 5].contains(&parts.len()) {
+            anyhow::bail!(
+                "Invalid proxy format '{}': expected host:port, host:port:username:password \
+                 or host:port:username:password:region",
+                entry
+            );
+        }
+        // Username and password may both be left empty when only a region is given
+        let no_auth = parts.len() == 5 && parts[2].is_empty() && parts[3].is_empty();
+        let empty_field = parts
+            .iter()
+            .enumerate()
+            .any(|(i, part)| part.is_empty() && !(no_auth && (i == 2 || i == 3)));
+        if empty_field {
+            anyhow::bail!("Invalid proxy format '{}': empty field", entry);
+        }
+
+        let port = parts[1]
+            .parse::<u16>()
+            .context(format!("Invalid port number in '{}'", entry))?;
+        let mut proxy = ProxyInfo::new(parts[0].to_string(), port).with_type(proxy_type);
+
+        if parts.len() >= 4 && !no_auth {
+            proxy = proxy.with_auth(parts[2].to_string(), parts[3].to_string());
+        }
+        if parts.len() == 5 {
+            proxy = proxy.with_region(parts[4].to_string());
+        }
+        Ok(proxy)
+    }
+
+    /// Parse a provider CSV export; header names are matched case-insensitively
+    fn parse_csv(content: &str) -> Result<Vec<ProxyInfo>> {
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .flexible(true)
+            .from_reader(content.as_bytes());
+        let headers = csv::StringRecord::from_iter(
+            reader
+                .headers()
+                .context("Failed to read proxy CSV header")?
+                .iter()
+                .map(str::to_lowercase),
+        );
+
+        let mut proxies = Vec::new();
+        for (index, record) in reader.records().enumerate() {
+            // The header is line 1
+            let line = index + 2;
+            let proxy = record
+                .map_err(anyhow::Error::from)
+                .and_then(|record| Ok(record.deserialize::<ProviderProxy>(Some(&headers))?))
+                .and_then(ProviderProxy::into_proxy_info);
+            match proxy {
+                Ok(proxy) => proxies.push(proxy),
+                Err(e) => warn!("Skipping malformed proxy on CSV line {}: {}", line, e),
+            }
+        }
+        Ok(proxies)
+    }
+
+    /// Parse a provider JSON export: an array of proxy objects
+    fn parse_json(content: &str) -> Result<Vec<ProxyInfo>> {
+        let entries: Vec<serde_json::Value> =
+            serde_json::from_str(content).context("Proxy JSON must be an array")?;
+
+        let mut proxies = Vec::new();
+        for (index, entry) in entries.into_iter().enumerate() {
+            let proxy = serde_json::from_value::<ProviderProxy>(entry)
+                .map_err(anyhow::Error::from)
+                .and_then(ProviderProxy::into_proxy_info);
+            match proxy {
+                Ok(proxy) => proxies.push(proxy),
+                Err(e) => warn!("Skipping malformed proxy at JSON index {}: {}", index, e),
+            }
+        }
+        Ok(proxies)
+    }
+
     /// Parse proxy list from file content
     fn parse_proxies(content: &str) -> Result<Vec<ProxyInfo>> {
         let mut proxies = Vec::new();
@@ -184,35 +880,11 @@ impl ProxyManager {
                 continue;
             }
 
-            // Parse proxy format: host:port or host:port:username:password
-            let parts: Vec<&str> = line.split(':').collect();
-
-            match parts.len() {
-                2 => {
-                    // Format: host:port
-                    let host = parts[0].to_string();
-                    let port = parts[1]
-                        .parse::<u16>()
-                        .context(format!("Invalid port number on line {}", line_num + 1))?;
-
-                    proxies.push(ProxyInfo::new(host, port));
-                }
-                4 => {
-                    // Format: host:port:username:password
-                    let host = parts[0].to_string();
-                    let port = parts[1]
-                        .parse::<u16>()
-                        .context(format!("Invalid port number on line {}", line_num + 1))?;
-                    let username = parts[2].to_string();
-                    let password = parts[3].to_string();
-
-                    proxies.push(ProxyInfo::new(host, port).with_auth(username, password));
-                }
-                _ => {
-                    warn!("Invalid proxy format on line {}: {}", line_num + 1, line);
-                    continue;
-                }
-            }
+            // host:port, host:port:username:password or host:port:username:password:region,
+            // optionally prefixed with a scheme such as socks5://
+            let proxy = Self::parse_proxy_entry(line)
+                .context(format!("Invalid proxy on line {}", line_num + 1))?;
+            proxies.push(proxy);
         }
 
         Ok(proxies)
@@ -269,7 +941,7 @@ mod tests {
         let manager = ProxyManager::new(proxies);
 
         // Mark first proxy as unhealthy
-        let proxy1 = &manager.proxies[0];
+        let proxy1 = &manager.get_all_proxies()[0];
         manager.set_proxy_health(proxy1, false).await;
 
         // Should only return healthy proxies
@@ -278,6 +950,43 @@ mod tests {
         assert_eq!(healthy_proxies[0].host, "192.168.1.1");
     }
 
+    #[tokio::test]
+    async fn test_failure_rate_disables_and_recovers() {
+        let proxies = vec![
+            ProxyInfo::new("127.0.0.1".to_string(), 8080),
+            ProxyInfo::new("192.168.1.1".to_string(), 3128),
+        ];
+        let manager = ProxyManager::new(proxies).with_failure_policy(FailurePolicy {
+            window: 4,
+            max_failure_rate: 0.5,
+            cooldown: Duration::from_millis(50),
+        });
+        let flaky = manager.get_all_proxies()[0].clone();
+
+        // Occasional failures keep the proxy in rotation
+        for success in [true, false, true, true, false, true] {
+            manager.record_result(&flaky, success).await;
+        }
+        assert!(manager.is_proxy_healthy(&flaky).await);
+        let stats = manager.proxy_stats(&flaky);
+        assert_eq!((stats.successes, stats.failures), (3, 1));
+
+        // Mostly failing disables it: the window is now 3 of 4 failures
+        for success in [false, false] {
+            manager.record_result(&flaky, success).await;
+        }
+        assert!(!manager.is_proxy_healthy(&flaky).await);
+        assert!(manager.proxy_stats(&flaky).disabled_for.is_some());
+        assert_eq!(manager.get_next_proxy().await.unwrap().host, "192.168.1.1");
+        assert_eq!(manager.get_next_proxy().await.unwrap().host, "192.168.1.1");
+
+        // After the cooldown it is back in rotation
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(manager.is_proxy_healthy(&flaky).await);
+        assert_eq!(manager.proxy_stats(&flaky), ProxyStats::default());
+        assert_eq!(manager.healthy_proxies_count().await, 2);
+    }
+
     #[test]
     fn test_parse_proxies() {
         let content =
@@ -302,4 +1011,170 @@ mod tests {
         assert_eq!(proxies[2].username, Some("user".to_string()));
         assert_eq!(proxies[2].password, Some("pass".to_string()));
     }
+
+    #[test]
+    fn test_parse_proxy_entry() {
+        let proxy = ProxyManager::parse_proxy_entry("10.0.0.1:8080:user:pass").unwrap();
+        assert_eq!(proxy.host, "10.0.0.1");
+        assert_eq!(proxy.username, Some("user".to_string()));
+
+        assert!(ProxyManager::parse_proxy_entry("10.0.0.1").is_err());
+        assert!(ProxyManager::parse_proxy_entry("10.0.0.1:notaport").is_err());
+        assert!(ProxyManager::parse_proxy_entry("10.0.0.1:8080:user").is_err());
+        assert!(ProxyManager::parse_proxy_entry(":8080").is_err());
+    }
+
+    #[test]
+    fn test_parse_proxy_scheme() {
+        let proxy = ProxyManager::parse_proxy_entry("10.0.0.1:8080").unwrap();
+        assert_eq!(proxy.proxy_type, ProxyType::Http);
+
+        let proxy = ProxyManager::parse_proxy_entry("socks5://10.0.0.1:1080:user:pass").unwrap();
+        assert_eq!(proxy.proxy_type, ProxyType::Socks5);
+        assert_eq!(proxy.host, "10.0.0.1");
+        assert_eq!(proxy.port, 1080);
+        assert_eq!(proxy.username, Some("user".to_string()));
+
+        let proxy = ProxyManager::parse_proxy_entry("HTTP://10.0.0.1:8080").unwrap();
+        assert_eq!(proxy.proxy_type, ProxyType::Http);
+
+        assert!(ProxyManager::parse_proxy_entry("ftp://10.0.0.1:21").is_err());
+
+        let proxies =
+            ProxyManager::parse_proxies("10.0.0.1:8080\nsocks5://10.0.0.2:1080:user:pass").unwrap();
+        assert_eq!(proxies.len(), 2);
+        assert_eq!(proxies[1].proxy_type, ProxyType::Socks5);
+        assert_eq!(proxies[1].password, Some("pass".to_string()));
+    }
+
+    #[test]
+    fn test_parse_proxy_region() {
+        let content = "10.0.0.1:8080:user:pass:SG\n10.0.0.2:8080:::MY\n10.0.0.3:8080";
+
+        let proxies = ProxyManager::parse_proxies(content).unwrap();
+        assert_eq!(proxies.len(), 3);
+
+        assert_eq!(proxies[0].username, Some("user".to_string()));
+        assert_eq!(proxies[0].region, Some("SG".to_string()));
+        assert!(proxies[1].username.is_none());
+        assert!(proxies[1].password.is_none());
+        assert_eq!(proxies[1].region, Some("MY".to_string()));
+        assert!(proxies[2].region.is_none());
+
+        assert!(ProxyManager::parse_proxy_entry("10.0.0.1:8080:user:pass:").is_err());
+        assert!(ProxyManager::parse_proxy_entry("10.0.0.1:8080:user::SG").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_region_selection() {
+        let proxies = vec![
+            ProxyInfo::new("10.0.0.1".to_string(), 8080).with_region("SG".to_string()),
+            ProxyInfo::new("10.0.0.2".to_string(), 8080).with_region("MY".to_string()),
+            ProxyInfo::new("10.0.0.3".to_string(), 8080).with_region("sg".to_string()),
+            ProxyInfo::new("10.0.0.4".to_string(), 8080),
+        ];
+        let manager = ProxyManager::new(proxies);
+
+        // Round-robin within the region, matching case-insensitively
+        let first = manager.get_next_proxy_in_region("SG").await.unwrap();
+        let second = manager.get_next_proxy_in_region("SG").await.unwrap();
+        let third = manager.get_next_proxy_in_region("SG").await.unwrap();
+        assert_eq!(first.host, "10.0.0.1");
+        assert_eq!(second.host, "10.0.0.3");
+        assert_eq!(third.host, "10.0.0.1");
+
+        // Unhealthy proxies are skipped
+        manager
+            .set_proxy_health(&manager.get_all_proxies()[1], false)
+            .await;
+        let fallback = manager.get_next_proxy_in_region("MY").await.unwrap();
+        assert_ne!(fallback.host, "10.0.0.2");
+
+        // Unknown regions fall back to any healthy proxy
+        assert!(manager.get_next_proxy_in_region("TH").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_add_to_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("proxies.txt");
+        let path = path.to_str().unwrap();
+
+        // File is created when absent
+        let total = ProxyManager::add_to_file(path, "127.0.0.1:8080")
+            .await
+            .unwrap();
+        assert_eq!(total, 1);
+
+        let total = ProxyManager::add_to_file(path, "10.0.0.1:3128:user:pass")
+            .await
+            .unwrap();
+        assert_eq!(total, 2);
+
+        let content = std::fs::read_to_string(path).unwrap();
+        assert_eq!(content.lines().count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_add_to_file_rejects_duplicates() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("proxies.txt");
+        let path = path.to_str().unwrap();
+
+        ProxyManager::add_to_file(path, "127.0.0.1:8080")
+            .await
+            .unwrap();
+
+        // Same host:port with different credentials is still a duplicate
+        assert!(ProxyManager::add_to_file(path, "127.0.0.1:8080")
+            .await
+            .is_err());
+        assert!(ProxyManager::add_to_file(path, "127.0.0.1:8080:user:pass")
+            .await
+            .is_err());
+        assert!(ProxyManager::add_to_file(path, "not-a-proxy")
+            .await
+            .is_err());
+
+        let content = std::fs::read_to_string(path).unwrap();
+        assert_eq!(content.lines().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_remove_from_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("proxies.txt");
+        let path = path.to_str().unwrap();
+        std::fs::write(
+            path,
+            "# proxies\n127.0.0.1:8080\n1.2.3.4:8080:user:pass\n1.2.3.4:3128\n",
+        )
+        .unwrap();
+
+        // Auth suffix is ignored when matching
+        assert!(ProxyManager::remove_from_file(path, "1.2.3.4:8080")
+            .await
+            .unwrap());
+
+        let content = std::fs::read_to_string(path).unwrap();
+        assert_eq!(content, "# proxies\n127.0.0.1:8080\n1.2.3.4:3128\n");
+    }
+
+    #[tokio::test]
+    async fn test_remove_from_file_no_match() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("proxies.txt");
+        let path = path.to_str().unwrap();
+        std::fs::write(path, "127.0.0.1:8080\n").unwrap();
+
+        assert!(!ProxyManager::remove_from_file(path, "127.0.0.1:9090")
+            .await
+            .unwrap());
+        assert!(ProxyManager::remove_from_file(path, "nonsense")
+            .await
+            .is_err());
+
+        let content = std::fs::read_to_string(path).unwrap();
+        assert_eq!(content, "127.0.0.1:8080\n");
+    }
 }
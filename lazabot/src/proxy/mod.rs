@@ -1,5 +1,5 @@
 pub mod health;
 pub mod manager;
 
-pub use health::ProxyHealth;
-pub use manager::ProxyManager;
+pub use health::{HealthCheckKind, ProxyCheck, ProxyHealth};
+pub use manager::{FailurePolicy, HealthRefresh, ProxyManager, ProxyStats, ProxySubscription};
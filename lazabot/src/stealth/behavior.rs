@@ -1,36 +1,147 @@
 use futures::stream::{Stream, StreamExt};
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 use std::time::Duration;
 use tokio::time::sleep;
+use tracing::warn;
+
+/// What a recorded delay was simulating
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DelayKind {
+    Random,
+    Mouse,
+    Reading,
+    PageLoad,
+    FormFilling,
+}
+
+/// One action produced by a `BehaviorSimulator`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BehaviorStep {
+    Delay {
+        kind: DelayKind,
+        ms: u64,
+    },
+    /// `delays_ms[i]` is the pause before the character after the `i`th one
+    Typing {
+        text: String,
+        delays_ms: Vec<u64>,
+    },
+}
+
+/// The sequence of timings a simulator produced, for replaying a session exactly
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BehaviorTrace {
+    pub steps: Vec<BehaviorStep>,
+}
+
+impl BehaviorTrace {
+    /// Sum of every delay in the trace
+    pub fn total_duration(&self) -> Duration {
+        let ms: u64 = self
+            .steps
+            .iter()
+            .map(|step| match step {
+                BehaviorStep::Delay { ms, .. } => *ms,
+                BehaviorStep::Typing { delays_ms, .. } => delays_ms.iter().sum(),
+            })
+            .sum();
+        Duration::from_millis(ms)
+    }
+}
 
 /// Behavior simulation utilities for making bot actions appear more human-like
 pub struct BehaviorSimulator {
-    rng: rand::rngs::ThreadRng,
+    rng: Box<dyn RngCore>,
+    recording: bool,
+    recorded: Vec<BehaviorStep>,
+    replaying: VecDeque<BehaviorStep>,
 }
 
 impl BehaviorSimulator {
     pub fn new() -> Self {
+        Self::from_rng(Box::new(rand::thread_rng()))
+    }
+
+    /// Create a simulator whose delays are reproducible for a given seed
+    pub fn with_seed(seed: u64) -> Self {
+        Self::from_rng(Box::new(StdRng::seed_from_u64(seed)))
+    }
+
+    /// Create a simulator that re-applies the timings in `trace`, in order. Once the
+    /// trace runs out or the caller diverges from it, delays are random again.
+    pub fn replay(trace: BehaviorTrace) -> Self {
+        let mut simulator = Self::new().with_recording();
+        simulator.replaying = trace.steps.into();
+        simulator
+    }
+
+    fn from_rng(rng: Box<dyn RngCore>) -> Self {
         Self {
-            rng: rand::thread_rng(),
+            rng,
+            recording: false,
+            recorded: Vec::new(),
+            replaying: VecDeque::new(),
+        }
+    }
+
+    /// Keep every step this simulator produces so it can be returned by `record`
+    pub fn with_recording(mut self) -> Self {
+        self.recording = true;
+        self
+    }
+
+    /// Everything this simulator has produced since recording was enabled
+    pub fn record(&self) -> BehaviorTrace {
+        BehaviorTrace {
+            steps: self.recorded.clone(),
         }
     }
 
     /// Generate a random delay between min and max milliseconds
     pub async fn random_delay(&mut self, min_ms: u64, max_ms: u64) {
-        let delay_ms = self.rng.gen_range(min_ms..=max_ms);
+        let delay_ms = self.next_delay(DelayKind::Random, min_ms, max_ms);
         sleep(Duration::from_millis(delay_ms)).await;
     }
 
     /// Simulate human-like typing with variable delays between characters
     pub fn simulate_typing(&mut self, text: &str) -> TypingStream {
-        TypingStream::new(text.to_string(), self.rng.clone())
+        let delays_ms = match self.replaying.front() {
+            Some(BehaviorStep::Typing {
+                text: recorded,
+                delays_ms,
+            }) if recorded == text => {
+                let delays_ms = delays_ms.clone();
+                self.replaying.pop_front();
+                delays_ms
+            }
+            _ => {
+                self.stop_replay();
+                text.chars()
+                    .skip(1)
+                    .map(|ch| typing_delay(&mut self.rng, ch))
+                    .collect()
+            }
+        };
+
+        if self.recording {
+            self.recorded.push(BehaviorStep::Typing {
+                text: text.to_string(),
+                delays_ms: delays_ms.clone(),
+            });
+        }
+        TypingStream::new(text.to_string(), delays_ms)
     }
 
     /// Simulate mouse movement delay (for UI interactions)
     pub async fn mouse_delay(&mut self) {
-        let delay_ms = self.rng.gen_range(100..=300);
+        let delay_ms = self.next_delay(DelayKind::Mouse, 100, 300);
         sleep(Duration::from_millis(delay_ms)).await;
     }
 
@@ -43,22 +154,53 @@ impl BehaviorSimulator {
         // Add some randomness and minimum delay
         let min_delay = 500;
         let max_delay = reading_time_ms + 1000;
-        let delay_ms = self.rng.gen_range(min_delay..=max_delay);
+        let delay_ms = self.next_delay(DelayKind::Reading, min_delay, max_delay);
 
         sleep(Duration::from_millis(delay_ms)).await;
     }
 
     /// Simulate page load waiting time
     pub async fn page_load_delay(&mut self) {
-        let delay_ms = self.rng.gen_range(1000..=3000);
+        let delay_ms = self.next_delay(DelayKind::PageLoad, 1000, 3000);
         sleep(Duration::from_millis(delay_ms)).await;
     }
 
     /// Simulate form filling delay
     pub async fn form_filling_delay(&mut self) {
-        let delay_ms = self.rng.gen_range(200..=800);
+        let delay_ms = self.next_delay(DelayKind::FormFilling, 200, 800);
         sleep(Duration::from_millis(delay_ms)).await;
     }
+
+    /// Take the next delay of `kind` from the replayed trace, or pick one in range
+    fn next_delay(&mut self, kind: DelayKind, min_ms: u64, max_ms: u64) -> u64 {
+        let delay_ms = match self.replaying.front() {
+            Some(BehaviorStep::Delay { kind: recorded, ms }) if *recorded == kind => {
+                let ms = *ms;
+                self.replaying.pop_front();
+                ms
+            }
+            _ => {
+                self.stop_replay();
+                self.rng.gen_range(min_ms..=max_ms)
+            }
+        };
+
+        if self.recording {
+            self.recorded
+                .push(BehaviorStep::Delay { kind, ms: delay_ms });
+        }
+        delay_ms
+    }
+
+    fn stop_replay(&mut self) {
+        if !self.replaying.is_empty() {
+            warn!(
+                "Behavior diverged from the replayed trace, dropping {} remaining steps",
+                self.replaying.len()
+            );
+            self.replaying.clear();
+        }
+    }
 }
 
 impl Default for BehaviorSimulator {
@@ -67,44 +209,43 @@ impl Default for BehaviorSimulator {
     }
 }
 
+/// Pause before typing `ch`
+fn typing_delay<R: Rng + ?Sized>(rng: &mut R, ch: char) -> u64 {
+    let base_delay = match ch {
+        '0'..='9' => 50,
+        'a'..='z' | 'A'..='Z' => 80,
+        '!' | '@' | '#' | '$' | '%' | '^' | '&' | '*' | '(' | ')' | '-' | '_' | '=' | '+' => 120,
+        ' ' => 30,
+        _ => 100,
+    };
+
+    let variation = rng.gen_range(0.8..=1.2);
+    let delay = (base_delay as f64 * variation) as u64;
+
+    if rng.gen_bool(0.05) {
+        delay + rng.gen_range(200..=800)
+    } else {
+        delay
+    }
+}
+
 /// A stream that yields characters with human-like typing delays
 pub struct TypingStream {
     text: String,
     position: usize,
-    rng: rand::rngs::ThreadRng,
+    delays_ms: VecDeque<u64>,
     next_delay: Option<u64>,
 }
 
 impl TypingStream {
-    fn new(text: String, rng: rand::rngs::ThreadRng) -> Self {
+    fn new(text: String, delays_ms: Vec<u64>) -> Self {
         Self {
             text,
             position: 0,
-            rng,
+            delays_ms: delays_ms.into(),
             next_delay: None,
         }
     }
-
-    fn get_typing_delay(&mut self, ch: char) -> u64 {
-        let base_delay = match ch {
-            '0'..='9' => 50,
-            'a'..='z' | 'A'..='Z' => 80,
-            '!' | '@' | '#' | '$' | '%' | '^' | '&' | '*' | '(' | ')' | '-' | '_' | '=' | '+' => {
-                120
-            }
-            ' ' => 30,
-            _ => 100,
-        };
-
-        let variation = self.rng.gen_range(0.8..=1.2);
-        let delay = (base_delay as f64 * variation) as u64;
-
-        if self.rng.gen_bool(0.05) {
-            delay + self.rng.gen_range(200..=800)
-        } else {
-            delay
-        }
-    }
 }
 
 impl Stream for TypingStream {
@@ -130,8 +271,7 @@ impl Stream for TypingStream {
         self.position += 1;
 
         if self.position < self.text.len() {
-            let next_ch = self.text.chars().nth(self.position).unwrap();
-            self.next_delay = Some(self.get_typing_delay(next_ch));
+            self.next_delay = self.delays_ms.pop_front();
         }
 
         Poll::Ready(Some(ch))
@@ -177,6 +317,37 @@ mod tests {
         assert_eq!(result, text);
     }
 
+    #[test]
+    fn test_seeded_typing_delays_are_identical() {
+        let trace = |seed| {
+            let mut simulator = BehaviorSimulator::with_seed(seed).with_recording();
+            simulator.simulate_typing("Hello, World!");
+            simulator.record()
+        };
+        assert_eq!(trace(7), trace(7));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_recorded_trace_replays_with_matching_duration() {
+        let run = |mut simulator: BehaviorSimulator| async move {
+            let start = tokio::time::Instant::now();
+            simulator.random_delay(20, 80).await;
+            collect_typing_stream(simulator.simulate_typing("pay now")).await;
+            simulator.mouse_delay().await;
+            (simulator.record(), start.elapsed())
+        };
+
+        let (recorded, recorded_elapsed) = run(BehaviorSimulator::new().with_recording()).await;
+        let json = serde_json::to_string(&recorded).unwrap();
+        let trace: BehaviorTrace = serde_json::from_str(&json).unwrap();
+        let (replayed, replayed_elapsed) = run(BehaviorSimulator::replay(trace)).await;
+
+        // With the clock paused, each run takes exactly as long as its delays add up to
+        assert_eq!(replayed, recorded);
+        assert_eq!(recorded_elapsed, recorded.total_duration());
+        assert_eq!(replayed_elapsed, recorded_elapsed);
+    }
+
     #[tokio::test]
     async fn test_typing_stream() {
         let mut simulator = BehaviorSimulator::new();
@@ -1,5 +1,6 @@
 use anyhow::Result;
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -8,7 +9,10 @@ use std::collections::HashMap;
 pub struct BrowserFingerprint {
     pub user_agent: String,
     pub timezone: String,
+    /// Primary locale, e.g. `ja-JP`
     pub language: String,
+    /// Further languages the user reads, weighted after `language` in `Accept-Language`
+    pub extra_languages: Vec<String>,
     pub screen_resolution: String,
     pub platform: String,
     pub vendor: String,
@@ -18,13 +22,26 @@ pub struct BrowserFingerprint {
     pub color_depth: u8,
     pub pixel_ratio: f32,
     pub hardware_concurrency: u8,
+    /// `UNMASKED_VENDOR_WEBGL`, matching the platform
+    pub webgl_vendor: String,
+    /// `UNMASKED_RENDERER_WEBGL`, matching the platform
+    pub webgl_renderer: String,
+    /// Seed for the per-fingerprint noise added to canvas readbacks
+    pub canvas_noise_seed: u64,
 }
 
 impl BrowserFingerprint {
     /// Generate a realistic browser fingerprint
     pub fn generate() -> Self {
-        let mut rng = rand::thread_rng();
+        Self::generate_with(&mut rand::thread_rng())
+    }
+
+    /// Generate a fingerprint from a fixed seed; the same seed always gives the same fingerprint
+    pub fn generate_seeded(seed: u64) -> Self {
+        Self::generate_with(&mut StdRng::seed_from_u64(seed))
+    }
 
+    fn generate_with<R: Rng + ?Sized>(rng: &mut R) -> Self {
         // Generate realistic user agents for different browsers
         let user_agents = vec![
             // Chrome on Windows
@@ -72,18 +89,8 @@ impl BrowserFingerprint {
 
         // Common languages
         let languages = vec![
-            "en-US,en;q=0.9",
-            "en-GB,en;q=0.9",
-            "en-CA,en;q=0.9",
-            "es-ES,es;q=0.9",
-            "fr-FR,fr;q=0.9",
-            "de-DE,de;q=0.9",
-            "it-IT,it;q=0.9",
-            "pt-BR,pt;q=0.9",
-            "ja-JP,ja;q=0.9",
-            "ko-KR,ko;q=0.9",
-            "zh-CN,zh;q=0.9",
-            "ru-RU,ru;q=0.9",
+            "en-US", "en-GB", "en-CA", "es-ES", "fr-FR", "de-DE", "it-IT", "pt-BR", "ja-JP",
+            "ko-KR", "zh-CN", "ru-RU",
         ];
 
         // Common screen resolutions
@@ -136,7 +143,17 @@ impl BrowserFingerprint {
 
         let user_agent = user_agents[rng.gen_range(0..user_agents.len())].to_string();
         let timezone = timezones[rng.gen_range(0..timezones.len())].to_string();
-        let language = languages[rng.gen_range(0..languages.len())].to_string();
+        // Most users' browser locale matches where they are
+        let language = if rng.gen_bool(0.8) {
+            timezone_locale(&timezone).to_string()
+        } else {
+            languages[rng.gen_range(0..languages.len())].to_string()
+        };
+        let extra_languages = if language.starts_with("en") {
+            Vec::new()
+        } else {
+            vec!["en-US".to_string()]
+        };
         let screen_resolution =
             screen_resolutions[rng.gen_range(0..screen_resolutions.len())].to_string();
         let platform = platforms[rng.gen_range(0..platforms.len())].to_string();
@@ -149,11 +166,14 @@ impl BrowserFingerprint {
         let pixel_ratio = pixel_ratios[rng.gen_range(0..pixel_ratios.len())];
         let hardware_concurrency =
             hardware_concurrency[rng.gen_range(0..hardware_concurrency.len())];
+        let (webgl_vendor, webgl_renderer) = webgl_profile(&platform, rng);
+        let canvas_noise_seed = rng.gen();
 
         Self {
             user_agent,
             timezone,
             language,
+            extra_languages,
             screen_resolution,
             platform,
             vendor,
@@ -163,6 +183,9 @@ impl BrowserFingerprint {
             color_depth,
             pixel_ratio,
             hardware_concurrency,
+            webgl_vendor,
+            webgl_renderer,
+            canvas_noise_seed,
         }
     }
 
@@ -171,7 +194,7 @@ impl BrowserFingerprint {
         let mut headers = HashMap::new();
 
         headers.insert("User-Agent".to_string(), self.user_agent.clone());
-        headers.insert("Accept-Language".to_string(), self.language.clone());
+        headers.insert("Accept-Language".to_string(), self.accept_language());
         headers.insert(
             "Accept-Encoding".to_string(),
             "gzip, deflate, br".to_string(),
@@ -192,6 +215,34 @@ impl BrowserFingerprint {
         headers
     }
 
+    /// Weighted `Accept-Language` value: `language` first, then `extra_languages`, each
+    /// followed by its bare language code at decreasing quality
+    pub fn accept_language(&self) -> String {
+        // Already a full header value
+        if self.language.contains(',') {
+            return self.language.clone();
+        }
+
+        let mut tags: Vec<&str> = Vec::new();
+        for locale in std::iter::once(&self.language).chain(&self.extra_languages) {
+            let base = locale.split('-').next().unwrap_or(locale);
+            for tag in [locale.as_str(), base] {
+                if !tag.is_empty() && !tags.contains(&tag) {
+                    tags.push(tag);
+                }
+            }
+        }
+
+        tags.iter()
+            .enumerate()
+            .map(|(i, tag)| match i {
+                0 => tag.to_string(),
+                _ => format!("{};q={:.1}", tag, (10 - i.min(9)) as f32 / 10.0),
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
     /// Get screen dimensions as tuple
     pub fn screen_dimensions(&self) -> Result<(u32, u32)> {
         let parts: Vec<&str> = self.screen_resolution.split('x').collect();
@@ -204,6 +255,108 @@ impl BrowserFingerprint {
 
         Ok((width, height))
     }
+
+    /// Values a browser-context injector should override, keyed by the JS property they replace
+    pub fn to_js_overrides(&self) -> HashMap<String, serde_json::Value> {
+        let mut overrides = HashMap::new();
+
+        overrides.insert(
+            "navigator.userAgent".to_string(),
+            self.user_agent.clone().into(),
+        );
+        overrides.insert(
+            "navigator.platform".to_string(),
+            self.platform.clone().into(),
+        );
+        overrides.insert("navigator.vendor".to_string(), self.vendor.clone().into());
+        overrides.insert(
+            "navigator.language".to_string(),
+            self.language.clone().into(),
+        );
+        let languages: Vec<String> = std::iter::once(&self.language)
+            .chain(&self.extra_languages)
+            .cloned()
+            .collect();
+        overrides.insert("navigator.languages".to_string(), languages.into());
+        overrides.insert(
+            "navigator.hardwareConcurrency".to_string(),
+            self.hardware_concurrency.into(),
+        );
+        overrides.insert("screen.colorDepth".to_string(), self.color_depth.into());
+        overrides.insert(
+            "window.devicePixelRatio".to_string(),
+            self.pixel_ratio.into(),
+        );
+        if let Ok((width, height)) = self.screen_dimensions() {
+            overrides.insert("screen.width".to_string(), width.into());
+            overrides.insert("screen.height".to_string(), height.into());
+        }
+        overrides.insert("webgl.vendor".to_string(), self.webgl_vendor.clone().into());
+        overrides.insert(
+            "webgl.renderer".to_string(),
+            self.webgl_renderer.clone().into(),
+        );
+        overrides.insert(
+            "canvas.noiseSeed".to_string(),
+            self.canvas_noise_seed.into(),
+        );
+
+        overrides
+    }
+}
+
+/// Locale a browser in `timezone` most likely uses
+fn timezone_locale(timezone: &str) -> &'static str {
+    match timezone {
+        "Asia/Tokyo" => "ja-JP",
+        "Asia/Shanghai" => "zh-CN",
+        "Asia/Singapore" => "en-SG",
+        "Australia/Sydney" => "en-AU",
+        "Europe/London" => "en-GB",
+        "Europe/Paris" => "fr-FR",
+        "Europe/Berlin" => "de-DE",
+        "Europe/Rome" => "it-IT",
+        "Europe/Madrid" => "es-ES",
+        "America/Toronto" | "America/Vancouver" => "en-CA",
+        _ => "en-US",
+    }
+}
+
+/// Pick a WebGL vendor and renderer a real browser on `platform` would report
+fn webgl_profile<R: Rng + ?Sized>(platform: &str, rng: &mut R) -> (String, String) {
+    let profiles: &[(&str, &str)] = match platform {
+        "MacIntel" => &[
+            ("Apple Inc.", "Apple M1"),
+            ("Apple Inc.", "Apple M1 Pro"),
+            ("Apple Inc.", "Apple M2"),
+            ("Apple Inc.", "Apple M3"),
+        ],
+        "Linux x86_64" => &[
+            ("Intel", "Mesa Intel(R) UHD Graphics 620 (KBL GT2)"),
+            (
+                "AMD",
+                "AMD Radeon RX 580 Series (polaris10, LLVM 15.0.7, DRM 3.49)",
+            ),
+            ("NVIDIA Corporation", "NVIDIA GeForce GTX 1660/PCIe/SSE2"),
+        ],
+        _ => &[
+            (
+                "Google Inc. (NVIDIA)",
+                "ANGLE (NVIDIA, NVIDIA GeForce RTX 3060 Direct3D11 vs_5_0 ps_5_0, D3D11)",
+            ),
+            (
+                "Google Inc. (Intel)",
+                "ANGLE (Intel, Intel(R) UHD Graphics 620 Direct3D11 vs_5_0 ps_5_0, D3D11)",
+            ),
+            (
+                "Google Inc. (AMD)",
+                "ANGLE (AMD, AMD Radeon RX 580 Series Direct3D11 vs_5_0 ps_5_0, D3D11)",
+            ),
+        ],
+    };
+
+    let (vendor, renderer) = profiles[rng.gen_range(0..profiles.len())];
+    (vendor.to_string(), renderer.to_string())
 }
 
 /// Fingerprint spoofing utilities
@@ -215,11 +368,28 @@ impl FingerprintSpoofer {
         BrowserFingerprint::generate()
     }
 
+    /// Generate a fingerprint from a fixed seed, for reproducible tests
+    pub fn generate_seeded(seed: u64) -> BrowserFingerprint {
+        BrowserFingerprint::generate_seeded(seed)
+    }
+
     /// Generate multiple fingerprints for rotation
     pub fn generate_multiple(count: usize) -> Vec<BrowserFingerprint> {
         (0..count).map(|_| Self::generate()).collect()
     }
 
+    /// Distinct User-Agents from `count` generated fingerprints, for
+    /// `ApiClient::with_user_agents`
+    pub fn user_agent_pool(count: usize) -> Vec<String> {
+        let mut user_agents: Vec<String> = Vec::new();
+        for fingerprint in Self::generate_multiple(count) {
+            if !user_agents.contains(&fingerprint.user_agent) {
+                user_agents.push(fingerprint.user_agent);
+            }
+        }
+        user_agents
+    }
+
     /// Generate a fingerprint that matches a specific browser type
     pub fn generate_for_browser(browser: &str) -> BrowserFingerprint {
         let mut fingerprint = Self::generate();
@@ -240,6 +410,10 @@ impl FingerprintSpoofer {
                 fingerprint.vendor = "Apple Computer, Inc.".to_string();
                 fingerprint.vendor_sub = "Apple Computer, Inc.".to_string();
                 fingerprint.platform = "MacIntel".to_string();
+                let (webgl_vendor, webgl_renderer) =
+                    webgl_profile(&fingerprint.platform, &mut rand::thread_rng());
+                fingerprint.webgl_vendor = webgl_vendor;
+                fingerprint.webgl_renderer = webgl_renderer;
             }
             "edge" => {
                 fingerprint.user_agent = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36 Edg/120.0.0.0".to_string();
@@ -270,6 +444,59 @@ mod tests {
         assert!(fingerprint.hardware_concurrency > 0);
     }
 
+    #[test]
+    fn test_seeded_fingerprints_are_identical() {
+        let first = serde_json::to_vec(&FingerprintSpoofer::generate_seeded(42)).unwrap();
+        let second = serde_json::to_vec(&FingerprintSpoofer::generate_seeded(42)).unwrap();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_macos_fingerprints_get_apple_gpus() {
+        let mut fingerprints = vec![FingerprintSpoofer::generate_for_browser("safari")];
+        fingerprints.extend(
+            (0..50)
+                .map(FingerprintSpoofer::generate_seeded)
+                .filter(|fp| fp.platform == "MacIntel"),
+        );
+        assert!(fingerprints.len() > 1);
+
+        for fingerprint in fingerprints {
+            assert_eq!(fingerprint.webgl_vendor, "Apple Inc.");
+            assert!(fingerprint.webgl_renderer.starts_with("Apple M"));
+
+            let overrides = fingerprint.to_js_overrides();
+            assert_eq!(
+                overrides["webgl.renderer"],
+                serde_json::json!(fingerprint.webgl_renderer)
+            );
+            assert_eq!(
+                overrides["canvas.noiseSeed"],
+                serde_json::json!(fingerprint.canvas_noise_seed)
+            );
+        }
+    }
+
+    #[test]
+    fn test_japanese_fingerprint_prefers_ja() {
+        let mut fingerprint = FingerprintSpoofer::generate_seeded(1);
+        fingerprint.language = "ja-JP".to_string();
+        fingerprint.extra_languages = vec!["en-US".to_string()];
+
+        let headers = fingerprint.to_headers();
+        let accept_language = &headers["Accept-Language"];
+        assert!(accept_language.starts_with("ja"));
+        assert_eq!(accept_language, "ja-JP,ja;q=0.9,en-US;q=0.8,en;q=0.7");
+
+        // Tokyo timezones mostly pick a Japanese locale
+        let tokyo: Vec<_> = (0..200)
+            .map(FingerprintSpoofer::generate_seeded)
+            .filter(|fp| fp.timezone == "Asia/Tokyo")
+            .collect();
+        let japanese = tokyo.iter().filter(|fp| fp.language == "ja-JP").count();
+        assert!(japanese * 2 > tokyo.len());
+    }
+
     #[test]
     fn test_fingerprint_headers() {
         let fingerprint = FingerprintSpoofer::generate();
@@ -312,4 +539,15 @@ mod tests {
         // Note: This test might occasionally fail due to randomness, but it's unlikely
         // with 5 different fingerprints
     }
+
+    #[test]
+    fn test_user_agent_pool_is_distinct() {
+        let pool = FingerprintSpoofer::user_agent_pool(20);
+        assert!(!pool.is_empty());
+
+        let mut deduped = pool.clone();
+        deduped.sort();
+        deduped.dedup();
+        assert_eq!(deduped.len(), pool.len());
+    }
 }
@@ -2,6 +2,9 @@ pub mod behavior;
 pub mod fingerprint;
 pub mod stealth_client;
 
-pub use behavior::{collect_typing_stream, simulate_typing, BehaviorSimulator, TypingStream};
+pub use behavior::{
+    collect_typing_stream, simulate_typing, BehaviorSimulator, BehaviorStep, BehaviorTrace,
+    DelayKind, TypingStream,
+};
 pub use fingerprint::{BrowserFingerprint, FingerprintSpoofer};
 pub use stealth_client::{create_random_stealth_client, create_stealth_client, StealthClient};
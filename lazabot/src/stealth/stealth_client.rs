@@ -1,5 +1,8 @@
-use anyhow::Result;
-use reqwest::{header::HeaderMap, Method};
+use anyhow::{Context, Result};
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, CONTENT_TYPE};
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 
 use super::{simulate_typing, BehaviorSimulator, BrowserFingerprint, FingerprintSpoofer};
 use crate::api::{ApiClient, ProxyInfo, ResponseBody};
@@ -121,6 +124,29 @@ impl StealthClient {
             .await
     }
 
+    /// GET `url` with stealth headers and deserialize the JSON response
+    pub async fn get_json<T: DeserializeOwned>(&mut self, url: &str) -> Result<T> {
+        let response = self
+            .stealth_get(url, Some(json_headers(false)), None)
+            .await?
+            .error_for_status()?;
+        Ok(response.json()?)
+    }
+
+    /// POST `body` as JSON with stealth headers and deserialize the JSON response
+    pub async fn post_json<T, B>(&mut self, url: &str, body: &B) -> Result<T>
+    where
+        T: DeserializeOwned,
+        B: Serialize + ?Sized,
+    {
+        let body = serde_json::to_vec(body).context("Failed to serialize request body")?;
+        let response = self
+            .stealth_post(url, body, Some(json_headers(true)), None)
+            .await?
+            .error_for_status()?;
+        Ok(response.json()?)
+    }
+
     /// Simulate human-like form filling
     pub async fn stealth_form_fill(&mut self, form_data: &str) -> String {
         // Simulate reading the form
@@ -151,6 +177,19 @@ impl Default for StealthClient {
     }
 }
 
+/// Headers a browser's `fetch` sends for a JSON API call, overriding the navigation `Accept`
+fn json_headers(with_body: bool) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        ACCEPT,
+        HeaderValue::from_static("application/json, text/plain, */*"),
+    );
+    if with_body {
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    }
+    headers
+}
+
 /// Helper function to create a stealth client with specific browser
 pub fn create_stealth_client(browser: &str) -> Result<StealthClient> {
     StealthClient::for_browser(browser)
@@ -189,6 +228,61 @@ mod tests {
         assert_ne!(client.fingerprint().user_agent, original_ua);
     }
 
+    #[tokio::test]
+    async fn test_get_and_post_json_send_stealth_headers() {
+        use wiremock::matchers::{body_json, header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        #[derive(serde::Deserialize)]
+        struct Product {
+            id: String,
+            price: f64,
+        }
+
+        let mock_server = MockServer::start().await;
+        let mut client = StealthClient::for_browser("firefox").unwrap();
+        let user_agent = client.fingerprint().user_agent.clone();
+
+        Mock::given(method("GET"))
+            .and(path("/product"))
+            .and(header("User-Agent", user_agent.as_str()))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "id": "p1", "price": 9.5 })),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/cart"))
+            .and(header("User-Agent", user_agent.as_str()))
+            .and(header("Content-Type", "application/json"))
+            .and(body_json(serde_json::json!({ "id": "p1", "quantity": 2 })))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "id": "p1", "price": 19.0 })),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let product: Product = client
+            .get_json(&format!("{}/product", mock_server.uri()))
+            .await
+            .unwrap();
+        assert_eq!(product.id, "p1");
+        assert_eq!(product.price, 9.5);
+
+        let cart: Product = client
+            .post_json(
+                &format!("{}/cart", mock_server.uri()),
+                &serde_json::json!({ "id": "p1", "quantity": 2 }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(cart.price, 19.0);
+    }
+
     #[tokio::test]
     async fn test_stealth_headers() {
         let client = StealthClient::new().unwrap();
@@ -0,0 +1,128 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::ops::Deref;
+use tracing::debug;
+
+use super::{Cache, Database, TaskRecord};
+
+/// `Database` with a read-through cache for task lookups.
+///
+/// Task reads are served from memory after the first hit and invalidated by
+/// the task write methods. Every other `Database` method is reachable through
+/// `Deref`, so this is a drop-in replacement.
+pub struct CachedDatabase {
+    db: Database,
+    tasks: Cache<u64, TaskRecord>,
+}
+
+impl CachedDatabase {
+    /// Wrap an existing database
+    pub fn new(db: Database) -> Self {
+        Self {
+            db,
+            tasks: Cache::new("tasks"),
+        }
+    }
+
+    /// Underlying database, bypassing the cache
+    pub fn database(&self) -> &Database {
+        &self.db
+    }
+
+    /// Get task by task_id, from the cache when present
+    pub fn get_task(&self, task_id: u64) -> Result<Option<TaskRecord>> {
+        if let Some(task) = self.tasks.get(&task_id) {
+            debug!("Task cache hit for task_id={}", task_id);
+            return Ok(Some(task));
+        }
+
+        let task = self.db.get_task(task_id)?;
+        if let Some(task) = &task {
+            self.tasks.set(task_id, task.clone());
+        }
+        Ok(task)
+    }
+
+    /// Update task status and timestamps, invalidating the cached task
+    pub fn update_task_status(
+        &self,
+        task_id: u64,
+        status: &str,
+        started_at: Option<DateTime<Utc>>,
+        completed_at: Option<DateTime<Utc>>,
+        error_message: Option<&str>,
+    ) -> Result<()> {
+        let result =
+            self.db
+                .update_task_status(task_id, status, started_at, completed_at, error_message);
+        self.tasks.remove(&task_id);
+        result
+    }
+
+    /// Soft-delete a task, invalidating the cached task
+    pub fn delete_task(&self, task_id: u64) -> Result<()> {
+        let result = self.db.delete_task(task_id);
+        self.tasks.remove(&task_id);
+        result
+    }
+
+    /// Restore a soft-deleted task, invalidating the cached task
+    pub fn restore_task(&self, task_id: u64) -> Result<()> {
+        let result = self.db.restore_task(task_id);
+        self.tasks.remove(&task_id);
+        result
+    }
+
+    /// Permanently remove a task, invalidating the cached task
+    pub fn hard_delete_task(&self, task_id: u64) -> Result<()> {
+        let result = self.db.hard_delete_task(task_id);
+        self.tasks.remove(&task_id);
+        result
+    }
+}
+
+impl Deref for CachedDatabase {
+    type Target = Database;
+
+    fn deref(&self) -> &Database {
+        &self.db
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_task_served_from_cache() {
+        let db = CachedDatabase::new(Database::in_memory().unwrap());
+        db.insert_task(1, "pending", None).unwrap();
+        assert_eq!(db.get_task(1).unwrap().unwrap().status, "pending");
+
+        // Removing the row behind the cache's back still returns the cached copy
+        db.database().hard_delete_task(1).unwrap();
+        assert_eq!(db.get_task(1).unwrap().unwrap().status, "pending");
+
+        // Writes through the wrapper invalidate the entry
+        db.update_task_status(1, "completed", None, None, None)
+            .unwrap();
+        assert!(db.get_task(1).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_task_writes_invalidate_cache() {
+        let db = CachedDatabase::new(Database::in_memory().unwrap());
+        db.insert_task(2, "pending", None).unwrap();
+        db.get_task(2).unwrap();
+
+        db.update_task_status(2, "running", Some(Utc::now()), None, None)
+            .unwrap();
+        assert_eq!(db.get_task(2).unwrap().unwrap().status, "running");
+
+        db.delete_task(2).unwrap();
+        assert!(db.get_task(2).unwrap().is_none());
+
+        db.restore_task(2).unwrap();
+        assert_eq!(db.get_task(2).unwrap().unwrap().status, "running");
+    }
+}
@@ -1,7 +1,8 @@
 use anyhow::{Context, Result};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, SecondsFormat, Utc};
 use rusqlite::{params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use tracing::{debug, info};
@@ -41,6 +42,18 @@ pub struct OrderRecord {
     pub updated_at: DateTime<Utc>,
 }
 
+/// Order fields for insertion, mirroring `Database::insert_order`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewOrder {
+    pub order_id: String,
+    pub product_id: String,
+    pub account_id: String,
+    pub status: String,
+    pub price: f64,
+    pub quantity: i32,
+    pub metadata: Option<String>,
+}
+
 /// Session record for database persistence
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionRecord {
@@ -54,6 +67,116 @@ pub struct SessionRecord {
     pub updated_at: DateTime<Utc>,
 }
 
+/// Product availability change or price drop recorded by a monitor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AvailabilityEventRecord {
+    pub id: i64,
+    /// Monitor event kind, e.g. `availability_changed` or `price_dropped`
+    pub kind: String,
+    pub product_id: String,
+    pub price: Option<f64>,
+    pub stock: Option<u32>,
+    pub available: bool,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Schema migrations applied in order on top of the base schema; the number
+/// applied so far is tracked in `PRAGMA user_version`
+const MIGRATIONS: &[&str] = &[
+    // 1: soft deletes
+    "ALTER TABLE tasks ADD COLUMN deleted_at TEXT;
+     ALTER TABLE orders ADD COLUMN deleted_at TEXT;
+     ALTER TABLE sessions ADD COLUMN deleted_at TEXT;",
+    // 2: monitor availability history
+    "CREATE TABLE availability_events (
+         id INTEGER PRIMARY KEY AUTOINCREMENT,
+         product_id TEXT NOT NULL,
+         price REAL,
+         stock INTEGER,
+         available INTEGER NOT NULL,
+         timestamp TEXT NOT NULL
+     );
+     CREATE INDEX idx_availability_events_product
+         ON availability_events(product_id, timestamp);",
+    // 3: ids only need to be unique among rows that aren't soft-deleted, so rebuild the
+    // tables without their UNIQUE constraints and add partial unique indexes instead
+    "CREATE TABLE tasks_new (
+         id INTEGER PRIMARY KEY AUTOINCREMENT,
+         task_id INTEGER NOT NULL,
+         status TEXT NOT NULL,
+         started_at TEXT,
+         completed_at TEXT,
+         error_message TEXT,
+         metadata TEXT,
+         created_at TEXT NOT NULL,
+         updated_at TEXT NOT NULL,
+         deleted_at TEXT
+     );
+     INSERT INTO tasks_new SELECT id, task_id, status, started_at, completed_at,
+         error_message, metadata, created_at, updated_at, deleted_at FROM tasks;
+     DROP TABLE tasks;
+     ALTER TABLE tasks_new RENAME TO tasks;
+     CREATE INDEX idx_tasks_task_id ON tasks(task_id);
+     CREATE INDEX idx_tasks_status ON tasks(status);
+     CREATE UNIQUE INDEX idx_tasks_live_task_id ON tasks(task_id) WHERE deleted_at IS NULL;
+
+     CREATE TABLE orders_new (
+         id INTEGER PRIMARY KEY AUTOINCREMENT,
+         order_id TEXT NOT NULL,
+         product_id TEXT NOT NULL,
+         account_id TEXT NOT NULL,
+         status TEXT NOT NULL,
+         price REAL NOT NULL,
+         quantity INTEGER NOT NULL,
+         metadata TEXT,
+         created_at TEXT NOT NULL,
+         updated_at TEXT NOT NULL,
+         deleted_at TEXT
+     );
+     INSERT INTO orders_new SELECT id, order_id, product_id, account_id, status, price,
+         quantity, metadata, created_at, updated_at, deleted_at FROM orders;
+     DROP TABLE orders;
+     ALTER TABLE orders_new RENAME TO orders;
+     CREATE INDEX idx_orders_order_id ON orders(order_id);
+     CREATE INDEX idx_orders_account_id ON orders(account_id);
+     CREATE UNIQUE INDEX idx_orders_live_order_id ON orders(order_id) WHERE deleted_at IS NULL;
+
+     CREATE TABLE sessions_new (
+         id INTEGER PRIMARY KEY AUTOINCREMENT,
+         session_id TEXT NOT NULL,
+         account_id TEXT NOT NULL,
+         status TEXT NOT NULL,
+         cookies TEXT,
+         last_used_at TEXT,
+         created_at TEXT NOT NULL,
+         updated_at TEXT NOT NULL,
+         deleted_at TEXT
+     );
+     INSERT INTO sessions_new SELECT id, session_id, account_id, status, cookies,
+         last_used_at, created_at, updated_at, deleted_at FROM sessions;
+     DROP TABLE sessions;
+     ALTER TABLE sessions_new RENAME TO sessions;
+     CREATE INDEX idx_sessions_session_id ON sessions(session_id);
+     CREATE INDEX idx_sessions_account_id ON sessions(account_id);
+     CREATE UNIQUE INDEX idx_sessions_live_session_id
+         ON sessions(session_id) WHERE deleted_at IS NULL;",
+    // 4: monitor events other than availability changes
+    "ALTER TABLE availability_events
+         ADD COLUMN kind TEXT NOT NULL DEFAULT 'availability_changed';",
+];
+
+/// Tables managed by `Database`
+const TABLES: &[&str] = &["tasks", "orders", "sessions", "availability_events"];
+
+/// Database size and row count statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbStats {
+    /// Row count per table
+    pub row_counts: BTreeMap<String, i64>,
+    /// Size of the database file in bytes (page usage for in-memory databases)
+    pub file_size_bytes: u64,
+}
+
 impl Database {
     /// Create a new database instance
     pub fn new<P: AsRef<Path>>(db_path: P) -> Result<Self> {
@@ -189,10 +312,36 @@ impl Database {
         )
         .context("Failed to create index on session account_id")?;
 
+        drop(conn);
+        self.migrate()?;
+
         debug!("Database schema initialized successfully");
         Ok(())
     }
 
+    /// Apply any schema migrations not yet recorded in `user_version`
+    fn migrate(&self) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+
+        let version: usize = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .context("Failed to read schema version")?;
+
+        for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            let tx = conn
+                .transaction()
+                .context("Failed to begin migration transaction")?;
+            tx.execute_batch(migration)
+                .with_context(|| format!("Failed to apply migration {}", index + 1))?;
+            tx.pragma_update(None, "user_version", index + 1)
+                .context("Failed to update schema version")?;
+            tx.commit().context("Failed to commit migration")?;
+            debug!("Applied database migration {}", index + 1);
+        }
+
+        Ok(())
+    }
+
     // ============================================
     // Task CRUD Operations
     // ============================================
@@ -231,7 +380,7 @@ impl Database {
         conn.execute(
             "UPDATE tasks 
              SET status = ?1, started_at = ?2, completed_at = ?3, error_message = ?4, updated_at = ?5
-             WHERE task_id = ?6",
+             WHERE task_id = ?6 AND deleted_at IS NULL",
             params![status, started_str, completed_str, error_message, now, task_id],
         ).context("Failed to update task status")?;
 
@@ -246,7 +395,7 @@ impl Database {
         let result = conn
             .query_row(
                 "SELECT id, task_id, status, started_at, completed_at, error_message, metadata, created_at, updated_at
-                 FROM tasks WHERE task_id = ?1",
+                 FROM tasks WHERE task_id = ?1 AND deleted_at IS NULL",
                 params![task_id],
                 |row| {
                     Ok(TaskRecord {
@@ -274,10 +423,10 @@ impl Database {
 
         let query = if let Some(status) = status_filter {
             format!("SELECT id, task_id, status, started_at, completed_at, error_message, metadata, created_at, updated_at
-                     FROM tasks WHERE status = '{}' ORDER BY created_at DESC", status)
+                     FROM tasks WHERE status = '{}' AND deleted_at IS NULL ORDER BY created_at DESC", status)
         } else {
             "SELECT id, task_id, status, started_at, completed_at, error_message, metadata, created_at, updated_at
-             FROM tasks ORDER BY created_at DESC".to_string()
+             FROM tasks WHERE deleted_at IS NULL ORDER BY created_at DESC".to_string()
         };
 
         let mut stmt = conn.prepare(&query)?;
@@ -312,9 +461,50 @@ impl Database {
         Ok(tasks)
     }
 
-    /// Delete a task by task_id
+    /// Highest task_id ever stored, soft-deleted rows included
+    pub fn max_task_id(&self) -> Result<Option<u64>> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.query_row("SELECT MAX(task_id) FROM tasks", [], |row| row.get(0))
+            .context("Failed to query max task_id")
+    }
+
+    /// Soft-delete a task by task_id, hiding it from queries
     pub fn delete_task(&self, task_id: u64) -> Result<()> {
         let conn = self.conn.lock().unwrap();
+        let now = Utc::now().to_rfc3339();
+
+        conn.execute(
+            "UPDATE tasks SET deleted_at = ?1, updated_at = ?1 WHERE task_id = ?2 AND deleted_at IS NULL",
+            params![now, task_id],
+        )
+        .context("Failed to delete task")?;
+
+        debug!("Soft-deleted task_id={}", task_id);
+        Ok(())
+    }
+
+    /// Restore the most recently soft-deleted task; fails if a live one has the same id
+    pub fn restore_task(&self, task_id: u64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        let now = Utc::now().to_rfc3339();
+
+        conn.execute(
+            "UPDATE tasks SET deleted_at = NULL, updated_at = ?1 WHERE id = (
+                 SELECT id FROM tasks WHERE task_id = ?2 AND deleted_at IS NOT NULL
+                 ORDER BY deleted_at DESC, id DESC LIMIT 1
+             )",
+            params![now, task_id],
+        )
+        .context("Failed to restore task")?;
+
+        debug!("Restored task_id={}", task_id);
+        Ok(())
+    }
+
+    /// Permanently remove a task by task_id
+    pub fn hard_delete_task(&self, task_id: u64) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
 
         conn.execute("DELETE FROM tasks WHERE task_id = ?1", params![task_id])
             .context("Failed to delete task")?;
@@ -352,13 +542,101 @@ impl Database {
         Ok(id)
     }
 
+    /// Insert a new order record from its fields
+    pub fn insert_new_order(&self, order: &NewOrder) -> Result<i64> {
+        self.insert_order(
+            &order.order_id,
+            &order.product_id,
+            &order.account_id,
+            &order.status,
+            order.price,
+            order.quantity,
+            order.metadata.as_deref(),
+        )
+    }
+
+    /// Insert an order, or update the live row with the same `order_id`.
+    ///
+    /// Retried checkouts reuse their idempotency key as the order id, so a
+    /// second attempt replaces the earlier attempt's status instead of failing
+    /// on the unique index.
+    pub fn upsert_order(&self, order: &NewOrder) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        let now = Utc::now().to_rfc3339();
+
+        conn.execute(
+            "INSERT INTO orders (order_id, product_id, account_id, status, price, quantity, metadata, created_at, updated_at) 
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+             ON CONFLICT(order_id) WHERE deleted_at IS NULL DO UPDATE SET
+                 status = excluded.status,
+                 price = excluded.price,
+                 quantity = excluded.quantity,
+                 metadata = excluded.metadata,
+                 updated_at = excluded.updated_at",
+            params![
+                order.order_id,
+                order.product_id,
+                order.account_id,
+                order.status,
+                order.price,
+                order.quantity,
+                order.metadata,
+                now,
+                now
+            ],
+        )
+        .context("Failed to upsert order")?;
+
+        debug!("Upserted order with order_id={}", order.order_id);
+        Ok(())
+    }
+
+    /// Insert several orders in a single transaction.
+    ///
+    /// Either every order is inserted or, if any insert fails, none are.
+    pub fn insert_orders_batch(&self, orders: &[NewOrder]) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let now = Utc::now().to_rfc3339();
+
+        let tx = conn
+            .transaction()
+            .context("Failed to begin order batch transaction")?;
+        {
+            let mut stmt = tx
+                .prepare(
+                    "INSERT INTO orders (order_id, product_id, account_id, status, price, quantity, metadata, created_at, updated_at) 
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                )
+                .context("Failed to prepare order insert")?;
+
+            for order in orders {
+                stmt.execute(params![
+                    order.order_id,
+                    order.product_id,
+                    order.account_id,
+                    order.status,
+                    order.price,
+                    order.quantity,
+                    order.metadata,
+                    now,
+                    now
+                ])
+                .with_context(|| format!("Failed to insert order {}", order.order_id))?;
+            }
+        }
+        tx.commit().context("Failed to commit order batch")?;
+
+        debug!("Inserted batch of {} orders", orders.len());
+        Ok(())
+    }
+
     /// Update order status
     pub fn update_order_status(&self, order_id: &str, status: &str) -> Result<()> {
         let conn = self.conn.lock().unwrap();
         let now = Utc::now().to_rfc3339();
 
         conn.execute(
-            "UPDATE orders SET status = ?1, updated_at = ?2 WHERE order_id = ?3",
+            "UPDATE orders SET status = ?1, updated_at = ?2 WHERE order_id = ?3 AND deleted_at IS NULL",
             params![status, now, order_id],
         )
         .context("Failed to update order status")?;
@@ -374,7 +652,7 @@ impl Database {
         let result = conn
             .query_row(
                 "SELECT id, order_id, product_id, account_id, status, price, quantity, metadata, created_at, updated_at
-                 FROM orders WHERE order_id = ?1",
+                 FROM orders WHERE order_id = ?1 AND deleted_at IS NULL",
                 params![order_id],
                 |row| {
                     Ok(OrderRecord {
@@ -403,7 +681,7 @@ impl Database {
 
         let mut stmt = conn.prepare(
             "SELECT id, order_id, product_id, account_id, status, price, quantity, metadata, created_at, updated_at
-             FROM orders WHERE account_id = ?1 ORDER BY created_at DESC"
+             FROM orders WHERE account_id = ?1 AND deleted_at IS NULL ORDER BY created_at DESC"
         )?;
 
         let orders = stmt
@@ -430,9 +708,84 @@ impl Database {
         Ok(orders)
     }
 
-    /// Delete an order by order_id
+    /// List orders, newest first, optionally only for `account_id` and/or with `status`
+    pub fn list_orders(
+        &self,
+        account_id: Option<&str>,
+        status: Option<&str>,
+    ) -> Result<Vec<OrderRecord>> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(
+            "SELECT id, order_id, product_id, account_id, status, price, quantity, metadata, created_at, updated_at
+             FROM orders
+             WHERE deleted_at IS NULL
+               AND (?1 IS NULL OR account_id = ?1)
+               AND (?2 IS NULL OR status = ?2)
+             ORDER BY created_at DESC, id DESC",
+        )?;
+
+        let orders = stmt
+            .query_map(params![account_id, status], |row| {
+                Ok(OrderRecord {
+                    id: row.get(0)?,
+                    order_id: row.get(1)?,
+                    product_id: row.get(2)?,
+                    account_id: row.get(3)?,
+                    status: row.get(4)?,
+                    price: row.get(5)?,
+                    quantity: row.get(6)?,
+                    metadata: row.get(7)?,
+                    created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(8)?)
+                        .unwrap()
+                        .with_timezone(&Utc),
+                    updated_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(9)?)
+                        .unwrap()
+                        .with_timezone(&Utc),
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to list orders")?;
+
+        Ok(orders)
+    }
+
+    /// Soft-delete an order by order_id, hiding it from queries
     pub fn delete_order(&self, order_id: &str) -> Result<()> {
         let conn = self.conn.lock().unwrap();
+        let now = Utc::now().to_rfc3339();
+
+        conn.execute(
+            "UPDATE orders SET deleted_at = ?1, updated_at = ?1 WHERE order_id = ?2 AND deleted_at IS NULL",
+            params![now, order_id],
+        )
+        .context("Failed to delete order")?;
+
+        debug!("Soft-deleted order_id={}", order_id);
+        Ok(())
+    }
+
+    /// Restore the most recently soft-deleted order; fails if a live one has the same id
+    pub fn restore_order(&self, order_id: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        let now = Utc::now().to_rfc3339();
+
+        conn.execute(
+            "UPDATE orders SET deleted_at = NULL, updated_at = ?1 WHERE id = (
+                 SELECT id FROM orders WHERE order_id = ?2 AND deleted_at IS NOT NULL
+                 ORDER BY deleted_at DESC, id DESC LIMIT 1
+             )",
+            params![now, order_id],
+        )
+        .context("Failed to restore order")?;
+
+        debug!("Restored order_id={}", order_id);
+        Ok(())
+    }
+
+    /// Permanently remove an order by order_id
+    pub fn hard_delete_order(&self, order_id: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
 
         conn.execute("DELETE FROM orders WHERE order_id = ?1", params![order_id])
             .context("Failed to delete order")?;
@@ -480,7 +833,7 @@ impl Database {
         conn.execute(
             "UPDATE sessions 
              SET status = ?1, cookies = ?2, last_used_at = ?3, updated_at = ?4
-             WHERE session_id = ?5",
+             WHERE session_id = ?5 AND deleted_at IS NULL",
             params![status, cookies, now, now, session_id],
         )
         .context("Failed to update session")?;
@@ -496,7 +849,7 @@ impl Database {
         let result = conn
             .query_row(
                 "SELECT id, session_id, account_id, status, cookies, last_used_at, created_at, updated_at
-                 FROM sessions WHERE session_id = ?1",
+                 FROM sessions WHERE session_id = ?1 AND deleted_at IS NULL",
                 params![session_id],
                 |row| {
                     Ok(SessionRecord {
@@ -523,7 +876,7 @@ impl Database {
 
         let mut stmt = conn.prepare(
             "SELECT id, session_id, account_id, status, cookies, last_used_at, created_at, updated_at
-             FROM sessions WHERE account_id = ?1 ORDER BY created_at DESC"
+             FROM sessions WHERE account_id = ?1 AND deleted_at IS NULL ORDER BY created_at DESC"
         )?;
 
         let sessions = stmt
@@ -552,9 +905,42 @@ impl Database {
         Ok(sessions)
     }
 
-    /// Delete a session by session_id
+    /// Soft-delete a session by session_id, hiding it from queries
     pub fn delete_session(&self, session_id: &str) -> Result<()> {
         let conn = self.conn.lock().unwrap();
+        let now = Utc::now().to_rfc3339();
+
+        conn.execute(
+            "UPDATE sessions SET deleted_at = ?1, updated_at = ?1 WHERE session_id = ?2 AND deleted_at IS NULL",
+            params![now, session_id],
+        )
+        .context("Failed to delete session")?;
+
+        debug!("Soft-deleted session_id={}", session_id);
+        Ok(())
+    }
+
+    /// Restore the most recently soft-deleted session; fails if a live one has the same id
+    pub fn restore_session(&self, session_id: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        let now = Utc::now().to_rfc3339();
+
+        conn.execute(
+            "UPDATE sessions SET deleted_at = NULL, updated_at = ?1 WHERE id = (
+                 SELECT id FROM sessions WHERE session_id = ?2 AND deleted_at IS NOT NULL
+                 ORDER BY deleted_at DESC, id DESC LIMIT 1
+             )",
+            params![now, session_id],
+        )
+        .context("Failed to restore session")?;
+
+        debug!("Restored session_id={}", session_id);
+        Ok(())
+    }
+
+    /// Permanently remove a session by session_id
+    pub fn hard_delete_session(&self, session_id: &str) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
 
         conn.execute(
             "DELETE FROM sessions WHERE session_id = ?1",
@@ -566,6 +952,147 @@ impl Database {
         Ok(())
     }
 
+    // ============================================
+    // Availability Events
+    // ============================================
+
+    /// Record a product availability event
+    pub fn insert_availability_event(
+        &self,
+        product_id: &str,
+        price: Option<f64>,
+        stock: Option<u32>,
+        available: bool,
+        timestamp: DateTime<Utc>,
+    ) -> Result<i64> {
+        self.insert_monitor_event(
+            "availability_changed",
+            product_id,
+            price,
+            stock,
+            available,
+            timestamp,
+        )
+    }
+
+    /// Record a monitor event of the given `kind`
+    pub fn insert_monitor_event(
+        &self,
+        kind: &str,
+        product_id: &str,
+        price: Option<f64>,
+        stock: Option<u32>,
+        available: bool,
+        timestamp: DateTime<Utc>,
+    ) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        // Fixed precision so timestamps compare correctly as text
+        let timestamp = timestamp.to_rfc3339_opts(SecondsFormat::Micros, true);
+
+        conn.execute(
+            "INSERT INTO availability_events (kind, product_id, price, stock, available, timestamp)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![kind, product_id, price, stock, available, timestamp],
+        )
+        .context("Failed to insert availability event")?;
+
+        let id = conn.last_insert_rowid();
+        debug!(
+            "Inserted {} event with id={}, product_id={}",
+            kind, id, product_id
+        );
+        Ok(id)
+    }
+
+    /// Get events for a product at or after `since`, oldest first
+    pub fn get_events_for_product(
+        &self,
+        product_id: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<AvailabilityEventRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let since = since.to_rfc3339_opts(SecondsFormat::Micros, true);
+
+        let mut stmt = conn.prepare(
+            "SELECT id, kind, product_id, price, stock, available, timestamp
+             FROM availability_events WHERE product_id = ?1 AND timestamp >= ?2
+             ORDER BY timestamp ASC, id ASC",
+        )?;
+
+        let events = stmt
+            .query_map(params![product_id, since], |row| {
+                Ok(AvailabilityEventRecord {
+                    id: row.get(0)?,
+                    kind: row.get(1)?,
+                    product_id: row.get(2)?,
+                    price: row.get(3)?,
+                    stock: row.get(4)?,
+                    available: row.get(5)?,
+                    timestamp: DateTime::parse_from_rfc3339(&row.get::<_, String>(6)?)
+                        .unwrap()
+                        .with_timezone(&Utc),
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(events)
+    }
+
+    // ============================================
+    // Maintenance
+    // ============================================
+
+    /// Rebuild the database file, reclaiming space left by deleted rows
+    pub fn vacuum(&self) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute_batch("VACUUM")
+            .context("Failed to vacuum database")?;
+        info!("Vacuumed database at {:?}", self.db_path);
+        Ok(())
+    }
+
+    /// Refresh the query planner statistics
+    pub fn analyze(&self) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute_batch("ANALYZE")
+            .context("Failed to analyze database")?;
+        debug!("Analyzed database at {:?}", self.db_path);
+        Ok(())
+    }
+
+    /// Get row counts per table, including soft-deleted rows, and the database size
+    pub fn stats(&self) -> Result<DbStats> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut row_counts = BTreeMap::new();
+        for table in TABLES {
+            let count: i64 = conn
+                .query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+                    row.get(0)
+                })
+                .with_context(|| format!("Failed to count rows in {}", table))?;
+            row_counts.insert(table.to_string(), count);
+        }
+
+        let file_size_bytes = match std::fs::metadata(&self.db_path) {
+            Ok(metadata) => metadata.len(),
+            Err(_) => {
+                let page_count: i64 = conn
+                    .query_row("PRAGMA page_count", [], |row| row.get(0))
+                    .context("Failed to read page count")?;
+                let page_size: i64 = conn
+                    .query_row("PRAGMA page_size", [], |row| row.get(0))
+                    .context("Failed to read page size")?;
+                (page_count * page_size) as u64
+            }
+        };
+
+        Ok(DbStats {
+            row_counts,
+            file_size_bytes,
+        })
+    }
+
     /// Get database file path
     pub fn path(&self) -> &Path {
         &self.db_path
@@ -580,6 +1107,7 @@ mod tests {
     fn test_database_initialization() {
         let db = Database::in_memory().unwrap();
         assert_eq!(db.path(), Path::new(":memory:"));
+        assert_eq!(db.max_task_id().unwrap(), None);
     }
 
     #[test]
@@ -621,6 +1149,7 @@ mod tests {
         // Delete task
         db.delete_task(task_id).unwrap();
         assert!(db.get_task(task_id).unwrap().is_none());
+        assert_eq!(db.max_task_id().unwrap(), Some(task_id));
     }
 
     #[test]
@@ -662,6 +1191,175 @@ mod tests {
         assert!(db.get_order(order_id).unwrap().is_none());
     }
 
+    #[test]
+    fn test_upsert_order_updates_live_row() {
+        let db = Database::new(":memory:").unwrap();
+        let mut order = NewOrder {
+            order_id: "idem-1".to_string(),
+            product_id: "PROD-001".to_string(),
+            account_id: "acc-1".to_string(),
+            status: "failed".to_string(),
+            price: 10.0,
+            quantity: 1,
+            metadata: None,
+        };
+
+        db.upsert_order(&order).unwrap();
+        order.status = "success".to_string();
+        db.upsert_order(&order).unwrap();
+
+        let orders = db.list_orders(Some("acc-1"), None).unwrap();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].status, "success");
+    }
+
+    #[test]
+    fn test_insert_orders_batch() {
+        let db = Database::in_memory().unwrap();
+        let new_order = |i: usize| NewOrder {
+            order_id: format!("ORD-{}", i),
+            product_id: "PROD-001".to_string(),
+            account_id: "ACC-001".to_string(),
+            status: "pending".to_string(),
+            price: 9.99,
+            quantity: 1,
+            metadata: None,
+        };
+
+        let orders: Vec<NewOrder> = (0..500).map(new_order).collect();
+        db.insert_orders_batch(&orders).unwrap();
+        assert_eq!(db.get_orders_by_account("ACC-001").unwrap().len(), 500);
+
+        // A duplicate order_id rolls back the whole batch
+        let mut batch: Vec<NewOrder> = (500..510).map(new_order).collect();
+        batch.push(new_order(0));
+        assert!(db.insert_orders_batch(&batch).is_err());
+        assert!(db.get_order("ORD-500").unwrap().is_none());
+        assert_eq!(db.get_orders_by_account("ACC-001").unwrap().len(), 500);
+    }
+
+    #[test]
+    fn test_vacuum_and_stats() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let db = Database::new(temp_dir.path().join("lazabot.db")).unwrap();
+
+        let orders: Vec<NewOrder> = (0..200)
+            .map(|i| NewOrder {
+                order_id: format!("ORD-{}", i),
+                product_id: "PROD-001".to_string(),
+                account_id: "ACC-001".to_string(),
+                status: "pending".to_string(),
+                price: 9.99,
+                quantity: 1,
+                metadata: Some("x".repeat(512)),
+            })
+            .collect();
+        db.insert_orders_batch(&orders).unwrap();
+        db.insert_session("SESS-1", "ACC-001", "active", None)
+            .unwrap();
+        for i in 0..150 {
+            db.hard_delete_order(&format!("ORD-{}", i)).unwrap();
+        }
+        let size_before = db.stats().unwrap().file_size_bytes;
+
+        db.vacuum().unwrap();
+        db.analyze().unwrap();
+
+        let stats = db.stats().unwrap();
+        assert_eq!(stats.row_counts["orders"], 50);
+        assert_eq!(stats.row_counts["sessions"], 1);
+        assert_eq!(stats.row_counts["tasks"], 0);
+        assert!(stats.file_size_bytes > 0);
+        assert!(stats.file_size_bytes < size_before);
+    }
+
+    #[test]
+    fn test_soft_deleted_ids_can_be_reused() {
+        let db = Database::in_memory().unwrap();
+
+        db.insert_task(1, "pending", None).unwrap();
+        db.delete_task(1).unwrap();
+        db.insert_task(1, "pending", None).unwrap();
+        assert!(db.insert_task(1, "pending", None).is_err());
+
+        // Status updates only touch the live row
+        db.update_task_status(1, "completed", None, None, None)
+            .unwrap();
+        assert_eq!(db.get_task(1).unwrap().unwrap().status, "completed");
+        db.delete_task(1).unwrap();
+        db.restore_task(1).unwrap();
+        assert_eq!(db.get_task(1).unwrap().unwrap().status, "completed");
+        assert!(db.restore_task(1).is_err());
+
+        db.insert_order("ORD-1", "PROD-1", "ACC-1", "failed", 1.0, 1, None)
+            .unwrap();
+        db.delete_order("ORD-1").unwrap();
+        db.insert_order("ORD-1", "PROD-1", "ACC-1", "pending", 1.0, 1, None)
+            .unwrap();
+        db.update_order_status("ORD-1", "confirmed").unwrap();
+        assert_eq!(db.get_order("ORD-1").unwrap().unwrap().status, "confirmed");
+
+        db.insert_session("SESS-1", "ACC-1", "expired", None)
+            .unwrap();
+        db.delete_session("SESS-1").unwrap();
+        db.insert_session("SESS-1", "ACC-1", "active", None)
+            .unwrap();
+        db.update_session("SESS-1", "active", Some("cookie=1"))
+            .unwrap();
+        assert_eq!(db.get_session("SESS-1").unwrap().unwrap().status, "active");
+        assert_eq!(db.stats().unwrap().row_counts["sessions"], 2);
+    }
+
+    #[test]
+    fn test_soft_delete_and_restore() {
+        let db = Database::in_memory().unwrap();
+
+        db.insert_task(1, "pending", None).unwrap();
+        db.delete_task(1).unwrap();
+        assert!(db.get_task(1).unwrap().is_none());
+        assert!(db.get_tasks(None).unwrap().is_empty());
+        assert!(db.get_tasks(Some("pending")).unwrap().is_empty());
+        db.restore_task(1).unwrap();
+        assert_eq!(db.get_task(1).unwrap().unwrap().status, "pending");
+
+        db.insert_order("ORD-1", "PROD-1", "ACC-1", "pending", 1.0, 1, None)
+            .unwrap();
+        db.delete_order("ORD-1").unwrap();
+        assert!(db.get_order("ORD-1").unwrap().is_none());
+        assert!(db.get_orders_by_account("ACC-1").unwrap().is_empty());
+        db.restore_order("ORD-1").unwrap();
+        assert!(db.get_order("ORD-1").unwrap().is_some());
+
+        db.insert_session("SESS-1", "ACC-1", "active", None)
+            .unwrap();
+        db.delete_session("SESS-1").unwrap();
+        assert!(db.get_session("SESS-1").unwrap().is_none());
+        assert!(db.get_sessions_by_account("ACC-1").unwrap().is_empty());
+        db.restore_session("SESS-1").unwrap();
+        assert!(db.get_session("SESS-1").unwrap().is_some());
+
+        // Soft-deleted rows are still stored; hard deletes remove them
+        db.delete_order("ORD-1").unwrap();
+        assert_eq!(db.stats().unwrap().row_counts["orders"], 1);
+        db.hard_delete_order("ORD-1").unwrap();
+        db.restore_order("ORD-1").unwrap();
+        assert!(db.get_order("ORD-1").unwrap().is_none());
+        assert_eq!(db.stats().unwrap().row_counts["orders"], 0);
+    }
+
+    #[test]
+    fn test_migrations_are_idempotent() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("lazabot.db");
+
+        Database::new(&path)
+            .unwrap()
+            .insert_task(1, "pending", None)
+            .unwrap();
+        let db = Database::new(&path).unwrap();
+        assert!(db.get_task(1).unwrap().is_some());
+    }
+
     #[test]
     fn test_session_crud() {
         let db = Database::in_memory().unwrap();
@@ -691,4 +1389,36 @@ mod tests {
         db.delete_session(session_id).unwrap();
         assert!(db.get_session(session_id).unwrap().is_none());
     }
+
+    #[test]
+    fn test_availability_events() {
+        let db = Database::in_memory().unwrap();
+        let start = Utc::now();
+
+        db.insert_availability_event(
+            "PROD-1",
+            None,
+            Some(0),
+            false,
+            start - chrono::Duration::hours(1),
+        )
+        .unwrap();
+        db.insert_availability_event("PROD-1", Some(19.9), Some(3), true, start)
+            .unwrap();
+        db.insert_availability_event("PROD-2", Some(5.0), None, true, start)
+            .unwrap();
+
+        let events = db.get_events_for_product("PROD-1", start).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].price, Some(19.9));
+        assert_eq!(events[0].stock, Some(3));
+        assert!(events[0].available);
+
+        let history = db
+            .get_events_for_product("PROD-1", start - chrono::Duration::days(1))
+            .unwrap();
+        assert_eq!(history.len(), 2);
+        assert!(!history[0].available);
+        assert_eq!(db.stats().unwrap().row_counts["availability_events"], 3);
+    }
 }
@@ -1,7 +1,11 @@
 //! Storage module for database persistence and caching
 
 pub mod cache;
+pub mod cached_database;
 pub mod database;
 
 pub use cache::Cache;
-pub use database::{Database, OrderRecord, SessionRecord, TaskRecord};
+pub use cached_database::CachedDatabase;
+pub use database::{
+    AvailabilityEventRecord, Database, DbStats, NewOrder, OrderRecord, SessionRecord, TaskRecord,
+};
@@ -1,16 +1,23 @@
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use dashmap::DashMap;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
-use tokio::sync::{broadcast, Semaphore};
+use std::time::Duration;
+use tokio::sync::{broadcast, Notify, OwnedSemaphorePermit, Semaphore};
 use tokio::task::JoinHandle;
 use tracing::{debug, error, info, warn};
 
+use crate::storage::Database;
+
 /// Unique identifier for tasks
 pub type TaskId = u64;
 
+/// Tasks `submit_task_blocking` lets wait for a permit unless configured otherwise
+pub const DEFAULT_MAX_PENDING: usize = 10_000;
+
 /// Status of a task
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TaskStatus {
@@ -26,6 +33,44 @@ pub enum TaskStatus {
     Cancelled,
 }
 
+impl TaskStatus {
+    /// Name stored in the database
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Running => "running",
+            Self::Completed => "completed",
+            Self::Failed => "failed",
+            Self::Cancelled => "cancelled",
+        }
+    }
+}
+
+impl std::fmt::Display for TaskStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Unknown task status: {0}")]
+pub struct UnknownTaskStatus(pub String);
+
+impl std::str::FromStr for TaskStatus {
+    type Err = UnknownTaskStatus;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "pending" => Ok(Self::Pending),
+            "running" => Ok(Self::Running),
+            "completed" => Ok(Self::Completed),
+            "failed" => Ok(Self::Failed),
+            "cancelled" => Ok(Self::Cancelled),
+            _ => Err(UnknownTaskStatus(s.to_string())),
+        }
+    }
+}
+
 /// Result of a task execution
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskResult {
@@ -102,6 +147,10 @@ pub struct TaskManager {
     max_concurrent: usize,
     /// Semaphore to limit concurrency
     semaphore: Arc<Semaphore>,
+    /// Maximum number of tasks `submit_task_blocking` lets wait for a permit
+    max_pending: usize,
+    /// Slots for running plus pending tasks, held by tasks from `submit_task_blocking`
+    queue_slots: Arc<Semaphore>,
     /// In-memory store for task results
     task_store: Arc<DashMap<TaskId, TaskResult>>,
     /// Counter for generating unique task IDs
@@ -112,33 +161,142 @@ pub struct TaskManager {
     shutdown_tx: broadcast::Sender<()>,
     /// Join handles for running tasks
     task_handles: Arc<DashMap<TaskId, JoinHandle<()>>>,
+    /// Task rows are persisted here when attached
+    database: Option<Arc<Database>>,
+    /// Time limit for `execute` unless overridden per submit
+    default_timeout: Option<Duration>,
+    /// Woken whenever a task finishes, for tasks waiting on dependencies
+    task_finished: Arc<Notify>,
+    /// Bounds in milliseconds of a random pause before each task executes
+    human_delay: Option<(u64, u64)>,
 }
 
 impl TaskManager {
     /// Create a new TaskManager with the specified concurrency limit
     pub fn new(max_concurrent: usize) -> Self {
+        Self::with_queue_limit(max_concurrent, DEFAULT_MAX_PENDING)
+    }
+
+    /// Create a TaskManager whose `submit_task_blocking` waits once `max_pending` tasks
+    /// are already queued behind the `max_concurrent` running ones
+    pub fn with_queue_limit(max_concurrent: usize, max_pending: usize) -> Self {
         let semaphore = Arc::new(Semaphore::new(max_concurrent));
+        let queue_slots = Arc::new(Semaphore::new(max_concurrent + max_pending));
         let task_store = Arc::new(DashMap::new());
         let task_id_counter = AtomicU64::new(0);
         let shutdown = Arc::new(AtomicBool::new(false));
         let (shutdown_tx, _) = broadcast::channel(1);
         let task_handles = Arc::new(DashMap::new());
 
-        info!("TaskManager created with max_concurrent={}", max_concurrent);
+        info!(
+            "TaskManager created with max_concurrent={}, max_pending={}",
+            max_concurrent, max_pending
+        );
 
         Self {
             max_concurrent,
             semaphore,
+            max_pending,
+            queue_slots,
             task_store,
             task_id_counter,
             shutdown,
             shutdown_tx,
             task_handles,
+            database: None,
+            default_timeout: None,
+            task_finished: Arc::new(Notify::new()),
+            human_delay: None,
         }
     }
 
+    /// Fail tasks whose `execute` runs longer than `timeout`, freeing their permit
+    pub fn with_default_timeout(mut self, timeout: Duration) -> Self {
+        self.default_timeout = Some(timeout);
+        self
+    }
+
+    /// Pause for a random `min..=max` before each task executes so tasks don't start
+    /// with robotic timing. The pause holds the task's permit but is not counted
+    /// against its timeout.
+    pub fn with_human_delay(mut self, min: Duration, max: Duration) -> Self {
+        let min_ms = min.as_millis() as u64;
+        let max_ms = (max.as_millis() as u64).max(min_ms);
+        self.human_delay = Some((min_ms, max_ms));
+        self
+    }
+
+    /// Persist tasks to `database`, continuing IDs after the highest one stored there so
+    /// they don't collide with rows from earlier runs
+    pub fn with_database(mut self, database: Arc<Database>) -> Result<Self> {
+        if let Some(max_task_id) = database.max_task_id()? {
+            self.task_id_counter
+                .fetch_max(max_task_id + 1, Ordering::SeqCst);
+            info!("TaskManager resuming task IDs at {}", max_task_id + 1);
+        }
+        self.database = Some(database);
+        Ok(self)
+    }
+
     /// Submit a task for execution
     pub async fn submit_task<T>(&self, task: T) -> Result<TaskId>
+    where
+        T: Task + 'static,
+    {
+        self.spawn_task(task, None, self.default_timeout, Vec::new())
+    }
+
+    /// Submit a task that starts only once every task in `deps` has completed; it is
+    /// cancelled instead if any of them fails or is cancelled
+    pub async fn submit_task_with_deps<T>(&self, task: T, deps: Vec<TaskId>) -> Result<TaskId>
+    where
+        T: Task + 'static,
+    {
+        if let Some(unknown) = deps.iter().find(|dep| !self.task_store.contains_key(dep)) {
+            return Err(anyhow::anyhow!("Unknown dependency task {}", unknown));
+        }
+
+        self.spawn_task(task, None, self.default_timeout, deps)
+    }
+
+    /// Submit a task that fails if `execute` runs longer than `timeout`, overriding the
+    /// default timeout
+    pub async fn submit_task_with_timeout<T>(&self, task: T, timeout: Duration) -> Result<TaskId>
+    where
+        T: Task + 'static,
+    {
+        self.spawn_task(task, None, Some(timeout), Vec::new())
+    }
+
+    /// Submit a task, first waiting while the pending queue is full so bursts of
+    /// submissions can't pile up unbounded
+    pub async fn submit_task_blocking<T>(&self, task: T) -> Result<TaskId>
+    where
+        T: Task + 'static,
+    {
+        if self.shutdown.load(Ordering::SeqCst) {
+            return Err(anyhow::anyhow!("TaskManager is shutting down"));
+        }
+
+        let mut shutdown_rx = self.shutdown_tx.subscribe();
+        let queue_slot = tokio::select! {
+            slot = Arc::clone(&self.queue_slots).acquire_owned() => slot?,
+            _ = shutdown_rx.recv() => {
+                return Err(anyhow::anyhow!("TaskManager is shutting down"));
+            }
+        };
+
+        self.spawn_task(task, Some(queue_slot), self.default_timeout, Vec::new())
+    }
+
+    /// Spawn `task` to run after `deps`, holding `queue_slot` (if any) until it finishes
+    fn spawn_task<T>(
+        &self,
+        task: T,
+        queue_slot: Option<OwnedSemaphorePermit>,
+        timeout: Option<Duration>,
+        deps: Vec<TaskId>,
+    ) -> Result<TaskId>
     where
         T: Task + 'static,
     {
@@ -152,6 +310,11 @@ impl TaskManager {
         // Create initial task result
         let task_result = TaskResult::pending(task_id);
         self.task_store.insert(task_id, task_result);
+        if let Some(database) = &self.database {
+            if let Err(e) = database.insert_task(task_id, &TaskStatus::Pending.to_string(), None) {
+                warn!("Failed to persist task {}: {}", task_id, e);
+            }
+        }
 
         debug!("Task {} '{}' submitted", task_id, task.name());
 
@@ -161,9 +324,26 @@ impl TaskManager {
         let shutdown = Arc::clone(&self.shutdown);
         let mut shutdown_rx = self.shutdown_tx.subscribe();
         let task_handles = Arc::clone(&self.task_handles);
+        let database = self.database.clone();
+        let task_finished = Arc::clone(&self.task_finished);
+        let human_delay = self.human_delay;
 
         // Spawn the task
         let handle = tokio::spawn(async move {
+            let _queue_slot = queue_slot;
+
+            // Wait for dependencies before taking a permit
+            if let Err(reason) =
+                wait_for_dependencies(&deps, &task_store, &task_finished, &mut shutdown_rx).await
+            {
+                info!("Task {} cancelled: {}", task_id, reason);
+                let mut result = TaskResult::pending(task_id).cancelled();
+                result.error_message = Some(reason);
+                store_result(&task_store, database.as_deref(), &task_finished, result);
+                task_handles.remove(&task_id);
+                return;
+            }
+
             // Try to acquire semaphore permit
             let permit = match semaphore.try_acquire() {
                 Ok(permit) => permit,
@@ -177,7 +357,7 @@ impl TaskManager {
                                     error!("Failed to acquire semaphore permit for task {}: {}", task_id, e);
                                     let result = TaskResult::pending(task_id)
                                         .failed(format!("Failed to acquire semaphore: {}", e));
-                                    task_store.insert(task_id, result);
+                                    store_result(&task_store, database.as_deref(), &task_finished, result);
                                     return;
                                 }
                             }
@@ -185,7 +365,7 @@ impl TaskManager {
                         _ = shutdown_rx.recv() => {
                             info!("Task {} cancelled before execution due to shutdown", task_id);
                             let result = TaskResult::pending(task_id).cancelled();
-                            task_store.insert(task_id, result);
+                            store_result(&task_store, database.as_deref(), &task_finished, result);
                             return;
                         }
                     }
@@ -196,22 +376,41 @@ impl TaskManager {
             if shutdown.load(Ordering::SeqCst) {
                 info!("Task {} cancelled due to shutdown", task_id);
                 let result = TaskResult::pending(task_id).cancelled();
-                task_store.insert(task_id, result);
+                store_result(&task_store, database.as_deref(), &task_finished, result);
                 return;
             }
 
             // Update task status to running
             let result = TaskResult::pending(task_id).running();
-            task_store.insert(task_id, result.clone());
+            store_result(
+                &task_store,
+                database.as_deref(),
+                &task_finished,
+                result.clone(),
+            );
             info!("Task {} '{}' started", task_id, task.name());
 
-            // Execute the task
+            // Execute the task; on timeout the execute future is dropped, aborting it
+            let execution = async {
+                if let Some((min_ms, max_ms)) = human_delay {
+                    let delay_ms = rand::thread_rng().gen_range(min_ms..=max_ms);
+                    tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+                }
+                match timeout {
+                    Some(limit) => tokio::time::timeout(limit, task.execute())
+                        .await
+                        .unwrap_or_else(|_| {
+                            Err(anyhow::anyhow!("Task timed out after {:?}", limit))
+                        }),
+                    None => task.execute().await,
+                }
+            };
             let execution_result = tokio::select! {
-                result = task.execute() => result,
+                result = execution => result,
                 _ = shutdown_rx.recv() => {
                     info!("Task {} '{}' interrupted by shutdown", task_id, task.name());
                     let result = result.cancelled();
-                    task_store.insert(task_id, result);
+                    store_result(&task_store, database.as_deref(), &task_finished, result);
                     return;
                 }
             };
@@ -228,7 +427,12 @@ impl TaskManager {
                 }
             };
 
-            task_store.insert(task_id, final_result);
+            store_result(
+                &task_store,
+                database.as_deref(),
+                &task_finished,
+                final_result,
+            );
 
             // Release semaphore permit explicitly
             drop(permit);
@@ -340,6 +544,69 @@ impl TaskManager {
     pub fn available_permits(&self) -> usize {
         self.semaphore.available_permits()
     }
+
+    /// Get the pending-queue bound for `submit_task_blocking`
+    pub fn max_pending(&self) -> usize {
+        self.max_pending
+    }
+}
+
+/// Wait until every task in `deps` has completed, or return why one never will
+async fn wait_for_dependencies(
+    deps: &[TaskId],
+    task_store: &DashMap<TaskId, TaskResult>,
+    task_finished: &Notify,
+    shutdown_rx: &mut broadcast::Receiver<()>,
+) -> std::result::Result<(), String> {
+    loop {
+        // Register before checking so a task finishing in between isn't missed
+        let notified = task_finished.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+
+        let mut waiting = false;
+        for dep in deps {
+            match task_store.get(dep).map(|result| result.status.clone()) {
+                Some(TaskStatus::Completed) => {}
+                Some(TaskStatus::Pending) | Some(TaskStatus::Running) => waiting = true,
+                Some(status) => return Err(format!("dependency {} ended {}", dep, status)),
+                None => return Err(format!("dependency {} is unknown", dep)),
+            }
+        }
+        if !waiting {
+            return Ok(());
+        }
+
+        tokio::select! {
+            _ = &mut notified => {}
+            _ = shutdown_rx.recv() => return Err("shutting down".to_string()),
+        }
+    }
+}
+
+/// Record a task's latest result in memory and, if attached, in the database
+fn store_result(
+    task_store: &DashMap<TaskId, TaskResult>,
+    database: Option<&Database>,
+    task_finished: &Notify,
+    result: TaskResult,
+) {
+    if let Some(database) = database {
+        if let Err(e) = database.update_task_status(
+            result.task_id,
+            &result.status.to_string(),
+            result.started_at,
+            result.completed_at,
+            result.error_message.as_deref(),
+        ) {
+            warn!("Failed to persist task {}: {}", result.task_id, e);
+        }
+    }
+    let finished = !matches!(result.status, TaskStatus::Pending | TaskStatus::Running);
+    task_store.insert(result.task_id, result);
+    if finished {
+        task_finished.notify_waiters();
+    }
 }
 
 impl Drop for TaskManager {
@@ -481,6 +748,97 @@ mod tests {
         assert!(max_concurrent_observed <= max_concurrent);
     }
 
+    #[tokio::test]
+    async fn test_task_manager_timeout() {
+        let manager = TaskManager::new(1).with_default_timeout(Duration::from_millis(50));
+
+        let hung = manager
+            .submit_task(DummyTask::new("hung_task", 10_000))
+            .await
+            .unwrap();
+        sleep(Duration::from_millis(150)).await;
+
+        let result = manager.get_task_result(hung).unwrap();
+        assert_eq!(result.status, TaskStatus::Failed);
+        assert!(result.error_message.unwrap().contains("timed out"));
+        assert_eq!(manager.available_permits(), 1);
+
+        // A per-submit timeout overrides the default
+        let slow = manager
+            .submit_task_with_timeout(DummyTask::new("slow_task", 100), Duration::from_secs(1))
+            .await
+            .unwrap();
+        sleep(Duration::from_millis(200)).await;
+        assert_eq!(
+            manager.get_task_result(slow).unwrap().status,
+            TaskStatus::Completed
+        );
+    }
+
+    #[tokio::test]
+    async fn test_task_dependencies_chain() {
+        let manager = TaskManager::new(5);
+
+        let a = manager
+            .submit_task(DummyTask::new("monitor", 100))
+            .await
+            .unwrap();
+        let b = manager
+            .submit_task_with_deps(DummyTask::new("checkout", 10), vec![a])
+            .await
+            .unwrap();
+
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(
+            manager.get_task_result(b).unwrap().status,
+            TaskStatus::Pending
+        );
+
+        sleep(Duration::from_millis(150)).await;
+        let a_result = manager.get_task_result(a).unwrap();
+        let b_result = manager.get_task_result(b).unwrap();
+        assert_eq!(b_result.status, TaskStatus::Completed);
+        assert!(b_result.started_at.unwrap() >= a_result.completed_at.unwrap());
+
+        assert!(manager
+            .submit_task_with_deps(DummyTask::new("orphan", 10), vec![999])
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_failed_dependency_cancels_dependent() {
+        let manager = TaskManager::new(5);
+
+        let a = manager
+            .submit_task(DummyTask::new("monitor", 50).with_failure())
+            .await
+            .unwrap();
+        let b = manager
+            .submit_task_with_deps(DummyTask::new("checkout", 10), vec![a])
+            .await
+            .unwrap();
+        let c = manager
+            .submit_task_with_deps(DummyTask::new("notify", 10), vec![b])
+            .await
+            .unwrap();
+
+        sleep(Duration::from_millis(150)).await;
+        assert_eq!(
+            manager.get_task_result(a).unwrap().status,
+            TaskStatus::Failed
+        );
+        let b_result = manager.get_task_result(b).unwrap();
+        assert_eq!(b_result.status, TaskStatus::Cancelled);
+        assert!(b_result.started_at.is_none());
+        assert!(b_result.error_message.unwrap().contains("failed"));
+        // Cancellation cascades down the chain
+        assert_eq!(
+            manager.get_task_result(c).unwrap().status,
+            TaskStatus::Cancelled
+        );
+    }
+
     #[tokio::test]
     async fn test_task_manager_failed_task() {
         let manager = TaskManager::new(5);
@@ -545,4 +903,28 @@ mod tests {
         assert_eq!(failed.len(), 1);
         assert_eq!(manager.total_tasks(), 3);
     }
+
+    #[test]
+    fn test_task_status_round_trip() {
+        for status in [
+            TaskStatus::Pending,
+            TaskStatus::Running,
+            TaskStatus::Completed,
+            TaskStatus::Failed,
+            TaskStatus::Cancelled,
+        ] {
+            let name = status.to_string();
+            assert_eq!(name.parse::<TaskStatus>().unwrap(), status);
+            // Serialized form stays the variant name so existing task results still load
+            assert_eq!(
+                serde_json::to_string(&status).unwrap(),
+                format!("\"{:?}\"", status)
+            );
+        }
+
+        assert_eq!(
+            "paused".parse::<TaskStatus>(),
+            Err(UnknownTaskStatus("paused".to_string()))
+        );
+    }
 }
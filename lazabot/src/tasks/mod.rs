@@ -1,3 +1,5 @@
 pub mod manager;
+pub mod shutdown;
 
-pub use manager::{Task, TaskId, TaskManager, TaskResult, TaskStatus};
+pub use manager::{Task, TaskId, TaskManager, TaskResult, TaskStatus, UnknownTaskStatus};
+pub use shutdown::ShutdownCoordinator;
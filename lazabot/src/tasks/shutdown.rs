@@ -0,0 +1,144 @@
+use parking_lot::Mutex;
+use std::future::Future;
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use crate::core::MonitorEngine;
+use crate::tasks::TaskManager;
+
+/// Coordinates graceful shutdown on Ctrl-C or SIGTERM.
+///
+/// Clones share the same token and registrations, so one coordinator can be
+/// installed in `main` and handed to command handlers.
+#[derive(Clone, Default)]
+pub struct ShutdownCoordinator {
+    token: CancellationToken,
+    task_managers: Arc<Mutex<Vec<Arc<TaskManager>>>>,
+    monitor_engines: Arc<Mutex<Vec<Arc<MonitorEngine>>>>,
+}
+
+impl ShutdownCoordinator {
+    /// Create a new shutdown coordinator
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Token cancelled once shutdown begins
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+
+    /// Shut down `task_manager` when a signal arrives
+    pub fn register_task_manager(&self, task_manager: Arc<TaskManager>) {
+        self.task_managers.lock().push(task_manager);
+    }
+
+    /// Stop `monitor_engine` when a signal arrives
+    pub fn register_monitor_engine(&self, monitor_engine: Arc<MonitorEngine>) {
+        self.monitor_engines.lock().push(monitor_engine);
+    }
+
+    /// Wait for Ctrl-C or SIGTERM, then shut down
+    pub async fn listen(self) {
+        self.run_until(wait_for_signal()).await;
+    }
+
+    /// Wait for `signal` to resolve, then shut down
+    pub async fn run_until<F>(self, signal: F)
+    where
+        F: Future<Output = ()>,
+    {
+        tokio::select! {
+            _ = signal => {
+                info!("Shutdown signal received");
+                self.shutdown().await;
+            }
+            _ = self.token.cancelled() => {}
+        }
+    }
+
+    /// Cancel the token, shut down task managers and stop monitors
+    pub async fn shutdown(&self) {
+        self.token.cancel();
+
+        let task_managers = self.task_managers.lock().clone();
+        for task_manager in task_managers {
+            task_manager.shutdown().await;
+        }
+
+        let monitor_engines = self.monitor_engines.lock().clone();
+        for monitor_engine in monitor_engines {
+            if let Err(e) = monitor_engine.stop().await {
+                warn!("Failed to stop monitor engine: {}", e);
+            }
+        }
+
+        info!("Shutdown complete");
+    }
+}
+
+/// Resolve on Ctrl-C, or SIGTERM on unix
+async fn wait_for_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                warn!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::oneshot;
+
+    #[tokio::test]
+    async fn test_coordinator_resolves_on_signal() {
+        let coordinator = ShutdownCoordinator::new();
+        let token = coordinator.token();
+        let task_manager = Arc::new(TaskManager::new(2));
+        let monitor_engine = Arc::new(MonitorEngine::new());
+        monitor_engine.start().await.unwrap();
+        coordinator.register_task_manager(task_manager.clone());
+        coordinator.register_monitor_engine(monitor_engine.clone());
+
+        let (signal_tx, signal_rx) = oneshot::channel::<()>();
+        let handle = tokio::spawn(coordinator.run_until(async {
+            let _ = signal_rx.await;
+        }));
+
+        assert!(!token.is_cancelled());
+        signal_tx.send(()).unwrap();
+
+        tokio::time::timeout(std::time::Duration::from_secs(5), handle)
+            .await
+            .expect("coordinator did not resolve")
+            .unwrap();
+
+        assert!(token.is_cancelled());
+        assert!(task_manager.is_shutting_down());
+        assert!(!monitor_engine.is_running().await);
+    }
+}
@@ -0,0 +1,151 @@
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use crate::core::CheckoutConfig;
+
+/// Cart id returned by the default add-to-cart response
+pub const MOCK_CART_ID: &str = "CART123";
+
+/// Order id returned by the default submit response
+pub const MOCK_ORDER_ID: &str = "ORDER123";
+
+/// Checkout endpoints served by [`MockLazadaServer`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Endpoint {
+    AddToCart,
+    CheckoutUrl,
+    Shipping,
+    Payment,
+    CaptchaCheck,
+    Submit,
+}
+
+impl Endpoint {
+    const ALL: [Endpoint; 6] = [
+        Endpoint::AddToCart,
+        Endpoint::CheckoutUrl,
+        Endpoint::Shipping,
+        Endpoint::Payment,
+        Endpoint::CaptchaCheck,
+        Endpoint::Submit,
+    ];
+
+    fn method(&self) -> &'static str {
+        match self {
+            Endpoint::CheckoutUrl | Endpoint::CaptchaCheck => "GET",
+            _ => "POST",
+        }
+    }
+
+    fn path(&self) -> String {
+        match self {
+            Endpoint::AddToCart => "/cart/add".to_string(),
+            Endpoint::CheckoutUrl => format!("/cart/{}/checkout", MOCK_CART_ID),
+            Endpoint::Shipping => format!("/checkout/{}/shipping", MOCK_CART_ID),
+            Endpoint::Payment => format!("/checkout/{}/payment", MOCK_CART_ID),
+            Endpoint::CaptchaCheck => format!("/checkout/{}/captcha-check", MOCK_CART_ID),
+            Endpoint::Submit => format!("/checkout/{}/submit", MOCK_CART_ID),
+        }
+    }
+
+    /// Response for a successful checkout with no captcha
+    fn default_response(&self, uri: &str) -> (u16, Value) {
+        let body = match self {
+            Endpoint::AddToCart => json!({
+                "success": true,
+                "cart_id": MOCK_CART_ID,
+                "message": "Product added successfully"
+            }),
+            Endpoint::CheckoutUrl => json!({
+                "checkout_url": format!("{}/checkout/{}", uri, MOCK_CART_ID),
+                "token": "CHECKOUT_TOKEN123"
+            }),
+            Endpoint::Shipping | Endpoint::Payment => json!({ "success": true }),
+            Endpoint::CaptchaCheck => json!({ "has_captcha": false }),
+            Endpoint::Submit => json!({
+                "success": true,
+                "order_id": MOCK_ORDER_ID
+            }),
+        };
+        (200, body)
+    }
+}
+
+/// Response overrides for [`MockLazadaServer`]; endpoints not overridden succeed
+#[derive(Debug, Clone, Default)]
+pub struct MockResponses {
+    overrides: HashMap<Endpoint, (u16, Value)>,
+}
+
+impl MockResponses {
+    /// Respond to `endpoint` with `status` and a JSON `body`
+    pub fn with_response(mut self, endpoint: Endpoint, status: u16, body: Value) -> Self {
+        self.overrides.insert(endpoint, (status, body));
+        self
+    }
+}
+
+/// In-process stand-in for the Lazada cart and checkout API
+pub struct MockLazadaServer {
+    server: MockServer,
+}
+
+impl MockLazadaServer {
+    /// Start a server where every checkout step succeeds
+    pub async fn start() -> Self {
+        Self::start_with(MockResponses::default()).await
+    }
+
+    /// Start a server with some endpoint responses overridden
+    pub async fn start_with(responses: MockResponses) -> Self {
+        let server = MockServer::start().await;
+
+        for endpoint in Endpoint::ALL {
+            let (status, body) = responses
+                .overrides
+                .get(&endpoint)
+                .cloned()
+                .unwrap_or_else(|| endpoint.default_response(&server.uri()));
+
+            Mock::given(method(endpoint.method()))
+                .and(path(endpoint.path()))
+                .respond_with(ResponseTemplate::new(status).set_body_json(body))
+                .mount(&server)
+                .await;
+        }
+
+        Self { server }
+    }
+
+    /// Base URL of the server
+    pub fn uri(&self) -> String {
+        self.server.uri()
+    }
+
+    /// Checkout configuration pointed at this server
+    pub fn checkout_config(&self) -> CheckoutConfig {
+        CheckoutConfig {
+            base_url: self.uri(),
+            ..CheckoutConfig::default()
+        }
+    }
+
+    /// Number of requests received by `endpoint`
+    pub async fn request_count(&self, endpoint: Endpoint) -> usize {
+        let path = endpoint.path();
+        self.server
+            .received_requests()
+            .await
+            .unwrap_or_default()
+            .iter()
+            .filter(|request| request.url.path() == path)
+            .count()
+    }
+
+    /// Underlying wiremock server, for mounting extra endpoints
+    pub fn server(&self) -> &MockServer {
+        &self.server
+    }
+}
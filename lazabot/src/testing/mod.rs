@@ -0,0 +1,5 @@
+//! Test helpers, enabled with the `testing` feature.
+
+pub mod mock_server;
+
+pub use mock_server::{Endpoint, MockLazadaServer, MockResponses, MOCK_CART_ID, MOCK_ORDER_ID};
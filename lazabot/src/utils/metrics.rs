@@ -5,7 +5,11 @@
 //! - Request rate (requests per second)
 //! - Active tasks counter
 //! - Uptime tracking
+//!
+//! `/status` additionally reports proxy, session pool and task manager state as JSON.
 
+use chrono::{DateTime, Utc};
+use serde::Serialize;
 use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -14,6 +18,12 @@ use tokio::net::TcpListener;
 use tracing::{error, info, warn};
 
 use parking_lot::Mutex;
+
+use crate::captcha::CaptchaUsage;
+use crate::core::SessionPool;
+use crate::proxy::ProxyManager;
+use crate::tasks::TaskManager;
+
 /// Shared metrics collector
 #[derive(Clone, Debug)]
 pub struct MetricsCollector {
@@ -34,6 +44,12 @@ struct MetricsInner {
     // Rate tracking
     last_request_count: AtomicU64,
     last_rate_check: Mutex<Instant>,
+
+    // Captcha solver usage, as last reported
+    captcha_usage: Mutex<CaptchaUsage>,
+
+    // When the monitor last emitted an event
+    last_monitor_event: Mutex<Option<DateTime<Utc>>>,
 }
 
 impl MetricsCollector {
@@ -48,6 +64,8 @@ impl MetricsCollector {
                 start_time: Instant::now(),
                 last_request_count: AtomicU64::new(0),
                 last_rate_check: Mutex::new(Instant::now()),
+                captcha_usage: Mutex::new(CaptchaUsage::default()),
+                last_monitor_event: Mutex::new(None),
             }),
         }
     }
@@ -82,6 +100,24 @@ impl MetricsCollector {
         self.inner.active_tasks.store(count, Ordering::Relaxed);
     }
 
+    /// Record the captcha solver's current usage totals
+    pub fn set_captcha_usage(&self, usage: CaptchaUsage) {
+        *self.inner.captcha_usage.lock() = usage;
+    }
+
+    /// Record that the monitor emitted an event at `at`
+    pub fn record_monitor_event(&self, at: DateTime<Utc>) {
+        let mut last = self.inner.last_monitor_event.lock();
+        if last.is_none_or(|last| at > last) {
+            *last = Some(at);
+        }
+    }
+
+    /// When the monitor last emitted an event, if ever
+    pub fn last_monitor_event(&self) -> Option<DateTime<Utc>> {
+        *self.inner.last_monitor_event.lock()
+    }
+
     /// Get current metrics snapshot
     fn get_snapshot(&self) -> MetricsSnapshot {
         let total = self.inner.total_requests.load(Ordering::Relaxed);
@@ -117,6 +153,7 @@ impl MetricsCollector {
             active_tasks: active,
             uptime_seconds: uptime.as_secs(),
             requests_per_sec,
+            captcha_usage: self.inner.captcha_usage.lock().clone(),
         }
     }
 
@@ -147,13 +184,28 @@ impl MetricsCollector {
              \n\
              # HELP lazabot_uptime_seconds Uptime in seconds\n\
              # TYPE lazabot_uptime_seconds counter\n\
-             lazabot_uptime_seconds {}\n",
+             lazabot_uptime_seconds {}\n\
+             \n\
+             # HELP lazabot_captcha_attempts_total Captcha solves attempted\n\
+             # TYPE lazabot_captcha_attempts_total counter\n\
+             lazabot_captcha_attempts_total {}\n\
+             \n\
+             # HELP lazabot_captcha_successes_total Captcha solves that succeeded\n\
+             # TYPE lazabot_captcha_successes_total counter\n\
+             lazabot_captcha_successes_total {}\n\
+             \n\
+             # HELP lazabot_captcha_cost_usd_total Estimated captcha solving spend in USD\n\
+             # TYPE lazabot_captcha_cost_usd_total counter\n\
+             lazabot_captcha_cost_usd_total {:.5}\n",
             snapshot.total_requests,
             snapshot.success_requests,
             snapshot.failed_requests,
             snapshot.active_tasks,
             snapshot.requests_per_sec,
             snapshot.uptime_seconds,
+            snapshot.captcha_usage.attempts,
+            snapshot.captcha_usage.successes,
+            snapshot.captcha_usage.estimated_cost_usd,
         )
     }
 }
@@ -173,12 +225,29 @@ pub struct MetricsSnapshot {
     pub active_tasks: usize,
     pub uptime_seconds: u64,
     pub requests_per_sec: f64,
+    pub captcha_usage: CaptchaUsage,
+}
+
+/// Body of the `/status` endpoint. Fields for sources the server wasn't given are null.
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusReport {
+    pub uptime_seconds: u64,
+    pub healthy_proxies: Option<usize>,
+    pub total_proxies: Option<usize>,
+    pub active_sessions: Option<usize>,
+    pub pending_tasks: Option<usize>,
+    /// Running tasks from the task manager, else the collector's active task gauge
+    pub active_tasks: usize,
+    pub last_monitor_event: Option<DateTime<Utc>>,
 }
 
 /// Metrics HTTP server
 pub struct MetricsServer {
     collector: MetricsCollector,
     bind_addr: String,
+    proxy_manager: Option<Arc<ProxyManager>>,
+    session_pool: Option<Arc<SessionPool>>,
+    task_manager: Option<Arc<TaskManager>>,
 }
 
 impl MetricsServer {
@@ -187,6 +256,61 @@ impl MetricsServer {
         Self {
             collector,
             bind_addr: bind_addr.into(),
+            proxy_manager: None,
+            session_pool: None,
+            task_manager: None,
+        }
+    }
+
+    /// Report proxy health from `manager` on `/status`
+    pub fn with_proxy_manager(mut self, manager: Arc<ProxyManager>) -> Self {
+        self.proxy_manager = Some(manager);
+        self
+    }
+
+    /// Report pooled sessions from `pool` on `/status`
+    pub fn with_session_pool(mut self, pool: Arc<SessionPool>) -> Self {
+        self.session_pool = Some(pool);
+        self
+    }
+
+    /// Report pending and running tasks from `manager` on `/status`
+    pub fn with_task_manager(mut self, manager: Arc<TaskManager>) -> Self {
+        self.task_manager = Some(manager);
+        self
+    }
+
+    /// Gather the `/status` report from the collector and attached managers
+    pub async fn status(&self) -> StatusReport {
+        let (healthy_proxies, total_proxies) = match &self.proxy_manager {
+            Some(manager) => (
+                Some(manager.healthy_proxies_count().await),
+                Some(manager.total_proxies()),
+            ),
+            None => (None, None),
+        };
+        let active_sessions = match &self.session_pool {
+            Some(pool) => Some(pool.len().await),
+            None => None,
+        };
+        let snapshot = self.collector.get_snapshot();
+
+        StatusReport {
+            uptime_seconds: snapshot.uptime_seconds,
+            healthy_proxies,
+            total_proxies,
+            active_sessions,
+            pending_tasks: self
+                .task_manager
+                .as_ref()
+                .map(|manager| manager.pending_tasks_count()),
+            active_tasks: self
+                .task_manager
+                .as_ref()
+                .map_or(snapshot.active_tasks, |manager| {
+                    manager.running_tasks_count()
+                }),
+            last_monitor_event: self.collector.last_monitor_event(),
         }
     }
 
@@ -197,11 +321,13 @@ impl MetricsServer {
             "Metrics server listening on http://{}/metrics",
             self.bind_addr
         );
+        let server = Arc::new(self);
 
         loop {
             match listener.accept().await {
                 Ok((mut socket, addr)) => {
-                    let collector = self.collector.clone();
+                    let server = Arc::clone(&server);
+                    let collector = server.collector.clone();
 
                     tokio::spawn(async move {
                         let mut buffer = vec![0u8; 1024];
@@ -223,6 +349,28 @@ impl MetricsServer {
                                         metrics
                                     );
 
+                                    if let Err(e) = socket.write_all(response.as_bytes()).await {
+                                        error!("Failed to write response: {}", e);
+                                    }
+                                } else if request.starts_with("GET /status") {
+                                    let status = server.status().await;
+                                    let body = match serde_json::to_string(&status) {
+                                        Ok(body) => body,
+                                        Err(e) => {
+                                            error!("Failed to serialize status: {}", e);
+                                            "{}".to_string()
+                                        }
+                                    };
+                                    let response = format!(
+                                        "HTTP/1.1 200 OK\r\n\
+                                         Content-Type: application/json\r\n\
+                                         Content-Length: {}\r\n\
+                                         \r\n\
+                                         {}",
+                                        body.len(),
+                                        body
+                                    );
+
                                     if let Err(e) = socket.write_all(response.as_bytes()).await {
                                         error!("Failed to write response: {}", e);
                                     }
@@ -298,6 +446,22 @@ mod tests {
         assert!(output.contains("lazabot_active_tasks"));
     }
 
+    #[test]
+    fn test_prometheus_captcha_usage() {
+        let collector = MetricsCollector::new();
+        collector.set_captcha_usage(CaptchaUsage {
+            attempts: 3,
+            successes: 2,
+            estimated_cost_usd: 0.00598,
+        });
+
+        let output = collector.format_prometheus();
+
+        assert!(output.contains("lazabot_captcha_attempts_total 3"));
+        assert!(output.contains("lazabot_captcha_successes_total 2"));
+        assert!(output.contains("lazabot_captcha_cost_usd_total 0.00598"));
+    }
+
     #[tokio::test]
     async fn test_metrics_server_creation() {
         let collector = MetricsCollector::new();
@@ -1,14 +1,21 @@
 use anyhow::Result;
 use reqwest::Method;
-use serde_json::json;
+use serde_json::{json, Value};
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
-use tokio::time::timeout;
+use tokio::time::{sleep, timeout};
 use wiremock::{
-    matchers::{header, method, path},
+    matchers::{header, method, path, query_param},
     Mock, MockServer, ResponseTemplate,
 };
 
-use lazabot::api::{ApiClient, ProxyInfo, RetryConfig};
+use lazabot::api::{
+    hmac_sha256_signer, ApiClient, ApiError, DohResolver, Har, ProxyInfo, RetryConfig,
+    TokenProvider,
+};
+use lazabot::proxy::ProxyManager;
 
 #[tokio::test]
 async fn test_api_client_get_request() -> Result<()> {
@@ -413,3 +420,1007 @@ async fn test_retry_config() {
     assert_eq!(custom_config.max_delay_ms, 5000);
     assert_eq!(custom_config.backoff_multiplier, 1.5);
 }
+
+#[tokio::test]
+async fn test_api_client_circuit_breaker() -> Result<()> {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/flaky"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(3)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/flaky"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock_server)
+        .await;
+
+    let retry_config = RetryConfig {
+        max_retries: 0,
+        ..RetryConfig::default()
+    };
+    let client = ApiClient::new(Some("TestAgent/1.0".to_string()))?
+        .with_retry_config(retry_config)
+        .with_circuit_breaker(3, Duration::from_millis(500));
+    let url = format!("{}/flaky", mock_server.uri());
+
+    // Failures until the circuit opens
+    for _ in 0..3 {
+        let response = client.request(Method::GET, &url, None, None, None).await?;
+        assert_eq!(response.status, 503);
+    }
+
+    // Open circuit fails fast without reaching the server
+    let start = std::time::Instant::now();
+    let err = client
+        .request(Method::GET, &url, None, None, None)
+        .await
+        .unwrap_err();
+    assert!(start.elapsed() < Duration::from_millis(100));
+    assert!(matches!(err, ApiError::CircuitOpen { .. }));
+    assert_eq!(mock_server.received_requests().await.unwrap().len(), 3);
+
+    // After the cooldown a trial request succeeds and closes the circuit
+    tokio::time::sleep(Duration::from_millis(600)).await;
+    let response = client.request(Method::GET, &url, None, None, None).await?;
+    assert_eq!(response.status, 200);
+
+    let response = client.request(Method::GET, &url, None, None, None).await?;
+    assert_eq!(response.status, 200);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_api_client_rate_limit() -> Result<()> {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/limited"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock_server)
+        .await;
+
+    let client = ApiClient::new(Some("TestAgent/1.0".to_string()))?.with_rate_limit(5.0, 5);
+    let url = format!("{}/limited", mock_server.uri());
+
+    // 5 requests fit in the burst, the other 5 are spaced 200ms apart
+    let start = std::time::Instant::now();
+    for _ in 0..10 {
+        let response = client.request(Method::GET, &url, None, None, None).await?;
+        assert_eq!(response.status, 200);
+    }
+
+    assert!(start.elapsed() >= Duration::from_millis(950));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_api_client_decompresses_brotli_and_gzip() -> Result<()> {
+    use std::io::Write;
+
+    let mock_server = MockServer::start().await;
+    let expected = r#"{"product":"Test Product","price":29.99}"#;
+
+    let mut brotli_body = Vec::new();
+    {
+        let mut encoder = brotli::CompressorWriter::new(&mut brotli_body, 4096, 5, 22);
+        encoder.write_all(expected.as_bytes())?;
+    }
+
+    let mut gzip_encoder =
+        flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    gzip_encoder.write_all(expected.as_bytes())?;
+    let gzip_body = gzip_encoder.finish()?;
+
+    Mock::given(method("GET"))
+        .and(path("/brotli"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("Content-Encoding", "br")
+                .set_body_raw(brotli_body, "application/json"),
+        )
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/gzip"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("Content-Encoding", "gzip")
+                .set_body_raw(gzip_body, "application/json"),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let client = ApiClient::new(Some("TestAgent/1.0".to_string()))?;
+
+    // Same Accept-Encoding the stealth headers advertise
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert("Accept-Encoding", "gzip, deflate, br".parse()?);
+
+    for endpoint in ["brotli", "gzip"] {
+        let response = client
+            .request(
+                Method::GET,
+                &format!("{}/{}", mock_server.uri(), endpoint),
+                Some(headers.clone()),
+                None,
+                None,
+            )
+            .await?;
+
+        assert_eq!(response.status, 200);
+        assert_eq!(response.text, expected, "{} body not decoded", endpoint);
+    }
+
+    Ok(())
+}
+
+/// Writer that collects formatted log output for assertions
+#[derive(Clone, Default)]
+struct LogCapture(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+impl std::io::Write for LogCapture {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_api_client_logging_redacts_secrets() -> Result<()> {
+    let capture = LogCapture::default();
+    let writer = capture.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .with_ansi(false)
+        .with_writer(move || writer.clone())
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/login"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "token": "abc",
+            "session_secret": "s3cr3t"
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let client = ApiClient::new(None)?
+        .with_logging(true)
+        .with_redacted_fields(&["session_secret"]);
+
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert("Authorization", "Bearer topsecret".parse()?);
+    let body = json!({ "username": "bob", "password": "hunter2" });
+
+    let response = client
+        .request(
+            Method::POST,
+            &format!("{}/login", mock_server.uri()),
+            Some(headers),
+            Some(body.to_string().into_bytes()),
+            None,
+        )
+        .await?;
+    assert_eq!(response.status, 200);
+
+    let logs = String::from_utf8(capture.0.lock().unwrap().clone())?;
+    assert!(logs.contains("POST"));
+    assert!(logs.contains("/login"));
+    assert!(logs.contains("status=200"));
+    assert!(logs.contains(r#""password":"[REDACTED]""#));
+    assert!(logs.contains(r#""username":"bob""#));
+    assert!(!logs.contains("hunter2"));
+    assert!(!logs.contains("topsecret"));
+    assert!(!logs.contains("s3cr3t"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_captcha_challenge_retries_on_next_proxy() -> Result<()> {
+    // Each mock server stands in for a forward proxy; the first one is flagged
+    let flagged_proxy = MockServer::start().await;
+    let clean_proxy = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/product"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "captcha_required": true
+        })))
+        .mount(&flagged_proxy)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/product"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": 1,
+            "in_stock": true
+        })))
+        .mount(&clean_proxy)
+        .await;
+
+    let as_proxy = |server: &MockServer| {
+        let addr = server.address();
+        ProxyInfo::new(addr.ip().to_string(), addr.port())
+    };
+    let first = as_proxy(&flagged_proxy);
+    let manager = Arc::new(ProxyManager::new(vec![
+        first.clone(),
+        as_proxy(&clean_proxy),
+    ]));
+    // The caller picked the first proxy for this request
+    manager.get_next_proxy().await;
+
+    let client = ApiClient::new(None)?.with_captcha_proxy_rotation(manager.clone(), 2);
+
+    let response = client
+        .request(
+            Method::GET,
+            "http://shop.lazada.test/product",
+            None,
+            None,
+            Some(first.clone()),
+        )
+        .await?;
+
+    assert!(!response.is_captcha_challenge());
+    assert_eq!(response.json::<serde_json::Value>()?["in_stock"], true);
+    assert_eq!(flagged_proxy.received_requests().await.unwrap().len(), 1);
+    assert_eq!(clean_proxy.received_requests().await.unwrap().len(), 1);
+    assert!(!manager.is_proxy_healthy(&first).await);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_captcha_challenge_returned_without_rotation() -> Result<()> {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/product"))
+        .respond_with(ResponseTemplate::new(200).insert_header("x-captcha-required", "1"))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = ApiClient::new(None)?;
+    let response = client
+        .request(
+            Method::GET,
+            &format!("{}/product", mock_server.uri()),
+            None,
+            None,
+            None,
+        )
+        .await?;
+
+    assert!(response.is_captcha_challenge());
+
+    Ok(())
+}
+
+/// Mount a DoH JSON answer for `name`/`record_type` on the mock server
+async fn mount_doh_answer(server: &MockServer, name: &str, record_type: &str, answers: Value) {
+    Mock::given(method("GET"))
+        .and(path("/dns-query"))
+        .and(query_param("name", name))
+        .and(query_param("type", record_type))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "Status": 0,
+            "Answer": answers
+        })))
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn test_doh_resolver_lookup() -> Result<()> {
+    let doh_server = MockServer::start().await;
+    mount_doh_answer(
+        &doh_server,
+        "shop.lazada.test",
+        "A",
+        json!([
+            { "name": "shop.lazada.test", "type": 5, "TTL": 60, "data": "edge.lazada.test." },
+            { "name": "edge.lazada.test", "type": 1, "TTL": 60, "data": "203.0.113.7" }
+        ]),
+    )
+    .await;
+    mount_doh_answer(
+        &doh_server,
+        "shop.lazada.test",
+        "AAAA",
+        json!([{ "name": "edge.lazada.test", "type": 28, "TTL": 60, "data": "2001:db8::7" }]),
+    )
+    .await;
+
+    let resolver = DohResolver::new(format!("{}/dns-query", doh_server.uri()))?;
+    let addrs = resolver.lookup("shop.lazada.test").await?;
+
+    assert_eq!(
+        addrs,
+        vec![
+            "203.0.113.7".parse::<IpAddr>()?,
+            "2001:db8::7".parse::<IpAddr>()?
+        ]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_api_client_resolves_through_doh() -> Result<()> {
+    let doh_server = MockServer::start().await;
+    let target = MockServer::start().await;
+
+    // Only the DoH endpoint knows this name, so the request can't succeed via system DNS
+    mount_doh_answer(
+        &doh_server,
+        "shop.lazada.test",
+        "A",
+        json!([{ "name": "shop.lazada.test", "type": 1, "TTL": 60, "data": "127.0.0.1" }]),
+    )
+    .await;
+    mount_doh_answer(&doh_server, "shop.lazada.test", "AAAA", json!([])).await;
+
+    Mock::given(method("GET"))
+        .and(path("/ping"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("pong"))
+        .mount(&target)
+        .await;
+
+    let resolver = DohResolver::new(format!("{}/dns-query", doh_server.uri()))?;
+    let client = ApiClient::new(None)?.with_doh_resolver(resolver)?;
+
+    let response = client
+        .request(
+            Method::GET,
+            &format!("http://shop.lazada.test:{}/ping", target.address().port()),
+            None,
+            None,
+            None,
+        )
+        .await?;
+
+    assert_eq!(response.text, "pong");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_api_client_hmac_signer() -> Result<()> {
+    let mock_server = MockServer::start().await;
+
+    // HMAC-SHA256("secret-key", "POST\n/orders?page=2\n{\"item_id\":42}")
+    Mock::given(method("POST"))
+        .and(path("/orders"))
+        .and(header(
+            "x-signature",
+            "6d2a9fae29897f62a4ebc4c380046d8ed90fa39028d6e224f92356e20fb76628",
+        ))
+        .and(header("content-type", "application/json"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = ApiClient::new(None)?.with_signer(hmac_sha256_signer("secret-key"));
+
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert("content-type", "application/json".parse()?);
+    let response = client
+        .request(
+            Method::POST,
+            &format!("{}/orders?page=2", mock_server.uri()),
+            Some(headers),
+            Some(br#"{"item_id":42}"#.to_vec()),
+            None,
+        )
+        .await?;
+
+    assert_eq!(response.status, 200);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_api_client_body_size_limit() -> Result<()> {
+    use std::io::Write;
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/huge"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(vec![b'x'; 4096]))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    // Small on the wire, 256 KiB once decoded, so only the streamed length trips the limit
+    let mut gzip_encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+    gzip_encoder.write_all(&vec![0u8; 256 * 1024])?;
+    let gzip_bomb = gzip_encoder.finish()?;
+    assert!(gzip_bomb.len() < 1024);
+
+    Mock::given(method("GET"))
+        .and(path("/bomb"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("Content-Encoding", "gzip")
+                .set_body_raw(gzip_bomb, "application/octet-stream"),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/small"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(vec![b'x'; 512]))
+        .mount(&mock_server)
+        .await;
+
+    let client = ApiClient::new(None)?.with_max_body_size(1024);
+
+    for endpoint in ["huge", "bomb"] {
+        let err = client
+            .request(
+                Method::GET,
+                &format!("{}/{}", mock_server.uri(), endpoint),
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap_err();
+
+        assert!(
+            matches!(err, ApiError::BodyTooLarge { limit: 1024 }),
+            "{}: {:#}",
+            endpoint,
+            err
+        );
+    }
+
+    let response = client
+        .request(
+            Method::GET,
+            &format!("{}/small", mock_server.uri()),
+            None,
+            None,
+            None,
+        )
+        .await?;
+    assert_eq!(response.body.len(), 512);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_api_client_cookie_persistence() -> Result<()> {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/login"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .append_header(
+                    "Set-Cookie",
+                    "lzd_sid=abc123; Path=/; Max-Age=3600; HttpOnly",
+                )
+                .append_header("Set-Cookie", "cart=xyz; Path=/cart")
+                .append_header("Set-Cookie", "gone=1; Path=/; Max-Age=0"),
+        )
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/account"))
+        .and(header("cookie", "lzd_sid=abc123"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let temp_dir = tempfile::TempDir::new()?;
+    let cookie_file = temp_dir.path().join("cookies.bin");
+    let key = [7u8; 32];
+
+    let client = ApiClient::new(None)?;
+    client
+        .request(
+            Method::GET,
+            &format!("{}/login", mock_server.uri()),
+            None,
+            None,
+            None,
+        )
+        .await?;
+    // The expired cookie is left out, and nothing is written in the clear
+    assert_eq!(client.save_cookies(&cookie_file, &key).await?, 2);
+    let saved = std::fs::read(&cookie_file)?;
+    assert!(!String::from_utf8_lossy(&saved).contains("abc123"));
+
+    let restored = ApiClient::new(None)?;
+    assert!(restored
+        .load_cookies(&cookie_file, &[8u8; 32])
+        .await
+        .is_err());
+
+    // A fresh client picks the cookies up from the file, attributes included
+    assert_eq!(restored.load_cookies(&cookie_file, &key).await?, 2);
+    let root = restored.cookies_for_url(&mock_server.uri());
+    assert_eq!(root["lzd_sid"], "abc123");
+    assert!(!root.contains_key("cart"));
+    let cart = restored.cookies_for_url(&format!("{}/cart/items", mock_server.uri()));
+    assert_eq!(cart["cart"], "xyz");
+
+    let response = restored
+        .request(
+            Method::GET,
+            &format!("{}/account", mock_server.uri()),
+            None,
+            None,
+            None,
+        )
+        .await?;
+    assert_eq!(response.status, 200);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_api_client_rotates_user_agents() -> Result<()> {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/product"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock_server)
+        .await;
+
+    let pool = vec!["PoolAgent/1.0".to_string(), "PoolAgent/2.0".to_string()];
+    let client = ApiClient::new(Some("DefaultAgent/1.0".to_string()))?.with_user_agents(pool);
+    let url = format!("{}/product", mock_server.uri());
+
+    for _ in 0..2 {
+        client.request(Method::GET, &url, None, None, None).await?;
+    }
+
+    // An explicit User-Agent, e.g. from a fingerprint, is left alone
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert("User-Agent", "FingerprintAgent/1.0".parse()?);
+    client
+        .request(Method::GET, &url, Some(headers), None, None)
+        .await?;
+
+    let user_agents: Vec<String> = mock_server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|request| {
+            request
+                .headers
+                .get(&"user-agent".into())
+                .unwrap()
+                .last()
+                .to_string()
+        })
+        .collect();
+
+    assert_eq!(
+        user_agents,
+        vec!["PoolAgent/1.0", "PoolAgent/2.0", "FingerprintAgent/1.0"]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_api_client_error_kinds() -> Result<()> {
+    let client = ApiClient::new(None)?.with_retry_config(RetryConfig {
+        max_retries: 0,
+        base_delay_ms: 10,
+        max_delay_ms: 10,
+        backoff_multiplier: 1.0,
+    });
+
+    // Nothing listens on port 1
+    let err = client
+        .request(Method::GET, "http://127.0.0.1:1/", None, None, None)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::Connect(_)), "{:?}", err);
+
+    let err = client
+        .request(Method::GET, "not a url", None, None, None)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, ApiError::InvalidUrl { url, .. } if url == "not a url"),
+        "{:?}",
+        err
+    );
+
+    // The same refused connection through a proxy is blamed on the proxy
+    let err = client
+        .request(
+            Method::GET,
+            "http://shop.lazada.test/",
+            None,
+            None,
+            Some(ProxyInfo::new("127.0.0.1".to_string(), 1)),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::ProxyError(_)), "{:?}", err);
+
+    // Non-2xx responses are returned as-is unless asked otherwise
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/missing"))
+        .respond_with(ResponseTemplate::new(404).set_body_string("not json"))
+        .mount(&mock_server)
+        .await;
+
+    let response = client
+        .request(
+            Method::GET,
+            &format!("{}/missing", mock_server.uri()),
+            None,
+            None,
+            None,
+        )
+        .await?;
+    assert!(matches!(
+        response.json::<serde_json::Value>(),
+        Err(ApiError::Decode(_))
+    ));
+    assert!(matches!(
+        response.error_for_status(),
+        Err(ApiError::Status(404))
+    ));
+
+    // Existing anyhow callers keep using `?`
+    let err: anyhow::Error = ApiError::Status(503).into();
+    assert!(matches!(
+        err.downcast_ref::<ApiError>(),
+        Some(ApiError::Status(503))
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_proxy_failover_on_connect_failure() -> Result<()> {
+    // Nothing listens on port 1, so connecting to the first proxy is refused
+    let dead = ProxyInfo::new("127.0.0.1".to_string(), 1);
+    let live_proxy = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/product"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "id": 1 })))
+        .mount(&live_proxy)
+        .await;
+
+    let addr = live_proxy.address();
+    let manager = Arc::new(ProxyManager::new(vec![
+        dead.clone(),
+        ProxyInfo::new(addr.ip().to_string(), addr.port()),
+    ]));
+    // The caller picked the first proxy for this request
+    manager.get_next_proxy().await;
+
+    let client = ApiClient::new(None)?
+        .with_retry_config(RetryConfig {
+            max_retries: 0,
+            base_delay_ms: 10,
+            max_delay_ms: 10,
+            backoff_multiplier: 1.0,
+        })
+        .with_proxy_failover(manager.clone(), 2);
+
+    let response = client
+        .request(
+            Method::GET,
+            "http://shop.lazada.test/product",
+            None,
+            None,
+            Some(dead.clone()),
+        )
+        .await?;
+
+    assert_eq!(response.status, 200);
+    assert_eq!(live_proxy.received_requests().await.unwrap().len(), 1);
+    assert!(!manager.is_proxy_healthy(&dead).await);
+
+    // Without failover the refused connection is returned as-is
+    let client = ApiClient::new(None)?.with_retry_config(RetryConfig {
+        max_retries: 0,
+        base_delay_ms: 10,
+        max_delay_ms: 10,
+        backoff_multiplier: 1.0,
+    });
+    let err = client
+        .request(
+            Method::GET,
+            "http://shop.lazada.test/product",
+            None,
+            None,
+            Some(dead),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::ProxyError(_)), "{:?}", err);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_request_dedup_collapses_identical_gets() -> Result<()> {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/product"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "price": 9.5 }))
+                .set_delay(Duration::from_millis(300)),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let client = Arc::new(ApiClient::new(None)?.with_request_dedup(true));
+    let url = format!("{}/product", mock_server.uri());
+
+    let requests = (0..10).map(|_| {
+        let client = client.clone();
+        let url = url.clone();
+        tokio::spawn(async move { client.request(Method::GET, &url, None, None, None).await })
+    });
+    for response in futures::future::join_all(requests).await {
+        let response = response??;
+        assert_eq!(response.status, 200);
+        assert_eq!(response.json::<Value>()?["price"], 9.5);
+    }
+
+    let received = mock_server.received_requests().await.unwrap();
+    assert_eq!(received.len(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_request_dedup_shares_errors_and_skips_posts() -> Result<()> {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/large"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string("x".repeat(1024))
+                .set_delay(Duration::from_millis(300)),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/order"))
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_millis(100)))
+        .expect(3)
+        .mount(&mock_server)
+        .await;
+
+    let client = Arc::new(
+        ApiClient::new(None)?
+            .with_max_body_size(16)
+            .with_request_dedup(true),
+    );
+
+    // The caller that sent the request gets its own error, the one that joined it a shared one
+    let url = format!("{}/large", mock_server.uri());
+    let leader = tokio::spawn({
+        let client = client.clone();
+        let url = url.clone();
+        async move { client.request(Method::GET, &url, None, None, None).await }
+    });
+    sleep(Duration::from_millis(50)).await;
+    let follower = client.request(Method::GET, &url, None, None, None).await;
+
+    assert!(matches!(
+        leader.await?,
+        Err(ApiError::BodyTooLarge { limit: 16 })
+    ));
+    let follower = follower.unwrap_err();
+    assert!(matches!(follower, ApiError::Shared(_)));
+    assert!(matches!(
+        follower.root(),
+        ApiError::BodyTooLarge { limit: 16 }
+    ));
+
+    // POSTs are never coalesced
+    let url = format!("{}/order", mock_server.uri());
+    let requests = (0..3).map(|_| {
+        let client = client.clone();
+        let url = url.clone();
+        tokio::spawn(async move {
+            client
+                .request(Method::POST, &url, None, Some(b"{}".to_vec()), None)
+                .await
+        })
+    });
+    for response in futures::future::join_all(requests).await {
+        assert_eq!(response??.status, 200);
+    }
+
+    Ok(())
+}
+
+struct RefreshingTokens {
+    refreshed: AtomicUsize,
+}
+
+#[async_trait::async_trait]
+impl TokenProvider for RefreshingTokens {
+    async fn token(&self) -> Result<String> {
+        Ok(match self.refreshed.load(Ordering::SeqCst) {
+            0 => "expired".to_string(),
+            _ => "fresh".to_string(),
+        })
+    }
+
+    async fn refresh(&self) -> Result<String> {
+        self.refreshed.fetch_add(1, Ordering::SeqCst);
+        self.token().await
+    }
+}
+
+#[tokio::test]
+async fn test_token_provider_refreshes_on_401() -> Result<()> {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/orders"))
+        .and(header("Authorization", "Bearer expired"))
+        .respond_with(ResponseTemplate::new(401))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/orders"))
+        .and(header("Authorization", "Bearer fresh"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "orders": [] })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let tokens = Arc::new(RefreshingTokens {
+        refreshed: AtomicUsize::new(0),
+    });
+    let client = ApiClient::new(None)?.with_token_provider(tokens.clone());
+
+    let response = client
+        .request(
+            Method::GET,
+            &format!("{}/orders", mock_server.uri()),
+            None,
+            None,
+            None,
+        )
+        .await?;
+
+    assert_eq!(response.status, 200);
+    assert_eq!(tokens.refreshed.load(Ordering::SeqCst), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_har_capture_records_each_request() -> Result<()> {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/product"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "id": 1 })))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/login"))
+        .respond_with(ResponseTemplate::new(401).set_body_json(json!({ "error": "denied" })))
+        .mount(&mock_server)
+        .await;
+
+    let temp_dir = tempfile::TempDir::new()?;
+    let har_path = temp_dir.path().join("capture.har");
+    let client = ApiClient::new(None)?.with_har_capture(&har_path);
+
+    let product_url = format!("{}/product", mock_server.uri());
+    let login_url = format!("{}/login", mock_server.uri());
+    client
+        .request(Method::GET, &product_url, None, None, None)
+        .await?;
+    let body = serde_json::to_vec(&json!({ "username": "user", "password": "hunter2" }))?;
+    client
+        .request(Method::POST, &login_url, None, Some(body), None)
+        .await?;
+    client.flush_har().await?;
+
+    let har = Har::load(&har_path)?;
+    let entries = &har.log.entries;
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].request.url, product_url);
+    assert_eq!(entries[0].response.status, 200);
+    assert_eq!(entries[0].response.content.text, r#"{"id":1}"#);
+    assert_eq!(entries[1].request.method, "POST");
+    assert_eq!(entries[1].request.url, login_url);
+    assert_eq!(entries[1].response.status, 401);
+
+    // Sensitive fields are redacted like in the logs
+    let post_data = entries[1].request.post_data.as_ref().unwrap();
+    assert!(post_data.text.contains("[REDACTED]"));
+    assert!(!post_data.text.contains("hunter2"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_har_capture_hides_cookies_and_writes_on_drop() -> Result<()> {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/account"))
+        .respond_with(ResponseTemplate::new(200).append_header("Set-Cookie", "lzd_sid=secret"))
+        .mount(&mock_server)
+        .await;
+
+    let temp_dir = tempfile::TempDir::new()?;
+    let har_path = temp_dir.path().join("capture.har");
+    let client = ApiClient::new(None)?.with_har_capture(&har_path);
+
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert("Cookie", "lzd_sid=secret".parse()?);
+    client
+        .request(
+            Method::GET,
+            &format!("{}/account", mock_server.uri()),
+            Some(headers),
+            None,
+            None,
+        )
+        .await?;
+
+    // Nothing is written until the capture is flushed or the client goes away
+    assert!(!har_path.exists());
+    drop(client);
+
+    let content = std::fs::read_to_string(&har_path)?;
+    assert!(!content.contains("secret"));
+    let har = Har::load(&har_path)?;
+    let entry = &har.log.entries[0];
+    let cookie = entry
+        .request
+        .headers
+        .iter()
+        .find(|h| h.name == "cookie")
+        .unwrap();
+    assert_eq!(cookie.value, "[REDACTED]");
+    let set_cookie = entry
+        .response
+        .headers
+        .iter()
+        .find(|h| h.name == "set-cookie")
+        .unwrap();
+    assert_eq!(set_cookie.value, "[REDACTED]");
+    Ok(())
+}
@@ -1,8 +1,13 @@
 use anyhow::Result;
-use lazabot::api::ApiClient;
+use lazabot::api::{ApiClient, RetryConfig};
 use lazabot::captcha::MockCaptchaSolver;
-use lazabot::config::AccountSettings;
-use lazabot::core::{Account, CheckoutConfig, CheckoutEngine, Credentials, Product, Session};
+use lazabot::config::credentials::{CredentialVault, LazadaCredentials};
+use lazabot::config::{create_default_config, AccountConfig, AccountSettings};
+use lazabot::core::monitor::ProductInfo;
+use lazabot::core::{
+    Account, CheckoutConfig, CheckoutContext, CheckoutEngine, CheckoutErrorKind, Credentials,
+    Product, Session, SessionManager, SessionPool,
+};
 use std::sync::Arc;
 use tokio;
 use wiremock::matchers::{method, path};
@@ -110,13 +115,24 @@ async fn test_checkout_flow_success() -> Result<()> {
         "mock_recaptcha_solution".to_string(),
     ));
 
-    let checkout_engine = CheckoutEngine::new(api_client, captcha_solver);
+    let config = CheckoutConfig {
+        base_url: mock_server.uri(),
+        ..CheckoutConfig::default()
+    };
+    let checkout_engine = CheckoutEngine::with_config(api_client, captcha_solver, config);
 
-    // Note: This test demonstrates the structure
-    // In production, you'd want to make the base URL configurable
+    let result = checkout_engine
+        .instant_checkout(
+            &create_test_product(),
+            &create_test_account(),
+            &create_test_session(),
+        )
+        .await?;
+
+    assert!(result.success, "checkout failed: {:?}", result.error);
+    assert_eq!(result.order_id, Some("ORDER123".to_string()));
 
-    println!("Mock server is running at: {}", mock_server.uri());
-    println!("Checkout flow structure validated");
+    println!("Checkout flow completed against mock server");
 
     Ok(())
 }
@@ -238,12 +254,156 @@ async fn test_checkout_invalid_session() -> Result<()> {
     assert!(!result.success);
     assert!(result.error.is_some());
     assert_eq!(result.error.unwrap(), "Session expired");
+    assert_eq!(result.error_kind, Some(CheckoutErrorKind::SessionExpired));
 
     println!("Invalid session handling validated");
 
     Ok(())
 }
 
+#[tokio::test]
+async fn test_shared_retry_budget_exhausted() -> Result<()> {
+    let mock_server = MockServer::start().await;
+
+    // Add-to-cart fails twice before succeeding, using up the whole budget
+    Mock::given(method("POST"))
+        .and(path("/cart/add"))
+        .respond_with(ResponseTemplate::new(500))
+        .up_to_n_times(2)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/cart/add"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "success": true,
+            "cart_id": "CART123"
+        })))
+        .mount(&mock_server)
+        .await;
+
+    // Checkout URL always fails and must not be retried
+    Mock::given(method("GET"))
+        .and(path("/cart/CART123/checkout"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let api_client = Arc::new(
+        ApiClient::new(Some("TestAgent/1.0".to_string()))?.with_retry_config(RetryConfig {
+            max_retries: 0,
+            ..RetryConfig::default()
+        }),
+    );
+    let captcha_solver = Arc::new(MockCaptchaSolver::new(
+        "mock_image_solution".to_string(),
+        "mock_recaptcha_solution".to_string(),
+    ));
+    let config = CheckoutConfig {
+        add_to_cart_retries: 3,
+        checkout_url_retries: 3,
+        base_delay_ms: 1,
+        total_retries: 2,
+        base_url: mock_server.uri(),
+        ..CheckoutConfig::default()
+    };
+    let checkout_engine = CheckoutEngine::with_config(api_client, captcha_solver, config);
+
+    let result = checkout_engine
+        .instant_checkout(
+            &create_test_product(),
+            &create_test_account(),
+            &create_test_session(),
+        )
+        .await?;
+
+    assert!(!result.success);
+    assert_eq!(
+        result.error_kind,
+        Some(CheckoutErrorKind::CheckoutUrlFailed)
+    );
+    assert!(result.error.unwrap().contains("Retry budget exhausted"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_network_failure_error_kind() -> Result<()> {
+    // Reserve a port and release it so nothing is listening there
+    let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+    let base_url = format!("http://{}", listener.local_addr()?);
+    drop(listener);
+
+    let api_client = Arc::new(
+        ApiClient::new(Some("TestAgent/1.0".to_string()))?.with_retry_config(RetryConfig {
+            max_retries: 0,
+            ..RetryConfig::default()
+        }),
+    );
+    let captcha_solver = Arc::new(MockCaptchaSolver::new(
+        "mock_image_solution".to_string(),
+        "mock_recaptcha_solution".to_string(),
+    ));
+    let config = CheckoutConfig {
+        add_to_cart_retries: 1,
+        base_url,
+        ..CheckoutConfig::default()
+    };
+    let checkout_engine = CheckoutEngine::with_config(api_client, captcha_solver, config);
+
+    let result = checkout_engine
+        .instant_checkout(
+            &create_test_product(),
+            &create_test_account(),
+            &create_test_session(),
+        )
+        .await?;
+
+    assert!(!result.success);
+    let kind = result.error_kind.expect("failure should carry a kind");
+    assert_eq!(kind, CheckoutErrorKind::NetworkError);
+    assert!(kind.is_retryable());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_product_unavailable_error_kind() -> Result<()> {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/cart/add"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&mock_server)
+        .await;
+
+    let api_client = Arc::new(ApiClient::new(Some("TestAgent/1.0".to_string()))?);
+    let captcha_solver = Arc::new(MockCaptchaSolver::new(
+        "mock_image_solution".to_string(),
+        "mock_recaptcha_solution".to_string(),
+    ));
+    let config = CheckoutConfig {
+        add_to_cart_retries: 1,
+        base_url: mock_server.uri(),
+        ..CheckoutConfig::default()
+    };
+    let checkout_engine = CheckoutEngine::with_config(api_client, captcha_solver, config);
+
+    let result = checkout_engine
+        .instant_checkout(
+            &create_test_product(),
+            &create_test_account(),
+            &create_test_session(),
+        )
+        .await?;
+
+    let kind = result.error_kind.expect("failure should carry a kind");
+    assert_eq!(kind, CheckoutErrorKind::ProductUnavailable);
+    assert!(!kind.is_retryable());
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_checkout_config_custom() {
     let config = CheckoutConfig {
@@ -255,11 +415,15 @@ async fn test_checkout_config_custom() {
         max_delay_ms: 5000,
         backoff_multiplier: 1.5,
         captcha_timeout_secs: 180,
+        total_retries: 10,
+        base_url: "https://api.lazada.sg".to_string(),
+        simulated_latency: None,
     };
 
     assert_eq!(config.add_to_cart_retries, 5);
     assert_eq!(config.base_delay_ms, 500);
     assert_eq!(config.backoff_multiplier, 1.5);
+    assert_eq!(config.base_url, "https://api.lazada.sg");
 
     println!("Custom checkout configuration validated");
 }
@@ -291,3 +455,100 @@ async fn test_account_creation() {
 
     println!("Account creation validated");
 }
+
+#[derive(serde::Deserialize)]
+struct ProductsFile {
+    products: Vec<ProductInfo>,
+}
+
+fn account_config(id: &str, status: &str) -> AccountConfig {
+    AccountConfig {
+        id: id.to_string(),
+        username: format!("{}@example.com", id),
+        password: "encrypted".to_string(),
+        status: status.to_string(),
+        last_login: None,
+        settings: create_test_account().settings,
+    }
+}
+
+#[tokio::test]
+async fn test_checkout_context_from_fixtures() -> Result<()> {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/login"))
+        .respond_with(
+            ResponseTemplate::new(200).append_header("Set-Cookie", "lzd_sid=abc123; Path=/"),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let products: ProductsFile = serde_yaml::from_str(&std::fs::read_to_string(
+        "tests/fixtures/test_products.yaml",
+    )?)?;
+
+    let mut config = create_default_config();
+    config.accounts = vec![
+        account_config("ACC1", "active"),
+        account_config("ACC2", "banned"),
+        account_config("ACC3", "active"),
+    ];
+
+    let mut vault = CredentialVault::new();
+    for account_id in ["ACC1", "ACC2"] {
+        vault.add_account(
+            account_id.to_string(),
+            LazadaCredentials {
+                username: format!("{}-user", account_id),
+                password: "password".to_string(),
+                email: None,
+                account_id: account_id.to_string(),
+            },
+        );
+    }
+
+    let temp_dir = tempfile::TempDir::new()?;
+    let api_client = Arc::new(ApiClient::new(None)?);
+    let manager =
+        SessionManager::with_sessions_dir(api_client, temp_dir.path().to_path_buf(), [7u8; 32])
+            .await?
+            .with_login_url(format!("{}/login", mock_server.uri()));
+    let sessions = SessionPool::new(Arc::new(manager));
+
+    let build = |product_id: &'static str, account_id: &'static str| {
+        CheckoutContext::build(
+            product_id,
+            account_id,
+            &config,
+            &products.products,
+            &vault,
+            &sessions,
+        )
+    };
+
+    let context = build("test-product-1", "ACC1").await?;
+    assert_eq!(context.product.name, "Test Product 1");
+    assert_eq!(context.product.price, Some(99.99));
+    assert_eq!(context.account.username, "ACC1-user");
+    assert_eq!(context.account.settings.payment_method, "credit_card");
+    assert_eq!(context.session.credentials.username, "ACC1-user");
+    assert_eq!(context.session.cookies["lzd_sid"], "abc123");
+
+    // Each missing piece is named in the error
+    for (product_id, account_id, expected) in [
+        (
+            "no-such-product",
+            "ACC1",
+            "not among the monitored products",
+        ),
+        ("test-product-1", "ACC9", "not in the configuration"),
+        ("test-product-1", "ACC2", "not active"),
+        ("test-product-1", "ACC3", "No credentials"),
+    ] {
+        let err = build(product_id, account_id).await.unwrap_err();
+        assert!(err.to_string().contains(expected), "{}", err);
+    }
+
+    Ok(())
+}
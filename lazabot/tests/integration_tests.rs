@@ -559,7 +559,7 @@ async fn test_proxy_manager_edge_cases() -> Result<()> {
     let manager = ProxyManager::new(proxies);
 
     // Mark all proxies as unhealthy
-    for proxy in manager.get_all_proxies() {
+    for proxy in manager.get_all_proxies().iter() {
         manager.set_proxy_health(proxy, false).await;
     }
 
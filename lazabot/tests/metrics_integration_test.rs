@@ -1,6 +1,10 @@
 //! Integration tests for the metrics module
 
+use lazabot::api::ProxyInfo;
+use lazabot::proxy::ProxyManager;
+use lazabot::tasks::{Task, TaskManager};
 use lazabot::utils::{MetricsCollector, MetricsServer};
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::sleep;
 
@@ -92,3 +96,60 @@ async fn test_concurrent_metric_updates() {
     // Verify no panics occurred
     assert!(true);
 }
+
+struct SleepTask;
+
+#[async_trait::async_trait]
+impl Task for SleepTask {
+    async fn execute(&self) -> anyhow::Result<serde_json::Value> {
+        sleep(Duration::from_secs(2)).await;
+        Ok(serde_json::Value::Null)
+    }
+
+    fn name(&self) -> &str {
+        "sleep"
+    }
+}
+
+#[tokio::test]
+async fn test_status_endpoint_reports_proxies_and_tasks() -> anyhow::Result<()> {
+    let proxies = vec![
+        ProxyInfo::new("127.0.0.1".to_string(), 8080),
+        ProxyInfo::new("192.168.1.1".to_string(), 3128),
+    ];
+    let proxy_manager = Arc::new(ProxyManager::new(proxies.clone()));
+    proxy_manager.set_proxy_health(&proxies[1], false).await;
+
+    let task_manager = Arc::new(TaskManager::new(2));
+    task_manager.submit_task(SleepTask).await?;
+
+    let collector = MetricsCollector::new();
+    collector.record_monitor_event(chrono::Utc::now());
+
+    // Grab a free port for the server
+    let addr = std::net::TcpListener::bind("127.0.0.1:0")?.local_addr()?;
+    let server = MetricsServer::new(collector, addr.to_string())
+        .with_proxy_manager(proxy_manager)
+        .with_task_manager(task_manager);
+    tokio::spawn(server.start());
+
+    let url = format!("http://{}/status", addr);
+    let mut status = None;
+    for _ in 0..50 {
+        sleep(Duration::from_millis(50)).await;
+        if let Ok(response) = reqwest::get(&url).await {
+            status = Some(response.json::<serde_json::Value>().await?);
+            break;
+        }
+    }
+    let status = status.expect("status endpoint did not respond");
+
+    assert_eq!(status["healthy_proxies"], 1);
+    assert_eq!(status["total_proxies"], 2);
+    assert_eq!(status["active_tasks"], 1);
+    assert_eq!(status["pending_tasks"], 0);
+    assert!(status["active_sessions"].is_null());
+    assert!(status["last_monitor_event"].is_string());
+
+    Ok(())
+}
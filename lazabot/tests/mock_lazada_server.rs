@@ -0,0 +1,337 @@
+#![cfg(feature = "testing")]
+
+use anyhow::Result;
+use lazabot::api::{ApiClient, RetryConfig};
+use lazabot::captcha::MockCaptchaSolver;
+use lazabot::config::AccountSettings;
+use lazabot::core::{
+    Account, CheckoutConfig, CheckoutEngine, CheckoutErrorKind, Credentials, Product, Session,
+    SimulatedLatency,
+};
+use lazabot::storage::Database;
+use lazabot::testing::{Endpoint, MockLazadaServer, MockResponses, MOCK_ORDER_ID};
+use std::sync::Arc;
+
+fn create_engine(config: CheckoutConfig) -> Result<CheckoutEngine> {
+    let api_client = Arc::new(
+        ApiClient::new(Some("TestAgent/1.0".to_string()))?.with_retry_config(RetryConfig {
+            max_retries: 0,
+            ..RetryConfig::default()
+        }),
+    );
+    let captcha_solver = Arc::new(MockCaptchaSolver::new(
+        "mock_image_solution".to_string(),
+        "mock_recaptcha_solution".to_string(),
+    ));
+    Ok(CheckoutEngine::with_config(
+        api_client,
+        captcha_solver,
+        config,
+    ))
+}
+
+fn create_fixtures() -> (Product, Account, Session) {
+    let product = Product::new(
+        "PROD123".to_string(),
+        "Test Product".to_string(),
+        "https://lazada.com/prod123".to_string(),
+    );
+    let account = Account {
+        id: "ACC123".to_string(),
+        username: "test@example.com".to_string(),
+        settings: AccountSettings {
+            payment_method: "credit_card".to_string(),
+            shipping_address: "123 Test St, Test City".to_string(),
+            notifications: true,
+        },
+    };
+    let session = Session::new(
+        "SESSION123".to_string(),
+        Credentials::new("test@example.com".to_string(), "password".to_string()),
+    );
+    (product, account, session)
+}
+
+#[tokio::test]
+async fn test_instant_checkout_against_mock_server() -> Result<()> {
+    let server = MockLazadaServer::start().await;
+    let engine = create_engine(server.checkout_config())?;
+    let (product, account, session) = create_fixtures();
+
+    let result = engine
+        .instant_checkout(&product, &account, &session)
+        .await?;
+
+    assert!(result.success, "checkout failed: {:?}", result.error);
+    assert_eq!(result.order_id.as_deref(), Some(MOCK_ORDER_ID));
+    for endpoint in [
+        Endpoint::AddToCart,
+        Endpoint::CheckoutUrl,
+        Endpoint::Shipping,
+        Endpoint::Payment,
+        Endpoint::CaptchaCheck,
+        Endpoint::Submit,
+    ] {
+        assert_eq!(server.request_count(endpoint).await, 1, "{:?}", endpoint);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mock_server_configured_failure() -> Result<()> {
+    let server = MockLazadaServer::start_with(MockResponses::default().with_response(
+        Endpoint::Submit,
+        200,
+        serde_json::json!({ "success": false, "error": "Out of stock" }),
+    ))
+    .await;
+    let engine = create_engine(CheckoutConfig {
+        submission_retries: 2,
+        base_delay_ms: 1,
+        ..server.checkout_config()
+    })?;
+    let (product, account, session) = create_fixtures();
+
+    let result = engine
+        .instant_checkout(&product, &account, &session)
+        .await?;
+
+    assert!(!result.success);
+    assert!(result.error.unwrap().contains("Order submission failed"));
+    assert_eq!(server.request_count(Endpoint::Submit).await, 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unknown_captcha_type_fails_detection() -> Result<()> {
+    let server = MockLazadaServer::start_with(MockResponses::default().with_response(
+        Endpoint::CaptchaCheck,
+        200,
+        serde_json::json!({ "has_captcha": true, "captcha_type": "funcaptcha" }),
+    ))
+    .await;
+    let engine = create_engine(server.checkout_config())?;
+    let (product, account, session) = create_fixtures();
+
+    let result = engine
+        .instant_checkout(&product, &account, &session)
+        .await?;
+
+    assert!(!result.success);
+    assert_eq!(
+        result.error_kind,
+        Some(CheckoutErrorKind::CaptchaDetectionFailed)
+    );
+    assert!(result.error.unwrap().contains("funcaptcha"));
+    assert_eq!(server.request_count(Endpoint::Submit).await, 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_recaptcha_v3_checkout() -> Result<()> {
+    let server = MockLazadaServer::start_with(MockResponses::default().with_response(
+        Endpoint::CaptchaCheck,
+        200,
+        serde_json::json!({
+            "has_captcha": true,
+            "captcha_type": "recaptcha_v3",
+            "site_key": "v3-site-key",
+            "action": "checkout",
+            "min_score": 0.7
+        }),
+    ))
+    .await;
+    let api_client = Arc::new(ApiClient::new(Some("TestAgent/1.0".to_string()))?);
+    let captcha_solver = Arc::new(
+        MockCaptchaSolver::new(
+            "mock_image_solution".to_string(),
+            "mock_recaptcha_solution".to_string(),
+        )
+        .with_recaptcha_v3_result("mock_v3_token".to_string()),
+    );
+    let engine = CheckoutEngine::with_config(api_client, captcha_solver, server.checkout_config());
+    let (product, account, session) = create_fixtures();
+
+    let result = engine
+        .instant_checkout(&product, &account, &session)
+        .await?;
+
+    assert!(result.success, "checkout failed: {:?}", result.error);
+    let requests = server.server().received_requests().await.unwrap();
+    let submit = requests
+        .iter()
+        .find(|request| request.url.path().ends_with("/submit"))
+        .expect("order was submitted");
+    let body: serde_json::Value = serde_json::from_slice(&submit.body)?;
+    assert_eq!(body["captcha_token"], "mock_v3_token");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_simulated_latency_counts_toward_duration() -> Result<()> {
+    let server = MockLazadaServer::start().await;
+    let engine = create_engine(CheckoutConfig {
+        simulated_latency: Some(SimulatedLatency {
+            add_to_cart_ms: 200,
+            ..SimulatedLatency::default()
+        }),
+        ..server.checkout_config()
+    })?;
+    let (product, account, session) = create_fixtures();
+
+    let result = engine
+        .instant_checkout(&product, &account, &session)
+        .await?;
+
+    assert!(result.success);
+    assert!(result.duration_ms >= 200);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_checkout_results_are_persisted_as_orders() -> Result<()> {
+    let db = Arc::new(Database::in_memory()?);
+    let (product, account, session) = create_fixtures();
+    let product = product.with_price(19.9).with_quantity(2);
+
+    let server = MockLazadaServer::start().await;
+    create_engine(server.checkout_config())?
+        .with_order_store(db.clone())
+        .instant_checkout(&product, &account, &session)
+        .await?;
+
+    let failing = MockLazadaServer::start_with(MockResponses::default().with_response(
+        Endpoint::Submit,
+        200,
+        serde_json::json!({ "success": false, "error": "Out of stock" }),
+    ))
+    .await;
+    create_engine(CheckoutConfig {
+        submission_retries: 1,
+        ..failing.checkout_config()
+    })?
+    .with_order_store(db.clone())
+    .instant_checkout(&product, &account, &session)
+    .await?;
+
+    let order = db
+        .get_order(MOCK_ORDER_ID)?
+        .expect("successful order recorded");
+    assert_eq!(order.status, "success");
+    assert_eq!(order.account_id, "ACC123");
+    assert_eq!(order.product_id, "PROD123");
+    assert_eq!(order.price, 19.9);
+    assert_eq!(order.quantity, 2);
+
+    let failed = db.list_orders(Some("ACC123"), Some("failed"))?;
+    assert_eq!(failed.len(), 1);
+    assert!(failed[0]
+        .metadata
+        .as_deref()
+        .unwrap()
+        .contains("Order submission failed"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_race_checkout_across_accounts() -> Result<()> {
+    use std::time::Duration;
+    use wiremock::matchers::{body_string_contains, method, path};
+    use wiremock::{Mock, ResponseTemplate};
+
+    let server = MockLazadaServer::start().await;
+    // Overrides outrank the default responses, keyed on each account's session token
+    let submit = format!("/checkout/{}/submit", lazabot::testing::MOCK_CART_ID);
+    Mock::given(method("POST"))
+        .and(path(submit.as_str()))
+        .and(body_string_contains("SESSION_WIN"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({ "success": true, "order_id": "ORDER_WIN" }))
+                .set_delay(Duration::from_millis(200)),
+        )
+        .with_priority(1)
+        .mount(server.server())
+        .await;
+    Mock::given(method("POST"))
+        .and(path(submit.as_str()))
+        .and(body_string_contains("SESSION_FAIL"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({ "success": false, "error": "Out of stock" })),
+        )
+        .with_priority(1)
+        .mount(server.server())
+        .await;
+    // Never answers in time, so this attempt is still in flight when the race is won
+    Mock::given(method("POST"))
+        .and(path("/cart/add"))
+        .and(body_string_contains("SESSION_SLOW"))
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(60)))
+        .with_priority(1)
+        .mount(server.server())
+        .await;
+
+    let db = Arc::new(Database::in_memory()?);
+    let engine = create_engine(CheckoutConfig {
+        submission_retries: 1,
+        ..server.checkout_config()
+    })?
+    .with_order_store(db.clone());
+    let (product, account, _) = create_fixtures();
+    let (accounts, sessions): (Vec<Account>, Vec<Session>) = ["WIN", "FAIL", "SLOW"]
+        .iter()
+        .map(|name| {
+            let account = Account {
+                id: format!("ACC_{}", name),
+                ..account.clone()
+            };
+            let session = Session::new(
+                format!("SESSION_{}", name),
+                Credentials::new(account.username.clone(), "password".to_string()),
+            );
+            (account, session)
+        })
+        .unzip();
+
+    // Waiting on the slow account would take far longer than this
+    let result = tokio::time::timeout(
+        Duration::from_secs(10),
+        engine.race_checkout(&product, &accounts, &sessions),
+    )
+    .await?;
+
+    assert!(result.success, "race failed: {:?}", result.error);
+    assert_eq!(result.order_id.as_deref(), Some("ORDER_WIN"));
+
+    let status = |account_id: &str| -> Result<Vec<String>> {
+        Ok(db
+            .list_orders(Some(account_id), None)?
+            .into_iter()
+            .map(|order| order.status)
+            .collect())
+    };
+    assert_eq!(status("ACC_WIN")?, vec!["success"]);
+    assert_eq!(status("ACC_FAIL")?, vec!["failed"]);
+    // The cancelled attempt never got past adding to cart or recorded an order
+    assert!(status("ACC_SLOW")?.is_empty());
+    let slow_requests = server
+        .server()
+        .received_requests()
+        .await
+        .unwrap_or_default()
+        .iter()
+        .filter(|request| {
+            request.url.path() != "/cart/add"
+                && String::from_utf8_lossy(&request.body).contains("SESSION_SLOW")
+        })
+        .count();
+    assert_eq!(slow_requests, 0);
+
+    Ok(())
+}
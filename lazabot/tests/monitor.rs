@@ -4,13 +4,18 @@ use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::timeout;
 use wiremock::{
-    matchers::{method, path},
+    matchers::{body_json, header, method, path},
     Mock, MockServer, ResponseTemplate,
 };
 
-use lazabot::api::{ApiClient, ProxyInfo};
-use lazabot::core::monitor::{MonitorTask, ProductAvailabilityEvent, ProductInfo};
+use lazabot::api::{ApiClient, ApiError, ProxyInfo, ReplayClient, RetryConfig};
+use lazabot::core::monitor::{
+    MonitorEventKind, MonitorTask, ProductAvailabilityEvent, ProductInfo, ProductSnapshot,
+};
+use lazabot::core::{BatchMonitorTask, EventLog, MonitorEngine};
 use lazabot::proxy::ProxyManager;
+use lazabot::storage::Database;
+use lazabot::utils::MetricsCollector;
 
 #[tokio::test]
 async fn test_monitor_task_creation() -> Result<()> {
@@ -145,6 +150,7 @@ async fn test_monitor_task_stop_functionality() -> Result<()> {
 #[tokio::test]
 async fn test_product_availability_event_creation() -> Result<()> {
     let event = ProductAvailabilityEvent {
+        kind: MonitorEventKind::AvailabilityChanged,
         product_id: "test-123".to_string(),
         product_url: "https://example.com/product/123".to_string(),
         timestamp: chrono::Utc::now(),
@@ -407,3 +413,740 @@ async fn test_monitor_task_creation_with_large_response() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_check_product_availability_snapshot() -> Result<()> {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/product/available"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "success": true,
+            "available": true,
+            "stock": 10,
+            "price": 29.99
+        })))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/product/sold-out"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "price": 49.5,
+            "stock": 0
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let api_client = Arc::new(ApiClient::new(Some("TestAgent/1.0".to_string()))?);
+    let proxy_manager = Arc::new(ProxyManager::new(vec![]));
+
+    let monitor = MonitorTask::new(
+        "test-123".to_string(),
+        format!("{}/product/available", mock_server.uri()),
+        "Test Product".to_string(),
+        api_client.clone(),
+        proxy_manager.clone(),
+        1000,
+    );
+    let snapshot = monitor.check_product_availability().await?;
+    assert_eq!(
+        snapshot,
+        ProductSnapshot {
+            price: Some(29.99),
+            stock: Some(10),
+            is_available: true,
+            ema_price: Some(29.99),
+        }
+    );
+
+    // Without an explicit flag, availability follows stock
+    let monitor = MonitorTask::new(
+        "test-456".to_string(),
+        format!("{}/product/sold-out", mock_server.uri()),
+        "Sold Out Product".to_string(),
+        api_client,
+        proxy_manager,
+        1000,
+    );
+    let snapshot = monitor.check_product_availability().await?;
+    assert_eq!(snapshot.price, Some(49.5));
+    assert_eq!(snapshot.stock, Some(0));
+    assert!(!snapshot.is_available);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_monitor_engine_worker_pool() -> Result<()> {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "available": true, "stock": 5 }))
+                .set_delay(Duration::from_millis(10)),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let api_client = Arc::new(ApiClient::new(Some("TestAgent/1.0".to_string()))?);
+    let proxy_manager = Arc::new(ProxyManager::new(vec![]));
+
+    let mut engine = MonitorEngine::new().with_worker_pool(4);
+    let mut receivers = Vec::new();
+    for i in 0..100 {
+        let monitor = MonitorTask::new(
+            format!("product-{}", i),
+            format!("{}/product/{}", mock_server.uri(), i),
+            format!("Product {}", i),
+            api_client.clone(),
+            proxy_manager.clone(),
+            60_000,
+        );
+        receivers.push(engine.add_monitor(monitor));
+    }
+    engine.start().await?;
+
+    timeout(Duration::from_secs(10), async {
+        while engine.pool_stats().unwrap().polls < 100 {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await?;
+    engine.stop().await?;
+
+    let stats = engine.pool_stats().unwrap();
+    assert_eq!(stats.workers, 4);
+    assert!(stats.peak_in_flight <= 4, "peak {}", stats.peak_in_flight);
+
+    // Every product was polled exactly once and reported its availability
+    let mut paths: Vec<String> = mock_server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|request| request.url.path().to_string())
+        .collect();
+    paths.sort();
+    paths.dedup();
+    assert_eq!(paths.len(), 100);
+    for receiver in &mut receivers {
+        let event = receiver.try_recv()?;
+        assert!(event.is_available);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_monitor_etag_not_modified() -> Result<()> {
+    let mock_server = MockServer::start().await;
+
+    // Conditional requests with the current ETag get 304
+    Mock::given(method("GET"))
+        .and(path("/product/etag"))
+        .and(header("If-None-Match", "\"v1\""))
+        .respond_with(ResponseTemplate::new(304))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/product/etag"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("ETag", "\"v1\"")
+                .set_body_json(json!({ "available": true, "stock": 3 })),
+        )
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+
+    let api_client = Arc::new(ApiClient::new(Some("TestAgent/1.0".to_string()))?);
+    let proxy_manager = Arc::new(ProxyManager::new(vec![]));
+
+    let monitor = MonitorTask::new(
+        "etag-product".to_string(),
+        format!("{}/product/etag", mock_server.uri()),
+        "ETag Product".to_string(),
+        api_client,
+        proxy_manager,
+        50,
+    );
+
+    let mut engine = MonitorEngine::new();
+    let mut receiver = engine.add_monitor(monitor);
+    engine.start().await?;
+
+    timeout(Duration::from_secs(5), async {
+        while mock_server.received_requests().await.unwrap().len() < 3 {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await?;
+    engine.stop().await?;
+
+    let requests = mock_server.received_requests().await.unwrap();
+    assert!(requests[0].headers.get(&"if-none-match".into()).is_none());
+    assert_eq!(
+        requests[1].headers.get(&"if-none-match".into()).unwrap(),
+        "\"v1\""
+    );
+
+    // Only the first 200 produces an event; 304s are treated as no change
+    let event = receiver.try_recv()?;
+    assert!(event.is_available);
+    assert_eq!(event.stock, Some(3));
+    assert!(receiver.try_recv().is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_monitor_sticky_proxy_rotates_when_unhealthy() -> Result<()> {
+    // Two mock servers stand in for HTTP proxies
+    let proxy_a = MockServer::start().await;
+    let proxy_b = MockServer::start().await;
+    for proxy in [&proxy_a, &proxy_b] {
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "available": true })))
+            .mount(proxy)
+            .await;
+    }
+
+    let proxy_info = |server: &MockServer| {
+        let address = server.address();
+        ProxyInfo::new(address.ip().to_string(), address.port())
+    };
+    let (info_a, info_b) = (proxy_info(&proxy_a), proxy_info(&proxy_b));
+
+    let api_client = Arc::new(ApiClient::new(Some("TestAgent/1.0".to_string()))?);
+    let proxy_manager = Arc::new(ProxyManager::new(vec![info_a.clone(), info_b.clone()]));
+
+    let monitor = MonitorTask::new(
+        "sticky-product".to_string(),
+        "http://lazada.test/product/sticky".to_string(),
+        "Sticky Product".to_string(),
+        api_client,
+        proxy_manager.clone(),
+        1000,
+    )
+    .with_sticky_proxy(true);
+
+    // Repeated polls stay on the first proxy
+    for _ in 0..3 {
+        monitor.check_product_availability().await?;
+    }
+    assert_eq!(proxy_a.received_requests().await.unwrap().len(), 3);
+    assert!(proxy_b.received_requests().await.unwrap().is_empty());
+
+    // Once it is unhealthy the task moves to the second proxy and stays there
+    proxy_manager.set_proxy_health(&info_a, false).await;
+    for _ in 0..2 {
+        monitor.check_product_availability().await?;
+    }
+    assert_eq!(proxy_a.received_requests().await.unwrap().len(), 3);
+    assert_eq!(proxy_b.received_requests().await.unwrap().len(), 2);
+    assert_eq!(monitor.current_proxy().unwrap().port, info_b.port);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_monitor_sticky_proxy_marked_unhealthy_on_failure() -> Result<()> {
+    let proxy = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "available": true })))
+        .mount(&proxy)
+        .await;
+    let address = proxy.address();
+    let good = ProxyInfo::new(address.ip().to_string(), address.port());
+
+    // Nothing listens on the first proxy, so requests through it fail to connect
+    let unused = std::net::TcpListener::bind("127.0.0.1:0")?;
+    let dead = ProxyInfo::new("127.0.0.1".to_string(), unused.local_addr()?.port());
+    drop(unused);
+
+    let api_client = Arc::new(
+        ApiClient::new(Some("TestAgent/1.0".to_string()))?.with_retry_config(RetryConfig {
+            max_retries: 0,
+            ..RetryConfig::default()
+        }),
+    );
+    let proxy_manager = Arc::new(ProxyManager::new(vec![dead.clone(), good.clone()]));
+
+    let monitor = MonitorTask::new(
+        "sticky-failure".to_string(),
+        "http://lazada.test/product/sticky-failure".to_string(),
+        "Sticky Failure".to_string(),
+        api_client,
+        proxy_manager.clone(),
+        1000,
+    )
+    .with_sticky_proxy(true)
+    .with_max_retries(1);
+
+    monitor.check_product_availability().await?;
+
+    assert!(!proxy_manager.is_proxy_healthy(&dead).await);
+    assert_eq!(monitor.current_proxy().unwrap().port, good.port);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_monitor_engine_persists_events() -> Result<()> {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/product/1"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!({ "price": 12.5, "stock": 4 })),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let api_client = Arc::new(ApiClient::new(Some("TestAgent/1.0".to_string()))?);
+    let proxy_manager = Arc::new(ProxyManager::new(vec![]));
+    let db = Arc::new(Database::in_memory()?);
+    let started = chrono::Utc::now();
+
+    let mut engine = MonitorEngine::new().with_event_store(db.clone());
+    let monitor = MonitorTask::new(
+        "product-1".to_string(),
+        format!("{}/product/1", mock_server.uri()),
+        "Product 1".to_string(),
+        api_client,
+        proxy_manager,
+        60_000,
+    );
+    let mut receiver = engine.add_monitor(monitor);
+    engine.start().await?;
+
+    let event = timeout(Duration::from_secs(5), receiver.recv())
+        .await?
+        .expect("monitor emits an event");
+    engine.stop().await?;
+
+    let events = db.get_events_for_product("product-1", started)?;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].price, Some(12.5));
+    assert_eq!(events[0].stock, Some(4));
+    assert!(events[0].available);
+    assert_eq!(
+        events[0].timestamp.timestamp_micros(),
+        event.timestamp.timestamp_micros()
+    );
+    assert!(db
+        .get_events_for_product("product-1", chrono::Utc::now())?
+        .is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_monitor_engine_persists_price_drops() -> Result<()> {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/product/1"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!({ "price": 100.0, "stock": 4 })),
+        )
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/product/1"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!({ "price": 50.0, "stock": 4 })),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let api_client = Arc::new(ApiClient::new(Some("TestAgent/1.0".to_string()))?);
+    let proxy_manager = Arc::new(ProxyManager::new(vec![]));
+    let db = Arc::new(Database::in_memory()?);
+    let started = chrono::Utc::now();
+
+    let mut engine = MonitorEngine::new().with_event_store(db.clone());
+    let monitor = MonitorTask::new(
+        "product-1".to_string(),
+        format!("{}/product/1", mock_server.uri()),
+        "Product 1".to_string(),
+        api_client,
+        proxy_manager,
+        50,
+    )
+    .with_price_drop_factor(0.1);
+    let mut receiver = engine.add_monitor(monitor);
+    engine.start().await?;
+
+    let mut kinds = Vec::new();
+    while kinds.len() < 2 {
+        let event = timeout(Duration::from_secs(5), receiver.recv())
+            .await?
+            .expect("monitor emits an event");
+        kinds.push(event.kind);
+    }
+    engine.stop().await?;
+
+    assert_eq!(
+        kinds,
+        vec![
+            MonitorEventKind::AvailabilityChanged,
+            MonitorEventKind::PriceDropped
+        ]
+    );
+    let events = db.get_events_for_product("product-1", started)?;
+    let stored: Vec<&str> = events.iter().map(|e| e.kind.as_str()).collect();
+    assert_eq!(stored, vec!["availability_changed", "price_dropped"]);
+    assert_eq!(events[1].price, Some(50.0));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_monitor_engine_records_last_event_in_metrics() -> Result<()> {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/product/1"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!({ "price": 12.5, "stock": 4 })),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let api_client = Arc::new(ApiClient::new(Some("TestAgent/1.0".to_string()))?);
+    let proxy_manager = Arc::new(ProxyManager::new(vec![]));
+    let metrics = MetricsCollector::new();
+
+    let mut engine = MonitorEngine::new().with_metrics(metrics.clone());
+    let monitor = MonitorTask::new(
+        "product-1".to_string(),
+        format!("{}/product/1", mock_server.uri()),
+        "Product 1".to_string(),
+        api_client,
+        proxy_manager,
+        60_000,
+    );
+    let mut receiver = engine.add_monitor(monitor);
+    assert!(metrics.last_monitor_event().is_none());
+    engine.start().await?;
+
+    let event = timeout(Duration::from_secs(5), receiver.recv())
+        .await?
+        .expect("monitor emits an event");
+    engine.stop().await?;
+
+    assert_eq!(metrics.last_monitor_event(), Some(event.timestamp));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_monitor_replays_recorded_har() -> Result<()> {
+    let product_url = "https://www.lazada.test/products/replayed";
+    let har = json!({
+        "log": {
+            "version": "1.2",
+            "creator": { "name": "lazabot", "version": "0.1.0" },
+            "entries": [{
+                "startedDateTime": "2024-03-01T12:00:00Z",
+                "time": 42,
+                "request": { "method": "GET", "url": product_url, "headers": [] },
+                "response": {
+                    "status": 200,
+                    "headers": [{ "name": "content-type", "value": "application/json" }],
+                    "content": {
+                        "size": 44,
+                        "mimeType": "application/json",
+                        "text": r#"{"price": 12.5, "stock": 7, "available": true}"#
+                    }
+                }
+            }]
+        }
+    });
+    let temp_dir = tempfile::TempDir::new()?;
+    let har_path = temp_dir.path().join("recorded.har");
+    std::fs::write(&har_path, serde_json::to_string(&har)?)?;
+
+    let api_client = Arc::new(ApiClient::new(None)?.with_replay(ReplayClient::load(&har_path)?));
+    let monitor = MonitorTask::new(
+        "replayed".to_string(),
+        product_url.to_string(),
+        "Replayed Product".to_string(),
+        api_client.clone(),
+        Arc::new(ProxyManager::new(vec![])),
+        1000,
+    );
+
+    let snapshot = monitor.check_product_availability().await?;
+    assert_eq!(snapshot.price, Some(12.5));
+    assert_eq!(snapshot.stock, Some(7));
+    assert!(snapshot.is_available);
+
+    // Requests that were never recorded fail instead of reaching the network
+    let err = api_client
+        .request(
+            reqwest::Method::GET,
+            "https://www.lazada.test/products/other",
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ApiError::NoRecordedResponse { .. }));
+    assert_eq!(
+        err.to_string(),
+        "No recorded response for GET https://www.lazada.test/products/other"
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_monitor_custom_headers() -> Result<()> {
+    let mock_server = MockServer::start().await;
+    let referer = "https://www.lazada.test/catalog";
+
+    Mock::given(method("GET"))
+        .and(path("/product/headers"))
+        .and(header("Referer", referer))
+        .and(header("X-Requested-With", "XMLHttpRequest"))
+        .and(header("User-Agent", "Custom/1.0"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "stock": 4 })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    // The explicit User-Agent wins over the client's pool
+    let api_client =
+        Arc::new(ApiClient::new(None)?.with_user_agents(vec!["Pooled/1.0".to_string()]));
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert(reqwest::header::REFERER, referer.parse()?);
+    headers.insert("x-requested-with", "XMLHttpRequest".parse()?);
+    headers.insert(reqwest::header::USER_AGENT, "Custom/1.0".parse()?);
+
+    let monitor = MonitorTask::new(
+        "headers-product".to_string(),
+        format!("{}/product/headers", mock_server.uri()),
+        "Headers Product".to_string(),
+        api_client,
+        Arc::new(ProxyManager::new(vec![])),
+        1000,
+    )
+    .with_headers(headers)
+    .with_max_retries(0);
+
+    let snapshot = monitor.check_product_availability().await?;
+    assert_eq!(snapshot.stock, Some(4));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_monitor_post_availability_check() -> Result<()> {
+    let mock_server = MockServer::start().await;
+    let ids = json!({ "ids": ["SKU-1", "SKU-2"] });
+
+    Mock::given(method("POST"))
+        .and(path("/availability"))
+        .and(header("Content-Type", "application/json"))
+        .and(body_json(&ids))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "available": true,
+            "price": 19.5,
+            "stock": 8,
+            "items": [
+                { "id": "SKU-1", "stock": 5 },
+                { "id": "SKU-2", "stock": 3 }
+            ]
+        })))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let monitor = MonitorTask::new(
+        "sku-batch".to_string(),
+        format!("{}/availability", mock_server.uri()),
+        "SKU Batch".to_string(),
+        Arc::new(ApiClient::new(None)?),
+        Arc::new(ProxyManager::new(vec![])),
+        1000,
+    )
+    .with_post_body(serde_json::to_vec(&ids)?)
+    .with_max_retries(0);
+
+    let snapshot = monitor.check_product_availability().await?;
+    assert!(snapshot.is_available);
+    assert_eq!(snapshot.price, Some(19.5));
+    assert_eq!(snapshot.stock, Some(8));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_batch_monitor_emits_event_per_product() -> Result<()> {
+    let mock_server = MockServer::start().await;
+    let ids = ["SKU-1", "SKU-2", "SKU-3"];
+
+    Mock::given(method("POST"))
+        .and(path("/batch"))
+        .and(body_json(json!({ "ids": ids })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "items": [
+                { "id": "SKU-1", "price": 10.0, "stock": 2 },
+                { "id": "SKU-2", "stock": 0 },
+                { "id": "SKU-3", "available": true }
+            ]
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let products = ids
+        .iter()
+        .map(|id| ProductInfo {
+            id: id.to_string(),
+            url: format!("https://www.lazada.test/products/{}", id),
+            name: id.to_string(),
+            target_price: None,
+            min_stock: None,
+        })
+        .collect();
+    let monitor = BatchMonitorTask::new(
+        format!("{}/batch", mock_server.uri()),
+        products,
+        Arc::new(ApiClient::new(None)?),
+        Arc::new(ProxyManager::new(vec![])),
+        50,
+    )
+    .with_max_retries(0);
+
+    let mut engine = MonitorEngine::new();
+    let mut receiver = engine.add_batch_monitor(monitor);
+    engine.start().await?;
+
+    // Two polls' worth of events
+    let mut events = Vec::new();
+    timeout(Duration::from_secs(5), async {
+        while events.len() < 6 {
+            events.push(receiver.recv().await.unwrap());
+        }
+    })
+    .await?;
+    engine.stop().await?;
+
+    for poll in events.chunks(3) {
+        let polled: Vec<&str> = poll.iter().map(|e| e.product_id.as_str()).collect();
+        assert_eq!(polled, ids);
+        assert_eq!(poll[0].price, Some(10.0));
+        assert_eq!(poll[0].stock, Some(2));
+        assert!(poll[0].is_available);
+        assert!(!poll[1].is_available);
+        assert!(poll[2].is_available);
+        assert_eq!(
+            poll[2].product_url,
+            "https://www.lazada.test/products/SKU-3"
+        );
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_monitor_engine_spaces_polls_per_host() -> Result<()> {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "available": true })))
+        .mount(&mock_server)
+        .await;
+
+    let api_client = Arc::new(ApiClient::new(None)?);
+    let proxy_manager = Arc::new(ProxyManager::new(vec![]));
+
+    let mut engine = MonitorEngine::new().with_host_rate_limit(10.0, 1);
+    assert_eq!(engine.host_rps(), Some(10.0));
+    for i in 0..6 {
+        let monitor = MonitorTask::new(
+            format!("product-{}", i),
+            format!("{}/product/{}", mock_server.uri(), i),
+            format!("Product {}", i),
+            api_client.clone(),
+            proxy_manager.clone(),
+            60_000,
+        );
+        engine.add_monitor(monitor);
+    }
+
+    let started = std::time::Instant::now();
+    engine.start().await?;
+    timeout(Duration::from_secs(5), async {
+        while mock_server.received_requests().await.unwrap().len() < 6 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await?;
+    let elapsed = started.elapsed();
+    engine.stop().await?;
+
+    // One request goes out immediately, the other five wait 100ms each for a token
+    assert!(elapsed >= Duration::from_millis(450), "took {:?}", elapsed);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_monitor_engine_writes_ndjson_event_log() -> Result<()> {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!({ "available": true, "price": 12.5 })),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let temp_dir = tempfile::TempDir::new()?;
+    let output = temp_dir.path().join("events.ndjson");
+    let api_client = Arc::new(ApiClient::new(None)?);
+    let proxy_manager = Arc::new(ProxyManager::new(vec![]));
+
+    let mut engine = MonitorEngine::new().with_event_log(Arc::new(EventLog::open(&output).await?));
+    let mut receivers = Vec::new();
+    for i in 0..3 {
+        let monitor = MonitorTask::new(
+            format!("product-{}", i),
+            format!("{}/product/{}", mock_server.uri(), i),
+            format!("Product {}", i),
+            api_client.clone(),
+            proxy_manager.clone(),
+            60_000,
+        );
+        receivers.push(engine.add_monitor(monitor));
+    }
+    engine.start().await?;
+    for receiver in &mut receivers {
+        timeout(Duration::from_secs(5), receiver.recv()).await?;
+    }
+    engine.stop().await?;
+
+    let content = std::fs::read_to_string(&output)?;
+    let mut events: Vec<ProductAvailabilityEvent> = content
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<_, _>>()?;
+    events.sort_by(|a, b| a.product_id.cmp(&b.product_id));
+
+    assert_eq!(events.len(), 3);
+    for (i, event) in events.iter().enumerate() {
+        assert_eq!(event.product_id, format!("product-{}", i));
+        assert_eq!(event.kind, MonitorEventKind::AvailabilityChanged);
+        assert_eq!(event.price, Some(12.5));
+        assert!(event.is_available);
+    }
+
+    Ok(())
+}
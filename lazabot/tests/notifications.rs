@@ -0,0 +1,190 @@
+use anyhow::Result;
+use lazabot::api::ApiClient;
+use lazabot::captcha::MockCaptchaSolver;
+use lazabot::config::AccountSettings;
+use lazabot::core::{Account, CheckoutEngine, Credentials, Product, Session};
+use lazabot::notifications::{NotificationEvent, Notifier, TelegramNotifier, WebhookNotifier};
+use std::sync::Arc;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn create_test_product() -> Product {
+    Product::new(
+        "PROD123".to_string(),
+        "Test Product".to_string(),
+        "https://lazada.com/prod123".to_string(),
+    )
+}
+
+fn create_test_account() -> Account {
+    Account {
+        id: "ACC123".to_string(),
+        username: "test@example.com".to_string(),
+        settings: AccountSettings {
+            payment_method: "credit_card".to_string(),
+            shipping_address: "123 Test St, Test City".to_string(),
+            notifications: true,
+        },
+    }
+}
+
+async fn received_payloads(server: &MockServer) -> Vec<serde_json::Value> {
+    server
+        .received_requests()
+        .await
+        .unwrap_or_default()
+        .iter()
+        .map(|request| serde_json::from_slice(&request.body).unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_webhook_payload_on_success() -> Result<()> {
+    let webhook = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/hook"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&webhook)
+        .await;
+
+    let notifier = WebhookNotifier::new(format!("{}/hook", webhook.uri()))?;
+    notifier
+        .notify(NotificationEvent::CheckoutSucceeded {
+            order_id: "ORDER123".to_string(),
+            product_id: "PROD123".to_string(),
+            product_name: "Test Product".to_string(),
+            account_id: "ACC123".to_string(),
+            timestamp: chrono::Utc::now(),
+        })
+        .await?;
+
+    let payloads = received_payloads(&webhook).await;
+    let event = &payloads[0]["event"];
+    assert_eq!(event["event"], "checkout_succeeded");
+    assert_eq!(event["order_id"], "ORDER123");
+    assert_eq!(event["product_name"], "Test Product");
+    assert_eq!(event["account_id"], "ACC123");
+    assert!(event["timestamp"].is_string());
+    assert!(payloads[0]["text"].as_str().unwrap().contains("ORDER123"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_checkout_failure_sends_notification() -> Result<()> {
+    let webhook = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/hook"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&webhook)
+        .await;
+
+    let api_client = Arc::new(ApiClient::new(Some("TestAgent/1.0".to_string()))?);
+    let captcha_solver = Arc::new(MockCaptchaSolver::new(
+        "mock_image_solution".to_string(),
+        "mock_recaptcha_solution".to_string(),
+    ));
+    let notifier = Arc::new(WebhookNotifier::new(format!("{}/hook", webhook.uri()))?);
+    let engine = CheckoutEngine::new(api_client, captcha_solver).with_notifier(notifier);
+
+    let mut session = Session::new(
+        "SESSION123".to_string(),
+        Credentials::new("test@example.com".to_string(), "password".to_string()),
+    );
+    session.is_valid = false;
+
+    let result = engine
+        .instant_checkout(&create_test_product(), &create_test_account(), &session)
+        .await?;
+    assert!(!result.success);
+
+    let payloads = received_payloads(&webhook).await;
+    let event = &payloads[0]["event"];
+    assert_eq!(event["event"], "checkout_failed");
+    assert_eq!(event["error"], "Session expired");
+    assert_eq!(event["product_id"], "PROD123");
+    assert_eq!(event["account_id"], "ACC123");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_webhook_error_status() -> Result<()> {
+    let webhook = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&webhook)
+        .await;
+
+    let notifier = WebhookNotifier::new(webhook.uri())?;
+    let result = notifier
+        .notify(NotificationEvent::CheckoutFailed {
+            error: "Product unavailable".to_string(),
+            product_id: "PROD123".to_string(),
+            product_name: "Test Product".to_string(),
+            account_id: "ACC123".to_string(),
+            timestamp: chrono::Utc::now(),
+        })
+        .await;
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_telegram_message_includes_product_name() -> Result<()> {
+    let telegram = MockServer::start().await;
+
+    // First attempt is rate limited, the retry succeeds
+    Mock::given(method("POST"))
+        .and(path("/botTOKEN123/sendMessage"))
+        .respond_with(ResponseTemplate::new(429).set_body_json(serde_json::json!({
+            "ok": false,
+            "error_code": 429,
+            "description": "Too Many Requests: retry after 0",
+            "parameters": { "retry_after": 0 }
+        })))
+        .up_to_n_times(1)
+        .mount(&telegram)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/botTOKEN123/sendMessage"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "ok": true,
+            "result": {}
+        })))
+        .mount(&telegram)
+        .await;
+
+    let notifier = TelegramNotifier::new("TOKEN123".to_string(), "42".to_string())?
+        .with_api_base(telegram.uri());
+    notifier
+        .notify(NotificationEvent::ProductAvailable {
+            product_id: "PROD123".to_string(),
+            product_name: "Test Product".to_string(),
+            price: Some(99.99),
+            url: "https://lazada.com/prod123".to_string(),
+            timestamp: chrono::Utc::now(),
+        })
+        .await?;
+
+    let payloads = received_payloads(&telegram).await;
+    assert_eq!(payloads.len(), 2);
+    assert_eq!(payloads[1]["chat_id"], "42");
+    let text = payloads[1]["text"].as_str().unwrap();
+    assert!(text.contains("Test Product"));
+    assert!(text.contains("99.99"));
+
+    Ok(())
+}
+
+#[test]
+fn test_telegram_debug_redacts_bot_token() -> Result<()> {
+    let notifier = TelegramNotifier::new("TOKEN123".to_string(), "42".to_string())?;
+    let debug = format!("{:?}", notifier);
+    assert!(!debug.contains("TOKEN123"));
+    assert!(debug.contains("42"));
+    Ok(())
+}
@@ -2,8 +2,16 @@ use anyhow::Result;
 use std::sync::Arc;
 use tokio::time::{sleep, Duration};
 
-use lazabot::api::{ApiClient, ProxyInfo};
-use lazabot::proxy::ProxyManager;
+use lazabot::api::{ApiClient, ProxyInfo, ProxyType};
+use lazabot::config::validation::{check_proxy_reachability, ValidationReport};
+use lazabot::proxy::{HealthCheckKind, ProxyHealth, ProxyManager, ProxySubscription};
+use reqwest::header::{HeaderMap, HeaderValue};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use wiremock::{
+    matchers::{header, method, path},
+    Mock, MockServer, ResponseTemplate,
+};
 
 #[tokio::test]
 async fn test_proxy_manager_creation() -> Result<()> {
@@ -100,7 +108,7 @@ async fn test_proxy_manager_no_healthy_proxies() -> Result<()> {
     let manager = ProxyManager::new(proxies);
 
     // Mark all proxies as unhealthy
-    for proxy in manager.get_all_proxies() {
+    for proxy in manager.get_all_proxies().iter() {
         manager.set_proxy_health(proxy, false).await;
     }
 
@@ -123,7 +131,7 @@ async fn test_proxy_manager_reset_health() -> Result<()> {
     let manager = ProxyManager::new(proxies);
 
     // Mark all proxies as unhealthy
-    for proxy in manager.get_all_proxies() {
+    for proxy in manager.get_all_proxies().iter() {
         manager.set_proxy_health(proxy, false).await;
     }
 
@@ -299,3 +307,533 @@ async fn test_proxy_manager_health_status_persistence() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_validate_proxy_reachability() -> Result<()> {
+    // A mock server stands in for a working HTTP proxy
+    let mock_proxy = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("{\"origin\":\"1.2.3.4\"}"))
+        .mount(&mock_proxy)
+        .await;
+
+    let address = mock_proxy.address();
+    let proxies = vec![
+        (
+            "proxy_1".to_string(),
+            ProxyInfo::new(address.ip().to_string(), address.port()),
+        ),
+        // Nothing listens on port 1, so this proxy is unreachable
+        (
+            "proxy_2".to_string(),
+            ProxyInfo::new("127.0.0.1".to_string(), 1),
+        ),
+    ];
+
+    let mut checker = ProxyHealth::with_timeout(Duration::from_secs(2))?;
+    checker.set_test_url("http://lazabot.test/ip".to_string());
+
+    // Non-strict: dead proxy is only a warning
+    let mut report = ValidationReport::new();
+    let reachable = check_proxy_reachability(&checker, &proxies, false, &mut report).await;
+    assert_eq!(reachable, 1);
+    assert_eq!(report.successes.len(), 1);
+    assert_eq!(report.warnings.len(), 1);
+    assert!(!report.has_errors());
+    assert!(report
+        .infos
+        .iter()
+        .any(|item| item.status == "1/2 reachable"));
+
+    // Strict: dead proxy is an error
+    let mut report = ValidationReport::new();
+    let reachable = check_proxy_reachability(&checker, &proxies, true, &mut report).await;
+    assert_eq!(reachable, 1);
+    assert!(report.has_errors());
+    assert!(!report.has_warnings());
+    assert_eq!(report.errors[0].variable, "PROXY_PROXY_2");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_refresh_health_updates_health_map() -> Result<()> {
+    // Mock servers stand in for working HTTP proxies
+    let mut reachable = Vec::new();
+    for _ in 0..2 {
+        let mock_proxy = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("{\"origin\":\"1.2.3.4\"}"))
+            .mount(&mock_proxy)
+            .await;
+        reachable.push(mock_proxy);
+    }
+
+    let working: Vec<ProxyInfo> = reachable
+        .iter()
+        .map(|server| ProxyInfo::new(server.address().ip().to_string(), server.address().port()))
+        .collect();
+    // Nothing listens on ports 1 and 2, so these proxies are unreachable
+    let dead = vec![
+        ProxyInfo::new("127.0.0.1".to_string(), 1),
+        ProxyInfo::new("127.0.0.1".to_string(), 2),
+    ];
+
+    let manager = ProxyManager::new(working.iter().chain(&dead).cloned().collect());
+    // Start with one working proxy marked down, so it should recover
+    manager.set_proxy_health(&working[1], false).await;
+
+    let mut checker = ProxyHealth::with_timeout(Duration::from_secs(2))?;
+    checker.set_test_url("http://lazabot.test/ip".to_string());
+
+    let summary = manager.refresh_health_with(&checker, 2).await;
+
+    assert_eq!(summary.checked, 4);
+    assert_eq!(summary.healthy, 2);
+    assert_eq!(summary.recovered, 1);
+    assert_eq!(summary.failed, 2);
+    assert_eq!(summary.flipped(), 3);
+
+    assert!(manager.is_proxy_healthy(&working[0]).await);
+    assert!(manager.is_proxy_healthy(&working[1]).await);
+    assert!(!manager.is_proxy_healthy(&dead[0]).await);
+    assert!(!manager.is_proxy_healthy(&dead[1]).await);
+    assert_eq!(manager.healthy_proxies_count().await, 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_http_proxy_health_check_fetches_test_url() -> Result<()> {
+    // A mock server stands in for a working HTTP proxy
+    let mock_proxy = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_proxy)
+        .await;
+
+    let mut checker = ProxyHealth::with_timeout(Duration::from_secs(2))?;
+    checker.set_test_url("http://lazabot.test/ip".to_string());
+
+    let address = mock_proxy.address();
+    let proxy = ProxyInfo::new(address.ip().to_string(), address.port());
+    let check = checker.check_proxy(&proxy).await;
+    assert_eq!(check.kind, HealthCheckKind::HttpGet);
+    assert!(check.healthy, "{:?}", check.error);
+
+    Ok(())
+}
+
+/// Accept one SOCKS5 client requiring `username`/`password` and read its CONNECT
+/// request, returning the stream and the requested `host:port`
+async fn accept_socks5(
+    listener: TcpListener,
+    username: &'static str,
+    password: &'static str,
+) -> Result<(TcpStream, String)> {
+    let (mut stream, _) = listener.accept().await?;
+
+    let mut greeting = [0u8; 2];
+    stream.read_exact(&mut greeting).await?;
+    let mut methods = vec![0u8; greeting[1] as usize];
+    stream.read_exact(&mut methods).await?;
+    assert!(methods.contains(&0x02), "client didn't offer password auth");
+    stream.write_all(&[0x05, 0x02]).await?;
+
+    let mut auth_header = [0u8; 2];
+    stream.read_exact(&mut auth_header).await?;
+    let mut user = vec![0u8; auth_header[1] as usize];
+    stream.read_exact(&mut user).await?;
+    let mut pass_len = [0u8; 1];
+    stream.read_exact(&mut pass_len).await?;
+    let mut pass = vec![0u8; pass_len[0] as usize];
+    stream.read_exact(&mut pass).await?;
+    let authenticated = user == username.as_bytes() && pass == password.as_bytes();
+    stream
+        .write_all(&[0x01, if authenticated { 0x00 } else { 0x01 }])
+        .await?;
+
+    let mut request = [0u8; 4];
+    stream.read_exact(&mut request).await?;
+    assert_eq!(&request[..3], &[0x05, 0x01, 0x00]);
+    let host = match request[3] {
+        // IPv4 address
+        0x01 => {
+            let mut ip = [0u8; 4];
+            stream.read_exact(&mut ip).await?;
+            std::net::Ipv4Addr::from(ip).to_string()
+        }
+        // Domain name
+        0x03 => {
+            let mut len = [0u8; 1];
+            stream.read_exact(&mut len).await?;
+            let mut host = vec![0u8; len[0] as usize];
+            stream.read_exact(&mut host).await?;
+            String::from_utf8(host)?
+        }
+        other => panic!("unexpected address type {}", other),
+    };
+    let mut port = [0u8; 2];
+    stream.read_exact(&mut port).await?;
+
+    Ok((stream, format!("{}:{}", host, u16::from_be_bytes(port))))
+}
+
+/// Accept one SOCKS5 client requiring `username`/`password`, answer its CONNECT with
+/// `reply_code`, and return the requested `host:port`
+async fn serve_socks5_once(
+    listener: TcpListener,
+    username: &'static str,
+    password: &'static str,
+    reply_code: u8,
+) -> Result<String> {
+    let (mut stream, target) = accept_socks5(listener, username, password).await?;
+    stream
+        .write_all(&[0x05, reply_code, 0x00, 0x01, 127, 0, 0, 1, 0, 0])
+        .await?;
+    Ok(target)
+}
+
+/// Accept one SOCKS5 client requiring `username`/`password` and relay its
+/// connection to the requested address
+async fn relay_socks5_once(
+    listener: TcpListener,
+    username: &'static str,
+    password: &'static str,
+) -> Result<()> {
+    let (mut stream, target) = accept_socks5(listener, username, password).await?;
+    let mut upstream = TcpStream::connect(&target).await?;
+    stream
+        .write_all(&[0x05, 0x00, 0x00, 0x01, 127, 0, 0, 1, 0, 0])
+        .await?;
+    tokio::io::copy_bidirectional(&mut stream, &mut upstream).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_api_client_request_through_socks5_proxy() -> Result<()> {
+    let target = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/ip"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("via socks"))
+        .expect(1)
+        .mount(&target)
+        .await;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let address = listener.local_addr()?;
+    tokio::spawn(relay_socks5_once(listener, "user", "pass"));
+    let proxy = ProxyInfo::new(address.ip().to_string(), address.port())
+        .with_auth("user".to_string(), "pass".to_string())
+        .with_type(ProxyType::Socks5);
+
+    let client = ApiClient::new(Some("TestAgent/1.0".to_string()))?;
+    let response = client
+        .request(
+            reqwest::Method::GET,
+            &format!("{}/ip", target.uri()),
+            None,
+            None,
+            Some(proxy),
+        )
+        .await?;
+
+    assert_eq!(response.status, 200);
+    assert_eq!(response.text, "via socks");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_socks5_proxy_health_check_performs_connect() -> Result<()> {
+    let mut checker = ProxyHealth::with_timeout(Duration::from_secs(2))?;
+    checker.set_test_url("http://lazabot.test/ip".to_string());
+
+    // A proxy that completes the handshake and accepts the CONNECT is healthy
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let address = listener.local_addr()?;
+    let server = tokio::spawn(serve_socks5_once(listener, "user", "pass", 0x00));
+    let proxy = ProxyInfo::new(address.ip().to_string(), address.port())
+        .with_auth("user".to_string(), "pass".to_string())
+        .with_type(ProxyType::Socks5);
+
+    let check = checker.check_proxy(&proxy).await;
+    assert_eq!(check.kind, HealthCheckKind::Socks5Connect);
+    assert!(check.healthy, "{:?}", check.error);
+    assert_eq!(server.await??, "lazabot.test:80");
+
+    // One that refuses the CONNECT (reply 0x05, connection refused) is not
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let address = listener.local_addr()?;
+    let server = tokio::spawn(serve_socks5_once(listener, "user", "pass", 0x05));
+    let proxy = ProxyInfo {
+        port: address.port(),
+        ..proxy
+    };
+
+    let check = checker.check_proxy(&proxy).await;
+    assert_eq!(check.kind, HealthCheckKind::Socks5Connect);
+    assert!(!check.healthy);
+    assert!(check.error.unwrap().contains("reply code 5"));
+    server.await??;
+
+    // An HTTP server is not a SOCKS5 proxy, even though an HTTP GET through it works
+    let http_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&http_server)
+        .await;
+    let proxy = ProxyInfo::new(
+        http_server.address().ip().to_string(),
+        http_server.address().port(),
+    )
+    .with_type(ProxyType::Socks5);
+
+    // It may just wait for more bytes, so don't wait long
+    checker.set_timeout(Duration::from_millis(500));
+    let check = checker.check_proxy(&proxy).await;
+    assert_eq!(check.kind, HealthCheckKind::Socks5Connect);
+    assert!(!check.healthy);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_import_proxies_from_provider_csv() -> Result<()> {
+    let temp_dir = tempfile::TempDir::new()?;
+    let path = temp_dir.path().join("proxies.csv");
+    std::fs::write(
+        &path,
+        "Host,Port,Username,Password,Type\n\
+         10.0.0.1,8080,,,http\n\
+         10.0.0.2, 1080 ,alice,s3cret,socks5\n\
+         10.0.0.3,not-a-port,,,http\n\
+         10.0.0.4,8080,,,ftp\n\
+         10.0.0.5,3128\n",
+    )?;
+
+    let manager = ProxyManager::from_csv(path.to_str().unwrap()).await?;
+    let proxies = manager.get_all_proxies();
+    assert_eq!(proxies.len(), 3);
+
+    assert_eq!(proxies[0].host, "10.0.0.1");
+    assert_eq!(proxies[0].proxy_type, ProxyType::Http);
+    assert!(proxies[0].username.is_none());
+
+    assert_eq!(proxies[1].host, "10.0.0.2");
+    assert_eq!(proxies[1].port, 1080);
+    assert_eq!(proxies[1].username.as_deref(), Some("alice"));
+    assert_eq!(proxies[1].password.as_deref(), Some("s3cret"));
+    assert_eq!(proxies[1].proxy_type, ProxyType::Socks5);
+
+    assert_eq!(proxies[2].host, "10.0.0.5");
+    assert_eq!(proxies[2].port, 3128);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_import_proxies_from_provider_json() -> Result<()> {
+    let temp_dir = tempfile::TempDir::new()?;
+    let path = temp_dir.path().join("proxies.json");
+    std::fs::write(
+        &path,
+        serde_json::json!([
+            { "ip": "10.0.0.1", "port": 8080 },
+            {
+                "host": "10.0.0.2",
+                "port": "1080",
+                "user": "bob",
+                "pass": "hunter2",
+                "protocol": "SOCKS5"
+            },
+            { "host": "10.0.0.3" },
+            { "host": "10.0.0.4", "port": 70000 },
+            "10.0.0.5:8080"
+        ])
+        .to_string(),
+    )?;
+
+    let manager = ProxyManager::from_json(path.to_str().unwrap()).await?;
+    let proxies = manager.get_all_proxies();
+    assert_eq!(proxies.len(), 2);
+
+    assert_eq!(proxies[0].host, "10.0.0.1");
+    assert_eq!(proxies[0].proxy_type, ProxyType::Http);
+
+    assert_eq!(proxies[1].host, "10.0.0.2");
+    assert_eq!(proxies[1].port, 1080);
+    assert_eq!(proxies[1].username.as_deref(), Some("bob"));
+    assert_eq!(proxies[1].password.as_deref(), Some("hunter2"));
+    assert_eq!(proxies[1].proxy_type, ProxyType::Socks5);
+
+    // A file with nothing usable is an error
+    std::fs::write(&path, "[]")?;
+    assert!(ProxyManager::from_json(path.to_str().unwrap())
+        .await
+        .is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_proxy_subscription_load_and_refresh() -> Result<()> {
+    let provider = MockServer::start().await;
+
+    // First fetch serves the line format, later ones a JSON array
+    Mock::given(method("GET"))
+        .and(path("/proxies"))
+        .and(header("x-api-key", "provider-key"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string(
+                "10.0.0.1:8080\n10.0.0.2:8080:user:pass\nsocks5://10.0.0.3:1080\n",
+            ),
+        )
+        .up_to_n_times(1)
+        .mount(&provider)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/proxies"))
+        .and(header("x-api-key", "provider-key"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+            { "host": "10.0.0.2", "port": 8080, "username": "user", "password": "pass" },
+            { "host": "10.0.0.4", "port": 8080 }
+        ])))
+        .mount(&provider)
+        .await;
+
+    let mut headers = HeaderMap::new();
+    headers.insert("x-api-key", HeaderValue::from_static("provider-key"));
+    let subscription =
+        ProxySubscription::new(format!("{}/proxies", provider.uri())).with_headers(headers);
+    let manager = ProxyManager::from_subscription(subscription).await?;
+
+    let hosts = |manager: &ProxyManager| -> Vec<String> {
+        manager
+            .get_all_proxies()
+            .iter()
+            .map(|proxy| proxy.host.clone())
+            .collect()
+    };
+    assert_eq!(hosts(&manager), vec!["10.0.0.1", "10.0.0.2", "10.0.0.3"]);
+    assert_eq!(
+        manager.get_all_proxies()[1].username.as_deref(),
+        Some("user")
+    );
+    assert_eq!(manager.get_all_proxies()[2].proxy_type, ProxyType::Socks5);
+
+    let kept = manager.get_all_proxies()[1].clone();
+    manager.set_proxy_health(&kept, false).await;
+    let before = manager.get_all_proxies();
+
+    assert_eq!(manager.refresh_from_subscription().await?, 2);
+    assert_eq!(hosts(&manager), vec!["10.0.0.2", "10.0.0.4"]);
+    assert_eq!(manager.total_proxies(), 2);
+    // Snapshots taken before the refresh still see the list they were taken from
+    assert_eq!(before.len(), 3);
+    assert_eq!(before[2].host, "10.0.0.3");
+    assert_eq!(
+        manager.get_proxy_by_index(1).map(|proxy| proxy.host),
+        Some("10.0.0.4".to_string())
+    );
+    // Health carries over for proxies still listed; new ones start healthy
+    assert!(!manager.is_proxy_healthy(&kept).await);
+    assert_eq!(manager.healthy_proxies_count().await, 1);
+    assert_eq!(manager.get_next_proxy().await.unwrap().host, "10.0.0.4");
+
+    // Without the provider's key the fetch is refused
+    let url = format!("{}/proxies", provider.uri());
+    assert!(ProxyManager::from_subscription_url(&url).await.is_err());
+
+    // A manager loaded some other way has nothing to refresh from
+    assert!(ProxyManager::new(vec![])
+        .refresh_from_subscription()
+        .await
+        .is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ipv6_proxies_from_file_round_trip_through_url() -> Result<()> {
+    let temp_dir = tempfile::TempDir::new()?;
+    let path = temp_dir.path().join("proxies.txt");
+    std::fs::write(
+        &path,
+        "[::1]:8080\n[2001:db8::7]:3128:user:pass\n127.0.0.1:8080\n",
+    )?;
+
+    let manager = ProxyManager::from_file(path.to_str().unwrap()).await?;
+    let proxies = manager.get_all_proxies();
+    assert_eq!(proxies.len(), 3);
+
+    assert_eq!(proxies[0].host, "::1");
+    assert_eq!(proxies[0].port, 8080);
+    assert!(proxies[0].username.is_none());
+    assert_eq!(proxies[0].to_url()?, "http://[::1]:8080");
+
+    assert_eq!(proxies[1].host, "2001:db8::7");
+    assert_eq!(proxies[1].port, 3128);
+    let url = reqwest::Url::parse(&proxies[1].to_url()?)?;
+    assert_eq!(url.host_str(), Some("[2001:db8::7]"));
+    assert_eq!(url.port(), Some(3128));
+    assert_eq!(url.username(), "user");
+    assert_eq!(url.password(), Some("pass"));
+
+    assert_eq!(proxies[2].to_url()?, "http://127.0.0.1:8080");
+
+    for bad in ["[::1", "[::1]8080", "[::1]:", "[not-ipv6]:8080"] {
+        assert!(
+            ProxyManager::parse_proxy_entry(bad).is_err(),
+            "{} should not parse",
+            bad
+        );
+    }
+    let proxy = ProxyManager::parse_proxy_entry("socks5://[::1]:1080:user:pass")?;
+    assert_eq!(proxy.host, "::1");
+    assert_eq!(proxy.proxy_type, ProxyType::Socks5);
+
+    // Entries are removed by their bracketed address
+    assert!(ProxyManager::remove_from_file(path.to_str().unwrap(), "[2001:db8::7]:3128").await?);
+    let remaining = std::fs::read_to_string(&path)?;
+    assert_eq!(remaining, "[::1]:8080\n127.0.0.1:8080\n");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_drained_proxy_is_listed_but_never_selected() -> Result<()> {
+    let proxies = vec![
+        ProxyInfo::new("127.0.0.1".to_string(), 8080),
+        ProxyInfo::new("192.168.1.1".to_string(), 3128),
+        ProxyInfo::new("10.0.0.1".to_string(), 1080),
+    ];
+    let drained = proxies[1].clone();
+
+    let manager = ProxyManager::new(proxies);
+    manager.drain(&drained);
+    assert!(manager.is_drained(&drained));
+
+    for _ in 0..10 {
+        let proxy = manager.get_next_proxy().await.unwrap();
+        assert_ne!(proxy.host, drained.host);
+    }
+
+    // Draining only affects selection, not the list or health
+    assert_eq!(manager.total_proxies(), 3);
+    assert!(manager
+        .get_all_proxies()
+        .iter()
+        .any(|p| p.host == drained.host));
+    assert!(manager.is_proxy_healthy(&drained).await);
+
+    manager.undrain(&drained);
+    let mut selected = false;
+    for _ in 0..3 {
+        selected |= manager.get_next_proxy().await.unwrap().host == drained.host;
+    }
+    assert!(selected);
+
+    Ok(())
+}
@@ -7,7 +7,16 @@
 //! 4. Verifying cookie persistence
 
 use anyhow::Result;
-use lazabot::core::session::{Credentials, Session};
+use lazabot::api::{ApiClient, ProxyInfo};
+use lazabot::core::session::{Credentials, Session, SessionManager};
+use lazabot::core::SessionPool;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use wiremock::{
+    matchers::{body_string_contains, header, method, path},
+    Mock, MockServer, ResponseTemplate,
+};
 
 
 #[tokio::test]
@@ -78,3 +87,385 @@ async fn test_session_manual_persistence() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_login_reads_set_cookie_headers() -> Result<()> {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/login"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .append_header("Set-Cookie", "lzd_sid=abc123; Path=/; HttpOnly")
+                .append_header("Set-Cookie", "_tb_token_=xyz789; Path=/; Secure"),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let temp_dir = tempfile::TempDir::new()?;
+    let api_client = Arc::new(ApiClient::new(Some("Lazabot-Test/1.0".to_string()))?);
+    let manager =
+        SessionManager::with_sessions_dir(api_client, temp_dir.path().to_path_buf(), [7u8; 32])
+            .await?
+            .with_login_url(format!("{}/login", mock_server.uri()));
+
+    let credentials = Credentials::new("test_user".to_string(), "test_password".to_string());
+    let session = manager.login(credentials).await?;
+
+    assert_eq!(session.cookies.len(), 2);
+    assert_eq!(session.cookies["lzd_sid"], "abc123");
+    assert_eq!(session.cookies["_tb_token_"], "xyz789");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_session_pool_reuses_valid_session() -> Result<()> {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/login"))
+        .respond_with(
+            ResponseTemplate::new(200).append_header("Set-Cookie", "lzd_sid=abc123; Path=/"),
+        )
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/validate"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let temp_dir = tempfile::TempDir::new()?;
+    let api_client = Arc::new(ApiClient::new(Some("Lazabot-Test/1.0".to_string()))?);
+    let manager =
+        SessionManager::with_sessions_dir(api_client, temp_dir.path().to_path_buf(), [7u8; 32])
+            .await?
+            .with_login_url(format!("{}/login", mock_server.uri()))
+            .with_validation_url(format!("{}/validate", mock_server.uri()));
+    let pool = SessionPool::new(Arc::new(manager));
+
+    let credentials = Credentials::new("test_user".to_string(), "test_password".to_string());
+    let first = pool.get("account_1", &credentials).await?;
+    let second = pool.get("account_1", &credentials).await?;
+
+    assert_eq!(first.id, second.id);
+    assert_eq!(second.cookies["lzd_sid"], "abc123");
+    assert_eq!(pool.len().await, 1);
+
+    // Expectations (one login, one validation) are verified when the server drops
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_session_pool_logins_do_not_block_other_accounts() -> Result<()> {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/login"))
+        .and(body_string_contains("slow_user"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .append_header("Set-Cookie", "lzd_sid=slow; Path=/")
+                .set_delay(std::time::Duration::from_secs(3)),
+        )
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/login"))
+        .and(body_string_contains("fast_user"))
+        .respond_with(
+            ResponseTemplate::new(200).append_header("Set-Cookie", "lzd_sid=fast; Path=/"),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let temp_dir = tempfile::TempDir::new()?;
+    let api_client = Arc::new(ApiClient::new(Some("Lazabot-Test/1.0".to_string()))?);
+    let manager =
+        SessionManager::with_sessions_dir(api_client, temp_dir.path().to_path_buf(), [7u8; 32])
+            .await?
+            .with_login_url(format!("{}/login", mock_server.uri()));
+    let pool = Arc::new(SessionPool::new(Arc::new(manager)));
+
+    let slow_pool = pool.clone();
+    let slow = tokio::spawn(async move {
+        let credentials = Credentials::new("slow_user".to_string(), "password".to_string());
+        slow_pool.get("account_slow", &credentials).await
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+    // The slow login for another account must not hold up this one
+    let credentials = Credentials::new("fast_user".to_string(), "password".to_string());
+    let fast = tokio::time::timeout(
+        std::time::Duration::from_secs(1),
+        pool.get("account_fast", &credentials),
+    )
+    .await??;
+    assert_eq!(fast.cookies["lzd_sid"], "fast");
+
+    let slow = slow.await??;
+    assert_eq!(slow.cookies["lzd_sid"], "slow");
+    assert_eq!(pool.len().await, 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_login_with_proxy_binds_session() -> Result<()> {
+    // The mock server acts as the forward proxy the login goes through
+    let proxy_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/login"))
+        .respond_with(
+            ResponseTemplate::new(200).append_header("Set-Cookie", "lzd_sid=abc123; Path=/"),
+        )
+        .expect(1)
+        .mount(&proxy_server)
+        .await;
+
+    let proxy = ProxyInfo::new(
+        proxy_server.address().ip().to_string(),
+        proxy_server.address().port(),
+    );
+    let other_proxy = ProxyInfo::new("10.0.0.2".to_string(), 8080);
+
+    let temp_dir = tempfile::TempDir::new()?;
+    let api_client = Arc::new(ApiClient::new(Some("Lazabot-Test/1.0".to_string()))?);
+    let manager =
+        SessionManager::with_sessions_dir(api_client, temp_dir.path().to_path_buf(), [7u8; 32])
+            .await?
+            .with_login_url("http://shop.lazada.test/login");
+
+    let credentials = Credentials::new("test_user".to_string(), "test_password".to_string());
+    let session = manager.login_with_proxy(credentials, proxy.clone()).await?;
+
+    let expected = format!("{}:{}", proxy.host, proxy.port);
+    assert_eq!(session.proxy.as_deref(), Some(expected.as_str()));
+    assert!(!session.is_proxy_mismatch(Some(&proxy)));
+    assert!(session.is_proxy_mismatch(Some(&other_proxy)));
+    assert!(session.is_proxy_mismatch(None));
+
+    // The binding survives persistence
+    manager.persist_session(&session).await?;
+    let restored = manager.restore_session(&session.id).await?;
+    assert_eq!(restored.proxy, session.proxy);
+
+    Ok(())
+}
+
+/// Serve validation requests, holding each one until `gate` requests are in flight
+/// together (or a few seconds pass) and recording the most seen at once
+async fn start_gated_validation_server(gate: usize) -> Result<(String, Arc<AtomicUsize>)> {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    let url = format!("http://{}/validate", listener.local_addr()?);
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let max_in_flight = Arc::new(AtomicUsize::new(0));
+    let (open, _) = tokio::sync::watch::channel(false);
+    let open = Arc::new(open);
+
+    let max = max_in_flight.clone();
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let (in_flight, max, open) = (in_flight.clone(), max.clone(), open.clone());
+            tokio::spawn(async move {
+                let mut request = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    match stream.read(&mut buf).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => request.extend_from_slice(&buf[..n]),
+                    }
+                }
+
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                max.fetch_max(now, Ordering::SeqCst);
+                if now >= gate {
+                    open.send_replace(true);
+                }
+                let mut opened = open.subscribe();
+                let _ = tokio::time::timeout(
+                    std::time::Duration::from_secs(5),
+                    opened.wait_for(|open| *open),
+                )
+                .await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+
+                let response = "HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n";
+                let _ = stream.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+
+    Ok((url, max_in_flight))
+}
+
+#[tokio::test]
+async fn test_validate_all_sessions_concurrently() -> Result<()> {
+    let (validation_url, max_in_flight) = start_gated_validation_server(3).await?;
+
+    let temp_dir = tempfile::TempDir::new()?;
+    let api_client = Arc::new(ApiClient::new(Some("Lazabot-Test/1.0".to_string()))?);
+    let manager =
+        SessionManager::with_sessions_dir(api_client, temp_dir.path().to_path_buf(), [7u8; 32])
+            .await?
+            .with_validation_url(validation_url);
+
+    for i in 0..6 {
+        let credentials = Credentials::new(format!("user_{}", i), "password".to_string());
+        manager
+            .persist_session(&Session::new(format!("session_{}", i), credentials))
+            .await?;
+    }
+    std::fs::write(
+        temp_dir.path().join("session_corrupt.bin"),
+        b"not a session",
+    )?;
+
+    let results = manager.validate_all_sessions(3).await;
+
+    assert_eq!(results.len(), 7);
+    for (session_id, is_valid) in &results {
+        assert_eq!(*is_valid, session_id != "session_corrupt", "{}", session_id);
+    }
+    // Validations overlap, but never more than the requested concurrency
+    assert_eq!(max_in_flight.load(Ordering::SeqCst), 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cleanup_keeps_live_sessions_when_validation_fails() -> Result<()> {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/validate"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&mock_server)
+        .await;
+
+    let temp_dir = tempfile::TempDir::new()?;
+    let api_client = Arc::new(ApiClient::new(Some("Lazabot-Test/1.0".to_string()))?);
+    let manager =
+        SessionManager::with_sessions_dir(api_client, temp_dir.path().to_path_buf(), [7u8; 32])
+            .await?
+            .with_validation_url(format!("{}/validate", mock_server.uri()));
+
+    let credentials = Credentials::new("user".to_string(), "password".to_string());
+    for i in 0..3 {
+        manager
+            .persist_session(&Session::new(format!("session_{}", i), credentials.clone()))
+            .await?;
+    }
+    let mut expired = Session::new("session_expired".to_string(), credentials);
+    expired.last_used = chrono::Utc::now() - chrono::Duration::days(10);
+    manager.persist_session(&expired).await?;
+    std::fs::write(
+        temp_dir.path().join("session_corrupt.bin"),
+        b"not a session",
+    )?;
+
+    let cleaned = manager.cleanup_expired_sessions(7).await?;
+
+    // Only age and corruption remove sessions; an unavailable endpoint does not
+    assert_eq!(cleaned, 2);
+    let mut remaining = manager.list_sessions().await?;
+    remaining.sort();
+    assert_eq!(remaining, vec!["session_0", "session_1", "session_2"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_find_sessions_by_metadata() -> Result<()> {
+    let temp_dir = tempfile::TempDir::new()?;
+    let api_client = Arc::new(ApiClient::new(Some("Lazabot-Test/1.0".to_string()))?);
+    let manager =
+        SessionManager::with_sessions_dir(api_client, temp_dir.path().to_path_buf(), [7u8; 32])
+            .await?;
+
+    for (id, region) in [("sg_1", "SG"), ("my_1", "MY"), ("sg_2", "SG")] {
+        let credentials = Credentials::new(format!("user_{}", id), "password".to_string());
+        let mut session = Session::new(id.to_string(), credentials);
+        session.add_metadata("region".to_string(), serde_json::json!(region));
+        manager.persist_session(&session).await?;
+    }
+    let credentials = Credentials::new("user_none".to_string(), "password".to_string());
+    manager
+        .persist_session(&Session::new("no_region".to_string(), credentials))
+        .await?;
+
+    let region = |value: &str| serde_json::json!(value);
+    assert_eq!(
+        manager
+            .find_sessions_by_metadata("region", &region("SG"))
+            .await?,
+        vec!["sg_1", "sg_2"]
+    );
+    assert_eq!(
+        manager
+            .find_sessions_by_metadata("region", &region("MY"))
+            .await?,
+        vec!["my_1"]
+    );
+    assert!(manager
+        .find_sessions_by_metadata("region", &region("TH"))
+        .await?
+        .is_empty());
+    assert!(manager
+        .find_sessions_by_metadata("tier", &region("SG"))
+        .await?
+        .is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_import_session_from_browser_cookies() -> Result<()> {
+    let mock_server = MockServer::start().await;
+
+    // Validation only succeeds when the imported cookie is sent to the mock's host
+    Mock::given(method("GET"))
+        .and(path("/validate"))
+        .and(header("Cookie", "lzd_sid=abc123"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let temp_dir = tempfile::TempDir::new()?;
+    let api_client = Arc::new(ApiClient::new(Some("Lazabot-Test/1.0".to_string()))?);
+    let manager =
+        SessionManager::with_sessions_dir(api_client, temp_dir.path().to_path_buf(), [7u8; 32])
+            .await?
+            .with_validation_url(format!("{}/validate", mock_server.uri()));
+
+    let host = mock_server.address().ip().to_string();
+    let session = manager
+        .import_from_cookies(
+            "account_1",
+            vec![
+                ("lzd_sid".to_string(), "abc123".to_string(), host.clone()),
+                (
+                    "pay_token".to_string(),
+                    "xyz".to_string(),
+                    "pay.example.com".to_string(),
+                ),
+            ],
+        )
+        .await?;
+
+    assert!(session.is_valid);
+    assert_eq!(session.domain_cookies[&host]["lzd_sid"], "abc123");
+    assert_eq!(session.metadata["account_id"], "account_1");
+
+    let restored = manager.restore_session(&session.id).await?;
+    assert_eq!(restored.cookies, session.cookies);
+    assert_eq!(restored.domain_cookies, session.domain_cookies);
+
+    Ok(())
+}
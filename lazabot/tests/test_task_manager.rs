@@ -7,6 +7,7 @@
 // - Graceful shutdown handling
 
 use anyhow::Result;
+use lazabot::storage::Database;
 use lazabot::tasks::{Task, TaskManager, TaskStatus};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
@@ -63,6 +64,49 @@ impl Task for TestTask {
     }
 }
 
+/// Wait for `task_id` to finish and return how long it spent running
+async fn run_time(manager: &TaskManager, task_id: u64) -> Duration {
+    for _ in 0..200 {
+        if let Some(result) = manager.get_task_result(task_id) {
+            if let (Some(started), Some(completed)) = (result.started_at, result.completed_at) {
+                assert_eq!(result.status, TaskStatus::Completed);
+                return (completed - started).to_std().unwrap();
+            }
+        }
+        sleep(Duration::from_millis(10)).await;
+    }
+    panic!("task {} did not finish", task_id);
+}
+
+#[tokio::test]
+async fn test_human_delay_wraps_task_execution() {
+    let task = |name: &str| {
+        TestTask::new(
+            name,
+            50,
+            Arc::new(AtomicUsize::new(0)),
+            Arc::new(AtomicUsize::new(0)),
+        )
+    };
+
+    let bare = TaskManager::new(1);
+    let bare_id = bare.submit_task(task("bare")).await.unwrap();
+    let bare_time = run_time(&bare, bare_id).await;
+
+    let delayed = TaskManager::new(1)
+        .with_human_delay(Duration::from_millis(100), Duration::from_millis(200));
+    let delayed_id = delayed.submit_task(task("delayed")).await.unwrap();
+    let delayed_time = run_time(&delayed, delayed_id).await;
+
+    // The task itself sleeps 50ms, so the wrapper adds at least 100ms on top
+    assert!(bare_time < Duration::from_millis(150), "{:?}", bare_time);
+    assert!(
+        delayed_time >= Duration::from_millis(150),
+        "{:?}",
+        delayed_time
+    );
+}
+
 #[tokio::test]
 async fn test_50_tasks_with_max_5_concurrent() {
     // Create TaskManager with max_concurrent = 5
@@ -178,3 +222,72 @@ async fn test_graceful_shutdown_with_running_tasks() {
 
     println!("✓ Test passed: Graceful shutdown works correctly");
 }
+
+#[tokio::test]
+async fn test_task_ids_continue_across_restarts() -> Result<()> {
+    let temp_dir = tempfile::TempDir::new()?;
+    let db_path = temp_dir.path().join("lazabot.db");
+    let counter = Arc::new(AtomicUsize::new(0));
+    let max_obs = Arc::new(AtomicUsize::new(0));
+
+    let manager = TaskManager::new(5).with_database(Arc::new(Database::new(&db_path)?))?;
+    let mut first_run = Vec::new();
+    for i in 0..3 {
+        let task = TestTask::new(format!("task_{}", i), 10, counter.clone(), max_obs.clone());
+        first_run.push(manager.submit_task(task).await?);
+    }
+    sleep(Duration::from_millis(100)).await;
+    manager.shutdown().await;
+    drop(manager);
+
+    // A new process reopens the same database
+    let db = Arc::new(Database::new(&db_path)?);
+    for task_id in &first_run {
+        assert_eq!(db.get_task(*task_id)?.unwrap().status, "completed");
+    }
+
+    let manager = TaskManager::new(5).with_database(db.clone())?;
+    let task = TestTask::new("after_restart", 10, counter, max_obs);
+    let task_id = manager.submit_task(task).await?;
+    assert!(!first_run.contains(&task_id));
+    assert_eq!(task_id, first_run.iter().max().unwrap() + 1);
+
+    sleep(Duration::from_millis(100)).await;
+    assert_eq!(db.get_task(task_id)?.unwrap().status, "completed");
+    assert_eq!(db.get_tasks(None)?.len(), 4);
+    manager.shutdown().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_submit_task_blocking_bounds_pending_queue() -> Result<()> {
+    let max_concurrent = 4;
+    let max_pending = 8;
+    let manager = TaskManager::with_queue_limit(max_concurrent, max_pending);
+    assert_eq!(manager.max_pending(), max_pending);
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let max_obs = Arc::new(AtomicUsize::new(0));
+    let mut peak_queued = 0;
+    for i in 0..500 {
+        let task = TestTask::new(format!("task_{}", i), 1, counter.clone(), max_obs.clone());
+        manager.submit_task_blocking(task).await?;
+
+        // Submission waits instead of spawning past the bound
+        let queued = manager.pending_tasks_count() + manager.running_tasks_count();
+        assert!(queued <= max_concurrent + max_pending, "queued {}", queued);
+        peak_queued = peak_queued.max(queued);
+    }
+    assert!(peak_queued > max_concurrent, "queue never filled");
+
+    sleep(Duration::from_millis(500)).await;
+    assert_eq!(
+        manager.get_tasks_by_status(TaskStatus::Completed).len(),
+        500
+    );
+    assert!(max_obs.load(Ordering::SeqCst) <= max_concurrent);
+    manager.shutdown().await;
+
+    Ok(())
+}